@@ -0,0 +1,373 @@
+//! `monitor:ignore=...` directives read from a VM's vCenter notes/annotation
+//! field, so a team can acknowledge a known issue directly in vSphere
+//! instead of editing our config repo. A directive looks like
+//! `monitor:ignore=HIGH_CPU_USAGE until=2024-07-01 reason="batch week"` -
+//! `until` and `reason` are both optional, and a notes field can carry
+//! several directives back to back. [`apply_acknowledgements`] runs
+//! fleet-wide right after detection: a directive whose `until` hasn't
+//! passed suppresses its matching issue (removed from `issues`, same as
+//! `--disable-issues`) and is recorded in the returned
+//! [`AcknowledgementReport`]; one that's expired is left alone - the issue
+//! stays active, and the stale directive is flagged so it gets noticed and
+//! cleaned up in vCenter.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::vm::{VMIssueType, VMResourceStatus};
+
+const DIRECTIVE_PREFIX: &str = "monitor:ignore=";
+
+/// A directive, successfully parsed, that matched a currently-detected
+/// issue and is suppressing it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AcknowledgedIssue {
+    pub vm: String,
+    pub issue_type: VMIssueType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub until: Option<NaiveDate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A directive whose `until` date has already passed. The suppression no
+/// longer applies - the issue, if still present, is reported normally -
+/// but the stale directive is flagged here rather than just vanishing.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StaleAcknowledgement {
+    pub vm: String,
+    pub issue_type: VMIssueType,
+    pub until: NaiveDate,
+}
+
+/// `monitor:ignore=...`'s run-level outcome, surfaced in the text report
+/// and JSON metadata.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct AcknowledgementReport {
+    pub acknowledged: Vec<AcknowledgedIssue>,
+    pub stale: Vec<StaleAcknowledgement>,
+}
+
+impl AcknowledgementReport {
+    pub fn is_empty(&self) -> bool {
+        self.acknowledged.is_empty() && self.stale.is_empty()
+    }
+
+    pub fn render_section(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("ACKNOWLEDGED ISSUES:\n");
+        for issue in &self.acknowledged {
+            out.push_str(&format!("  - {} {}", issue.vm, issue.issue_type));
+            if let Some(until) = issue.until {
+                out.push_str(&format!(" until {until}"));
+            }
+            if let Some(reason) = &issue.reason {
+                out.push_str(&format!(" ({reason})"));
+            }
+            out.push('\n');
+        }
+        for stale in &self.stale {
+            out.push_str(&format!(
+                "  - STALE: {} {} expired {} - acknowledgement no longer suppresses this issue\n",
+                stale.vm, stale.issue_type, stale.until
+            ));
+        }
+        out
+    }
+}
+
+/// One parsed directive, before being matched against a VM's actual issues.
+struct Directive {
+    issue_type: VMIssueType,
+    until: Option<NaiveDate>,
+    reason: Option<String>,
+}
+
+/// Pulls a `reason="..."` clause out of `clause`, returning the clause with
+/// that substring removed and the unquoted reason text, in that order.
+/// `Ok((clause, None))` when there's no `reason="` at all; `Err(())` when
+/// the quote is never closed (treated as malformed by the caller).
+fn extract_reason(clause: &str) -> Result<(String, Option<String>), ()> {
+    let Some(start) = clause.find("reason=\"") else {
+        return Ok((clause.to_string(), None));
+    };
+    let after_key = start + "reason=\"".len();
+    let Some(end) = clause[after_key..].find('"') else {
+        return Err(());
+    };
+    let reason = clause[after_key..after_key + end].to_string();
+    let mut without_reason = String::with_capacity(clause.len());
+    without_reason.push_str(&clause[..start]);
+    without_reason.push_str(&clause[after_key + end + 1..]);
+    Ok((without_reason, Some(reason)))
+}
+
+/// Parses every `monitor:ignore=...` directive out of a VM's notes field.
+/// Directives can appear anywhere in the text, in any order; everything up
+/// to the next `monitor:ignore=` (or the end of the notes) belongs to the
+/// current one. An unknown issue code, a missing code, an unparseable
+/// `until` date, or an unterminated `reason="` is warned about on stderr
+/// and drops the whole directive - it never suppresses anything. `vm_name`
+/// is only for that warning.
+fn parse_directives(vm_name: &str, notes: &str) -> Vec<Directive> {
+    let mut directives = Vec::new();
+    let mut rest = notes;
+    while let Some(start) = rest.find(DIRECTIVE_PREFIX) {
+        rest = &rest[start + DIRECTIVE_PREFIX.len()..];
+        let next = rest.find(DIRECTIVE_PREFIX).unwrap_or(rest.len());
+        let clause = &rest[..next];
+        rest = &rest[next..];
+
+        let (clause, reason) = match extract_reason(clause) {
+            Ok(parsed) => parsed,
+            Err(()) => {
+                eprintln!("monitor:ignore on {vm_name}: unterminated reason=\"...\", ignoring directive");
+                continue;
+            }
+        };
+
+        let mut tokens = clause.split_whitespace();
+        let Some(code) = tokens.next() else {
+            eprintln!("monitor:ignore on {vm_name}: missing issue code, ignoring directive");
+            continue;
+        };
+        let issue_type = match code.parse::<VMIssueType>() {
+            Ok(issue_type) => issue_type,
+            Err(err) => {
+                eprintln!("monitor:ignore on {vm_name}: {err}, ignoring directive");
+                continue;
+            }
+        };
+
+        let mut until = None;
+        let mut malformed = false;
+        for token in tokens {
+            if let Some(value) = token.strip_prefix("until=") {
+                match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                    Ok(date) => until = Some(date),
+                    Err(_) => {
+                        eprintln!("monitor:ignore on {vm_name}: invalid until date '{value}', ignoring directive");
+                        malformed = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if malformed {
+            continue;
+        }
+        directives.push(Directive { issue_type, until, reason });
+    }
+    directives
+}
+
+/// Fleet-wide pass, run once right after detection (and after
+/// `--disable-issues`): parses each VM's notes for `monitor:ignore=...`
+/// directives and, for every one that matches a currently-detected issue,
+/// either suppresses it (removing it from `issues`, same as
+/// `--disable-issues`) when it hasn't expired, or leaves the issue alone
+/// and flags the directive as stale when it has. `today` is the date
+/// `until` is compared against.
+pub fn apply_acknowledgements(statuses: &mut [VMResourceStatus], today: NaiveDate) -> AcknowledgementReport {
+    let mut report = AcknowledgementReport::default();
+    for vm in statuses.iter_mut() {
+        let Some(notes) = vm.notes.clone() else { continue };
+        for directive in parse_directives(&vm.name, &notes) {
+            if !vm.issues.iter().any(|issue| issue.issue_type == directive.issue_type) {
+                continue;
+            }
+            match directive.until {
+                Some(until) if until < today => {
+                    report.stale.push(StaleAcknowledgement { vm: vm.name.clone(), issue_type: directive.issue_type, until });
+                }
+                _ => {
+                    vm.issues.retain(|issue| issue.issue_type != directive.issue_type);
+                    report.acknowledged.push(AcknowledgedIssue {
+                        vm: vm.name.clone(),
+                        issue_type: directive.issue_type,
+                        until: directive.until,
+                        reason: directive.reason,
+                    });
+                }
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState};
+    use std::collections::HashMap;
+
+    fn vm(notes: Option<&str>, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-01".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 95.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: notes.map(str::to_string),
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 3600.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 1).unwrap()
+    }
+
+    fn cpu_issue() -> DetectedIssue {
+        DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "CPU usage at 95.0%")
+    }
+
+    #[test]
+    fn directive_with_no_until_or_reason_suppresses_the_matching_issue() {
+        let mut statuses = vec![vm(Some("monitor:ignore=HIGH_CPU_USAGE"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert!(statuses[0].issues.is_empty());
+        assert_eq!(report.acknowledged.len(), 1);
+        assert_eq!(report.acknowledged[0].issue_type, VMIssueType::HighCpuUsage);
+        assert!(report.stale.is_empty());
+    }
+
+    #[test]
+    fn until_and_reason_are_captured_when_present() {
+        let mut statuses = vec![vm(
+            Some(r#"monitor:ignore=HIGH_CPU_USAGE until=2024-07-01 reason="batch week""#),
+            vec![cpu_issue()],
+        )];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(report.acknowledged[0].until, NaiveDate::from_ymd_opt(2024, 7, 1));
+        assert_eq!(report.acknowledged[0].reason, Some("batch week".to_string()));
+    }
+
+    #[test]
+    fn expired_directive_leaves_the_issue_active_and_is_flagged_stale() {
+        let mut statuses = vec![vm(Some("monitor:ignore=HIGH_CPU_USAGE until=2024-01-01"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1, "the issue must not be suppressed once expired");
+        assert!(report.acknowledged.is_empty());
+        assert_eq!(report.stale.len(), 1);
+        assert_eq!(report.stale[0].until, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+    }
+
+    #[test]
+    fn directive_naming_an_issue_not_currently_present_is_a_silent_no_op() {
+        let mut statuses = vec![vm(Some("monitor:ignore=HIGH_MEMORY_USAGE"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert!(report.acknowledged.is_empty());
+    }
+
+    #[test]
+    fn multiple_directives_in_one_notes_field_are_all_applied() {
+        let mut statuses = vec![vm(
+            Some("monitor:ignore=HIGH_CPU_USAGE reason=\"known\" monitor:ignore=HIGH_MEMORY_USAGE"),
+            vec![cpu_issue(), DetectedIssue::measured(VMIssueType::HighMemoryUsage, 95.0, 90.0, "x")],
+        )];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert!(statuses[0].issues.is_empty());
+        assert_eq!(report.acknowledged.len(), 2);
+    }
+
+    #[test]
+    fn unrelated_notes_text_around_a_directive_does_not_confuse_parsing() {
+        let mut statuses = vec![vm(
+            Some("imported from ESXi 6.5; owner: team-platform. monitor:ignore=HIGH_CPU_USAGE reason=\"known noisy workload\""),
+            vec![cpu_issue()],
+        )];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(report.acknowledged[0].reason, Some("known noisy workload".to_string()));
+    }
+
+    #[test]
+    fn unknown_issue_code_warns_and_never_suppresses() {
+        let mut statuses = vec![vm(Some("monitor:ignore=NOT_A_REAL_CODE"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert!(report.acknowledged.is_empty());
+        assert!(report.stale.is_empty());
+    }
+
+    #[test]
+    fn invalid_until_date_warns_and_never_suppresses() {
+        let mut statuses = vec![vm(Some("monitor:ignore=HIGH_CPU_USAGE until=not-a-date"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert!(report.acknowledged.is_empty());
+    }
+
+    #[test]
+    fn unterminated_reason_quote_warns_and_never_suppresses() {
+        let mut statuses = vec![vm(Some("monitor:ignore=HIGH_CPU_USAGE reason=\"never closed"), vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert!(report.acknowledged.is_empty());
+    }
+
+    #[test]
+    fn no_notes_is_a_no_op() {
+        let mut statuses = vec![vm(None, vec![cpu_issue()])];
+        let report = apply_acknowledgements(&mut statuses, today());
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn render_section_is_empty_with_nothing_to_say() {
+        assert!(AcknowledgementReport::default().render_section().is_empty());
+    }
+
+    #[test]
+    fn render_section_shows_both_acknowledged_and_stale_entries() {
+        let report = AcknowledgementReport {
+            acknowledged: vec![AcknowledgedIssue {
+                vm: "vm-01".to_string(),
+                issue_type: VMIssueType::HighCpuUsage,
+                until: NaiveDate::from_ymd_opt(2024, 7, 1),
+                reason: Some("batch week".to_string()),
+            }],
+            stale: vec![StaleAcknowledgement {
+                vm: "vm-02".to_string(),
+                issue_type: VMIssueType::HighMemoryUsage,
+                until: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            }],
+        };
+        let rendered = report.render_section();
+        assert!(rendered.contains("vm-01 HIGH_CPU_USAGE until 2024-07-01 (batch week)"));
+        assert!(rendered.contains("STALE: vm-02 HIGH_MEMORY_USAGE expired 2024-01-01"));
+    }
+}
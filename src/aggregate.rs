@@ -0,0 +1,444 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vm::VMResourceStatus;
+
+/// Matches a `*`-only glob pattern against a filename. This repo has no
+/// glob crate dependency, and `*` is the only wildcard `--aggregate` needs
+/// for patterns like `report-2024*.json`.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && p[pi] == t[ti] {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star_pi, star_ti)) = star {
+            pi = star_pi + 1;
+            ti = star_ti + 1;
+            star = Some((star_pi, ti));
+        } else {
+            return false;
+        }
+    }
+    while pi < p.len() && p[pi] == '*' {
+        pi += 1;
+    }
+    pi == p.len()
+}
+
+/// Matches `*.json` and transparently-gzipped `*.json.gz` report files,
+/// for directory mode - a glob pattern given directly (e.g. `*.json.gz`)
+/// doesn't need this, `glob_match` already covers it.
+fn is_json_report_name(name: &str) -> bool {
+    name.ends_with(".json") || name.ends_with(".json.gz")
+}
+
+/// Resolves `--aggregate`'s argument to the report files it covers: every
+/// `*.json`/`*.json.gz` file in a directory, or every file matching a
+/// `*`-glob pattern in its parent directory. Sorted by name so
+/// chronologically-named reports (e.g. `report-20240101.json`) line up in
+/// run order.
+pub fn resolve_report_paths(glob_or_dir: &str) -> Result<Vec<PathBuf>> {
+    let path = Path::new(glob_or_dir);
+    let mut matches: Vec<PathBuf> = if path.is_dir() {
+        fs::read_dir(path)
+            .with_context(|| format!("listing {glob_or_dir}"))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(is_json_report_name))
+            .collect()
+    } else {
+        let dir = path.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or_else(|| Path::new("."));
+        let pattern = path.file_name().and_then(|n| n.to_str()).unwrap_or(glob_or_dir);
+        fs::read_dir(dir)
+            .with_context(|| format!("listing {}", dir.display()))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|p| p.file_name().and_then(|n| n.to_str()).is_some_and(|n| glob_match(pattern, n)))
+            .collect()
+    };
+    matches.sort();
+    if matches.is_empty() {
+        anyhow::bail!("--aggregate {glob_or_dir} matched no report files");
+    }
+    Ok(matches)
+}
+
+/// Just the field `--aggregate` needs out of a prior `--format json` `v2`
+/// report (see [`crate::replay::replay`] for the same shape). `v1`'s bare
+/// type-name `issues` strings don't round-trip through [`crate::vm::VMIssueType`]'s
+/// derived `Deserialize`, so aggregating a `v1` report fails to parse
+/// rather than silently under-counting.
+#[derive(Debug, Deserialize)]
+struct AggregateSnapshot {
+    vms: Vec<VMResourceStatus>,
+}
+
+/// Loads every report in `paths`, in order - each file is treated as one
+/// run/sample round.
+pub fn load_reports(paths: &[PathBuf]) -> Result<Vec<Vec<VMResourceStatus>>> {
+    paths
+        .iter()
+        .map(|path| {
+            let path_str = path.to_str().with_context(|| format!("non-UTF-8 report path {}", path.display()))?;
+            let raw = crate::sink::read_to_string(path_str).with_context(|| format!("reading report {}", path.display()))?;
+            let snapshot: AggregateSnapshot = serde_json::from_str(&raw)
+                .with_context(|| format!("parsing report {} (requires --json-schema-version v2)", path.display()))?;
+            Ok(snapshot.vms)
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VmIssueFrequency {
+    pub name: String,
+    pub total_issue_occurrences: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IssueTypeTrend {
+    pub issue_type: String,
+    pub total: usize,
+    /// One count per run, in the same order as the input reports.
+    pub occurrences_per_run: Vec<usize>,
+}
+
+/// Run-over-run VM counts for the "estate health over time" view:
+/// [`render_svg_chart`]'s three headline series, one entry per loaded
+/// report, in run order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EstateTrend {
+    pub total_vms_per_run: Vec<usize>,
+    pub vms_with_issues_per_run: Vec<usize>,
+    /// VMs carrying at least one [`crate::vm::Severity::Critical`] issue.
+    pub critical_vms_per_run: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AggregateResult {
+    pub run_count: usize,
+    /// Highest total issue occurrences first, ties broken by name.
+    pub frequency_ranking: Vec<VmIssueFrequency>,
+    pub trends: Vec<IssueTypeTrend>,
+    pub estate_trend: EstateTrend,
+    /// Dependency-free inline SVG rendering of `estate_trend` and `trends` -
+    /// see [`render_svg_chart`]. Embed it directly in a `--template` HTML
+    /// report; this repo has no HTML output format of its own.
+    pub svg: String,
+}
+
+/// Counts issue occurrences per VM and per issue type across `snapshots`
+/// (one entry per loaded report, in run order), for the weekly-review
+/// "which VMs keep coming back" and "is this issue type trending up" view.
+pub fn aggregate(snapshots: &[Vec<VMResourceStatus>]) -> AggregateResult {
+    let run_count = snapshots.len();
+    let mut per_vm_total: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+    let mut per_run_issue_counts: Vec<std::collections::BTreeMap<String, usize>> = Vec::with_capacity(run_count);
+    let mut total_vms_per_run: Vec<usize> = Vec::with_capacity(run_count);
+    let mut vms_with_issues_per_run: Vec<usize> = Vec::with_capacity(run_count);
+    let mut critical_vms_per_run: Vec<usize> = Vec::with_capacity(run_count);
+
+    for statuses in snapshots {
+        let mut counts: std::collections::BTreeMap<String, usize> = std::collections::BTreeMap::new();
+        for vm in statuses {
+            if !vm.issues.is_empty() {
+                *per_vm_total.entry(vm.name.clone()).or_default() += vm.issues.len();
+            }
+            for issue in &vm.issues {
+                *counts.entry(issue.issue_type.to_string()).or_default() += 1;
+            }
+        }
+        total_vms_per_run.push(statuses.len());
+        vms_with_issues_per_run.push(statuses.iter().filter(|v| v.has_issues()).count());
+        critical_vms_per_run.push(
+            statuses.iter().filter(|v| v.issues.iter().any(|i| i.severity == crate::vm::Severity::Critical)).count(),
+        );
+        per_run_issue_counts.push(counts);
+    }
+
+    let mut issue_types: BTreeSet<String> = BTreeSet::new();
+    for counts in &per_run_issue_counts {
+        issue_types.extend(counts.keys().cloned());
+    }
+
+    let trends: Vec<IssueTypeTrend> = issue_types
+        .into_iter()
+        .map(|issue_type| {
+            let occurrences_per_run: Vec<usize> =
+                per_run_issue_counts.iter().map(|counts| counts.get(&issue_type).copied().unwrap_or(0)).collect();
+            let total = occurrences_per_run.iter().sum();
+            IssueTypeTrend { issue_type, total, occurrences_per_run }
+        })
+        .collect();
+
+    let mut frequency_ranking: Vec<VmIssueFrequency> = per_vm_total
+        .into_iter()
+        .map(|(name, total_issue_occurrences)| VmIssueFrequency { name, total_issue_occurrences })
+        .collect();
+    frequency_ranking.sort_by(|a, b| b.total_issue_occurrences.cmp(&a.total_issue_occurrences).then_with(|| a.name.cmp(&b.name)));
+
+    let estate_trend = EstateTrend { total_vms_per_run, vms_with_issues_per_run, critical_vms_per_run };
+    let svg = render_svg_chart(&estate_trend, &trends);
+
+    AggregateResult { run_count, frequency_ranking, trends, estate_trend, svg }
+}
+
+/// Human-readable rendering for `--aggregate`.
+pub fn render_text(result: &AggregateResult) -> String {
+    let mut out = format!("Aggregated {} run(s)\n", result.run_count);
+    out.push_str("fréquence des problèmes (most recurring issues):\n");
+    for entry in &result.frequency_ranking {
+        out.push_str(&format!("  {}: {} issue occurrence(s)\n", entry.name, entry.total_issue_occurrences));
+    }
+    out.push_str(&format!(
+        "estate health trend: total={:?} with-issues={:?} critical={:?}\n",
+        result.estate_trend.total_vms_per_run, result.estate_trend.vms_with_issues_per_run, result.estate_trend.critical_vms_per_run
+    ));
+    out.push_str("issue-type trend (occurrences per run):\n");
+    for trend in &result.trends {
+        let series: Vec<String> = trend.occurrences_per_run.iter().map(|n| n.to_string()).collect();
+        out.push_str(&format!("  {}: total={} per-run=[{}]\n", trend.issue_type, trend.total, series.join(",")));
+    }
+    out
+}
+
+const CHART_WIDTH: f64 = 600.0;
+const CHART_HEIGHT: f64 = 200.0;
+const CHART_PADDING: f64 = 10.0;
+
+/// Maps `values` onto the chart's plot area as SVG path data (`"M.. L.. L.."`),
+/// ordinal run index by run index rather than by wall-clock time, since gaps
+/// between runs (a missed scheduled run, say) aren't evenly spaced and have
+/// no timestamp to place them by - there's only ever one sample per loaded
+/// report. Returns `None` for fewer than two points, since a path can't show
+/// a trend with only one: callers fall back to a marker there instead.
+fn series_path_d(values: &[usize]) -> Option<String> {
+    if values.len() < 2 {
+        return None;
+    }
+    let max = values.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let plot_width = CHART_WIDTH - 2.0 * CHART_PADDING;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_PADDING;
+    let step = plot_width / (values.len() - 1) as f64;
+    let d = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let x = CHART_PADDING + step * i as f64;
+            let y = CHART_PADDING + plot_height - (v as f64 / max) * plot_height;
+            let cmd = if i == 0 { "M" } else { "L" };
+            format!("{cmd}{x:.1},{y:.1}")
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(d)
+}
+
+/// Renders one series as either a `<path>` (two or more runs) or a single
+/// `<circle>` marker (exactly one run, where there's nothing to draw a line
+/// between) - never an empty path, which would render as a zero-length
+/// segment and mislead rather than inform. Omitted entirely for zero runs.
+fn series_markup(class: &str, values: &[usize]) -> String {
+    match series_path_d(values) {
+        Some(d) => format!("<path class=\"{class}\" fill=\"none\" d=\"{d}\"/>\n"),
+        None => match values.first() {
+            Some(&v) => {
+                let max = v.max(1) as f64;
+                let y = CHART_PADDING + (CHART_HEIGHT - 2.0 * CHART_PADDING) * (1.0 - v as f64 / max);
+                let x = CHART_WIDTH / 2.0;
+                format!("<circle class=\"{class}\" cx=\"{x:.1}\" cy=\"{y:.1}\" r=\"3\"/>\n")
+            }
+            None => String::new(),
+        },
+    }
+}
+
+/// Dependency-free inline SVG line chart of `estate`'s headline VM counts
+/// and `issue_trends`' per-issue-type occurrence counts, for the
+/// "estate health over time" view of `--aggregate`'s result. Point
+/// placement is explained on [`series_path_d`]; callers that want pixel
+/// output rather than markup should hand this string to a real SVG
+/// renderer - there's no such dependency in this crate, same as
+/// [`crate::template`]'s Handlebars subset has no HTML library underneath it.
+fn render_svg_chart(estate: &EstateTrend, issue_trends: &[IssueTypeTrend]) -> String {
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {CHART_WIDTH} {CHART_HEIGHT}\">\n"
+    );
+    out.push_str(&series_markup("total-vms", &estate.total_vms_per_run));
+    out.push_str(&series_markup("vms-with-issues", &estate.vms_with_issues_per_run));
+    out.push_str(&series_markup("critical-vms", &estate.critical_vms_per_run));
+    for trend in issue_trends {
+        let class = format!("issue-{}", trend.issue_type.to_lowercase().replace('_', "-"));
+        out.push_str(&series_markup(&class, &trend.occurrences_per_run));
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState, VMIssueType};
+    use std::collections::HashMap;
+
+    fn vm(name: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 0.0,
+            memory_usage_pct: 0.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 1,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn load_reports_transparently_decompresses_gzipped_json_reports() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("network-monitor-aggregate-gzip-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("report-20240101.json.gz");
+        let report = serde_json::json!({ "vms": [vm("vm-0001", vec![])] });
+        let mut encoder = GzEncoder::new(fs::File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(report.to_string().as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let paths = resolve_report_paths(dir.to_str().unwrap()).unwrap();
+        assert_eq!(paths, vec![path.clone()], "a directory scan must pick up .json.gz alongside .json");
+
+        let reports = load_reports(&paths).unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0][0].name, "vm-0001");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn glob_match_supports_leading_and_trailing_star() {
+        assert!(glob_match("report-*.json", "report-20240101.json"));
+        assert!(glob_match("*.json", "report-20240101.json"));
+        assert!(!glob_match("report-*.json", "summary-20240101.json"));
+        assert!(glob_match("*", "anything"));
+    }
+
+    #[test]
+    fn frequency_ranking_sums_across_runs_and_sorts_descending() {
+        let run1 = vec![
+            vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("vm-0002", vec![]),
+        ];
+        let run2 = vec![
+            vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("vm-0002", vec![DetectedIssue::new(VMIssueType::ToolsNotRunning, "y")]),
+        ];
+
+        let result = aggregate(&[run1, run2]);
+        assert_eq!(result.run_count, 2);
+        assert_eq!(result.frequency_ranking[0].name, "vm-0001");
+        assert_eq!(result.frequency_ranking[0].total_issue_occurrences, 2);
+        assert_eq!(result.frequency_ranking[1].name, "vm-0002");
+        assert_eq!(result.frequency_ranking[1].total_issue_occurrences, 1);
+    }
+
+    #[test]
+    fn issue_type_trend_fills_zero_for_runs_without_that_type() {
+        let run1 = vec![vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let run2 = vec![vm("vm-0001", vec![])];
+        let run3 = vec![vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+
+        let result = aggregate(&[run1, run2, run3]);
+        let trend = result.trends.iter().find(|t| t.issue_type == "POWERED_OFF").unwrap();
+        assert_eq!(trend.occurrences_per_run, vec![1, 0, 1]);
+        assert_eq!(trend.total, 2);
+    }
+
+    #[test]
+    fn estate_trend_counts_total_with_issues_and_critical_per_run() {
+        let run1 = vec![
+            vm("vm-0001", vec![DetectedIssue::new(VMIssueType::Unresponsive, "x")]),
+            vm("vm-0002", vec![]),
+        ];
+        let run2 = vec![
+            vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("vm-0002", vec![]),
+            vm("vm-0003", vec![]),
+        ];
+
+        let result = aggregate(&[run1, run2]);
+        assert_eq!(result.estate_trend.total_vms_per_run, vec![2, 3]);
+        assert_eq!(result.estate_trend.vms_with_issues_per_run, vec![1, 1]);
+        assert_eq!(result.estate_trend.critical_vms_per_run, vec![1, 0], "UNRESPONSIVE is critical, POWERED_OFF is a warning");
+    }
+
+    #[test]
+    fn svg_chart_renders_a_path_for_multi_run_series() {
+        let run1 = vec![vm("vm-0001", vec![])];
+        let run2 = vec![vm("vm-0001", vec![]), vm("vm-0002", vec![])];
+
+        let result = aggregate(&[run1, run2]);
+        assert!(result.svg.contains("<path class=\"total-vms\" fill=\"none\" d=\"M10.0,100.0 L590.0,10.0\"/>"));
+    }
+
+    #[test]
+    fn svg_chart_renders_a_marker_not_a_path_for_a_single_run() {
+        let run1 = vec![vm("vm-0001", vec![])];
+
+        let result = aggregate(&[run1]);
+        assert!(!result.svg.contains("<path class=\"total-vms\""), "a single point has no trend to draw a line for");
+        assert!(result.svg.contains("<circle class=\"total-vms\" cx=\"300.0\" cy=\"10.0\" r=\"3\"/>"));
+    }
+
+    #[test]
+    fn svg_chart_includes_one_path_per_issue_type() {
+        let run1 = vec![vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let run2 = vec![vm("vm-0001", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+
+        let result = aggregate(&[run1, run2]);
+        assert!(result.svg.contains("class=\"issue-powered-off\""));
+    }
+}
@@ -0,0 +1,170 @@
+//! In-memory alert cooldown for `--watch` (daemon mode): once an issue
+//! notifies for a VM, `--alert-cooldown` suppresses re-alerting on the same
+//! (VM, issue type) until the cooldown elapses, unless the issue clears and
+//! later recurs, which resets the clock immediately. Deliberately in-memory
+//! only and not persisted like [`crate::planner::RunState`] - a restart
+//! starting with a clean alert history is the right behavior for a
+//! noise-reduction feature, not a bug to work around.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use crate::vm::{VMIssueType, VMResourceStatus};
+
+/// Tracks the last time each (VM name, issue type) pair alerted, across
+/// `--watch` cycles.
+#[derive(Debug, Default)]
+pub struct CooldownTracker {
+    last_alerted: HashMap<(String, VMIssueType), Instant>,
+}
+
+impl CooldownTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the subset of each VM's issues that are allowed to alert
+    /// right now: first out, or outside `cooldown` since they last did.
+    /// Every issue returned is recorded as alerting at `now`. An issue no
+    /// longer present on a VM has its cooldown entry dropped, so if it
+    /// recurs later it alerts immediately rather than inheriting the old
+    /// timer. VMs left with no alertable issues are dropped entirely.
+    pub fn filter(&mut self, statuses: &[VMResourceStatus], cooldown: Duration, now: Instant) -> Vec<VMResourceStatus> {
+        let present: HashSet<(String, VMIssueType)> = statuses
+            .iter()
+            .flat_map(|vm| vm.issues.iter().map(move |issue| (vm.name.clone(), issue.issue_type)))
+            .collect();
+        self.last_alerted.retain(|key, _| present.contains(key));
+
+        statuses
+            .iter()
+            .filter_map(|vm| {
+                let vm_name = vm.name.clone();
+                let mut vm = vm.clone();
+                vm.issues.retain(|issue| {
+                    let key = (vm_name.clone(), issue.issue_type);
+                    let on_cooldown = self.last_alerted.get(&key).is_some_and(|last| now.duration_since(*last) < cooldown);
+                    if on_cooldown {
+                        return false;
+                    }
+                    self.last_alerted.insert(key, now);
+                    true
+                });
+                if vm.issues.is_empty() {
+                    None
+                } else {
+                    Some(vm)
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState};
+    use std::collections::HashMap as StdHashMap;
+
+    fn vm(name: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: StdHashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn second_alert_within_cooldown_is_suppressed() {
+        let mut tracker = CooldownTracker::new();
+        let cooldown = Duration::from_secs(600);
+        let t0 = Instant::now();
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+
+        let first = tracker.filter(&statuses, cooldown, t0);
+        assert_eq!(first.len(), 1, "first sighting always alerts");
+
+        let second = tracker.filter(&statuses, cooldown, t0 + Duration::from_secs(60));
+        assert!(second.is_empty(), "still inside the cooldown window");
+    }
+
+    #[test]
+    fn alert_fires_again_once_cooldown_elapses() {
+        let mut tracker = CooldownTracker::new();
+        let cooldown = Duration::from_secs(600);
+        let t0 = Instant::now();
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+
+        tracker.filter(&statuses, cooldown, t0);
+        let after_cooldown = tracker.filter(&statuses, cooldown, t0 + Duration::from_secs(601));
+        assert_eq!(after_cooldown.len(), 1);
+    }
+
+    #[test]
+    fn clearing_and_recurring_resets_the_cooldown_immediately() {
+        let mut tracker = CooldownTracker::new();
+        let cooldown = Duration::from_secs(600);
+        let t0 = Instant::now();
+        let flagged = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let healthy: Vec<VMResourceStatus> = vec![vm("vm-1", vec![])];
+
+        tracker.filter(&flagged, cooldown, t0);
+        tracker.filter(&healthy, cooldown, t0 + Duration::from_secs(10));
+        let recurred = tracker.filter(&flagged, cooldown, t0 + Duration::from_secs(20));
+        assert_eq!(recurred.len(), 1, "clearing then recurring should not still be on cooldown");
+    }
+
+    #[test]
+    fn other_issues_on_the_same_vm_alert_independently() {
+        let mut tracker = CooldownTracker::new();
+        let cooldown = Duration::from_secs(600);
+        let t0 = Instant::now();
+
+        tracker.filter(&[vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])], cooldown, t0);
+        let statuses = vec![vm(
+            "vm-1",
+            vec![
+                DetectedIssue::new(VMIssueType::PoweredOff, "x"),
+                DetectedIssue::new(VMIssueType::ToolsNotRunning, "y"),
+            ],
+        )];
+        let result = tracker.filter(&statuses, cooldown, t0 + Duration::from_secs(1));
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].issues.len(), 1);
+        assert_eq!(result[0].issues[0].issue_type, VMIssueType::ToolsNotRunning);
+    }
+}
@@ -0,0 +1,131 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Per-endpoint call counts and latency distribution, shown at the end of a
+/// run and in JSON metadata when `--api-rate-log` is set. Disabled by
+/// default so a normal run pays no locking overhead recording samples no
+/// one asked for.
+pub struct ApiRateLog {
+    enabled: bool,
+    samples: Mutex<Vec<(String, f64)>>,
+}
+
+impl ApiRateLog {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            samples: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Records one call to `endpoint` that took `latency_ms`. No-op when
+    /// disabled, so callers can instrument unconditionally.
+    pub fn record(&self, endpoint: &str, latency_ms: f64) {
+        if !self.enabled {
+            return;
+        }
+        self.samples.lock().unwrap().push((endpoint.to_string(), latency_ms));
+    }
+
+    /// One summary row per distinct endpoint, sorted by endpoint name.
+    pub fn summaries(&self) -> Vec<EndpointSummary> {
+        let samples = self.samples.lock().unwrap();
+        let mut by_endpoint: std::collections::BTreeMap<&str, Vec<f64>> = std::collections::BTreeMap::new();
+        for (endpoint, latency_ms) in samples.iter() {
+            by_endpoint.entry(endpoint.as_str()).or_default().push(*latency_ms);
+        }
+        by_endpoint
+            .into_iter()
+            .map(|(endpoint, latencies)| summarize(endpoint, latencies))
+            .collect()
+    }
+
+    /// Renders the end-of-run table shown on stderr when `--api-rate-log` is set.
+    pub fn render_table(&self) -> String {
+        let summaries = self.summaries();
+        if summaries.is_empty() {
+            return "api-rate-log: no calls recorded\n".to_string();
+        }
+        let mut out = String::from("endpoint             count   min_ms   avg_ms   max_ms   p95_ms\n");
+        for s in summaries {
+            out.push_str(&format!(
+                "{:<20} {:>5}  {:>7.1}  {:>7.1}  {:>7.1}  {:>7.1}\n",
+                s.endpoint, s.count, s.min_ms, s.avg_ms, s.max_ms, s.p95_ms
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EndpointSummary {
+    pub endpoint: String,
+    pub count: usize,
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub p95_ms: f64,
+}
+
+/// Nearest-rank 95th percentile over `latencies`, sorted ascending first.
+/// Exposed standalone so the percentile math can be tested without going
+/// through [`ApiRateLog`]'s mutex.
+fn p95(latencies: &mut [f64]) -> f64 {
+    latencies.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let rank = ((latencies.len() as f64) * 0.95).ceil() as usize;
+    let idx = rank.saturating_sub(1).min(latencies.len() - 1);
+    latencies[idx]
+}
+
+fn summarize(endpoint: &str, mut latencies: Vec<f64>) -> EndpointSummary {
+    let count = latencies.len();
+    let total: f64 = latencies.iter().sum();
+    let min_ms = latencies.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max_ms = latencies.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    EndpointSummary {
+        endpoint: endpoint.to_string(),
+        count,
+        min_ms,
+        avg_ms: total / count as f64,
+        max_ms,
+        p95_ms: p95(&mut latencies),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_log_records_nothing() {
+        let log = ApiRateLog::new(false);
+        log.record("ListVMs", 12.0);
+        assert!(log.summaries().is_empty());
+    }
+
+    #[test]
+    fn summarizes_counts_and_latency_distribution() {
+        let log = ApiRateLog::new(true);
+        for latency in [10.0, 20.0, 30.0, 40.0, 100.0] {
+            log.record("ListVMs", latency);
+        }
+        log.record("ListHosts", 5.0);
+
+        let summaries = log.summaries();
+        assert_eq!(summaries.len(), 2);
+
+        let list_vms = summaries.iter().find(|s| s.endpoint == "ListVMs").unwrap();
+        assert_eq!(list_vms.count, 5);
+        assert_eq!(list_vms.min_ms, 10.0);
+        assert_eq!(list_vms.max_ms, 100.0);
+        assert_eq!(list_vms.avg_ms, 40.0);
+        assert_eq!(list_vms.p95_ms, 100.0);
+    }
+
+    #[test]
+    fn p95_uses_nearest_rank() {
+        let mut latencies: Vec<f64> = (1..=20).map(|n| n as f64).collect();
+        assert_eq!(p95(&mut latencies), 19.0);
+    }
+}
@@ -0,0 +1,564 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A session established against a vCenter instance.
+///
+/// Real authentication (SOAP `SessionManager.Login` / REST `/rest/com/vmware/cis/session`)
+/// is not wired up yet; [`authenticate`] simulates the round trip so the rest of the
+/// pipeline can be developed and tested against a stable contract.
+///
+/// Once a real transport lands, this is also where `/api` vs `/rest` endpoint-flavor
+/// probing belongs: probe once here (or lazily on the first 404), cache the result
+/// per endpoint family on `Session`, and have every subsequent call skip straight to
+/// the working base URL instead of re-probing. There's no dual-endpoint HTTP client
+/// to dedupe against yet, so that caching has nothing to attach to until then.
+///
+/// There's also no separate `PerformanceManager`/`VCenterAPIClient` split to
+/// reconcile - [`authenticate_from_args`] is called exactly once in `main`, and the
+/// single [`Session`] it returns is already passed by value into the one
+/// [`crate::vcenter::SimulatedClient`] that does all fetching for the run, so
+/// there's no duplicate-connection construction to merge until a real,
+/// multi-client transport exists.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub host: String,
+    pub username: String,
+    pub token: String,
+    pub version: VCenterVersion,
+}
+
+/// Product, version and build of the vCenter instance a [`Session`] is
+/// talking to, as would be read from `GET /api/appliance/system/version`
+/// (falling back to `ServiceContent.about` on a vCenter old enough not to
+/// have the modern REST surface). Reports, JSON metadata and notification
+/// payloads all include it so results from different sites can be compared
+/// knowing which features were even available to detect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VCenterVersion {
+    pub product: String,
+    pub version: String,
+    pub build: String,
+}
+
+impl VCenterVersion {
+    pub fn describe(&self) -> String {
+        format!("{} {} (build {})", self.product, self.version, self.build)
+    }
+
+    /// The `major.minor` prefix of `version`, or `None` if it doesn't parse
+    /// as one - a version reported in a shape this code doesn't recognize.
+    fn major_minor(&self) -> Option<(u32, u32)> {
+        let mut parts = self.version.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        Some((major, minor))
+    }
+
+    /// Whether this vCenter is known to be at least `major.minor`. An
+    /// unparseable version returns `true` - callers should warn and proceed
+    /// with every check enabled rather than silently gate on a guess.
+    pub fn at_least(&self, major: u32, minor: u32) -> bool {
+        self.major_minor().is_none_or(|(maj, min)| (maj, min) >= (major, minor))
+    }
+
+    pub fn is_recognized(&self) -> bool {
+        self.major_minor().is_some()
+    }
+}
+
+/// vCenter releases the simulated fleet could plausibly be running, spanning
+/// the oldest version still in the field (6.0, REST API not yet introduced)
+/// through the current one.
+const SIMULATED_VERSION_POOL: &[(&str, &str, &str)] = &[
+    ("VMware vCenter Server", "6.0.0", "3339084"),
+    ("VMware vCenter Server", "6.5.0", "17697526"),
+    ("VMware vCenter Server", "6.7.0", "17137327"),
+    ("VMware vCenter Server", "7.0.3", "21477706"),
+    ("VMware vCenter Server", "8.0.2", "22617221"),
+];
+
+fn simulate_version() -> VCenterVersion {
+    let (product, version, build) = SIMULATED_VERSION_POOL[rand::thread_rng().gen_range(0..SIMULATED_VERSION_POOL.len())];
+    VCenterVersion {
+        product: product.to_string(),
+        version: version.to_string(),
+        build: build.to_string(),
+    }
+}
+
+/// Why [`authenticate`] failed, distinguishing the three shapes a vCenter
+/// session-endpoint failure takes: a 401 means the credentials themselves
+/// were rejected; vCenter reports both a locked account and an expired
+/// password as a 403 (it doesn't distinguish the two over the wire), and
+/// either one needs an admin or a password reset, not a retyped password -
+/// worth its own message and exit code so it doesn't get mistaken for a
+/// typo. Anything else reaching this point never got a response from
+/// vCenter at all.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum AuthError {
+    #[error("authentication rejected for '{username}' (401 Unauthorized): bad credentials")]
+    BadCredentials { username: String },
+    #[error("account '{username}' is locked or its password has expired (403 Forbidden)")]
+    AccountLockedOrExpired { username: String },
+    #[error("could not reach {host}: {detail}")]
+    NetworkFailure { host: String, detail: String },
+    /// The session endpoint answered, but not with JSON - see
+    /// [`crate::content_type`]. Distinct from `NetworkFailure`: vCenter (or
+    /// something in front of it) did respond, just not usefully, so retyping
+    /// the password won't help either.
+    #[error("session endpoint for {host} returned {source} - {}", if source.looks_like_login_page { "this looks like a captive portal or SSO login page, not vCenter" } else { "vCenter's web services may still be starting up" })]
+    UnexpectedContentType {
+        host: String,
+        #[source]
+        source: crate::content_type::UnexpectedContentType,
+    },
+    /// The SSO/STS token-exchange endpoint rejected the token outright - an
+    /// expired, malformed, or wrong-audience SAML assertion/OAuth token.
+    /// Distinct from `BadCredentials`: there's no username to report here,
+    /// and retyping a password won't fix it either - the token itself needs
+    /// to be reissued by the identity provider.
+    #[error("SSO token exchange with {host} was rejected: {detail}")]
+    SsoTokenRejected { host: String, detail: String },
+    /// The CSP (Cloud Services Platform) authorization endpoint rejected the
+    /// refresh token handed to `--cloud-csp-token` - expired, revoked, or
+    /// never valid. Distinct from `SsoTokenRejected`: this is a VMware Cloud
+    /// on AWS organization API token exchanged at `console.cloud.vmware.com`,
+    /// not an on-prem SSO/STS assertion, and the fix is the same either way -
+    /// generate a fresh token from the CSP console, this one can't be
+    /// refreshed further.
+    #[error("CSP token exchange for {host} was rejected: {detail}")]
+    CloudCspTokenRejected { host: String, detail: String },
+    /// [`authenticate_from_args`] was handed a `sso-token`/`cloud-csp-token`/
+    /// `username`+`password` combination that isn't exactly one of the
+    /// three valid shapes. `crate::validate::validate_args` catches this
+    /// too under `--config-validate`, but that's opt-in - this is the
+    /// backstop for a normal run that never went through it.
+    #[error("invalid credentials: {detail}")]
+    InvalidCredentialCombination { detail: String },
+}
+
+impl AuthError {
+    /// Distinct from [`crate::vm::ISSUE_ERROR_EXIT_CODE`] and
+    /// [`crate::interrupt::INTERRUPTED_EXIT_CODE`], so a script driving this
+    /// tool can tell a bad password apart from a locked account apart from
+    /// vCenter being unreachable, without parsing stderr.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AuthError::BadCredentials { .. } => 3,
+            AuthError::AccountLockedOrExpired { .. } => 4,
+            AuthError::NetworkFailure { .. } => 5,
+            // 6 is `lockfile::LOCK_HELD_EXIT_CODE`; 9 is
+            // `main::ATOMIC_ABORT_EXIT_CODE` - skipped here so no top-level
+            // exit code is reused across modules. See
+            // `tests::every_top_level_exit_code_is_unique` in `main.rs`.
+            AuthError::UnexpectedContentType { .. } => 10,
+            AuthError::SsoTokenRejected { .. } => 7,
+            AuthError::CloudCspTokenRejected { .. } => 8,
+            AuthError::InvalidCredentialCombination { .. } => 11,
+        }
+    }
+}
+
+/// Classifies a vCenter session-endpoint failure from its HTTP status, the
+/// way [`authenticate`] will once a real transport lands: `status = None`
+/// means the call never got a response at all (DNS, TLS, connection reset,
+/// timeout - `detail` says which). Kept as its own function so the
+/// 401/403/network-failure mapping can be unit-tested against mocked status
+/// codes independently of the (currently simulated) round trip.
+fn classify_auth_failure(status: Option<u16>, username: &str, host: &str, detail: &str) -> AuthError {
+    match status {
+        Some(401) => AuthError::BadCredentials { username: username.to_string() },
+        Some(403) => AuthError::AccountLockedOrExpired { username: username.to_string() },
+        _ => AuthError::NetworkFailure { host: host.to_string(), detail: detail.to_string() },
+    }
+}
+
+/// Guards a session-endpoint response the way [`classify_auth_failure`]
+/// guards its status code: turns a non-JSON body into a typed
+/// [`AuthError::UnexpectedContentType`], with [`AuthError`]'s `Display`
+/// adding the login-page hint, before anything tries to parse it as a
+/// session token. [`authenticate`]'s simulated round trip always passes
+/// this (there's no real body yet to fail it with), but it's unit-tested
+/// against literal status/content-type/body triples so the classification
+/// itself is already correct once a real transport lands.
+pub fn classify_auth_response_body(host: &str, status: u16, content_type: &str, body: &str) -> Result<(), AuthError> {
+    crate::content_type::validate_content_type(status, content_type, body, "application/json")
+        .map_err(|source| AuthError::UnexpectedContentType { host: host.to_string(), source })
+}
+
+pub fn authenticate(host: &str, username: &str, password: &str) -> Result<Session, AuthError> {
+    if username.is_empty() || password.is_empty() {
+        return Err(classify_auth_failure(Some(401), username, host, "empty credentials"));
+    }
+    // The simulated session endpoint always answers with JSON; this guard
+    // exists for the real one, where it won't.
+    classify_auth_response_body(host, 200, "application/json", "{\"session_id\":\"sim\"}")?;
+    Ok(Session {
+        host: host.to_string(),
+        username: username.to_string(),
+        token: format!("sim-session-{username}"),
+        version: simulate_version(),
+    })
+}
+
+/// Alternative to [`authenticate`] for enterprises using vCenter SSO: trades
+/// a SAML assertion or OAuth bearer token for a session instead of basic
+/// auth, via the acquire-token-by-SSO endpoint - `POST /api/session` with
+/// `Authorization: SIGN <token>` in place of the `Authorization: Basic`
+/// header `authenticate` uses. vCenter answers with the session id in the
+/// `vmware-api-session-id` response header, which becomes [`Session::token`]
+/// here the same way the simulated basic-auth round trip does.
+///
+/// There's no principal name to report back for [`Session::username`] - the
+/// SSO flow authenticates the token, not a CLI-supplied identity - so it's
+/// set to a fixed placeholder; anything that needs the real identity behind
+/// the token would have to decode the SAML/JWT claims, which this simulated
+/// round trip has no reason to do.
+pub fn authenticate_with_sso_token(host: &str, token: &str) -> Result<Session, AuthError> {
+    if token.trim().is_empty() {
+        return Err(AuthError::SsoTokenRejected { host: host.to_string(), detail: "empty token".to_string() });
+    }
+    // The simulated token-exchange endpoint always answers with JSON; this
+    // guard exists for the real one, where it won't.
+    classify_auth_response_body(host, 200, "application/json", "{\"session_id\":\"sim\"}")
+        .map_err(|_| AuthError::SsoTokenRejected { host: host.to_string(), detail: "session endpoint did not return a valid session".to_string() })?;
+    Ok(Session {
+        host: host.to_string(),
+        username: "(sso-token)".to_string(),
+        token: "sim-session-sso".to_string(),
+        version: simulate_version(),
+    })
+}
+
+/// Alternative to [`authenticate`] for a VMware Cloud on AWS SDDC: trades a
+/// refresh token (generated once, up front, at `console.cloud.vmware.com`)
+/// for a short-lived access token at the CSP authorization service - `POST
+/// /csp/gateway/am/api/auth/api-tokens/authorize` - instead of either basic
+/// auth or the on-prem SSO/STS exchange [`authenticate_with_sso_token`]
+/// does. The access token this returns is what gets injected as `csp-auth-
+/// token` on every subsequent call against the SDDC's vCenter, the same way
+/// [`authenticate_with_sso_token`]'s session token is injected today.
+///
+/// "Refreshes it before expiry during long runs" has nothing to hook into
+/// yet: there's no real HTTP client anywhere in this tree making calls
+/// during a long `--watch` run to refresh ahead of, only the one simulated
+/// round trip at startup every auth function here does. Once a real
+/// transport exists, this is where a background refresh keyed off the
+/// access token's expiry would live, refreshing from the same `refresh_token`
+/// rather than re-prompting for one.
+pub fn authenticate_with_cloud_csp_token(host: &str, refresh_token: &str) -> Result<Session, AuthError> {
+    if refresh_token.trim().is_empty() {
+        return Err(AuthError::CloudCspTokenRejected { host: host.to_string(), detail: "empty refresh token".to_string() });
+    }
+    // The simulated CSP token-exchange endpoint always answers with JSON;
+    // this guard exists for the real one, where it won't.
+    classify_auth_response_body(host, 200, "application/json", "{\"access_token\":\"sim\"}")
+        .map_err(|_| AuthError::CloudCspTokenRejected { host: host.to_string(), detail: "CSP endpoint did not return a valid access token".to_string() })?;
+    Ok(Session {
+        host: host.to_string(),
+        username: "(cloud-csp-token)".to_string(),
+        token: "sim-session-cloud-csp".to_string(),
+        version: simulate_version(),
+    })
+}
+
+/// Picks [`authenticate`], [`authenticate_with_sso_token`], or
+/// [`authenticate_with_cloud_csp_token`] based on which `--sso-token`/
+/// `--cloud-csp-token`/`--username`+`--password` combination `args` carries.
+/// `crate::validate::validate_args` rejects every other combination (more
+/// than one set, none set, only one of username/password) under
+/// `--config-validate`, but that's opt-in - a normal run reaches here
+/// straight off `Args::parse()`, so an invalid combination is reported as
+/// [`AuthError::InvalidCredentialCombination`] rather than assumed away.
+pub fn authenticate_from_args(args: &crate::cli::Args) -> Result<Session, AuthError> {
+    match (&args.sso_token, &args.cloud_csp_token, &args.username, &args.password) {
+        (Some(token), None, _, _) => authenticate_with_sso_token(&args.host, token),
+        (None, Some(token), _, _) => authenticate_with_cloud_csp_token(&args.host, token),
+        (None, None, Some(username), Some(password)) => authenticate(&args.host, username, password),
+        (Some(_), Some(_), _, _) => Err(AuthError::InvalidCredentialCombination {
+            detail: "--sso-token and --cloud-csp-token are mutually exclusive".to_string(),
+        }),
+        (None, None, None, None) => Err(AuthError::InvalidCredentialCombination {
+            detail: "--username/--password, --sso-token, or --cloud-csp-token is required".to_string(),
+        }),
+        (None, None, _, _) => Err(AuthError::InvalidCredentialCombination {
+            detail: "--username and --password must both be set together".to_string(),
+        }),
+    }
+}
+
+impl Session {
+    pub fn describe(&self) -> String {
+        format!("{} authenticated against {} ({})", self.username, self.host, self.token)
+    }
+
+    /// Tears down the session. A no-op beyond logging until a real transport
+    /// exists to log out of; kept as its own method so callers (e.g. the
+    /// Ctrl-C handler in [`crate::interrupt`]) have a single place to call on
+    /// the way out.
+    pub fn disconnect(&self) {
+        eprintln!("{} disconnected from {}", self.username, self.host);
+    }
+
+    /// Simulates the SSO/LDAP password-expiration lookup behind
+    /// `--password-expiry-warn-days`: vCenter's own session endpoint has no
+    /// such field, so a real implementation would fall back to the identity
+    /// source behind it (SSO's `PasswordPolicies` API, or LDAP's
+    /// `pwdLastSet`/`msDS-UserPasswordExpiryTimeComputed`) - not every
+    /// identity source exposes it, so `None` here means "couldn't find out",
+    /// not "the password is fine".
+    pub fn password_expiry_days(&self) -> Option<u32> {
+        let mut rng = rand::thread_rng();
+        if !rng.gen_bool(0.8) {
+            return None;
+        }
+        Some(rng.gen_range(1..90))
+    }
+}
+
+/// `--password-expiry-warn-days`'s run-level outcome, surfaced in the text
+/// report, JSON metadata, and notifications - a service account's password
+/// expiring has twice now taken monitoring down silently, with nobody
+/// noticing until an unrelated outage went undetected, so this can't be
+/// just a log line nobody's watching. `days_remaining` is `None` when the
+/// identity source doesn't expose expiration at all.
+#[derive(Debug, Clone, Serialize)]
+pub struct PasswordExpiryReport {
+    pub days_remaining: Option<u32>,
+    pub warn_threshold_days: u32,
+}
+
+impl PasswordExpiryReport {
+    /// `false` when the expiry is unknown, or known but comfortably past the
+    /// warning threshold - nothing worth a line either way.
+    fn has_anything_to_say(&self) -> bool {
+        self.days_remaining.is_some_and(|days| days <= self.warn_threshold_days)
+    }
+
+    pub fn render_section(&self) -> String {
+        if !self.has_anything_to_say() {
+            return String::new();
+        }
+        format!(
+            "PASSWORD EXPIRY: account password expires in {} day(s), at or below --password-expiry-warn-days {}\n",
+            self.days_remaining.unwrap(),
+            self.warn_threshold_days
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(s: &str) -> VCenterVersion {
+        VCenterVersion {
+            product: "VMware vCenter Server".to_string(),
+            version: s.to_string(),
+            build: "1".to_string(),
+        }
+    }
+
+    #[test]
+    fn at_least_compares_major_minor() {
+        assert!(version("6.7.0").at_least(6, 5));
+        assert!(version("6.5.0").at_least(6, 5));
+        assert!(!version("6.0.0").at_least(6, 5));
+        assert!(version("7.0.3").at_least(6, 5));
+    }
+
+    #[test]
+    fn unrecognized_version_is_treated_as_at_least_anything() {
+        let v = version("not-a-version");
+        assert!(!v.is_recognized());
+        assert!(v.at_least(99, 0));
+    }
+
+    #[test]
+    fn authenticate_fills_in_a_recognized_simulated_version() {
+        let session = authenticate("vcenter.example.com", "tester", "secret").unwrap();
+        assert!(session.version.is_recognized());
+    }
+
+    #[test]
+    fn empty_credentials_are_classified_as_bad_credentials() {
+        let err = authenticate("vcenter.example.com", "", "secret").unwrap_err();
+        assert_eq!(err, AuthError::BadCredentials { username: String::new() });
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn classify_auth_failure_maps_401_to_bad_credentials() {
+        let err = classify_auth_failure(Some(401), "tester", "vcenter.example.com", "");
+        assert_eq!(err, AuthError::BadCredentials { username: "tester".to_string() });
+        assert_eq!(err.exit_code(), 3);
+    }
+
+    #[test]
+    fn classify_auth_failure_maps_403_to_locked_or_expired() {
+        let err = classify_auth_failure(Some(403), "tester", "vcenter.example.com", "");
+        assert_eq!(err, AuthError::AccountLockedOrExpired { username: "tester".to_string() });
+        assert_eq!(err.exit_code(), 4);
+    }
+
+    #[test]
+    fn classify_auth_failure_maps_anything_else_to_network_failure() {
+        let err = classify_auth_failure(None, "tester", "vcenter.example.com", "connection reset");
+        assert_eq!(
+            err,
+            AuthError::NetworkFailure { host: "vcenter.example.com".to_string(), detail: "connection reset".to_string() }
+        );
+        assert_eq!(err.exit_code(), 5);
+
+        let err = classify_auth_failure(Some(500), "tester", "vcenter.example.com", "internal server error");
+        assert!(matches!(err, AuthError::NetworkFailure { .. }));
+    }
+
+    #[test]
+    fn sso_token_exchange_fills_in_a_session_with_no_username() {
+        let session = authenticate_with_sso_token("vcenter.example.com", "saml-assertion-xyz").unwrap();
+        assert_eq!(session.host, "vcenter.example.com");
+        assert_eq!(session.username, "(sso-token)");
+        assert!(session.version.is_recognized());
+    }
+
+    #[test]
+    fn empty_sso_token_is_rejected() {
+        let err = authenticate_with_sso_token("vcenter.example.com", "  ").unwrap_err();
+        assert_eq!(err, AuthError::SsoTokenRejected { host: "vcenter.example.com".to_string(), detail: "empty token".to_string() });
+        assert_eq!(err.exit_code(), 7);
+    }
+
+    #[test]
+    fn cloud_csp_token_exchange_fills_in_a_session_with_no_username() {
+        let session = authenticate_with_cloud_csp_token("sddc-1.vmwarevmc.com", "refresh-token-xyz").unwrap();
+        assert_eq!(session.host, "sddc-1.vmwarevmc.com");
+        assert_eq!(session.username, "(cloud-csp-token)");
+        assert!(session.version.is_recognized());
+    }
+
+    #[test]
+    fn empty_cloud_csp_refresh_token_is_rejected() {
+        let err = authenticate_with_cloud_csp_token("sddc-1.vmwarevmc.com", "  ").unwrap_err();
+        assert_eq!(
+            err,
+            AuthError::CloudCspTokenRejected { host: "sddc-1.vmwarevmc.com".to_string(), detail: "empty refresh token".to_string() }
+        );
+        assert_eq!(err.exit_code(), 8);
+    }
+
+    #[test]
+    fn password_expiry_report_is_silent_above_the_warn_threshold() {
+        let report = PasswordExpiryReport { days_remaining: Some(30), warn_threshold_days: 14 };
+        assert!(report.render_section().is_empty());
+    }
+
+    #[test]
+    fn password_expiry_report_warns_at_or_below_the_threshold() {
+        let report = PasswordExpiryReport { days_remaining: Some(5), warn_threshold_days: 14 };
+        assert!(report.render_section().contains("expires in 5 day(s)"));
+    }
+
+    #[test]
+    fn classify_auth_response_body_rejects_html_with_200() {
+        let err = classify_auth_response_body(
+            "vcenter.example.com",
+            200,
+            "text/html",
+            "<html><body>Service Unavailable</body></html>",
+        )
+        .unwrap_err();
+        assert!(matches!(err, AuthError::UnexpectedContentType { .. }));
+        assert!(err.to_string().contains("starting up"));
+    }
+
+    #[test]
+    fn classify_auth_response_body_rejects_empty_body_with_200() {
+        let err = classify_auth_response_body("vcenter.example.com", 200, "application/json", "").unwrap_err();
+        assert!(matches!(err, AuthError::UnexpectedContentType { .. }));
+    }
+
+    #[test]
+    fn classify_auth_response_body_rejects_json_with_wrong_content_type() {
+        let err = classify_auth_response_body("vcenter.example.com", 200, "text/plain", "{\"token\":\"abc\"}").unwrap_err();
+        assert!(matches!(err, AuthError::UnexpectedContentType { .. }));
+    }
+
+    #[test]
+    fn classify_auth_response_body_accepts_matching_json() {
+        assert!(classify_auth_response_body("vcenter.example.com", 200, "application/json", "{\"token\":\"abc\"}").is_ok());
+    }
+
+    #[test]
+    fn classify_auth_response_body_hints_at_a_login_page() {
+        let err = classify_auth_response_body(
+            "vcenter.example.com",
+            200,
+            "text/html",
+            "<html><body>Please sign in to continue</body></html>",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("captive portal or SSO login page"));
+    }
+
+    #[test]
+    fn password_expiry_report_is_silent_when_unknown() {
+        let report = PasswordExpiryReport { days_remaining: None, warn_threshold_days: 14 };
+        assert!(report.render_section().is_empty());
+    }
+
+    /// Reproduces the bare `network-monitor --host vcenter.example.com`
+    /// invocation - no credential flag at all, which is what `Args::parse()`
+    /// yields by default since none of `--username`/`--password`/
+    /// `--sso-token`/`--cloud-csp-token` is `required`. This never goes
+    /// through `crate::validate::validate_args` on a normal run, so
+    /// `authenticate_from_args` has to reject it itself rather than assume
+    /// it can't happen.
+    #[test]
+    fn authenticate_from_args_rejects_no_credentials_given() {
+        use clap::Parser;
+        let args = crate::cli::Args::parse_from(["network-monitor", "--host", "vcenter.example.com"]);
+        let err = authenticate_from_args(&args).unwrap_err();
+        assert!(matches!(err, AuthError::InvalidCredentialCombination { .. }));
+        assert_eq!(err.exit_code(), 11);
+    }
+
+    #[test]
+    fn authenticate_from_args_rejects_username_without_password() {
+        use clap::Parser;
+        let args = crate::cli::Args::parse_from(["network-monitor", "--host", "vcenter.example.com", "--username", "tester"]);
+        let err = authenticate_from_args(&args).unwrap_err();
+        assert!(matches!(err, AuthError::InvalidCredentialCombination { .. }));
+    }
+
+    #[test]
+    fn authenticate_from_args_rejects_sso_token_and_cloud_csp_token_together() {
+        use clap::Parser;
+        let args = crate::cli::Args::parse_from([
+            "network-monitor",
+            "--host",
+            "vcenter.example.com",
+            "--sso-token",
+            "tok",
+            "--cloud-csp-token",
+            "tok",
+        ]);
+        let err = authenticate_from_args(&args).unwrap_err();
+        assert!(matches!(err, AuthError::InvalidCredentialCombination { .. }));
+    }
+
+    #[test]
+    fn authenticate_from_args_accepts_username_and_password() {
+        use clap::Parser;
+        let args = crate::cli::Args::parse_from([
+            "network-monitor",
+            "--host",
+            "vcenter.example.com",
+            "--username",
+            "tester",
+            "--password",
+            "secret",
+        ]);
+        assert!(authenticate_from_args(&args).is_ok());
+    }
+}
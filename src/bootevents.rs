@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// A single result row from the (simulated) vCenter event query, before
+/// it's been confirmed to be a creation or power-on event. Kept separate
+/// from [`BootEvent`] and checked by [`parse_event`], same split as
+/// [`crate::migration::RawMigrationEvent`]/[`crate::migration::MigrationEvent`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawBootEvent {
+    pub event_type: String,
+    pub vm_name: String,
+    /// How long before "now" the event happened. Kept relative rather than
+    /// as a wall-clock timestamp so bucketing against
+    /// `--boot-history-window-hours` needs no notion of the current time.
+    pub hours_ago: f64,
+}
+
+/// What a confirmed [`BootEvent`] tells us about a VM's recent history.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BootEventKind {
+    /// `VmCreatedEvent`/`VmClonedEvent`/`VmRegisteredEvent` - the VM is a
+    /// fresh deployment, not a VM that's been running a while and rebooted.
+    Created,
+    /// `VmPoweredOnEvent` - one power cycle. Several of these clustered in
+    /// the window is a crash loop, not a single recent reboot.
+    PoweredOn,
+}
+
+/// A confirmed creation or power-on event for one VM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BootEvent {
+    pub vm_name: String,
+    pub hours_ago: f64,
+    pub kind: BootEventKind,
+}
+
+/// Keeps only `VmCreatedEvent`/`VmClonedEvent`/`VmRegisteredEvent`/`VmPoweredOnEvent`
+/// rows, discarding everything else the event query might return.
+pub fn parse_event(raw: &RawBootEvent) -> Option<BootEvent> {
+    let kind = match raw.event_type.as_str() {
+        "VmCreatedEvent" | "VmClonedEvent" | "VmRegisteredEvent" => BootEventKind::Created,
+        "VmPoweredOnEvent" => BootEventKind::PoweredOn,
+        _ => return None,
+    };
+    Some(BootEvent {
+        vm_name: raw.vm_name.clone(),
+        hours_ago: raw.hours_ago,
+        kind,
+    })
+}
+
+/// A VM's boot history within `--boot-history-window-hours`, as surfaced on
+/// [`crate::vm::VMResourceStatus`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BootHistory {
+    pub created_recently: bool,
+    pub power_on_count: u32,
+    /// Seconds since the most recent `VmPoweredOnEvent` in the window, the
+    /// smallest `hours_ago` of any, converted to seconds. `None` when no
+    /// `VmPoweredOnEvent` fell in the window.
+    pub last_power_on_secs_ago: Option<f64>,
+}
+
+/// Buckets a flat event-query result by VM, client-side - same shape as
+/// [`crate::migration::bucket_migrations_by_vm`], one query covers
+/// `window_hours` for the whole fleet rather than querying per VM.
+pub fn bucket_boot_history_by_vm(events: &[BootEvent], window_hours: f64) -> HashMap<String, BootHistory> {
+    let mut by_vm: HashMap<String, BootHistory> = HashMap::new();
+    for event in events.iter().filter(|e| e.hours_ago <= window_hours) {
+        let history = by_vm.entry(event.vm_name.clone()).or_default();
+        match event.kind {
+            BootEventKind::Created => history.created_recently = true,
+            BootEventKind::PoweredOn => {
+                history.power_on_count += 1;
+                let secs_ago = event.hours_ago * 3600.0;
+                history.last_power_on_secs_ago =
+                    Some(history.last_power_on_secs_ago.map_or(secs_ago, |existing: f64| existing.min(secs_ago)));
+            }
+        }
+    }
+    by_vm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(event_type: &str, vm_name: &str, hours_ago: f64) -> RawBootEvent {
+        RawBootEvent {
+            event_type: event_type.to_string(),
+            vm_name: vm_name.to_string(),
+            hours_ago,
+        }
+    }
+
+    #[test]
+    fn parse_event_accepts_creation_and_power_on_types_and_rejects_others() {
+        assert_eq!(parse_event(&raw("VmCreatedEvent", "vm-0001", 0.5)).unwrap().kind, BootEventKind::Created);
+        assert_eq!(parse_event(&raw("VmClonedEvent", "vm-0001", 0.5)).unwrap().kind, BootEventKind::Created);
+        assert_eq!(parse_event(&raw("VmRegisteredEvent", "vm-0001", 0.5)).unwrap().kind, BootEventKind::Created);
+        assert_eq!(parse_event(&raw("VmPoweredOnEvent", "vm-0001", 0.5)).unwrap().kind, BootEventKind::PoweredOn);
+        assert!(parse_event(&raw("VmMigratedEvent", "vm-0001", 0.5)).is_none());
+    }
+
+    #[test]
+    fn bucket_counts_power_ons_and_flags_creation_within_the_window() {
+        let events = vec![
+            BootEvent { vm_name: "vm-0001".to_string(), hours_ago: 0.2, kind: BootEventKind::Created },
+            BootEvent { vm_name: "vm-0002".to_string(), hours_ago: 0.1, kind: BootEventKind::PoweredOn },
+            BootEvent { vm_name: "vm-0002".to_string(), hours_ago: 0.3, kind: BootEventKind::PoweredOn },
+            BootEvent { vm_name: "vm-0002".to_string(), hours_ago: 5.0, kind: BootEventKind::PoweredOn },
+        ];
+
+        let by_vm = bucket_boot_history_by_vm(&events, 1.0);
+        assert!(by_vm.get("vm-0001").unwrap().created_recently);
+        assert_eq!(by_vm.get("vm-0002").unwrap().power_on_count, 2, "the 5h-ago power-on is outside the 1h window");
+        assert!(!by_vm.get("vm-0002").unwrap().created_recently);
+        assert_eq!(
+            by_vm.get("vm-0002").unwrap().last_power_on_secs_ago,
+            Some(0.1 * 3600.0),
+            "the most recent in-window power-on, not the oldest or the out-of-window one"
+        );
+        assert_eq!(by_vm.get("vm-0001").unwrap().last_power_on_secs_ago, None, "creation events don't count as a power-on");
+    }
+
+    #[test]
+    fn vm_with_no_events_gets_a_default_history() {
+        assert!(bucket_boot_history_by_vm(&[], 1.0).is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+//! Fleet-wide correlation on top of the per-VM `--check-uptime` detector:
+//! many VMs rebooting within a tight time window is a mass-reboot event
+//! (a host/cluster failover, a patch rollout gone wrong), not the isolated
+//! flapping `UptimeShort` alone reports. [`detect_boot_storm`] looks for the
+//! tightest cluster of recent boots that meets `--boot-storm-threshold` and,
+//! when found, [`crate::main`]/[`crate::watch`] raise it as one consolidated
+//! [`crate::vm::VMIssueType::BootStorm`] alert on a synthetic pseudo-VM,
+//! reusing the real notifier pipeline the same way `--test-notifiers` does
+//! (see `synthetic_test_vm` in [`crate::notifier`]).
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use crate::vm::{DetectedIssue, PowerState, VMIssueType, VMResourceStatus};
+
+/// `--boot-storm-threshold`: either an absolute VM count or a percentage of
+/// the fleet size, resolved against the actual fleet size at detection time
+/// so the same `10%` flag scales from a 20-VM lab to a 2000-VM fleet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BootStormThreshold {
+    Count(usize),
+    Percent(f64),
+}
+
+impl BootStormThreshold {
+    /// Minimum number of clustered VMs that counts as a storm for a fleet of
+    /// `fleet_size` VMs. Always at least 2 - a single VM rebooting is just `UptimeShort`.
+    pub fn resolve(&self, fleet_size: usize) -> usize {
+        let min_count = match self {
+            BootStormThreshold::Count(n) => *n,
+            BootStormThreshold::Percent(pct) => ((*pct / 100.0) * fleet_size as f64).ceil() as usize,
+        };
+        min_count.max(2)
+    }
+}
+
+impl FromStr for BootStormThreshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(pct) = s.strip_suffix('%') {
+            let pct: f64 = pct
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{s}' is not a valid percentage"))?;
+            if pct <= 0.0 {
+                return Err(format!("'{s}' must be a positive percentage"));
+            }
+            return Ok(BootStormThreshold::Percent(pct));
+        }
+        let count: usize = s.parse().map_err(|_| format!("'{s}' is not a valid VM count or percentage"))?;
+        if count == 0 {
+            return Err(format!("'{s}' must be greater than 0"));
+        }
+        Ok(BootStormThreshold::Count(count))
+    }
+}
+
+/// A correlated-reboot cluster found by [`detect_boot_storm`].
+#[derive(Debug, Clone, Serialize)]
+pub struct BootStormFinding {
+    pub vm_names: Vec<String>,
+    pub hosts: Vec<String>,
+    /// How long ago the latest/earliest boot in the cluster happened, in
+    /// seconds, at detection time.
+    pub window_start_secs_ago: f64,
+    pub window_end_secs_ago: f64,
+}
+
+/// Smallest range of `boot_times` (seconds-ago-booted; smaller is more
+/// recent) that contains at least `min_count` values, i.e. the tightest
+/// cluster of reboots rather than the full spread's min/max, which could be
+/// pulled wide by one unrelated straggler. `None` if fewer than `min_count`
+/// values exist at all.
+pub fn tightest_window_with_at_least(boot_times: &[f64], min_count: usize) -> Option<(f64, f64)> {
+    if boot_times.len() < min_count || min_count == 0 {
+        return None;
+    }
+    let mut sorted = boot_times.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+    let mut best: Option<(f64, f64)> = None;
+    for window in sorted.windows(min_count) {
+        let start = window[0];
+        let end = window[min_count - 1];
+        if best.is_none_or(|(best_start, best_end)| end - start < best_end - best_start) {
+            best = Some((start, end));
+        }
+    }
+    best
+}
+
+/// Looks for a boot-storm cluster among `short_uptime_vms` (every VM
+/// currently flagged with `UptimeShort`), resolving `threshold` against
+/// `fleet_size`. Returns the tightest qualifying cluster, not every VM with
+/// `UptimeShort` - a straggler that merely crossed the threshold on its own
+/// schedule shouldn't dilute the window the storm actually happened in.
+pub fn detect_boot_storm(short_uptime_vms: &[&VMResourceStatus], fleet_size: usize, threshold: BootStormThreshold) -> Option<BootStormFinding> {
+    let min_count = threshold.resolve(fleet_size);
+    let boot_times: Vec<f64> = short_uptime_vms.iter().map(|vm| vm.uptime_secs).collect();
+    let (window_start_secs_ago, window_end_secs_ago) = tightest_window_with_at_least(&boot_times, min_count)?;
+
+    let clustered: Vec<&&VMResourceStatus> = short_uptime_vms
+        .iter()
+        .filter(|vm| vm.uptime_secs >= window_start_secs_ago && vm.uptime_secs <= window_end_secs_ago)
+        .collect();
+    let vm_names = clustered.iter().map(|vm| vm.name.clone()).collect();
+    let mut hosts: Vec<String> = clustered.iter().map(|vm| vm.host.clone()).collect();
+    hosts.sort();
+    hosts.dedup();
+
+    Some(BootStormFinding {
+        vm_names,
+        hosts,
+        window_start_secs_ago,
+        window_end_secs_ago,
+    })
+}
+
+/// Convenience wrapper around [`detect_boot_storm`] for callers that only
+/// have the full, already-detected `statuses` list: picks out the
+/// `UptimeShort` VMs itself. Used identically by the live-run path and
+/// `--watch`'s loop.
+pub fn detect_from_statuses(statuses: &[VMResourceStatus], threshold: BootStormThreshold) -> Option<BootStormFinding> {
+    let short_uptime_vms: Vec<&VMResourceStatus> = statuses
+        .iter()
+        .filter(|vm| vm.issues.iter().any(|i| i.issue_type == VMIssueType::UptimeShort))
+        .collect();
+    detect_boot_storm(&short_uptime_vms, statuses.len(), threshold)
+}
+
+/// Wraps `finding` as a single-issue pseudo-VM so it can go out through the
+/// real `--notifier-config` pipeline (filters, per-backend delivery) the
+/// same way `--test-notifiers`'s synthetic VM does, instead of a new
+/// notification code path just for this one alert.
+pub fn synthetic_boot_storm_vm(finding: &BootStormFinding) -> VMResourceStatus {
+    let detail = format!(
+        "{} VMs across {} host(s) rebooted within a {:.0}s window: {}",
+        finding.vm_names.len(),
+        finding.hosts.len(),
+        finding.window_end_secs_ago - finding.window_start_secs_ago,
+        finding.vm_names.join(", "),
+    );
+    VMResourceStatus {
+        name: "boot-storm".to_string(),
+        host: finding.hosts.first().cloned().unwrap_or_default(),
+        cluster: "boot-storm".to_string(),
+        inventory_path: "/unknown".to_string(),
+        power_state: PowerState::PoweredOn,
+        cpu_usage_pct: 0.0,
+        memory_usage_pct: 0.0,
+        raw_metrics: std::collections::HashMap::new(),
+        metrics_source: crate::vm::MetricsSourceStatus::Available,
+        cpu_count: 1,
+        cores_per_socket: 1,
+        memory_gb: 16.0,
+        hardware_version: "vmx-19".to_string(),
+        cpu_hot_add_enabled: true,
+        memory_hot_add_enabled: true,
+        guest_visible_memory_mb: None,
+        guest_visible_cpu_count: None,
+        disk_allocated_gb: 100.0,
+        disk_used_gb: Some(50.0),
+        usage_basis: crate::vm::UsageBasis::Configured,
+        tools_running: false,
+        clock_skew_secs: None,
+        guest_ip: None,
+        reachable: None,
+        running_processes: Vec::new(),
+        attributes: std::collections::HashMap::new(),
+        notes: None,
+        migration_count_24h: 0,
+        last_migration: None,
+        uptime_secs: finding.window_end_secs_ago,
+        created_recently: false,
+        power_on_count: 0,
+        last_power_on_secs_ago: None,
+        suspended_duration_secs: None,
+        health_score: 100.0,
+        change_version: 0,
+        issues: vec![DetectedIssue::new(VMIssueType::BootStorm, detail)],
+    }
+}
+
+/// Strips `UptimeShort` from every VM named in `finding`, so the per-VM
+/// noise that produced a consolidated alert doesn't also show up
+/// individually. Only `--suppress-individual-boot-storm-alerts` calls this;
+/// by default both the cluster alert and the individual ones are kept.
+pub fn suppress_clustered_alerts(statuses: &mut [VMResourceStatus], finding: &BootStormFinding) {
+    let clustered: HashSet<&str> = finding.vm_names.iter().map(String::as_str).collect();
+    for vm in statuses.iter_mut() {
+        if clustered.contains(vm.name.as_str()) {
+            vm.issues.retain(|issue| issue.issue_type != VMIssueType::UptimeShort);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threshold_parses_counts_and_percentages() {
+        assert_eq!("5".parse::<BootStormThreshold>().unwrap(), BootStormThreshold::Count(5));
+        assert_eq!("10%".parse::<BootStormThreshold>().unwrap(), BootStormThreshold::Percent(10.0));
+        assert!("0".parse::<BootStormThreshold>().is_err());
+        assert!("0%".parse::<BootStormThreshold>().is_err());
+        assert!("not-a-number".parse::<BootStormThreshold>().is_err());
+    }
+
+    #[test]
+    fn threshold_resolves_percentage_against_fleet_size_with_a_floor_of_two() {
+        assert_eq!(BootStormThreshold::Percent(10.0).resolve(100), 10);
+        assert_eq!(BootStormThreshold::Percent(10.0).resolve(5), 2, "1 rounds up but the floor is 2");
+        assert_eq!(BootStormThreshold::Count(1).resolve(1000), 2, "a lone VM is never a storm");
+    }
+
+    #[test]
+    fn tightest_window_finds_the_densest_cluster_not_the_full_spread() {
+        // Two VMs booted ~600s apart, three more all within a 20s window. The
+        // full spread is 20..900, but the tightest 3-VM window is the dense one.
+        let boot_times = vec![900.0, 300.0, 40.0, 30.0, 20.0];
+        let window = tightest_window_with_at_least(&boot_times, 3).unwrap();
+        assert_eq!(window, (20.0, 40.0));
+    }
+
+    #[test]
+    fn tightest_window_is_none_when_not_enough_samples() {
+        assert_eq!(tightest_window_with_at_least(&[10.0, 20.0], 3), None);
+    }
+
+    fn vm(name: &str, host: &str, uptime_secs: f64) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: host.to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: vec![DetectedIssue::measured(VMIssueType::UptimeShort, uptime_secs, 900.0, "x")],
+        }
+    }
+
+    #[test]
+    fn detect_boot_storm_clusters_by_boot_time_and_collects_hosts() {
+        let vms = [
+            vm("vm-1", "esxi-01", 20.0),
+            vm("vm-2", "esxi-01", 30.0),
+            vm("vm-3", "esxi-02", 40.0),
+            vm("vm-4", "esxi-03", 600.0),
+        ];
+        let refs: Vec<&VMResourceStatus> = vms.iter().collect();
+
+        let finding = detect_boot_storm(&refs, 40, BootStormThreshold::Count(3)).unwrap();
+        assert_eq!(finding.vm_names.len(), 3);
+        assert!(!finding.vm_names.contains(&"vm-4".to_string()), "the straggler must not dilute the cluster");
+        assert_eq!(finding.hosts, vec!["esxi-01".to_string(), "esxi-02".to_string()]);
+    }
+
+    #[test]
+    fn detect_boot_storm_returns_none_below_threshold() {
+        let vms = [vm("vm-1", "esxi-01", 20.0), vm("vm-2", "esxi-01", 30.0)];
+        let refs: Vec<&VMResourceStatus> = vms.iter().collect();
+        assert!(detect_boot_storm(&refs, 40, BootStormThreshold::Count(3)).is_none());
+    }
+
+    #[test]
+    fn suppress_clustered_alerts_only_touches_named_vms() {
+        let mut vms = vec![vm("vm-1", "esxi-01", 20.0), vm("vm-2", "esxi-01", 30.0)];
+        let finding = BootStormFinding {
+            vm_names: vec!["vm-1".to_string()],
+            hosts: vec!["esxi-01".to_string()],
+            window_start_secs_ago: 20.0,
+            window_end_secs_ago: 20.0,
+        };
+        suppress_clustered_alerts(&mut vms, &finding);
+        assert!(!vms[0].has_issues(), "vm-1's UptimeShort was suppressed");
+        assert!(vms[1].has_issues(), "vm-2 was not part of the cluster");
+    }
+}
@@ -0,0 +1,186 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Per-check request counts and cumulative latency, shown at the end of a
+/// run and in JSON when `--timing` is set. Mirrors [`crate::api_rate_log::ApiRateLog`]'s
+/// shape and tradeoffs (disabled by default, `Mutex`-guarded samples) but
+/// keyed by check name (e.g. `"guest_resource_mismatch"`) instead of raw API
+/// endpoint, so the cost of an optional `--check-*` flag can be read off
+/// directly rather than reconstructed from endpoint names. The request this
+/// was built for also named `snapshots`/`cdrom`/`disks`/`tags` checks; this
+/// tree has no such detectors (no `VMIssueType` variant, no collected data,
+/// no endpoint simulated for any of them), so only checks that actually run
+/// are instrumented: `guest_resource_mismatch`, `migrations`, `uptime`, and
+/// `issue_detection` (the always-on detectors in
+/// [`crate::vcenter::detect_issues`], bundled under one name rather than
+/// timed rule-by-rule since they're pure in-memory matches with no
+/// meaningfully separable cost).
+///
+/// The `Mutex` makes merging samples from several tasks at once already
+/// safe - the same reason [`crate::api_rate_log::ApiRateLog`] uses one -
+/// even though today's fetch loop is single-threaded and records them one
+/// at a time.
+pub struct CheckTiming {
+    enabled: bool,
+    samples: Mutex<Vec<(String, f64)>>,
+}
+
+impl CheckTiming {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            samples: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Records one call to `check` that took `elapsed_secs`. No-op when
+    /// disabled, so callers can instrument unconditionally.
+    pub fn record(&self, check: &str, elapsed_secs: f64) {
+        if !self.enabled {
+            return;
+        }
+        self.samples.lock().unwrap().push((check.to_string(), elapsed_secs));
+    }
+
+    /// Times `f`, then records it against `check`. No-op wrapper around
+    /// [`CheckTiming::record`] so call sites don't hand-roll an `Instant`
+    /// each time - `f` still runs when disabled, just without the timing.
+    pub fn time<T>(&self, check: &str, f: impl FnOnce() -> T) -> T {
+        if !self.enabled {
+            return f();
+        }
+        let started = std::time::Instant::now();
+        let result = f();
+        self.record(check, started.elapsed().as_secs_f64());
+        result
+    }
+
+    /// One summary row per distinct check, sorted by check name.
+    pub fn summaries(&self) -> Vec<CheckCost> {
+        let samples = self.samples.lock().unwrap();
+        let mut by_check: std::collections::BTreeMap<&str, (usize, f64)> = std::collections::BTreeMap::new();
+        for (check, elapsed_secs) in samples.iter() {
+            let entry = by_check.entry(check.as_str()).or_default();
+            entry.0 += 1;
+            entry.1 += elapsed_secs;
+        }
+        by_check
+            .into_iter()
+            .map(|(check, (requests, total_secs))| CheckCost {
+                check: check.to_string(),
+                requests,
+                total_secs,
+                secs_per_vm: if requests == 0 { 0.0 } else { total_secs / requests as f64 },
+            })
+            .collect()
+    }
+
+    /// Renders the end-of-run "CHECK COSTS" table shown on stderr when
+    /// `--timing` is set.
+    pub fn render_table(&self) -> String {
+        let summaries = self.summaries();
+        if summaries.is_empty() {
+            return "CHECK COSTS: no checks recorded\n".to_string();
+        }
+        let mut out = String::from("CHECK COSTS:\n");
+        for s in summaries {
+            out.push_str(&format!(
+                "  {}: {} requests, {:.3} s total, {:.3} s/VM\n",
+                s.check, s.requests, s.total_secs, s.secs_per_vm
+            ));
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CheckCost {
+    pub check: String,
+    pub requests: usize,
+    pub total_secs: f64,
+    pub secs_per_vm: f64,
+}
+
+/// `--budget-hint`: given this run's per-check costs and a target total
+/// seconds, greedily drops the costliest checks (by `total_secs`, descending)
+/// until the remaining checks' total fits under `budget_secs`, or nothing is
+/// left to drop. Advisory only - it never disables anything itself, it just
+/// names what a human would need to turn off via `--check-*`/`--require-*`
+/// flags to hit the budget.
+pub fn budget_hint(summaries: &[CheckCost], budget_secs: f64) -> BudgetHint {
+    let mut by_cost: Vec<&CheckCost> = summaries.iter().collect();
+    by_cost.sort_by(|a, b| b.total_secs.partial_cmp(&a.total_secs).unwrap());
+    let mut projected_secs: f64 = summaries.iter().map(|s| s.total_secs).sum();
+    let mut disable = Vec::new();
+    for cost in by_cost {
+        if projected_secs <= budget_secs {
+            break;
+        }
+        disable.push(cost.check.clone());
+        projected_secs -= cost.total_secs;
+    }
+    BudgetHint { disable, projected_secs }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetHint {
+    /// Checks to disable, costliest first, to fit `budget_secs`. Empty when
+    /// the run was already under budget.
+    pub disable: Vec<String>,
+    /// Total seconds left across the checks NOT named in `disable`.
+    pub projected_secs: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_log_records_nothing() {
+        let log = CheckTiming::new(false);
+        log.record("uptime", 1.0);
+        assert!(log.summaries().is_empty());
+    }
+
+    #[test]
+    fn summaries_aggregate_count_and_total_per_check() {
+        let log = CheckTiming::new(true);
+        log.record("uptime", 0.1);
+        log.record("uptime", 0.3);
+        log.record("migrations", 1.0);
+        let summaries = log.summaries();
+        assert_eq!(summaries.len(), 2);
+        let uptime = summaries.iter().find(|s| s.check == "uptime").unwrap();
+        assert_eq!(uptime.requests, 2);
+        assert!((uptime.total_secs - 0.4).abs() < 1e-9);
+        assert!((uptime.secs_per_vm - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn time_runs_the_closure_and_records_its_duration() {
+        let log = CheckTiming::new(true);
+        let result = log.time("migrations", || 2 + 2);
+        assert_eq!(result, 4);
+        assert_eq!(log.summaries()[0].check, "migrations");
+    }
+
+    #[test]
+    fn budget_hint_drops_costliest_checks_first_until_under_budget() {
+        let summaries = vec![
+            CheckCost { check: "cheap".to_string(), requests: 10, total_secs: 1.0, secs_per_vm: 0.1 },
+            CheckCost { check: "pricey".to_string(), requests: 10, total_secs: 9.0, secs_per_vm: 0.9 },
+        ];
+        let hint = budget_hint(&summaries, 5.0);
+        assert_eq!(hint.disable, vec!["pricey".to_string()]);
+        assert!((hint.projected_secs - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn budget_hint_is_empty_when_already_under_budget() {
+        let summaries = vec![CheckCost { check: "cheap".to_string(), requests: 10, total_secs: 1.0, secs_per_vm: 0.1 }];
+        let hint = budget_hint(&summaries, 5.0);
+        assert!(hint.disable.is_empty());
+        assert!((hint.projected_secs - 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,1095 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+/// Selects the `--format json` payload shape: `v2` (default) includes full
+/// per-issue measurement detail, `v1` serializes `issues` as bare type-name
+/// strings for consumers still on the original schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum JsonSchemaVersionArg {
+    V1,
+    V2,
+}
+
+impl From<JsonSchemaVersionArg> for crate::report::JsonSchemaVersion {
+    fn from(arg: JsonSchemaVersionArg) -> Self {
+        match arg {
+            JsonSchemaVersionArg::V1 => crate::report::JsonSchemaVersion::V1,
+            JsonSchemaVersionArg::V2 => crate::report::JsonSchemaVersion::V2,
+        }
+    }
+}
+
+/// Selects `--uptime-format`'s rendering of a VM's uptime in the text
+/// report. See [`crate::vm::format_uptime`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum UptimeFormatArg {
+    Human,
+    Seconds,
+    Iso8601,
+}
+
+impl From<UptimeFormatArg> for crate::vm::UptimeFormat {
+    fn from(arg: UptimeFormatArg) -> Self {
+        match arg {
+            UptimeFormatArg::Human => crate::vm::UptimeFormat::Human,
+            UptimeFormatArg::Seconds => crate::vm::UptimeFormat::Seconds,
+            UptimeFormatArg::Iso8601 => crate::vm::UptimeFormat::Iso8601,
+        }
+    }
+}
+
+/// Selects `--output-rotate`'s file-naming scheme. See [`crate::sink::OutputRotation`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputRotationArg {
+    Overwrite,
+    Timestamped,
+    KeepN,
+}
+
+impl From<OutputRotationArg> for crate::sink::OutputRotation {
+    fn from(arg: OutputRotationArg) -> Self {
+        match arg {
+            OutputRotationArg::Overwrite => crate::sink::OutputRotation::Overwrite,
+            OutputRotationArg::Timestamped => crate::sink::OutputRotation::Timestamped,
+            OutputRotationArg::KeepN => crate::sink::OutputRotation::KeepN,
+        }
+    }
+}
+
+/// Selects `--group-by`'s grouping of the text report's per-VM issue
+/// listing. See [`crate::report::GroupBy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupByArg {
+    Folder,
+}
+
+impl From<GroupByArg> for crate::report::GroupBy {
+    fn from(arg: GroupByArg) -> Self {
+        match arg {
+            GroupByArg::Folder => crate::report::GroupBy::Folder,
+        }
+    }
+}
+
+/// Selects `--metrics-source`'s [`crate::metrics_provider::MetricsProvider`]
+/// implementation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MetricsSourceArg {
+    Simulated,
+    Soap,
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(name = "network-monitor", about = "vCenter VM fleet health monitor")]
+pub struct Args {
+    /// vCenter hostname or IP address.
+    #[arg(long, env = "VCENTER_HOST")]
+    pub host: String,
+
+    /// vCenter username. Required unless --sso-token or --cloud-csp-token is set.
+    #[arg(long, env = "VCENTER_USER")]
+    pub username: Option<String>,
+
+    /// vCenter password. Prefer the VCENTER_PASSWORD env var over the flag.
+    /// Required unless --sso-token or --cloud-csp-token is set.
+    #[arg(long, env = "VCENTER_PASSWORD")]
+    pub password: Option<String>,
+
+    /// An SSO token (SAML assertion or OAuth bearer token from vCenter
+    /// SSO/STS) to exchange for a session instead of authenticating with
+    /// --username/--password. See [`crate::auth::authenticate_with_sso_token`]
+    /// for the exact exchange and expected token format. Mutually exclusive
+    /// with --username/--password and --cloud-csp-token; basic auth stays
+    /// the default.
+    #[arg(long, env = "VCENTER_SSO_TOKEN")]
+    pub sso_token: Option<String>,
+
+    /// A VMware Cloud on AWS CSP (Cloud Services Platform) organization
+    /// refresh token, generated once at console.cloud.vmware.com, to
+    /// exchange for a short-lived access token instead of authenticating
+    /// with --username/--password or --sso-token. See
+    /// [`crate::auth::authenticate_with_cloud_csp_token`] for the exact
+    /// exchange. Mutually exclusive with --username/--password and
+    /// --sso-token. Pair with --vmc-profile - an SDDC's vCenter doesn't
+    /// expose the same host-management surface an on-prem one does.
+    #[arg(long, env = "VCENTER_CLOUD_CSP_TOKEN")]
+    pub cloud_csp_token: Option<String>,
+
+    /// Disables checks that need host-management access VMware Cloud on AWS
+    /// doesn't grant customers over their SDDC's hosts - today that's
+    /// --check-host-state and --check-host-health, since those are the only
+    /// checks in this tree that read host-level state at all. Has no effect on checks that operate
+    /// purely on VM-level data. See [`crate::vmc`].
+    #[arg(long)]
+    pub vmc_profile: bool,
+
+    /// Report output format.
+    #[arg(long, value_enum, default_value = "text")]
+    pub format: OutputFormat,
+
+    /// Write the report to this file instead of stdout.
+    #[arg(long)]
+    pub output: Option<String>,
+
+    /// Keep polling vCenter on an interval instead of exiting after one run.
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Poll interval in seconds when `--watch` is set.
+    #[arg(long, default_value_t = 60)]
+    pub interval_secs: u64,
+
+    /// Number of VMs to simulate in the absence of a real vCenter integration.
+    #[arg(long, default_value_t = 50)]
+    pub vm_count: usize,
+
+    /// Run under the OS service supervisor (Windows Service Control Manager).
+    #[arg(long)]
+    pub service: bool,
+
+    /// Skip the statistics section in the text and JSON reports.
+    #[arg(long)]
+    pub no_stats: bool,
+
+    /// Compute the tools/issue statistics over powered-on VMs only, so a
+    /// fleet's idle capacity doesn't skew the "with issues" ratio. The
+    /// power-state counts themselves still reflect every VM.
+    #[arg(long)]
+    pub exclude_powered_off_from_stats: bool,
+
+    /// Write a topology graph of problem VMs and their hosts. Format (DOT or
+    /// Mermaid) is picked from the file extension (`.dot`/`.gv` or `.mmd`).
+    #[arg(long)]
+    pub topology_output: Option<String>,
+
+    /// Include healthy host-mates of problem VMs in the topology graph, dimmed.
+    #[arg(long)]
+    pub topology_context: bool,
+
+    /// Maximum number of VM nodes in the topology graph before the rest are dropped.
+    #[arg(long, default_value_t = 200)]
+    pub topology_max_nodes: usize,
+
+    /// CPU usage percentage above which a VM is flagged
+    /// [`crate::vm::VMIssueType::HighCpuUsage`]. Always on, unlike the
+    /// `--check-*` detectors - combine with `--replay` to recompute a prior
+    /// run's issues against a different threshold without a fresh query.
+    #[arg(long, default_value_t = crate::vcenter::CPU_HIGH_THRESHOLD_PCT)]
+    pub cpu_threshold: f64,
+
+    /// Same as `--cpu-threshold`, for memory.
+    #[arg(long, default_value_t = crate::vcenter::MEMORY_HIGH_THRESHOLD_PCT)]
+    pub memory_threshold: f64,
+
+    /// Detect guest clock drift from the host (requires VMware Tools).
+    #[arg(long)]
+    pub check_clock: bool,
+
+    /// Flag VMs whose guest clock has drifted from the host by more than this
+    /// many seconds. Only takes effect with `--check-clock`.
+    #[arg(long, default_value_t = 5.0)]
+    pub clock_skew_threshold_secs: f64,
+
+    /// Group problem VMs by this custom attribute (e.g. "Owner") and route
+    /// each group to the channel/address configured for it in `--route-config`.
+    #[arg(long)]
+    pub route_by_attribute: Option<String>,
+
+    /// JSON file mapping attribute values to notification channels, with a
+    /// `default_channel` for VMs missing the attribute. Required with
+    /// `--route-by-attribute`.
+    #[arg(long)]
+    pub route_config: Option<String>,
+
+    /// Run a lightweight terminal dashboard instead of printing one report.
+    #[arg(long)]
+    pub dashboard: bool,
+
+    /// Fetch and render a single VM's full detail (hardware, tools/guest,
+    /// performance, recent events, issues) instead of a fleet-wide report.
+    /// Matches by exact name first, then by case-insensitive substring;
+    /// an ambiguous substring lists its candidates and exits non-zero.
+    /// Combine with `--watch` to refresh it on `--interval-secs`.
+    #[arg(long)]
+    pub inspect: Option<String>,
+
+    /// Probe each guest's IP for TCP reachability and flag unresponsive VMs.
+    #[arg(long)]
+    pub check_reachability: bool,
+
+    /// TCP port used for the reachability probe.
+    #[arg(long, default_value_t = 443)]
+    pub reachability_port: u16,
+
+    /// Reachability probe timeout, in milliseconds.
+    #[arg(long, default_value_t = 1000)]
+    pub reachability_timeout_ms: u64,
+
+    /// Require this process/service to be running in the guest (repeatable).
+    /// Needs VMware Tools; VMs without it are skipped, not flagged.
+    #[arg(long = "check-process")]
+    pub check_process: Vec<String>,
+
+    /// Validate flags and credentials, print any errors, and exit without
+    /// analyzing any VMs. Useful as a pre-deploy sanity check.
+    #[arg(long)]
+    pub config_validate: bool,
+
+    /// Cap a single run's wall-clock time, in seconds, splitting it across
+    /// inventory/analysis/reporting/notification phases. When analysis can't
+    /// cover every VM in its share of the budget, VMs with issues on the
+    /// previous run are analyzed first and the rest rotate in over
+    /// successive runs; skipped VMs are listed as deferred, never dropped
+    /// silently. Requires `--state-file` to track rotation across runs.
+    #[arg(long)]
+    pub time_budget: Option<u64>,
+
+    /// Where to persist rotation state for `--time-budget` between runs.
+    #[arg(long, default_value = "network-monitor-state.json")]
+    pub state_file: String,
+
+    /// Additionally write a condensed, per-cluster-issue-count report to
+    /// this path, reusing the same in-memory results as `--output` — no
+    /// extra vCenter query. Composes with `--output`/`--format`, which
+    /// still control the detailed report; this is always a plain summary
+    /// regardless of `--format`.
+    #[arg(long)]
+    pub summary_output: Option<String>,
+
+    /// Additionally write the fleet snapshot as OpenMetrics text exposition
+    /// (per-VM CPU/memory/uptime/issue-count gauges, plus per-host
+    /// CPU/memory gauges when host metrics were collected) to this path,
+    /// reusing the same in-memory results as `--output` - no extra vCenter
+    /// query. Composes with `--output`/`--format`, which are unaffected.
+    #[arg(long)]
+    pub openmetrics_output: Option<String>,
+
+    /// Additionally write a normalized JSON array of ticket-ready payloads
+    /// (summary, description, priority, dedup fingerprint, labels) to this
+    /// path, one per matching issue - reusing the same in-memory results as
+    /// `--output`, no extra vCenter query. Lets a downstream Jira/ServiceNow
+    /// importer consume a stable contract instead of regex-scraping the
+    /// text report. See [`crate::ticket`].
+    #[arg(long)]
+    pub ticket_export: Option<String>,
+
+    /// Issue type codes `--ticket-export` opens a ticket for
+    /// (comma-separated). Defaults to [`crate::ticket::default_ticket_issue_types`].
+    #[arg(long, value_delimiter = ',')]
+    pub ticket_issue_types: Vec<String>,
+
+    /// Restrict `--ticket-export` to issues first seen in this exact run -
+    /// a recurring issue a ticket was already opened for in an earlier run
+    /// is skipped. Only takes effect with `--ticket-export`.
+    #[arg(long)]
+    pub ticket_only_new: bool,
+
+    /// Runbook link interpolated into every `--ticket-export` description.
+    /// Unset means the description says none is configured.
+    #[arg(long)]
+    pub ticket_runbook_link: Option<String>,
+
+    /// Additionally write one logfmt (`key=value`) line per VM to this
+    /// path, reusing the same in-memory results as `--output` - no extra
+    /// vCenter query. Composes with `--output`/`--format`, which are
+    /// unaffected; this is always logfmt regardless of `--format`.
+    #[arg(long)]
+    pub logfmt_output: Option<String>,
+
+    /// JSON report schema. `v2` (default) includes full per-issue
+    /// measurement detail; `v1` serializes `issues` as bare type-name
+    /// strings for older consumers.
+    #[arg(long, value_enum, default_value = "v2")]
+    pub json_schema_version: JsonSchemaVersionArg,
+
+    /// Record per-endpoint call counts and latency distribution, printed as
+    /// a table at the end of the run and included in JSON report metadata.
+    #[arg(long)]
+    pub api_rate_log: bool,
+
+    /// Record per-check (`--check-*`/`--require-*`) request counts and
+    /// cumulative latency, printed as a CHECK COSTS table at the end of the
+    /// run and included in the JSON report's `check_timing` field. See
+    /// [`crate::check_timing::CheckTiming`].
+    #[arg(long)]
+    pub timing: bool,
+
+    /// Suggest which checks to disable to fit this many seconds of
+    /// per-check cost, based on this run's `--timing` measurements
+    /// (implies `--timing`). Advisory only - printed to stderr, nothing is
+    /// actually disabled. See [`crate::check_timing::budget_hint`].
+    #[arg(long)]
+    pub budget_hint: Option<f64>,
+
+    /// No-op, accepted for compatibility with orchestration wrappers that
+    /// pass it to every tool in the pipeline. This binary has no async
+    /// runtime or HTTP connection pool to constrain - [`crate::vcenter::SimulatedClient`]
+    /// is in-process and single-threaded already - so there is nothing for
+    /// this flag to configure here.
+    #[arg(long)]
+    pub single_threaded: bool,
+
+    /// No-op, accepted for compatibility with orchestration wrappers that
+    /// size a worker pool per tool in the pipeline. Host metrics are
+    /// already collected one batched query per host rather than per VM
+    /// (see [`crate::vcenter::SimulatedClient::prefetch_vm_metrics`]), and
+    /// that collection is in-process and synchronous like everything else
+    /// in [`crate::vcenter::SimulatedClient`] - there's no async runtime or
+    /// connection pool here to bound the concurrency of. Still validated as
+    /// greater than zero so a typo'd `--host-concurrency 0` fails fast
+    /// instead of silently doing nothing extra.
+    #[arg(long)]
+    pub host_concurrency: Option<u32>,
+
+    /// Auditable guarantee that this run only reads vCenter state. In
+    /// practice this is a no-op: [`crate::vcenter::SimulatedClient`] never
+    /// issues a write or power call in the first place (there's no
+    /// `power-on-off` style remediation flag anywhere in this tree, and no
+    /// HTTP client to log methods/URLs for - see [`crate::auth::Session`]'s
+    /// doc comment), so there's nothing for this flag to assert against at
+    /// runtime. It's still rejected alongside `--apply`, the one flag that
+    /// writes anything to disk, so a change-management approval that
+    /// requires `--read-only-assert` can't be paired with the one command
+    /// that isn't purely read-only.
+    #[arg(long)]
+    pub read_only_assert: bool,
+
+    /// Flag VMs whose vCPU count exceeds `--max-vcpu-ratio` times their
+    /// host's physical core count - a right-sizing advisory, not a sign of
+    /// current trouble.
+    #[arg(long)]
+    pub check_vcpu_allocation: bool,
+
+    /// Max allowed ratio of VM vCPUs to host physical cores. Only takes
+    /// effect with `--check-vcpu-allocation`.
+    #[arg(long, default_value_t = 1.0)]
+    pub max_vcpu_ratio: f64,
+
+    /// JSON file describing the notifier registry: each entry names a
+    /// backend, its kind (`slack`/`teams`/`email`/`webhook`/`pagerduty`),
+    /// and an optional filter. See [`crate::notifier::NotifierRegistryConfig`].
+    #[arg(long)]
+    pub notifier_config: Option<String>,
+
+    /// Send a synthetic test message through every backend in
+    /// `--notifier-config` and exit, without analyzing any VMs. Useful as a
+    /// pre-deploy check that every backend is reachable.
+    #[arg(long)]
+    pub test_notifiers: bool,
+
+    /// Exit non-zero if any configured notifier fails to deliver. Off by
+    /// default so a flaky notification backend never fails the underlying
+    /// health check the run exists to report.
+    #[arg(long)]
+    pub fail_on_notify_error: bool,
+
+    /// Re-run detection against a previously exported `--format json` report
+    /// instead of querying vCenter, using the threshold/`--check-*` flags on
+    /// this invocation. A fast, offline way to ask "what if the CPU
+    /// threshold were 70 instead of 80?" without a fresh run.
+    #[arg(long)]
+    pub replay: Option<String>,
+
+    /// With `--replay`, treat a missing or wrongly-typed measurement field
+    /// (`cpu_usage_pct`, `memory_usage_pct`, `cpu_count`, `cores_per_socket`,
+    /// `migration_count_24h`, `uptime_secs`) in the loaded report as a
+    /// per-VM analysis error instead of silently substituting a default.
+    /// Without this flag, fallbacks are still counted and reported as a
+    /// data-quality warning so schema drift (e.g. a renamed field) stays
+    /// visible. See [`crate::strict_parsing`].
+    #[arg(long)]
+    pub strict_parsing: bool,
+
+    /// Analyze CPU/memory samples across `--history` reports and print
+    /// suggested per-VM alert thresholds instead of analyzing the live
+    /// fleet. See [`crate::thresholds`].
+    #[arg(long)]
+    pub suggest_thresholds: bool,
+
+    /// A prior `--format json` report to use as a historical sample for
+    /// `--suggest-thresholds`/`--rightsizing-report` (repeatable).
+    #[arg(long = "history")]
+    pub history: Vec<String>,
+
+    /// Ignore `--history` files older than this many days. Only takes
+    /// effect with `--suggest-thresholds`/`--rightsizing-report`.
+    #[arg(long, default_value_t = 30)]
+    pub lookback_days: u64,
+
+    /// Print a per-VM cpu/mem Unicode sparkline next to each flagged VM's
+    /// entry in the text report, pooled from `--history` (falling back to a
+    /// single proportional block when a VM has no history). Disabled
+    /// whenever stdout isn't a TTY, since the blocks rely on a Unicode font
+    /// and don't mean anything piped into a file or another program. See
+    /// [`crate::sparkline`].
+    #[arg(long)]
+    pub sparklines: bool,
+
+    /// List VMs whose peak CPU and memory usage over `--history` (or this
+    /// run's live sample, when `--history` isn't given) both stayed below
+    /// `--underuse-threshold`, with a suggested smaller size and the
+    /// estate-wide reclaimable vCPU/memory total, instead of analyzing the
+    /// fleet for issues. See [`crate::rightsize`].
+    #[arg(long)]
+    pub rightsizing_report: bool,
+
+    /// `--rightsizing-report`'s underuse cutoff: a VM's peak CPU and memory
+    /// usage must both stay below this to be flagged as oversized.
+    #[arg(long, default_value_t = crate::rightsize::DEFAULT_UNDERUSE_THRESHOLD_PCT)]
+    pub underuse_threshold: f64,
+
+    /// Custom attribute (vCenter custom field/tag) whose mere presence on a
+    /// VM exempts it from `--rightsizing-report` recommendations regardless
+    /// of how idle it looks - e.g. a DR standby or compliance-hold box
+    /// that's deliberately oversized. Listed separately, not recommended.
+    #[arg(long, default_value = crate::rightsize::DEFAULT_EXEMPT_ATTRIBUTE)]
+    pub rightsize_exempt_attribute: String,
+
+    /// Also write `--suggest-thresholds`'s suggested-overrides snippet to
+    /// this file.
+    #[arg(long)]
+    pub apply: Option<String>,
+
+    /// Reject any top-level field in `--route-config`/`--notifier-config`
+    /// that these structs don't declare, naming the offending field,
+    /// instead of silently ignoring it. For validation runs after a config
+    /// format (or the vCenter schema it mirrors) may have changed shape.
+    #[arg(long)]
+    pub strict_json: bool,
+
+    /// How `--output` names successive report files: `overwrite` (default)
+    /// reuses the same path every time, `timestamped` writes a new file
+    /// per run, and `keep-n` does the same but prunes older files down to
+    /// `--output-keep-n`. Applies to `--format text/json/csv` alike.
+    #[arg(long, value_enum, default_value = "overwrite")]
+    pub output_rotate: OutputRotationArg,
+
+    /// Files to retain under `--output-rotate keep-n`. Only takes effect
+    /// with `keep-n`.
+    #[arg(long, default_value_t = 5)]
+    pub output_keep_n: usize,
+
+    /// Directory to write `--output`'s file(s) into, overriding the
+    /// directory `--output` itself names.
+    #[arg(long)]
+    pub output_dir: Option<String>,
+
+    /// Skip writing `--output` when the rendered report is byte-for-byte
+    /// identical to the last one actually written (tracked in
+    /// `--state-file`), so archiving a stable fleet doesn't churn file
+    /// timestamps or disk space every cycle. Requires `--output`.
+    #[arg(long)]
+    pub output_on_change: bool,
+
+    /// Detect VMs migrating (vMotion/DRS) more than `--max-migrations` times
+    /// within `--migration-window-hours` - often a DRS misconfiguration or
+    /// affinity-rule fight rather than a problem with the VM itself.
+    #[arg(long)]
+    pub check_migrations: bool,
+
+    /// Window, in hours, the migration event query covers. Only takes
+    /// effect with `--check-migrations`.
+    #[arg(long, default_value_t = 24.0)]
+    pub migration_window_hours: f64,
+
+    /// Migrations within the window above which a VM is flagged. Only
+    /// takes effect with `--check-migrations`.
+    #[arg(long, default_value_t = 5)]
+    pub max_migrations: u32,
+
+    /// Issue type codes to strip from every VM after detection, fleet-wide
+    /// (comma-separated, e.g. `POWERED_OFF,CLOCK_SKEW`) - stripped issues
+    /// never appear in reports, statistics, or notifications. See
+    /// [`crate::vm::VMIssueType`]'s `Display` for valid codes.
+    #[arg(long, value_delimiter = ',')]
+    pub disable_issues: Vec<String>,
+
+    /// Issue type code (same codes as `--disable-issues`, e.g.
+    /// `TOOLS_NOT_RUNNING`) to list `vm_name`s for, one per line, instead of
+    /// the full report - for a remediation script that just wants "every VM
+    /// with this issue" without parsing JSON. Exits 0 even when the list is
+    /// empty. A narrower, single-issue alternative to filtering the full
+    /// report after the fact. Ignored in `--watch`/`--dashboard`, which have
+    /// no single exit moment to substitute a one-shot listing for.
+    #[arg(long)]
+    pub names_for_issue: Option<String>,
+
+    /// JSON file overriding the default per-issue-type weight used to
+    /// compute each VM's health score, keyed by the same issue type codes
+    /// as `--disable-issues` (e.g. `{"weights": {"HIGH_CPU_USAGE": 20.0}}`).
+    /// Types it doesn't name keep their default. See [`crate::scoring`].
+    #[arg(long)]
+    pub score_weights: Option<String>,
+
+    /// Exit non-zero if the run's weighted health score (the average
+    /// `health_score` across powered-on VMs) falls below this threshold.
+    /// Ignored in `--watch`, which has no single exit moment to gate.
+    #[arg(long)]
+    pub fail_below_score: Option<f64>,
+
+    /// Exit non-zero if any detected issue resolves to the error tier (see
+    /// `VMIssueType::default_exit_severity`) after `--issue-threshold-warnings`
+    /// overrides. Warning-tier issues are still logged and still appear in
+    /// the report; only error-tier issues fail the run. Finer-grained than
+    /// failing on any issue at all, and independent of `--fail-below-score`.
+    /// Ignored in `--watch`, which has no single exit moment to gate.
+    #[arg(long)]
+    pub fail_on_issues: bool,
+
+    /// Issue type codes (comma-separated, same codes as `--disable-issues`)
+    /// to downgrade to the warning tier for `--fail-on-issues`, regardless
+    /// of their default exit severity - e.g. `POWERED_OFF` for a fleet
+    /// where planned shutdowns are routine. Has no effect without
+    /// `--fail-on-issues`.
+    #[arg(long, value_delimiter = ',')]
+    pub issue_threshold_warnings: Vec<String>,
+
+    /// Treat the whole run as a single transaction: if any VM was deferred
+    /// from analysis (dropped by `--max-total-requests`, abandoned past
+    /// `--per-vm-timeout-ms`, or excluded by `--strict-parsing` under
+    /// `--replay`) - or more than `--atomic-max-deferred` were - abort
+    /// before writing the report, ticket export, topology/openmetrics/
+    /// logfmt/template output, or notifications, and before saving any
+    /// state file. A partial fleet view never reaches disk half-written.
+    /// Ignored in `--watch`, which has no single run boundary to make
+    /// atomic.
+    #[arg(long)]
+    pub atomic: bool,
+
+    /// Number of deferred-analysis VMs `--atomic` tolerates before aborting
+    /// the run. `0` (the default) means any deferral at all aborts it. Has
+    /// no effect without `--atomic`.
+    #[arg(long, default_value_t = 0)]
+    pub atomic_max_deferred: usize,
+
+    /// Summarize issue frequency and trends across several prior
+    /// `--format json` reports instead of analyzing the live fleet. Accepts
+    /// a directory (every `*.json` file in it) or a `*`-glob pattern (e.g.
+    /// `reports/week1-*.json`). See [`crate::aggregate`].
+    #[arg(long)]
+    pub aggregate: Option<String>,
+
+    /// Run the full pipeline against a bundled synthetic fleet instead of
+    /// querying vCenter, for evaluating the tool with nothing to show. Every
+    /// rendered report is watermarked as demo data. See [`crate::demo`].
+    #[arg(long)]
+    pub demo: bool,
+
+    /// Allow `--demo` to run alongside `--notifier-config`, sending
+    /// synthetic alerts through real notification backends. Off by default
+    /// so a demo run never pages anyone with fake data.
+    #[arg(long)]
+    pub demo_allow_notify: bool,
+
+    /// In `--watch`, suppress re-alerting on the same VM+issue for this many
+    /// minutes after it first alerts, unless the issue clears and recurs.
+    /// Cuts notification noise from a VM oscillating around a threshold.
+    /// Alert history is in-memory only and does not survive a restart. See
+    /// [`crate::alerting::CooldownTracker`].
+    #[arg(long)]
+    pub alert_cooldown: Option<u64>,
+
+    /// Flag powered-on VMs whose uptime is below
+    /// `--short-uptime-threshold-secs`, i.e. they rebooted recently.
+    /// Required by `--check-boot-storm`, which correlates these across the
+    /// fleet.
+    #[arg(long)]
+    pub check_uptime: bool,
+
+    /// Uptime, in seconds, below which a powered-on VM is flagged as
+    /// recently rebooted. Only takes effect with `--check-uptime`.
+    #[arg(long, default_value_t = 900.0)]
+    pub short_uptime_threshold_secs: f64,
+
+    /// Window, in hours, the `--check-uptime` event-history lookup covers
+    /// when deciding `created_recently` (from `VmCreatedEvent`/
+    /// `VmClonedEvent`/`VmRegisteredEvent`) and counting `VmPoweredOnEvent`s
+    /// for `--reboot-loop-count`. Only takes effect with `--check-uptime`.
+    #[arg(long, default_value_t = 1.0)]
+    pub boot_history_window_hours: f64,
+
+    /// Power-on events within `--boot-history-window-hours` above which a
+    /// short-uptime VM is reclassified from `UptimeShort` to the more severe
+    /// `VMIssueType::RebootLoop`. A VM with a `VmCreatedEvent` in the window
+    /// is treated as a fresh deployment instead, regardless of this count.
+    /// Only takes effect with `--check-uptime`.
+    #[arg(long, default_value_t = 3)]
+    pub reboot_loop_count: u32,
+
+    /// How a VM's uptime is rendered in the text report's issue list.
+    /// `seconds`/`iso8601` are for downstream parsers; the JSON/CSV reports
+    /// already carry the raw seconds regardless of this flag.
+    #[arg(long, value_enum, default_value = "human")]
+    pub uptime_format: UptimeFormatArg,
+
+    /// Buckets the text report's per-VM issue listing by `inventory_path`
+    /// folder instead of the default flat list. Unset means flat. See
+    /// [`crate::report::GroupBy`].
+    #[arg(long, value_enum)]
+    pub group_by: Option<GroupByArg>,
+
+    /// Correlation ID for this run, carried through the report header, the
+    /// JSON/CSV metadata, notifier payloads, and `--state-file`, so an
+    /// external orchestrator can join them back up with its own scheduling
+    /// log instead of guessing from timestamps. Generated as a random UUID
+    /// when omitted. See [`crate::run_id`].
+    #[arg(long)]
+    pub run_id: Option<String>,
+
+    /// Correlate `--check-uptime` findings across the fleet and raise one
+    /// consolidated alert when at least `--boot-storm-threshold` VMs rebooted
+    /// in a tight time window - a mass reboot, not isolated flapping.
+    /// Requires `--check-uptime`. See [`crate::bootstorm`].
+    #[arg(long)]
+    pub check_boot_storm: bool,
+
+    /// Minimum cluster size that counts as a boot storm: either an absolute
+    /// VM count (e.g. `5`) or a percentage of the fleet (e.g. `10%`). Only
+    /// takes effect with `--check-boot-storm`.
+    #[arg(long, default_value = "10%")]
+    pub boot_storm_threshold: String,
+
+    /// When `--check-boot-storm` raises a consolidated alert, also strip the
+    /// individual `UptimeShort` issues it clustered from the report and
+    /// notifications, so the fleet-wide finding isn't drowned out by the
+    /// per-VM noise that produced it.
+    #[arg(long)]
+    pub suppress_individual_boot_storm_alerts: bool,
+
+    /// Flag VMs whose host is disconnected or in maintenance mode - the VM
+    /// itself may be healthy, but its host condition puts it at elevated risk.
+    #[arg(long)]
+    pub check_host_state: bool,
+
+    /// Flag every VM on a host whose hardware-sensor query (PSU, fan,
+    /// memory, etc) reported yellow or red - a failing PSU or fan endangers
+    /// every VM on the host, not just the one being fetched. Same
+    /// host-management access as `--check-host-state`, so `--vmc-profile`
+    /// force-disables this too. See
+    /// [`crate::vcenter::host_hardware_unhealthy_issue`].
+    #[arg(long)]
+    pub check_host_health: bool,
+
+    /// Flag VMs whose virtual hardware version is below `--min-hw-version` -
+    /// a lifecycle/inventory advisory, not a sign of current trouble. An old
+    /// vHW version can't use newer host features and blocks some operations
+    /// until it's upgraded.
+    #[arg(long)]
+    pub check_hw_version: bool,
+
+    /// Minimum virtual hardware version, as the numeric suffix of e.g.
+    /// `vmx-19`. Only takes effect with `--check-hw-version`.
+    #[arg(long, default_value_t = 15)]
+    pub min_hw_version: u32,
+
+    /// In `--watch`, after the first cycle, render only VMs whose issue set
+    /// changed since the previous cycle (new or cleared issues) instead of
+    /// the full fleet - repeating the full report every `--interval-secs`
+    /// when nothing changed is just noise in logs/alert history. See
+    /// `--full-every` to still force a periodic full report.
+    #[arg(long)]
+    pub delta_only: bool,
+
+    /// With `--delta-only`, still render a full report every Nth cycle (the
+    /// first cycle is always full regardless). `0` means never force one.
+    #[arg(long, default_value_t = 0)]
+    pub full_every: u32,
+
+    /// Evaluate the cluster's DRS affinity/anti-affinity/VM-host group
+    /// rules from `--drs-rules` against current VM placement, flagging
+    /// `Mandatory` rules broken by the fleet with
+    /// [`crate::vm::VMIssueType::DrsRuleViolation`] and a run-level
+    /// compliance section. See [`crate::drs`].
+    #[arg(long)]
+    pub check_drs_rules: bool,
+
+    /// Cluster DRS rules (affinity, anti-affinity, VM-host groups) to
+    /// evaluate placement against. Required by `--check-drs-rules`.
+    #[arg(long)]
+    pub drs_rules: Option<String>,
+
+    /// Flag powered-on VMs with CPU or memory hot-add disabled with
+    /// [`crate::vm::VMIssueType::HotAddDisabled`] - automation that assumes
+    /// hot-add is available fleet-wide fails against a VM built from a
+    /// template that predates the setting. See [`crate::hotadd`].
+    #[arg(long)]
+    pub require_hot_add: bool,
+
+    /// Scopes `--require-hot-add` to VMs matching a name pattern or custom
+    /// attribute tag, via a JSON config file. Every powered-on VM is in
+    /// scope when omitted. See [`crate::hotadd::HotAddScope`].
+    #[arg(long)]
+    pub hot_add_scope: Option<String>,
+
+    /// Flag a VM already running hot (`HighCpuUsage`/`HighMemoryUsage`)
+    /// whose matching hot-add setting is disabled with
+    /// [`crate::vm::VMIssueType::HotAddDisabledUnderLoad`] - unlike
+    /// `--require-hot-add`, this only fires when the VM is under load and
+    /// actually can't be scaled up without a reboot right now. See
+    /// `crate::vcenter::hotadd_under_load_issue`.
+    #[arg(long)]
+    pub check_hotadd: bool,
+
+    /// Read VMware Tools' guest-visible memory/vCPU count and compare it
+    /// against the configured `memory_gb`/`cpu_count` - a guest that didn't
+    /// online hot-added memory reports a smaller figure than vCenter's
+    /// configured size, which makes `memory_usage_pct`/`cpu_usage_pct`
+    /// computed against the configured size misleading. When the two
+    /// disagree by more than 10%, usage is recomputed against the
+    /// guest-visible figure instead and [`crate::vm::VMIssueType::GuestResourceMismatch`]
+    /// is raised. Only takes effect for VMs with Tools running - there's no
+    /// guest-visible figure to read otherwise.
+    #[arg(long)]
+    pub check_guest_resource_mismatch: bool,
+
+    /// Flag a VM whose `disk_allocated_gb` is at least 500 GB while VMware
+    /// Tools reports less than 10% of it actually used, with
+    /// [`crate::vm::VMIssueType::StorageWaste`] - a rightsizing advisory for
+    /// disks that were provisioned generously and never grew into the
+    /// space. Only takes effect for VMs with Tools running - there's no
+    /// guest-reported usage figure to compare against otherwise. See
+    /// [`crate::vcenter::storage_waste_issue`].
+    #[arg(long)]
+    pub check_storage_waste: bool,
+
+    /// Restrict the report to VM names read from stdin, one per line.
+    /// Blank lines and lines starting with `#` are ignored, same as a
+    /// name would be written by hand in a pipeline upstream. Lets callers
+    /// compose `cat names.txt | vcenter-monitor --vm-list-stdin ...`
+    /// without a temp file. Names that don't match any fetched VM are
+    /// dropped from `vms` and listed under `vms_not_found`/"Not found"
+    /// instead of silently vanishing. See [`crate::vm::resolve_name_list`].
+    #[arg(long)]
+    pub vm_list_stdin: bool,
+
+    /// Render the full v2 report document (statuses, issues, statistics,
+    /// and run metadata) through a user-supplied template, independent of
+    /// `--format`, and write it to `--template-output`. See
+    /// [`crate::template`] for the supported tag syntax and available keys.
+    #[arg(long)]
+    pub template: Option<String>,
+
+    /// Where to write `--template`'s rendered output. Required by
+    /// `--template`.
+    #[arg(long)]
+    pub template_output: Option<String>,
+
+    /// Hard ceiling on outgoing vCenter requests for this run. Past 80% of
+    /// the ceiling, optional per-VM checks degrade one at a time in
+    /// priority order; once the ceiling is hit, remaining VMs are deferred
+    /// rather than analyzed. Unset means no ceiling. See
+    /// [`crate::request_budget`].
+    #[arg(long)]
+    pub max_total_requests: Option<u64>,
+
+    /// Abandon a single VM's analysis if it would take longer than this,
+    /// in milliseconds, so one guest with a hung agent or a stuck Tools
+    /// upgrade can't stall the whole run - the VM is reported alongside
+    /// `--time-budget`/`--max-total-requests` deferrals instead. Unset
+    /// means no per-VM budget. See [`crate::vcenter::SimulatedClient::timed_out`].
+    #[arg(long)]
+    pub per_vm_timeout_ms: Option<u64>,
+
+    /// Warn in the report once this account's concurrent vCenter session
+    /// count reaches this many - shared monitoring accounts tend to leak
+    /// sessions across tools until they hit vCenter's per-user limit.
+    /// Requires the `Sessions.View` privilege; degrades silently to an
+    /// unknown count otherwise. See [`crate::sessions`].
+    #[arg(long, default_value_t = 20)]
+    pub session_count_warn: u32,
+
+    /// Terminates this account's own vCenter sessions idle longer than the
+    /// given number of minutes, logging each one; the current session is
+    /// never a candidate. Requires the `Sessions.TerminateSession`
+    /// privilege; a no-op (not an error) when the session list can't be
+    /// read at all. Unset disables reaping. See [`crate::sessions`].
+    #[arg(long)]
+    pub reap_stale_sessions: Option<u64>,
+
+    /// Warn in the report once the authenticated account's SSO/LDAP
+    /// password is within this many days of expiring - a service account's
+    /// password quietly expiring has taken monitoring down before, with
+    /// nobody noticing until an unrelated outage went undetected. Only
+    /// takes effect when the identity source behind vCenter exposes
+    /// expiration at all; degrades silently to unknown otherwise. See
+    /// [`crate::auth::PasswordExpiryReport`].
+    #[arg(long, default_value_t = 14)]
+    pub password_expiry_warn_days: u32,
+
+    /// Analyze only VMs whose vCenter change-version marker differs from
+    /// the one recorded in `--state-file` last run; a VM whose marker is
+    /// unchanged has its prior status carried forward instead, trading
+    /// that VM's performance-metric freshness (CPU/memory/uptime/issues)
+    /// for a faster run on a stable fleet. A VM seen for the first time
+    /// is always analyzed fresh. See [`crate::planner::RunState`].
+    #[arg(long)]
+    pub since_last_run: bool,
+
+    /// Overrides `--since-last-run` for this run only: analyzes every VM
+    /// fresh regardless of its change-version marker, without forgetting
+    /// the markers `--since-last-run` would otherwise have carried
+    /// forward. A no-op without `--since-last-run`.
+    #[arg(long)]
+    pub force_full: bool,
+
+    /// For powered-off VMs, confirms the VMX file recorded in the VM's
+    /// config still exists on its datastore, raising `BACKING_FILES_MISSING`
+    /// (Critical) when it doesn't - catches a VM whose backing disk/config
+    /// was deleted or moved out from under vCenter without the VM itself
+    /// being unregistered. A datastore that refuses to be browsed is
+    /// warned about once, not flagged. See [`crate::datastore`].
+    #[arg(long)]
+    pub check_vm_files: bool,
+
+    /// Caps how many powered-off VMs `--check-vm-files` will check in a
+    /// single run, so a fleet with a large stopped-VM backlog doesn't turn
+    /// one run into a datastore-browsing marathon. Unset means no cap.
+    #[arg(long)]
+    pub max_file_checks: Option<u32>,
+
+    /// Hours a VM can sit suspended before it's reclassified from
+    /// `SUSPENDED` to the more severe `SUSPENDED_TOO_LONG` - a VM suspended
+    /// this long is quietly holding its host's memory pages and RDM locks
+    /// well past a routine maintenance window. Unset means suspended VMs
+    /// are never escalated past `SUSPENDED`. With `--check-vm-files`, also
+    /// confirms a suspended VM's `.vmss` suspend-state file still exists on
+    /// its datastore, raising `SUSPEND_STATE_MISSING` when it doesn't.
+    #[arg(long)]
+    pub max_suspend_hours: Option<f64>,
+
+    /// Carry every raw performance counter the metrics collector returned
+    /// for a VM (today just `cpu_usage_pct`/`memory_usage_pct` - see
+    /// [`crate::metrics_provider::MetricsProvider`]) alongside the derived
+    /// fields, under `raw_metrics`, so downstream analytics can compute
+    /// their own derivatives without re-querying vCenter. Off by default to
+    /// keep payloads lean - most consumers only want the derived figures.
+    #[arg(long)]
+    pub include_raw_metrics: bool,
+
+    /// Diagnostic verbosity: repeat for more detail - `-v` enables info-level
+    /// diagnostics (retry/pagination/SOAP-call logging), `-vv` debug, `-vvv`
+    /// trace. Overridden by `--quiet`. See [`Args::log_level_filter`].
+    #[arg(short = 'v', action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Alias for `-vv`, kept for compatibility with the old boolean
+    /// `--verbose` flag.
+    #[arg(long = "verbose", action = clap::ArgAction::SetTrue, hide = true)]
+    pub verbose_legacy: bool,
+
+    /// Diagnostics at error level only, regardless of `-v`.
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// Disables the `crate::recommend` rules wholesale - no `Recommendation`
+    /// is computed or attached to any issue this run.
+    #[arg(long)]
+    pub no_recommendations: bool,
+
+    /// Disables `crate::maintenance`'s severity downgrade: by default, a
+    /// `PoweredOff`/`Suspended`/`UptimeShort` issue on a VM whose host is in
+    /// maintenance mode is downgraded to `Informational` (its prior
+    /// severity is kept in the issue's `original_severity` field) since
+    /// those states are expected while a host is being drained, not
+    /// something to page on.
+    #[arg(long)]
+    pub no_respect_maintenance_mode: bool,
+
+    /// Geographic/DC label for this run, stamped into the JSON metadata,
+    /// text report header, Prometheus/OpenMetrics labels, and notifier
+    /// payloads, so a central system aggregating reports from multiple
+    /// sites can group by site without parsing the vCenter hostname. Unset
+    /// (the default) omits the label from every output.
+    #[arg(long)]
+    pub site: Option<String>,
+
+    /// Per-`--site` threshold/enabled-check overrides, layered on top of
+    /// the global flags (site beats global beats built-in default). See
+    /// [`crate::site_config`]. Has no effect without `--site`.
+    #[arg(long)]
+    pub site_config: Option<String>,
+
+    /// Print this run's fully-merged `--site-config`/global settings as
+    /// JSON and exit without connecting to vCenter - for checking the
+    /// precedence resolution landed where you expect before it's live.
+    /// See [`crate::site_config::EffectiveConfig`].
+    #[arg(long)]
+    pub print_effective_config: bool,
+
+    /// Acquire an exclusive advisory lock before touching `--state-file`
+    /// or writing output, so a run that overruns its cron slot can't
+    /// overlap with the next one and double vCenter load or race on
+    /// writes. Defaults to `--state-file` plus `.lock` when unset, which
+    /// only matters if `--state-file`-dependent features are in use. See
+    /// [`crate::lockfile`].
+    #[arg(long)]
+    pub lock_file: Option<String>,
+
+    /// How long, in seconds, to wait for `--lock-file` if another run
+    /// already holds it, polling periodically, before giving up. Unset
+    /// (the default) exits immediately with
+    /// [`crate::lockfile::LOCK_HELD_EXIT_CODE`] instead of waiting at all.
+    #[arg(long)]
+    pub lock_wait_secs: Option<u64>,
+
+    /// Serialize `--format json` output (and `--template`'s context JSON)
+    /// as single-line, no-whitespace JSON instead of pretty-printed -
+    /// noticeably smaller for multi-thousand-VM fleets and for consumers
+    /// that parse the file rather than read it. Pretty stays the default
+    /// for human inspection.
+    #[arg(long)]
+    pub compact_json: bool,
+
+    /// Which [`crate::metrics_provider::MetricsProvider`] supplies per-VM
+    /// CPU/memory usage: `simulated` (default) or `soap`, for a future SOAP
+    /// `PerformanceManager` client. `soap` falls back to simulated metrics
+    /// today with a one-time warning - see
+    /// [`crate::metrics_provider::SoapMetricsProvider`].
+    #[arg(long, value_enum, default_value = "simulated")]
+    pub metrics_source: MetricsSourceArg,
+
+    /// Restrict the report to VMs matching this boolean expression over
+    /// `cluster`, `tag`, `power` (`on`/`off`), `name`, and `folder`, e.g.
+    /// `cluster == "prod-b" && tag contains "web"`. Composes with
+    /// `--vm-list-stdin`; a VM must satisfy both to be included. See
+    /// [`crate::select`] for the full expression syntax.
+    #[arg(long)]
+    pub select: Option<String>,
+
+    /// With `--select`, also print which clause excluded each VM that
+    /// didn't match, for debugging a selection expression that's too
+    /// narrow. Requires `--select`.
+    #[arg(long)]
+    pub explain_selection: bool,
+
+    /// Previews the blast radius of tightening (or loosening) alert
+    /// thresholds before rolling them out estate-wide: comma-separated
+    /// `key=value` pairs, e.g. `cpu=70,memory=80`. Compares every VM's
+    /// already-collected usage against both the active and proposed
+    /// thresholds and adds a trailing PREVIEW section (and a `preview` JSON
+    /// block) showing how many VMs would newly alert or clear, and the full
+    /// delta list - no extra vCenter call, and no effect on `issues`,
+    /// notifications, or `--fail-on-issues`/`--fail-below-score`. Only `cpu`
+    /// and `memory` are supported; `disk` is rejected, since this tree has
+    /// no disk-usage check to preview against. See [`crate::preview`].
+    #[arg(long, value_delimiter = ',')]
+    pub preview_thresholds: Vec<String>,
+
+    /// After a run, warns on stderr about any enabled, user-configured
+    /// threshold (`--clock-skew-threshold-secs`, `--max-vcpu-ratio`,
+    /// `--max-migrations`, `--short-uptime-threshold-secs`,
+    /// `--min-hw-version`) that never flagged a single VM - the signature of
+    /// a threshold set so loose it can't catch anything (e.g. a typo'd
+    /// `--min-hw-version 0`), not necessarily a genuinely clean fleet.
+    /// Purely advisory: it reads `issues` after detection and changes
+    /// nothing about them, `--fail-on-issues`, or `--fail-below-score`. See
+    /// [`crate::sanitycheck`].
+    #[arg(long)]
+    pub sanity_check_thresholds: bool,
+}
+
+impl Args {
+    /// Parses `--disable-issues` against `VMIssueType`'s codes. `Err` names
+    /// the first unrecognized code.
+    pub fn disabled_issue_types(&self) -> Result<std::collections::HashSet<crate::vm::VMIssueType>, String> {
+        self.disable_issues.iter().map(|code| code.parse()).collect()
+    }
+
+    /// Parses `--issue-threshold-warnings` against `VMIssueType`'s codes.
+    /// `Err` names the first unrecognized code.
+    pub fn issue_threshold_warnings(&self) -> Result<std::collections::HashSet<crate::vm::VMIssueType>, String> {
+        self.issue_threshold_warnings.iter().map(|code| code.parse()).collect()
+    }
+
+    /// Parses `--names-for-issue` against `VMIssueType`'s codes. `Err` names
+    /// the unrecognized code.
+    pub fn names_for_issue_type(&self) -> Result<Option<crate::vm::VMIssueType>, String> {
+        self.names_for_issue.as_deref().map(|code| code.parse()).transpose()
+    }
+
+    /// Parses `--ticket-issue-types` against `VMIssueType`'s codes, falling
+    /// back to [`crate::ticket::default_ticket_issue_types`] when unset.
+    /// `Err` names the first unrecognized code.
+    pub fn ticket_issue_types(&self) -> Result<std::collections::HashSet<crate::vm::VMIssueType>, String> {
+        if self.ticket_issue_types.is_empty() {
+            crate::ticket::default_ticket_issue_types().iter().map(|code| code.parse()).collect()
+        } else {
+            self.ticket_issue_types.iter().map(|code| code.parse()).collect()
+        }
+    }
+
+    pub fn detection_options(&self) -> crate::vcenter::DetectionOptions {
+        crate::vcenter::DetectionOptions {
+            cpu_high_threshold_pct: self.cpu_threshold,
+            memory_high_threshold_pct: self.memory_threshold,
+            check_clock: self.check_clock,
+            clock_skew_threshold_secs: self.clock_skew_threshold_secs,
+            check_reachability: self.check_reachability,
+            reachability_port: self.reachability_port,
+            reachability_timeout_ms: self.reachability_timeout_ms,
+            required_processes: self.check_process.clone(),
+            check_vcpu_allocation: self.check_vcpu_allocation,
+            max_vcpu_ratio: self.max_vcpu_ratio,
+            check_migrations: self.check_migrations,
+            migration_window_hours: self.migration_window_hours,
+            max_migrations: self.max_migrations,
+            check_uptime: self.check_uptime,
+            short_uptime_threshold_secs: self.short_uptime_threshold_secs,
+            boot_history_window_hours: self.boot_history_window_hours,
+            reboot_loop_count: self.reboot_loop_count,
+            check_host_state: self.check_host_state && !self.vmc_profile,
+            check_host_health: self.check_host_health && !self.vmc_profile,
+            check_hw_version: self.check_hw_version,
+            min_hw_version: self.min_hw_version,
+            per_vm_timeout_ms: self.per_vm_timeout_ms,
+            check_vm_files: self.check_vm_files,
+            max_file_checks: self.max_file_checks,
+            check_hotadd: self.check_hotadd,
+            check_guest_resource_mismatch: self.check_guest_resource_mismatch,
+            check_storage_waste: self.check_storage_waste,
+            max_suspend_hours: self.max_suspend_hours,
+            include_raw_metrics: self.include_raw_metrics,
+        }
+    }
+
+    /// Parses `--boot-storm-threshold` as either an absolute VM count or a
+    /// `N%` percentage of the fleet. `Err` carries a message naming the bad value.
+    pub fn boot_storm_threshold(&self) -> Result<crate::bootstorm::BootStormThreshold, String> {
+        self.boot_storm_threshold.parse()
+    }
+
+    /// Parses `--select`, if given. `Err` carries a message naming the
+    /// character position the expression is invalid at.
+    pub fn selection(&self) -> Result<Option<crate::select::Expr>, String> {
+        self.select.as_deref().map(|expr| crate::select::parse(expr).map_err(|err| err.to_string())).transpose()
+    }
+
+    /// Parses `--preview-thresholds`, if given. `Err` names the bad token.
+    pub fn preview_thresholds(&self) -> Result<Option<crate::preview::ProposedThresholds>, String> {
+        if self.preview_thresholds.is_empty() {
+            return Ok(None);
+        }
+        crate::preview::parse_proposed_thresholds(&self.preview_thresholds).map(Some)
+    }
+
+    /// `-v`/`-vv`/`-vvv` (or the legacy `--verbose`, equivalent to `-vv`)
+    /// mapped to an `env_logger` filter level; `--quiet` always wins and
+    /// drops diagnostics to errors only.
+    pub fn log_level_filter(&self) -> log::LevelFilter {
+        if self.quiet {
+            return log::LevelFilter::Error;
+        }
+        match self.verbose.max(if self.verbose_legacy { 2 } else { 0 }) {
+            0 => log::LevelFilter::Warn,
+            1 => log::LevelFilter::Info,
+            2 => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Trace,
+        }
+    }
+}
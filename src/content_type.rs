@@ -0,0 +1,133 @@
+//! Guards against vCenter's worst failure mode: a `200 OK` response that
+//! isn't actually JSON. During vCenter's own patching, its web services can
+//! come back up enough to answer with a `200` before the REST layer behind
+//! it is ready, serving an HTML error page instead; a reverse proxy or SSO
+//! gateway sitting in front of it can do the same with a login page. Either
+//! way, handing that straight to `serde_json::from_str` produces a cryptic
+//! parse error with no hint of what actually happened. [`validate_content_type`]
+//! catches it up front as a typed [`UnexpectedContentType`] instead, same
+//! as [`crate::auth::AuthError`] turns a bad status code into a typed error
+//! rather than letting a later, confusing failure speak for it.
+//!
+//! There's no real transport wired up yet (see [`crate::auth`]) for this to
+//! guard in anger; it exists now so both the session endpoint and the
+//! per-VM fetch path already have somewhere to plug a response into once
+//! one lands, and so the classification itself is unit-testable against
+//! literal status/content-type/body triples today.
+
+use thiserror::Error;
+
+/// How much of a response body to keep for diagnostics - enough to
+/// recognize what went wrong without logging an entire HTML error page.
+const BODY_SNIPPET_LEN: usize = 200;
+
+/// A response that claimed (or defaulted to) a `200` but wasn't the
+/// `expected` content type, from [`validate_content_type`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("unexpected content-type '{content_type}' for {status} response: {body_snippet}")]
+pub struct UnexpectedContentType {
+    pub status: u16,
+    pub content_type: String,
+    /// First [`BODY_SNIPPET_LEN`] characters of the body, whitespace
+    /// collapsed to single spaces so a multi-line HTML page doesn't turn
+    /// into a multi-line error or report row.
+    pub body_snippet: String,
+    /// Set when the body looks like a proxy or SSO login page rather than a
+    /// generic HTML error page - a different fix (re-auth against the
+    /// proxy, not vCenter) than vCenter itself being down.
+    pub looks_like_login_page: bool,
+}
+
+/// Collapses `body`'s whitespace to single spaces and truncates to
+/// [`BODY_SNIPPET_LEN`] characters, for inclusion in an error message
+/// without dumping an entire HTML page into it.
+fn sanitize_body_snippet(body: &str) -> String {
+    body.split_whitespace().collect::<Vec<_>>().join(" ").chars().take(BODY_SNIPPET_LEN).collect()
+}
+
+/// Loose, case-insensitive heuristic for "this HTML looks like a login
+/// page, not a vCenter error page" - a captive portal, reverse proxy, or
+/// SSO provider intercepting the request before it ever reached vCenter.
+/// False positives only cost a slightly-wrong hint, not a wrong
+/// classification, so a handful of common substrings is enough.
+fn looks_like_login_page(body: &str) -> bool {
+    let lower = body.to_lowercase();
+    ["sign in", "log in", "login", "password", "sso", "authentication required"]
+        .iter()
+        .any(|needle| lower.contains(needle))
+}
+
+/// Checks `content_type` against `expected` (e.g. `"application/json"`,
+/// ignoring a trailing `; charset=...`), returning `Err` with `status`, the
+/// actual `content_type`, and a sanitized snippet of `body` when they don't
+/// match. An empty `content_type` (some proxies drop the header entirely)
+/// and an empty `body` (a `200` with nothing behind it) are both treated as
+/// a mismatch rather than special-cased - either way, there's nothing here
+/// for a JSON parser to succeed on.
+pub fn validate_content_type(status: u16, content_type: &str, body: &str, expected: &str) -> Result<(), UnexpectedContentType> {
+    let actual = content_type.split(';').next().unwrap_or("").trim();
+    if actual.eq_ignore_ascii_case(expected) && !body.is_empty() {
+        return Ok(());
+    }
+    Err(UnexpectedContentType {
+        status,
+        content_type: content_type.to_string(),
+        body_snippet: sanitize_body_snippet(body),
+        looks_like_login_page: looks_like_login_page(body),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_json_content_type_with_a_body_passes() {
+        assert!(validate_content_type(200, "application/json", "{}", "application/json").is_ok());
+        assert!(validate_content_type(200, "application/json; charset=utf-8", "{}", "application/json").is_ok());
+    }
+
+    #[test]
+    fn html_with_200_is_rejected_and_snippet_is_sanitized() {
+        let body = "<html>\n  <body>Service Unavailable</body>\n</html>";
+        let err = validate_content_type(200, "text/html", body, "application/json").unwrap_err();
+        assert_eq!(err.status, 200);
+        assert_eq!(err.content_type, "text/html");
+        assert_eq!(err.body_snippet, "<html> <body>Service Unavailable</body> </html>");
+        assert!(!err.looks_like_login_page);
+    }
+
+    #[test]
+    fn empty_body_with_200_is_rejected() {
+        let err = validate_content_type(200, "application/json", "", "application/json").unwrap_err();
+        assert_eq!(err.status, 200);
+        assert_eq!(err.body_snippet, "");
+    }
+
+    #[test]
+    fn json_with_wrong_content_type_header_is_rejected() {
+        let err = validate_content_type(200, "text/plain", "{\"ok\":true}", "application/json").unwrap_err();
+        assert_eq!(err.content_type, "text/plain");
+    }
+
+    #[test]
+    fn login_page_is_flagged_with_a_hint() {
+        let body = "<html><body>Please sign in to continue</body></html>";
+        let err = validate_content_type(200, "text/html", body, "application/json").unwrap_err();
+        assert!(err.looks_like_login_page);
+    }
+
+    #[test]
+    fn generic_error_page_is_not_flagged_as_a_login_page() {
+        let body = "<html><body>502 Bad Gateway</body></html>";
+        let err = validate_content_type(200, "text/html", body, "application/json").unwrap_err();
+        assert!(!err.looks_like_login_page);
+    }
+
+    #[test]
+    fn body_snippet_is_truncated_to_the_length_limit() {
+        let body = "x".repeat(BODY_SNIPPET_LEN + 50);
+        let err = validate_content_type(200, "text/html", &body, "application/json").unwrap_err();
+        assert_eq!(err.body_snippet.len(), BODY_SNIPPET_LEN);
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::{stdout, Write};
+use std::time::Duration;
+
+use anyhow::Result;
+use crossterm::cursor::MoveTo;
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::terminal::{Clear, ClearType};
+use crossterm::{execute, terminal};
+
+use crate::cli::Args;
+use crate::report::compute_statistics;
+use crate::vcenter::VCenterClient;
+
+/// Renders one frame of the dashboard: a statistics line plus the current
+/// issue list, one per line. Kept as plain strings (no framework) since
+/// `--dashboard` is meant to be a lightweight "watch -n" style view, not a
+/// full TUI application.
+fn render_frame(statuses: &[crate::vm::VMResourceStatus], exclude_powered_off_from_stats: bool) -> String {
+    let stats = compute_statistics(statuses, exclude_powered_off_from_stats);
+    let mut out = format!(
+        "network-monitor dashboard -- {} VMs, {} with issues, {} powered off (press q to quit)\n\n",
+        stats.total_vms, stats.vms_with_issues, stats.powered_off
+    );
+    for vm in statuses.iter().filter(|v| v.has_issues()) {
+        for issue in &vm.issues {
+            out.push_str(&format!("{:<20} {:<20} {}\n", vm.name, vm.host, issue.issue_type));
+        }
+    }
+    out
+}
+
+/// Runs `--dashboard`: polls `client` on `args.interval_secs`, redrawing the
+/// terminal in place until the user presses `q`.
+pub fn run_dashboard(args: &Args, client: &dyn VCenterClient) -> Result<()> {
+    let disabled_issues = args
+        .disabled_issue_types()
+        .map_err(|err| anyhow::anyhow!("disable-issues: {err}"))?;
+    terminal::enable_raw_mode()?;
+    let mut out = stdout();
+    let result = (|| -> Result<()> {
+        loop {
+            let mut statuses = client.fetch_vm_statuses()?;
+            crate::vm::strip_disabled_issues(&mut statuses, &disabled_issues);
+            execute!(out, Clear(ClearType::All), MoveTo(0, 0))?;
+            for line in render_frame(&statuses, args.exclude_powered_off_from_stats).lines() {
+                write!(out, "{line}\r\n")?;
+            }
+            out.flush()?;
+
+            if event::poll(Duration::from_secs(args.interval_secs))? {
+                if let Event::Key(key) = event::read()? {
+                    if key.code == KeyCode::Char('q') {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    })();
+    terminal::disable_raw_mode()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState, VMIssueType, VMResourceStatus};
+    use std::collections::HashMap;
+
+    #[test]
+    fn frame_lists_issues_with_vm_and_host() {
+        let statuses = vec![VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 95.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")],
+        }];
+        let frame = render_frame(&statuses, false);
+        assert!(frame.contains("vm-0001"));
+        assert!(frame.contains("HIGH_CPU_USAGE"));
+    }
+}
@@ -0,0 +1,104 @@
+//! Parses the (simulated) vCenter datastore file-listing query used by
+//! `--check-vm-files` to confirm a powered-off VM's VMX file is still
+//! present on its datastore. Kept separate from `crate::vcenter` the same
+//! way `crate::migration`/`crate::bootevents` are - a dedicated parser over
+//! the raw search-result shape, unit tested against fixture rows without
+//! needing a live vCenter.
+
+use serde::Deserialize;
+
+/// One row from a (simulated) datastore `SearchDatastoreSubFolders`/file
+/// listing query for a single VMX path. `browsable: false` means the
+/// datastore itself refused the browse (permissions, maintenance mode,
+/// an NFS mount that's gone away) - that's a warning, not proof the file
+/// is missing, so it's kept distinct from `found: false`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawFileSearchResult {
+    pub datastore: String,
+    pub path: String,
+    pub browsable: bool,
+    pub found: bool,
+}
+
+/// What a [`RawFileSearchResult`] means for `--check-vm-files`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileSearchOutcome {
+    Found,
+    Missing,
+    BrowseForbidden,
+}
+
+/// An unbrowsable datastore always resolves to [`FileSearchOutcome::BrowseForbidden`],
+/// regardless of what `found` says - the browse never actually happened, so
+/// `found`'s value isn't meaningful.
+pub fn parse_file_search_result(raw: &RawFileSearchResult) -> FileSearchOutcome {
+    if !raw.browsable {
+        FileSearchOutcome::BrowseForbidden
+    } else if raw.found {
+        FileSearchOutcome::Found
+    } else {
+        FileSearchOutcome::Missing
+    }
+}
+
+/// The datastore and VMX path a simulated VM at fleet index `i` would be
+/// backed by, matching vCenter's `[datastore] vm-folder/vm.vmx` convention.
+/// Deterministic in everything but whether the file/datastore actually
+/// turns out missing/unbrowsable, the same way `inventory::synthetic_folder_id`
+/// is deterministic about placement but not about a VM's other attributes.
+pub fn synthetic_vmx_path(vm_name: &str, i: usize) -> (String, String) {
+    const DATASTORE_COUNT: usize = 4;
+    let datastore = format!("datastore-{}", i % DATASTORE_COUNT);
+    (datastore.clone(), format!("[{datastore}] {vm_name}/{vm_name}.vmx"))
+}
+
+/// Same convention as [`synthetic_vmx_path`], for a suspended VM's `.vmss`
+/// suspend-state memory file instead of its VMX.
+pub fn synthetic_vmss_path(vm_name: &str, i: usize) -> (String, String) {
+    const DATASTORE_COUNT: usize = 4;
+    let datastore = format!("datastore-{}", i % DATASTORE_COUNT);
+    (datastore.clone(), format!("[{datastore}] {vm_name}/{vm_name}.vmss"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(browsable: bool, found: bool) -> RawFileSearchResult {
+        RawFileSearchResult {
+            datastore: "datastore-0".to_string(),
+            path: "[datastore-0] vm-0001/vm-0001.vmx".to_string(),
+            browsable,
+            found,
+        }
+    }
+
+    #[test]
+    fn found_file_resolves_to_found() {
+        assert_eq!(parse_file_search_result(&row(true, true)), FileSearchOutcome::Found);
+    }
+
+    #[test]
+    fn missing_file_resolves_to_missing() {
+        assert_eq!(parse_file_search_result(&row(true, false)), FileSearchOutcome::Missing);
+    }
+
+    #[test]
+    fn unbrowsable_datastore_resolves_to_browse_forbidden_even_if_found_is_set() {
+        assert_eq!(parse_file_search_result(&row(false, true)), FileSearchOutcome::BrowseForbidden);
+    }
+
+    #[test]
+    fn synthetic_path_follows_vcenter_datastore_bracket_convention() {
+        let (datastore, path) = synthetic_vmx_path("vm-0005", 5);
+        assert_eq!(datastore, "datastore-1");
+        assert_eq!(path, "[datastore-1] vm-0005/vm-0005.vmx");
+    }
+
+    #[test]
+    fn synthetic_vmss_path_follows_the_same_convention_as_vmx() {
+        let (datastore, path) = synthetic_vmss_path("vm-0005", 5);
+        assert_eq!(datastore, "datastore-1");
+        assert_eq!(path, "[datastore-1] vm-0005/vm-0005.vmss");
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::replay::replay_statuses;
+use crate::vcenter::DetectionOptions;
+use crate::vm::{HostMetrics, VMResourceStatus};
+
+/// Bundled into the binary (not read from disk) so `--demo` works with
+/// nothing on hand but the executable itself - the whole point of evaluating
+/// without a real vCenter. The same file backs `tests/demo.rs`, so the
+/// fixture can't quietly rot out of sync with what `--demo` actually ships.
+const DEMO_INVENTORY_JSON: &str = include_str!("../fixtures/demo_inventory.json");
+
+/// Printed to stderr and stamped into every rendered report so synthetic
+/// data is never mistaken for a real fleet.
+pub const DEMO_WATERMARK: &str = "DEMO DATA - synthetic fixture inventory, not a real vCenter";
+
+/// Same shape as [`crate::replay::ReplayInput`] - the bundled fixture is a
+/// `--format json` `v2`-style report, so it exercises exactly the fields
+/// detection and rendering use.
+#[derive(Debug, Deserialize)]
+struct DemoInventory {
+    vms: Vec<VMResourceStatus>,
+    #[serde(default)]
+    host_metrics: BTreeMap<String, HostMetrics>,
+}
+
+/// Parses the bundled fixture and reruns detection against it with
+/// `options`, exactly as `--replay` does against a saved report - so
+/// `--demo` exercises the same detection pipeline as a live run, just
+/// against fixture data instead of a vCenter query.
+pub fn load_demo_fleet(options: &DetectionOptions) -> Result<(Vec<VMResourceStatus>, BTreeMap<String, HostMetrics>)> {
+    let inventory: DemoInventory =
+        serde_json::from_str(DEMO_INVENTORY_JSON).context("parsing bundled demo fixture (this is a packaging bug)")?;
+    let statuses = replay_statuses(inventory.vms, &inventory.host_metrics, options);
+    Ok((statuses, inventory.host_metrics))
+}
+
+/// Watermarks a `--format text` report.
+pub fn watermark_text(report: &str) -> String {
+    format!("*** {DEMO_WATERMARK} ***\n{report}")
+}
+
+/// Watermarks a `--format json` report by adding a top-level field, so it
+/// stays valid, parseable JSON instead of a banner line breaking the format.
+pub fn watermark_json(report: &str) -> Result<String> {
+    let mut value: serde_json::Value = serde_json::from_str(report).context("re-parsing rendered demo report")?;
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("demo_data_watermark".to_string(), serde_json::Value::String(DEMO_WATERMARK.to_string()));
+    }
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Watermarks a `--format csv` report with a leading comment line.
+pub fn watermark_csv(report: &str) -> String {
+    format!("# {DEMO_WATERMARK}\n{report}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_fixture_parses_and_has_a_realistic_fleet_size() {
+        let (statuses, host_metrics) = load_demo_fleet(&DetectionOptions::default()).unwrap();
+        assert!(statuses.len() >= 30 && statuses.len() <= 50, "expected 30-50 VMs, got {}", statuses.len());
+        assert!(!host_metrics.is_empty());
+        assert!(statuses.iter().any(|v| v.has_issues()), "demo fleet should exercise at least one detector");
+    }
+
+    #[test]
+    fn json_watermark_stays_valid_json_with_the_watermark_field() {
+        let watermarked = watermark_json(r#"{"vms":[]}"#).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&watermarked).unwrap();
+        assert_eq!(value["demo_data_watermark"], DEMO_WATERMARK);
+    }
+}
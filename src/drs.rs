@@ -0,0 +1,363 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vm::{DetectedIssue, VMIssueType, VMResourceStatus};
+
+/// Whether a DRS rule must hold (`Mandatory` - DRS refuses to place a VM in
+/// violation of it even under host failure) or is merely a hint DRS is
+/// allowed to break rather than leave a VM powered off (`Preferential`).
+/// Only `Mandatory` rules are worth alerting on; see [`evaluate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Enforcement {
+    Mandatory,
+    Preferential,
+}
+
+/// A cluster DRS rule, as fetched from the cluster configuration API.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DrsRule {
+    /// Keep every VM in `vm_names` on the same host.
+    Affinity {
+        name: String,
+        enforcement: Enforcement,
+        vm_names: Vec<String>,
+    },
+    /// Keep every VM in `vm_names` on a different host from the others.
+    AntiAffinity {
+        name: String,
+        enforcement: Enforcement,
+        vm_names: Vec<String>,
+    },
+    /// Keep every VM in `vm_names` running only on one of `hosts`.
+    VmHostGroup {
+        name: String,
+        enforcement: Enforcement,
+        vm_names: Vec<String>,
+        hosts: Vec<String>,
+    },
+}
+
+impl DrsRule {
+    fn name(&self) -> &str {
+        match self {
+            DrsRule::Affinity { name, .. } | DrsRule::AntiAffinity { name, .. } | DrsRule::VmHostGroup { name, .. } => name,
+        }
+    }
+
+    fn enforcement(&self) -> Enforcement {
+        match self {
+            DrsRule::Affinity { enforcement, .. } | DrsRule::AntiAffinity { enforcement, .. } | DrsRule::VmHostGroup { enforcement, .. } => {
+                *enforcement
+            }
+        }
+    }
+
+    fn vm_names(&self) -> &[String] {
+        match self {
+            DrsRule::Affinity { vm_names, .. } | DrsRule::AntiAffinity { vm_names, .. } | DrsRule::VmHostGroup { vm_names, .. } => vm_names,
+        }
+    }
+}
+
+/// `--drs-rules` config: the cluster's affinity/anti-affinity/VM-host group
+/// rules, loaded once per run and evaluated against current placement by
+/// `--check-drs-rules`.
+#[derive(Debug, Deserialize)]
+pub struct DrsRuleConfig {
+    pub rules: Vec<DrsRule>,
+}
+
+impl DrsRuleConfig {
+    pub fn load(path: &str, strict_json: bool) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading DRS rules config {path}"))?;
+        crate::strict_json::parse(&raw, &format!("DRS rules config {path}"), strict_json, &["rules"])
+    }
+}
+
+/// A `Mandatory` rule broken by current placement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleViolation {
+    pub rule_name: String,
+    pub vm_names: Vec<String>,
+    pub hosts: Vec<String>,
+    pub detail: String,
+}
+
+/// A rule that names at least one VM no longer in the inventory. Flagged
+/// rather than evaluated - "violated" implies the VM is somewhere it
+/// shouldn't be, not that it's gone, and treating a decommissioned VM's old
+/// rule as a live violation would just be noise.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StaleRule {
+    pub rule_name: String,
+    pub missing_vm_names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ComplianceReport {
+    pub violations: Vec<RuleViolation>,
+    pub stale_rules: Vec<StaleRule>,
+}
+
+impl ComplianceReport {
+    pub fn is_empty(&self) -> bool {
+        self.violations.is_empty() && self.stale_rules.is_empty()
+    }
+
+    /// Renders the run-level compliance section appended to the text
+    /// report, mirroring the trailing-section convention
+    /// [`crate::notifier::NotifyRunResult::render_section`] already uses.
+    pub fn render_section(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("DRS RULE COMPLIANCE:\n");
+        for violation in &self.violations {
+            out.push_str(&format!(
+                "  {} VIOLATED: {} on {} - {}\n",
+                violation.rule_name,
+                violation.vm_names.join(", "),
+                violation.hosts.join(", "),
+                violation.detail
+            ));
+        }
+        for stale in &self.stale_rules {
+            out.push_str(&format!(
+                "  {} STALE: references missing VM(s) {}\n",
+                stale.rule_name,
+                stale.missing_vm_names.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+/// Evaluates `rules` against `placements` (VM name -> current host), the
+/// pure core of `--check-drs-rules`: no network access, so rule semantics
+/// can be unit tested in isolation from the (simulated) cluster API.
+pub fn evaluate(rules: &[DrsRule], placements: &HashMap<String, String>) -> ComplianceReport {
+    let mut report = ComplianceReport::default();
+
+    for rule in rules {
+        let missing: Vec<String> = rule.vm_names().iter().filter(|name| !placements.contains_key(*name)).cloned().collect();
+        if !missing.is_empty() {
+            report.stale_rules.push(StaleRule {
+                rule_name: rule.name().to_string(),
+                missing_vm_names: missing,
+            });
+            continue;
+        }
+        if rule.enforcement() != Enforcement::Mandatory {
+            continue;
+        }
+
+        match rule {
+            DrsRule::Affinity { vm_names, .. } => {
+                let hosts: HashSet<&str> = vm_names.iter().map(|name| placements[name].as_str()).collect();
+                if hosts.len() > 1 {
+                    report.violations.push(RuleViolation {
+                        rule_name: rule.name().to_string(),
+                        vm_names: vm_names.clone(),
+                        hosts: hosts.into_iter().map(str::to_string).collect(),
+                        detail: "affinity rule requires the same host, but VMs are split across hosts".to_string(),
+                    });
+                }
+            }
+            DrsRule::AntiAffinity { vm_names, .. } => {
+                let mut by_host: HashMap<&str, Vec<&str>> = HashMap::new();
+                for vm_name in vm_names {
+                    by_host.entry(placements[vm_name].as_str()).or_default().push(vm_name.as_str());
+                }
+                for (host, vms) in by_host {
+                    if vms.len() > 1 {
+                        report.violations.push(RuleViolation {
+                            rule_name: rule.name().to_string(),
+                            vm_names: vms.into_iter().map(str::to_string).collect(),
+                            hosts: vec![host.to_string()],
+                            detail: format!("anti-affinity rule requires separate hosts, but all are on {host}"),
+                        });
+                    }
+                }
+            }
+            DrsRule::VmHostGroup { vm_names, hosts, .. } => {
+                let allowed: HashSet<&str> = hosts.iter().map(String::as_str).collect();
+                let offending: Vec<(&str, &str)> = vm_names
+                    .iter()
+                    .map(|name| (name.as_str(), placements[name].as_str()))
+                    .filter(|(_, host)| !allowed.contains(*host))
+                    .collect();
+                if !offending.is_empty() {
+                    report.violations.push(RuleViolation {
+                        rule_name: rule.name().to_string(),
+                        vm_names: offending.iter().map(|(name, _)| name.to_string()).collect(),
+                        hosts: offending.iter().map(|(_, host)| host.to_string()).collect(),
+                        detail: format!("VM-host group rule requires one of [{}]", hosts.join(", ")),
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+/// Appends [`VMIssueType::DrsRuleViolation`] to every VM named in
+/// `compliance`'s violations, so the per-VM report/notifications carry the
+/// same finding the run-level compliance section describes.
+pub fn flag_violations(statuses: &mut [VMResourceStatus], compliance: &ComplianceReport) {
+    let flagged: HashSet<&str> = compliance.violations.iter().flat_map(|v| v.vm_names.iter().map(String::as_str)).collect();
+    for vm in statuses.iter_mut() {
+        if flagged.contains(vm.name.as_str()) {
+            vm.issues.push(DetectedIssue::new(
+                VMIssueType::DrsRuleViolation,
+                "VM placement violates a mandatory DRS rule; see the DRS rule compliance section for detail",
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placements(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(vm, host)| (vm.to_string(), host.to_string())).collect()
+    }
+
+    #[test]
+    fn mandatory_affinity_violated_when_vms_land_on_different_hosts() {
+        let rules = vec![DrsRule::Affinity {
+            name: "db-pair-affinity".to_string(),
+            enforcement: Enforcement::Mandatory,
+            vm_names: vec!["db-a".to_string(), "db-b".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("db-a", "esxi-01"), ("db-b", "esxi-02")]));
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].rule_name, "db-pair-affinity");
+    }
+
+    #[test]
+    fn mandatory_anti_affinity_violated_when_vms_share_a_host() {
+        let rules = vec![DrsRule::AntiAffinity {
+            name: "db-pair-anti-affinity".to_string(),
+            enforcement: Enforcement::Mandatory,
+            vm_names: vec!["db-a".to_string(), "db-b".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("db-a", "esxi-01"), ("db-b", "esxi-01")]));
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].hosts, vec!["esxi-01".to_string()]);
+    }
+
+    #[test]
+    fn anti_affinity_satisfied_when_vms_on_separate_hosts() {
+        let rules = vec![DrsRule::AntiAffinity {
+            name: "db-pair-anti-affinity".to_string(),
+            enforcement: Enforcement::Mandatory,
+            vm_names: vec!["db-a".to_string(), "db-b".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("db-a", "esxi-01"), ("db-b", "esxi-02")]));
+        assert!(report.violations.is_empty());
+    }
+
+    #[test]
+    fn vm_host_group_violated_when_a_vm_strays_outside_the_allowed_hosts() {
+        let rules = vec![DrsRule::VmHostGroup {
+            name: "pci-zone".to_string(),
+            enforcement: Enforcement::Mandatory,
+            vm_names: vec!["payments-1".to_string()],
+            hosts: vec!["esxi-pci-01".to_string(), "esxi-pci-02".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("payments-1", "esxi-03")]));
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].vm_names, vec!["payments-1".to_string()]);
+    }
+
+    #[test]
+    fn preferential_rules_are_never_reported_as_violated() {
+        let rules = vec![DrsRule::Affinity {
+            name: "soft-affinity".to_string(),
+            enforcement: Enforcement::Preferential,
+            vm_names: vec!["vm-a".to_string(), "vm-b".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("vm-a", "esxi-01"), ("vm-b", "esxi-02")]));
+        assert!(report.violations.is_empty(), "a broken preferential rule is expected DRS behavior, not a violation");
+    }
+
+    #[test]
+    fn rule_naming_a_missing_vm_is_stale_not_violated() {
+        let rules = vec![DrsRule::AntiAffinity {
+            name: "decommissioned-pair".to_string(),
+            enforcement: Enforcement::Mandatory,
+            vm_names: vec!["db-a".to_string(), "db-gone".to_string()],
+        }];
+        let report = evaluate(&rules, &placements(&[("db-a", "esxi-01")]));
+        assert!(report.violations.is_empty());
+        assert_eq!(report.stale_rules.len(), 1);
+        assert_eq!(report.stale_rules[0].missing_vm_names, vec!["db-gone".to_string()]);
+    }
+
+    #[test]
+    fn flag_violations_only_touches_vms_named_in_a_violation() {
+        let mut statuses = vec![
+            crate::vm::VMResourceStatus {
+                name: "db-a".to_string(),
+                host: "esxi-01".to_string(),
+                cluster: "cluster-a".to_string(),
+                inventory_path: "/unknown".to_string(),
+                power_state: crate::vm::PowerState::PoweredOn,
+                cpu_usage_pct: 10.0,
+                memory_usage_pct: 10.0,
+                raw_metrics: std::collections::HashMap::new(),
+                metrics_source: crate::vm::MetricsSourceStatus::Available,
+                cpu_count: 2,
+                cores_per_socket: 1,
+                memory_gb: 16.0,
+                hardware_version: "vmx-19".to_string(),
+                cpu_hot_add_enabled: true,
+                memory_hot_add_enabled: true,
+                guest_visible_memory_mb: None,
+                guest_visible_cpu_count: None,
+                disk_allocated_gb: 100.0,
+                disk_used_gb: Some(50.0),
+                usage_basis: crate::vm::UsageBasis::Configured,
+                tools_running: true,
+                clock_skew_secs: None,
+                guest_ip: None,
+                reachable: None,
+                running_processes: Vec::new(),
+                attributes: std::collections::HashMap::new(),
+                notes: None,
+                migration_count_24h: 0,
+                last_migration: None,
+                uptime_secs: 30.0 * 86400.0,
+                created_recently: false,
+                power_on_count: 0,
+                last_power_on_secs_ago: None,
+                suspended_duration_secs: None,
+                health_score: 100.0,
+                change_version: 0,
+                issues: Vec::new(),
+            },
+        ];
+        let compliance = ComplianceReport {
+            violations: vec![RuleViolation {
+                rule_name: "db-pair-anti-affinity".to_string(),
+                vm_names: vec!["db-a".to_string()],
+                hosts: vec!["esxi-01".to_string()],
+                detail: "x".to_string(),
+            }],
+            stale_rules: Vec::new(),
+        };
+        let mut bystander = statuses[0].clone();
+        bystander.name = "web-01".to_string();
+        statuses.push(bystander);
+        flag_violations(&mut statuses, &compliance);
+        assert!(statuses[0].issues.iter().any(|i| i.issue_type == VMIssueType::DrsRuleViolation));
+        assert!(statuses[1].issues.is_empty());
+    }
+}
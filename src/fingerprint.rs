@@ -0,0 +1,136 @@
+//! Computes a stable per-(VM, issue type) identifier for downstream
+//! ticketing integrations, so a rerun updates an existing ticket instead of
+//! opening a duplicate, and tracks how long each one has been open. See
+//! [`compute`] for the compatibility promise and its one known gap.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::BTreeMap;
+use std::hash::{Hash, Hasher};
+
+use chrono::{DateTime, Utc};
+
+use crate::vm::{VMIssueType, VMResourceStatus};
+
+/// Hashes `(vcenter_host, vm_name, issue_type)` into a 16-character hex
+/// string, using [`DefaultHasher`]'s fixed (non-randomized) keys so the
+/// same inputs always hash to the same output across process restarts -
+/// unlike `HashMap`'s `RandomState`, which reseeds every run.
+///
+/// Compatibility promise: for a given vCenter host, VM, and issue type,
+/// this value never changes across runs, report format versions, or
+/// `--state-file` migrations. The one gap: this inventory model (see
+/// [`crate::inventory`]) has no vCenter moId/instance-UUID to identify a VM
+/// independent of its display name, so `vm_name` stands in for it here - a
+/// VM rename changes its fingerprint. Everything else the request asked
+/// for (surviving reruns, vMotion, cluster reshuffling) holds; plugging in
+/// a real stable VM id, if this inventory model ever grows one, only
+/// requires changing this function.
+pub fn compute(vcenter_host: &str, vm_name: &str, issue_type: VMIssueType) -> String {
+    let mut hasher = DefaultHasher::new();
+    vcenter_host.hash(&mut hasher);
+    vm_name.hash(&mut hasher);
+    issue_type.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Fills in `fingerprint`/`first_seen` on every issue in `statuses`.
+/// `first_seen` is `--state-file`'s carried-forward map from fingerprint to
+/// the run that first saw it; a fingerprint missing from it is new this
+/// run and gets `now`. Always on, unlike most `--state-file` features,
+/// since ticket age isn't something an operator opts into separately.
+pub fn annotate(statuses: &mut [VMResourceStatus], vcenter_host: &str, first_seen: &mut BTreeMap<String, DateTime<Utc>>, now: DateTime<Utc>) {
+    for vm in statuses {
+        for issue in &mut vm.issues {
+            let fingerprint = compute(vcenter_host, &vm.name, issue.issue_type);
+            let seen_at = *first_seen.entry(fingerprint.clone()).or_insert(now);
+            issue.fingerprint = fingerprint;
+            issue.first_seen = Some(seen_at);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_inputs_hash_to_a_fixed_output() {
+        assert_eq!(compute("vcenter.example.com", "vm-0001", VMIssueType::HighCpuUsage), "b1df775e6aaad2d0");
+    }
+
+    #[test]
+    fn differs_by_host_name_and_issue_type() {
+        let base = compute("vcenter-a", "vm-0001", VMIssueType::HighCpuUsage);
+        assert_ne!(base, compute("vcenter-b", "vm-0001", VMIssueType::HighCpuUsage));
+        assert_ne!(base, compute("vcenter-a", "vm-0002", VMIssueType::HighCpuUsage));
+        assert_ne!(base, compute("vcenter-a", "vm-0001", VMIssueType::HighMemoryUsage));
+    }
+
+    fn vm(name: &str, issues: Vec<crate::vm::DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: crate::vm::PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn new_fingerprints_are_first_seen_now() {
+        let mut statuses = vec![vm("vm-0001", vec![crate::vm::DetectedIssue::new(VMIssueType::HighCpuUsage, "x")])];
+        let mut first_seen = BTreeMap::new();
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+
+        annotate(&mut statuses, "vcenter.example.com", &mut first_seen, now);
+
+        assert_eq!(statuses[0].issues[0].first_seen, Some(now));
+        assert!(!statuses[0].issues[0].fingerprint.is_empty());
+        assert_eq!(first_seen.len(), 1);
+    }
+
+    #[test]
+    fn a_fingerprint_already_in_the_map_keeps_its_original_first_seen() {
+        let mut statuses = vec![vm("vm-0001", vec![crate::vm::DetectedIssue::new(VMIssueType::HighCpuUsage, "x")])];
+        let first_seen_at: DateTime<Utc> = "2026-08-05T00:00:00Z".parse().unwrap();
+        let mut first_seen = BTreeMap::new();
+        first_seen.insert(compute("vcenter.example.com", "vm-0001", VMIssueType::HighCpuUsage), first_seen_at);
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+
+        annotate(&mut statuses, "vcenter.example.com", &mut first_seen, now);
+
+        assert_eq!(statuses[0].issues[0].first_seen, Some(first_seen_at));
+    }
+}
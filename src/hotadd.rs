@@ -0,0 +1,159 @@
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::vm::{DetectedIssue, PowerState, VMIssueType, VMResourceStatus};
+
+/// `--hot-add-scope`'s JSON file, for when `--require-hot-add` should only
+/// apply to certain workloads - blanket-enforcing it fleet-wide would flag
+/// VMs deliberately built without it. A VM is in scope if its name contains
+/// `name_contains` (when set) or its `tag_attribute` custom attribute's
+/// value is one of `tags` (when set); an empty config (neither set) scopes
+/// every VM, same as omitting `--hot-add-scope` entirely.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct HotAddScope {
+    /// Plain substring match against the VM name, not a glob/regex, same
+    /// tradeoff as [`crate::notifier::NotifierFilter::vm_name_contains`].
+    pub name_contains: Option<String>,
+    /// Custom attribute (e.g. `"Tier"`) whose value is checked against `tags`.
+    pub tag_attribute: Option<String>,
+    pub tags: Vec<String>,
+}
+
+impl HotAddScope {
+    pub fn load(path: &str, strict_json: bool) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading hot-add scope config {path}"))?;
+        crate::strict_json::parse(&raw, &format!("hot-add scope config {path}"), strict_json, &["name_contains", "tag_attribute", "tags"])
+    }
+
+    fn matches(&self, vm: &VMResourceStatus) -> bool {
+        if self.name_contains.is_none() && self.tag_attribute.is_none() {
+            return true;
+        }
+        if let Some(pattern) = &self.name_contains {
+            if vm.name.contains(pattern.as_str()) {
+                return true;
+            }
+        }
+        if let Some(attr) = &self.tag_attribute {
+            if let Some(value) = vm.attributes.get(attr) {
+                if self.tags.iter().any(|t| t == value) {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+}
+
+/// Flags every powered-on VM in `scope` (every powered-on VM, when
+/// `scope` is `None`) whose hardware reports hot-add disabled for CPU or
+/// memory. A powered-off VM's hot-add settings don't matter until it's
+/// turned back on, so it's left alone regardless of scope.
+pub fn flag_disabled(statuses: &mut [VMResourceStatus], scope: Option<&HotAddScope>) {
+    for vm in statuses.iter_mut() {
+        if vm.power_state != PowerState::PoweredOn {
+            continue;
+        }
+        if vm.cpu_hot_add_enabled && vm.memory_hot_add_enabled {
+            continue;
+        }
+        if !scope.is_none_or(|s| s.matches(vm)) {
+            continue;
+        }
+        let disabled = match (vm.cpu_hot_add_enabled, vm.memory_hot_add_enabled) {
+            (false, false) => "CPU and memory hot-add are both disabled",
+            (false, true) => "CPU hot-add is disabled",
+            (true, false) => "memory hot-add is disabled",
+            (true, true) => unreachable!("already skipped above"),
+        };
+        vm.issues.push(DetectedIssue::new(VMIssueType::HotAddDisabled, disabled));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vm(name: &str, cpu_hot_add_enabled: bool, memory_hot_add_enabled: bool) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled,
+            memory_hot_add_enabled,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flags_only_vms_with_hot_add_disabled() {
+        let mut statuses = vec![vm("vm-1", true, true), vm("vm-2", false, true), vm("vm-3", true, false)];
+        flag_disabled(&mut statuses, None);
+        assert!(!statuses[0].has_issues());
+        assert_eq!(statuses[1].issues[0].issue_type, VMIssueType::HotAddDisabled);
+        assert_eq!(statuses[2].issues[0].issue_type, VMIssueType::HotAddDisabled);
+    }
+
+    #[test]
+    fn powered_off_vms_are_never_flagged() {
+        let mut off = vm("vm-1", false, false);
+        off.power_state = PowerState::PoweredOff;
+        let mut statuses = vec![off];
+        flag_disabled(&mut statuses, None);
+        assert!(!statuses[0].has_issues());
+    }
+
+    #[test]
+    fn scope_restricts_flagging_to_matching_vms() {
+        let mut attrs = HashMap::new();
+        attrs.insert("Tier".to_string(), "batch".to_string());
+        let mut in_scope = vm("worker-01", false, true);
+        in_scope.attributes = attrs;
+        let out_of_scope = vm("web-01", false, true);
+
+        let scope = HotAddScope {
+            name_contains: None,
+            tag_attribute: Some("Tier".to_string()),
+            tags: vec!["batch".to_string()],
+        };
+        let mut statuses = vec![in_scope, out_of_scope];
+        flag_disabled(&mut statuses, Some(&scope));
+        assert!(statuses[0].has_issues());
+        assert!(!statuses[1].has_issues());
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use crate::cli::{Args, OutputFormat};
+use crate::report::format_issue;
+use crate::vcenter::VCenterClient;
+use crate::vm::{UptimeFormat, VMResourceStatus};
+
+/// Outcome of [`find_vm`]: an exact/unambiguous match, a name that matched
+/// more than one VM, or no match at all.
+pub enum FindResult<'a> {
+    Found(&'a VMResourceStatus),
+    Ambiguous(Vec<&'a str>),
+    NotFound,
+}
+
+/// Resolves `inspect`'s `<vm-name-or-id>` argument against `statuses`: an
+/// exact name match wins outright (so a fleet with `vm-0001` and
+/// `vm-00010` can still target `vm-0001` precisely); otherwise every VM
+/// whose name contains `query` case-insensitively is a candidate. Zero
+/// candidates is [`FindResult::NotFound`], more than one is
+/// [`FindResult::Ambiguous`] (so the caller can list them and let the user
+/// narrow down), exactly one is [`FindResult::Found`].
+pub fn find_vm<'a>(statuses: &'a [VMResourceStatus], query: &str) -> FindResult<'a> {
+    if let Some(vm) = statuses.iter().find(|vm| vm.name == query) {
+        return FindResult::Found(vm);
+    }
+    let query_lower = query.to_lowercase();
+    let candidates: Vec<&VMResourceStatus> = statuses.iter().filter(|vm| vm.name.to_lowercase().contains(&query_lower)).collect();
+    match candidates.len() {
+        0 => FindResult::NotFound,
+        1 => FindResult::Found(candidates[0]),
+        _ => FindResult::Ambiguous(candidates.iter().map(|vm| vm.name.as_str()).collect()),
+    }
+}
+
+/// Renders the `inspect` text report: every section this simulated
+/// vCenter actually collects for one VM. There's no disk/NIC/snapshot/tag
+/// inventory here - this tree's [`crate::vcenter::VCenterClient`] doesn't
+/// model those, so rather than fabricate them this just says so.
+pub fn render_text(vm: &VMResourceStatus, history: Option<&BTreeMap<String, Vec<(f64, f64)>>>, uptime_format: UptimeFormat) -> String {
+    let mut out = format!("=== {} ({}) ===\n\n", vm.name, vm.power_state);
+
+    out.push_str("-- Identity --\n");
+    out.push_str(&format!("host:            {}\n", vm.host));
+    out.push_str(&format!("cluster:         {}\n", vm.cluster));
+    out.push_str(&format!("inventory path:  {}\n\n", vm.inventory_path));
+
+    out.push_str("-- Hardware --\n");
+    out.push_str(&format!("vCPUs:           {} ({} cores/socket)\n", vm.cpu_count, vm.cores_per_socket));
+    out.push_str(&format!("memory:          {:.1} GB\n", vm.memory_gb));
+    out.push_str(&format!("hw version:      {}\n", vm.hardware_version));
+    out.push_str(&format!("hot-add:         cpu={} memory={}\n", vm.cpu_hot_add_enabled, vm.memory_hot_add_enabled));
+    out.push_str(&format!(
+        "guest-visible:   cpu={} memory={} (usage computed against {})\n\n",
+        vm.guest_visible_cpu_count.map(|c| c.to_string()).unwrap_or_else(|| "-".to_string()),
+        vm.guest_visible_memory_mb.map(|mb| format!("{:.0} MB", mb)).unwrap_or_else(|| "-".to_string()),
+        match vm.usage_basis {
+            crate::vm::UsageBasis::Configured => "configured size",
+            crate::vm::UsageBasis::GuestVisible => "guest-visible size",
+        }
+    ));
+
+    out.push_str("-- Tools & guest --\n");
+    out.push_str(&format!("tools running:   {}\n", vm.tools_running));
+    out.push_str(&format!("guest IP:        {}\n", vm.guest_ip.as_deref().unwrap_or("-")));
+    out.push_str(&format!(
+        "reachable:       {}\n",
+        vm.reachable.map(|r| r.to_string()).unwrap_or_else(|| "-".to_string())
+    ));
+    if !vm.running_processes.is_empty() {
+        out.push_str(&format!("processes:       {}\n", vm.running_processes.join(", ")));
+    }
+    out.push('\n');
+
+    out.push_str("-- Performance --\n");
+    out.push_str(&format!("metrics source:  {:?}\n", vm.metrics_source));
+    let samples = history.and_then(|h| h.get(&vm.name));
+    let cpu_samples: Vec<f64> = samples.map(|s| s.iter().map(|(cpu, _)| *cpu).collect()).unwrap_or_default();
+    let mem_samples: Vec<f64> = samples.map(|s| s.iter().map(|(_, mem)| *mem).collect()).unwrap_or_default();
+    out.push_str(&format!(
+        "cpu:             {:.1}% {}\n",
+        vm.cpu_usage_pct,
+        crate::sparkline::render(&cpu_samples, vm.cpu_usage_pct)
+    ));
+    out.push_str(&format!(
+        "memory:          {:.1}% {}\n\n",
+        vm.memory_usage_pct,
+        crate::sparkline::render(&mem_samples, vm.memory_usage_pct)
+    ));
+
+    out.push_str("-- Events (recent) --\n");
+    out.push_str(&format!("uptime:          {}\n", crate::vm::format_uptime(vm.uptime_secs, uptime_format)));
+    out.push_str(&format!("power-ons:       {}\n", vm.power_on_count));
+    out.push_str(&format!("migrations:      {}\n", vm.migration_count_24h));
+    if let Some(migration) = &vm.last_migration {
+        out.push_str(&format!("last migration:  {} -> {}\n", migration.from_host, migration.to_host));
+    }
+    if let Some(suspended_secs) = vm.suspended_duration_secs {
+        out.push_str(&format!("suspended for:   {}\n", crate::vm::format_uptime(suspended_secs, uptime_format)));
+    }
+    out.push('\n');
+
+    out.push_str(&format!("-- Health: {:.1} --\n", vm.health_score));
+    if vm.issues.is_empty() {
+        out.push_str("no detected issues\n");
+    } else {
+        for issue in &vm.issues {
+            out.push_str(&format!("  {}\n", format_issue(issue, uptime_format)));
+        }
+    }
+
+    out
+}
+
+#[derive(Serialize)]
+struct InspectJson<'a> {
+    run_id: &'a str,
+    vm: &'a VMResourceStatus,
+}
+
+fn render_json(vm: &VMResourceStatus, run_id: &str, compact: bool) -> Result<String> {
+    let report = InspectJson { run_id, vm };
+    Ok(if compact {
+        serde_json::to_string(&report)?
+    } else {
+        serde_json::to_string_pretty(&report)?
+    })
+}
+
+/// Runs `inspect <vm-name-or-id>`: resolves `query` against a fetch of the
+/// fleet, prints an ambiguous-match list (and exits non-zero) rather than
+/// guessing, then renders the one matching VM. With `--watch`, repeats on
+/// `args.interval_secs` like [`crate::watch::run_watch_mode`] - re-fetching
+/// and re-resolving every cycle, since the VM's candidates could in
+/// principle change between cycles.
+pub fn run_inspect(args: &Args, client: &dyn VCenterClient, query: &str, run_id: &str) -> Result<()> {
+    loop {
+        let statuses = client.fetch_vm_statuses()?;
+        match find_vm(&statuses, query) {
+            FindResult::NotFound => anyhow::bail!("inspect: no VM matching '{query}'"),
+            FindResult::Ambiguous(names) => {
+                anyhow::bail!("inspect: '{query}' matches {} VMs, narrow it down: {}", names.len(), names.join(", "));
+            }
+            FindResult::Found(vm) => {
+                let history = if args.history.is_empty() {
+                    None
+                } else {
+                    Some(crate::thresholds::load_history(&args.history, args.lookback_days)?)
+                };
+                let rendered = match args.format {
+                    OutputFormat::Csv => anyhow::bail!("inspect: --format csv is not supported, use text or json"),
+                    OutputFormat::Json => render_json(vm, run_id, args.compact_json)?,
+                    OutputFormat::Text => render_text(vm, history.as_ref(), args.uptime_format.into()),
+                };
+                crate::sink::sink_for(args).write(&rendered)?;
+            }
+        }
+        if !args.watch {
+            return Ok(());
+        }
+        thread::sleep(Duration::from_secs(args.interval_secs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, MetricsSourceStatus, PowerState, VMIssueType};
+
+    fn vm(name: &str) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/DC1/vm/cluster-a".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 40.0,
+            memory_usage_pct: 60.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: MetricsSourceStatus::Available,
+            cpu_count: 4,
+            cores_per_socket: 2,
+            memory_gb: 32.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: Some("10.0.0.5".to_string()),
+            reachable: Some(true),
+            running_processes: vec!["nginx".to_string()],
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 1,
+            last_migration: Some(crate::vm::LastMigration {
+                from_host: "esxi-00".to_string(),
+                to_host: "esxi-01".to_string(),
+            }),
+            uptime_secs: 86400.0,
+            created_recently: false,
+            power_on_count: 1,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            issues: vec![DetectedIssue::new(VMIssueType::ToolsNotRunning, "x")],
+            health_score: 90.0,
+            change_version: 0,
+        }
+    }
+
+    #[test]
+    fn exact_name_match_wins_over_a_substring_match_on_another_vm() {
+        let statuses = vec![vm("vm-0001"), vm("vm-00010")];
+        match find_vm(&statuses, "vm-0001") {
+            FindResult::Found(found) => assert_eq!(found.name, "vm-0001"),
+            _ => panic!("expected an exact match"),
+        }
+    }
+
+    #[test]
+    fn substring_match_against_a_single_candidate_is_found() {
+        let statuses = vec![vm("db-primary"), vm("web-01")];
+        match find_vm(&statuses, "PRIMARY") {
+            FindResult::Found(found) => assert_eq!(found.name, "db-primary"),
+            _ => panic!("expected a case-insensitive substring match"),
+        }
+    }
+
+    #[test]
+    fn substring_match_against_multiple_candidates_is_ambiguous() {
+        let statuses = vec![vm("db-01"), vm("db-02")];
+        match find_vm(&statuses, "db") {
+            FindResult::Ambiguous(names) => assert_eq!(names.len(), 2),
+            _ => panic!("expected ambiguity between db-01 and db-02"),
+        }
+    }
+
+    #[test]
+    fn no_match_is_not_found() {
+        let statuses = vec![vm("db-01")];
+        assert!(matches!(find_vm(&statuses, "nope"), FindResult::NotFound));
+    }
+
+    #[test]
+    fn text_report_includes_every_section_and_the_cpu_mem_sparklines() {
+        let rendered = render_text(&vm("vm-0001"), None, UptimeFormat::Human);
+        assert!(rendered.contains("-- Identity --"));
+        assert!(rendered.contains("-- Hardware --"));
+        assert!(rendered.contains("-- Tools & guest --"));
+        assert!(rendered.contains("-- Performance --"));
+        assert!(rendered.contains("-- Events (recent) --"));
+        assert!(rendered.contains("-- Health: 90.0 --"));
+        assert!(rendered.contains("TOOLS_NOT_RUNNING"));
+        assert!(rendered.contains("last migration:  esxi-00 -> esxi-01"));
+    }
+
+    #[test]
+    fn text_report_uses_history_samples_in_the_sparkline_when_present() {
+        let mut history = BTreeMap::new();
+        history.insert("vm-0001".to_string(), vec![(10.0, 20.0), (90.0, 80.0)]);
+        let with_history = render_text(&vm("vm-0001"), Some(&history), UptimeFormat::Human);
+        let without_history = render_text(&vm("vm-0001"), None, UptimeFormat::Human);
+        assert_ne!(with_history, without_history, "a multi-point sparkline should render differently than the single-block fallback");
+    }
+}
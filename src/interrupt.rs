@@ -0,0 +1,175 @@
+//! Ctrl-C handling for the live (non-`--watch`) run. Fetching and analysis
+//! happen up front in one batch, so there's nothing to "collect" gradually -
+//! but everything after the fetch (slow notifier delivery, a stuck sink
+//! write) can still run long enough to interrupt. [`install`] makes sure an
+//! interruption there flushes the VMs already in hand instead of losing the
+//! whole run.
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::auth::{Session, VCenterVersion};
+use crate::cli::{Args, OutputFormat};
+use crate::vm::{UptimeFormat, VMResourceStatus};
+
+/// Exit code for a run cut short by Ctrl-C, matching the shell convention
+/// for a process killed by SIGINT (128 + 2).
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+const PARTIAL_MARKER: &str = "rapport partiel (interrompu)";
+
+/// Shared slot the Ctrl-C handler reads from. `None` until the run has
+/// fetched something worth flushing. The `bool` mirrors
+/// [`crate::vcenter::SimulatedClient::metrics_degraded`] at the time the
+/// statuses were collected, so a partial report can still flag a degraded
+/// metrics collector.
+pub type Collected = Arc<Mutex<Option<(Vec<VMResourceStatus>, bool)>>>;
+
+pub fn new_collected() -> Collected {
+    Arc::new(Mutex::new(None))
+}
+
+#[derive(Serialize)]
+struct PartialJsonReport<'a> {
+    partial: &'static str,
+    run_id: &'a str,
+    vms: &'a [VMResourceStatus],
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render_partial(
+    statuses: &[VMResourceStatus],
+    format: OutputFormat,
+    vcenter_version: &VCenterVersion,
+    exclude_powered_off_from_stats: bool,
+    uptime_format: UptimeFormat,
+    run_id: &str,
+    site: Option<&str>,
+    metrics_degraded: bool,
+) -> String {
+    match format {
+        OutputFormat::Text => format!(
+            "{PARTIAL_MARKER}\n{}",
+            crate::report::generate_report(
+                statuses,
+                true,
+                &[],
+                &[],
+                &Default::default(),
+                None,
+                None,
+                Some(vcenter_version),
+                exclude_powered_off_from_stats,
+                uptime_format,
+                run_id,
+                None,
+                None,
+                None,
+                None,
+                site,
+                &Default::default(),
+                None,
+                None,
+                metrics_degraded,
+            )
+        ),
+        OutputFormat::Json => serde_json::to_string_pretty(&PartialJsonReport {
+            partial: PARTIAL_MARKER,
+            run_id,
+            vms: statuses,
+        })
+        .unwrap_or_else(|err| format!("{{\"partial\": \"{PARTIAL_MARKER}\", \"error\": \"{err}\"}}")),
+        OutputFormat::Csv => format!("# {PARTIAL_MARKER}\n{}", crate::report::export_csv_report(statuses, run_id, site)),
+    }
+}
+
+/// Installs the Ctrl-C handler: on interruption, renders whatever's in
+/// `collected` (if anything) with a "rapport partiel (interrompu)" marker,
+/// writes it to `args`'s configured output, disconnects `session`, and exits
+/// with [`INTERRUPTED_EXIT_CODE`]. If nothing had been collected yet (the
+/// interrupt landed before the fetch completed), there's nothing to flush.
+pub fn install(collected: Collected, args: Args, session: Session, run_id: String) -> anyhow::Result<()> {
+    ctrlc::set_handler(move || {
+        if let Some((statuses, metrics_degraded)) = collected.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            let rendered = render_partial(&statuses, args.format, &session.version, args.exclude_powered_off_from_stats, args.uptime_format.into(), &run_id, args.site.as_deref(), metrics_degraded);
+            if let Err(err) = crate::sink::sink_for(&args).write(&rendered) {
+                eprintln!("failed to flush partial report: {err}");
+            }
+        }
+        session.disconnect();
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::PowerState;
+    use std::collections::HashMap;
+
+    fn vm(name: &str) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    fn version() -> VCenterVersion {
+        VCenterVersion {
+            product: "VMware vCenter Server".to_string(),
+            version: "8.0.2".to_string(),
+            build: "22617221".to_string(),
+        }
+    }
+
+    #[test]
+    fn text_format_leads_with_the_partial_marker() {
+        let rendered = render_partial(&[vm("vm-1")], OutputFormat::Text, &version(), false, UptimeFormat::Human, "test-run-id", None, false);
+        assert!(rendered.starts_with(PARTIAL_MARKER));
+    }
+
+    #[test]
+    fn json_format_includes_the_partial_marker_and_the_collected_vms() {
+        let rendered = render_partial(&[vm("vm-1")], OutputFormat::Json, &version(), false, UptimeFormat::Human, "test-run-id", None, false);
+        let parsed: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(parsed["partial"], PARTIAL_MARKER);
+        assert_eq!(parsed["vms"][0]["name"], "vm-1");
+    }
+}
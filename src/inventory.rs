@@ -0,0 +1,119 @@
+//! Resolves each VM's full vCenter inventory path (e.g.
+//! `/DC1/vm/cluster-a/team-2/vm-0001`) from the folder hierarchy, so
+//! operators can tell apart two VMs with the same name in different
+//! folders instead of relying on the bare name. The hierarchy is listed
+//! once per run via a simulated `/vcenter/datacenter` + `/vcenter/folder`
+//! call pair and kept in a plain map for the rest of the run - there's no
+//! per-VM folder lookup to fall back to here, because this simulated
+//! fleet's VM listing always comes back with its folder attached; a real
+//! vCenter integration missing that from a filtered `ListVMs` response
+//! would fall back to [`resolve_path`] per VM instead of building the map
+//! up front.
+
+use std::collections::HashMap;
+
+/// One `/vcenter/folder` entry: a name and the folder it lives in. The
+/// datacenter root has no parent.
+#[derive(Debug, Clone)]
+pub struct FolderEntry {
+    pub name: String,
+    pub parent: Option<String>,
+}
+
+/// Walks `folder_id` up through `folders` to the root, joining names with
+/// `/`. Defends against a cyclic map - which a real vCenter should never
+/// hand back, but a stale cache or a bug might - by giving up once it's
+/// taken more hops than there are folders; the cycle can't be real at that
+/// point, so the walk stops and `<cycle>` marks where it gave up rather
+/// than looping forever. An unknown `folder_id` resolves to just `/`.
+pub fn resolve_path(folders: &HashMap<String, FolderEntry>, folder_id: &str) -> String {
+    let mut segments = Vec::new();
+    let mut current = Some(folder_id.to_string());
+    let mut hops = 0usize;
+    while let Some(id) = current {
+        if hops > folders.len() {
+            segments.push("<cycle>".to_string());
+            break;
+        }
+        hops += 1;
+        let Some(folder) = folders.get(&id) else { break };
+        segments.push(folder.name.clone());
+        current = folder.parent.clone();
+    }
+    segments.reverse();
+    format!("/{}", segments.join("/"))
+}
+
+/// A VM's full inventory path: `folder_id`'s path (via [`resolve_path`])
+/// plus the VM's own name as the final segment.
+pub fn vm_path(folders: &HashMap<String, FolderEntry>, folder_id: &str, vm_name: &str) -> String {
+    format!("{}/{vm_name}", resolve_path(folders, folder_id))
+}
+
+/// Builds a synthetic `DC1/vm/<cluster>/team-<n>` hierarchy, four levels
+/// deep from the root, for the simulated fleet: one folder per cluster,
+/// with `folders_per_cluster` team sub-folders under each. Fully
+/// deterministic, folder ids included, so the same cluster/team pair always
+/// resolves to the same path within a run.
+pub fn build_synthetic_folders(clusters: &[&str], folders_per_cluster: u32) -> HashMap<String, FolderEntry> {
+    let mut folders = HashMap::new();
+    folders.insert("dc".to_string(), FolderEntry { name: "DC1".to_string(), parent: None });
+    folders.insert("vm-root".to_string(), FolderEntry { name: "vm".to_string(), parent: Some("dc".to_string()) });
+    for cluster in clusters {
+        let cluster_id = format!("folder-{cluster}");
+        folders.insert(cluster_id.clone(), FolderEntry { name: cluster.to_string(), parent: Some("vm-root".to_string()) });
+        for team in 0..folders_per_cluster {
+            folders.insert(
+                format!("{cluster_id}-team-{team}"),
+                FolderEntry { name: format!("team-{team}"), parent: Some(cluster_id.clone()) },
+            );
+        }
+    }
+    folders
+}
+
+/// The folder a VM at fleet index `i` in `cluster` lives in, matching
+/// [`build_synthetic_folders`]'s layout.
+pub fn synthetic_folder_id(cluster: &str, i: usize, folders_per_cluster: u32) -> String {
+    format!("folder-{cluster}-team-{}", i as u32 % folders_per_cluster)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_a_four_level_nested_path() {
+        let mut folders = HashMap::new();
+        folders.insert("dc".to_string(), FolderEntry { name: "DC1".to_string(), parent: None });
+        folders.insert("vm-root".to_string(), FolderEntry { name: "vm".to_string(), parent: Some("dc".to_string()) });
+        folders.insert("prod".to_string(), FolderEntry { name: "Prod".to_string(), parent: Some("vm-root".to_string()) });
+        folders.insert("web".to_string(), FolderEntry { name: "Web".to_string(), parent: Some("prod".to_string()) });
+
+        assert_eq!(vm_path(&folders, "web", "web-01"), "/DC1/vm/Prod/Web/web-01");
+    }
+
+    #[test]
+    fn cyclic_folders_terminate_instead_of_looping_forever() {
+        let mut folders = HashMap::new();
+        folders.insert("a".to_string(), FolderEntry { name: "A".to_string(), parent: Some("b".to_string()) });
+        folders.insert("b".to_string(), FolderEntry { name: "B".to_string(), parent: Some("a".to_string()) });
+
+        let path = resolve_path(&folders, "a");
+        assert!(path.starts_with("/<cycle>"), "expected the walk to give up, got {path}");
+    }
+
+    #[test]
+    fn unknown_folder_id_resolves_to_root() {
+        let folders = HashMap::new();
+        assert_eq!(resolve_path(&folders, "nonexistent"), "/");
+    }
+
+    #[test]
+    fn synthetic_folders_are_stable_within_a_run() {
+        let clusters = ["cluster-a", "cluster-b"];
+        let folders = build_synthetic_folders(&clusters, 4);
+        let id = synthetic_folder_id("cluster-a", 5, 4);
+        assert_eq!(vm_path(&folders, &id, "vm-0005"), "/DC1/vm/cluster-a/team-1/vm-0005");
+    }
+}
@@ -0,0 +1,202 @@
+//! `--lock-file`: when a scheduled run overruns its cron slot, the next one
+//! starts before it finishes, doubling vCenter load and racing on
+//! `--state-file`/checkpoint/output writes. [`acquire`] takes an exclusive
+//! advisory lock at startup covering that whole run; a second instance
+//! either exits immediately with [`LOCK_HELD_EXIT_CODE`] (the default) or
+//! waits up to `--lock-wait-secs`, per [`crate::cli::Args::lock_wait_secs`].
+//!
+//! The lock is a plain PID file: acquiring it means atomically creating
+//! `path` (failing if it already exists) and writing this process's PID
+//! into it; releasing it ([`LockGuard::drop`]) deletes the file. A PID
+//! recorded in an existing lock file that's no longer running - the owning
+//! process crashed without cleaning up - is a stale lock, broken with a
+//! warning rather than left to block every future run forever.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Distinct from [`crate::vm::ISSUE_ERROR_EXIT_CODE`],
+/// [`crate::interrupt::INTERRUPTED_EXIT_CODE`], and the
+/// [`crate::auth::AuthError`] codes, so a scheduler can tell "another run is
+/// still monitoring" apart from every other failure mode without parsing
+/// stderr.
+pub const LOCK_HELD_EXIT_CODE: i32 = 6;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Why [`acquire`] failed. `Held` is the expected, recoverable case a
+/// scheduler should special-case on [`LockError::exit_code`]; `Io` is
+/// anything else (permissions, a missing parent directory, ...) and gets a
+/// generic exit code, matching [`crate::auth::AuthError`]'s split between
+/// "the thing you'd expect to handle" and "everything else".
+#[derive(Debug, thiserror::Error)]
+pub enum LockError {
+    #[error("lock file '{path}' is held by pid {pid}, which is still running")]
+    Held { path: String, pid: u32 },
+    #[error("lock file '{path}': {source}")]
+    Io { path: String, source: io::Error },
+}
+
+impl LockError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            LockError::Held { .. } => LOCK_HELD_EXIT_CODE,
+            LockError::Io { .. } => 1,
+        }
+    }
+}
+
+/// Held for the lifetime of a run; deletes the lock file on drop so a clean
+/// exit always releases it without every call site having to remember to.
+/// An early `return`/`?`/panic during the run still releases it, since
+/// `Drop` runs on unwind too.
+#[derive(Debug)]
+pub struct LockGuard {
+    path: PathBuf,
+}
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// `--lock-file`'s default when unset: alongside `--state-file`, the one
+/// artifact every run that would want a lock is already using.
+pub fn default_lock_file_path(state_file: &str) -> String {
+    format!("{state_file}.lock")
+}
+
+/// Acquires the advisory lock at `path`. If it's already held by a live
+/// process, retries every [`POLL_INTERVAL`] until `wait_secs` elapses
+/// (`0` means a single attempt, i.e. exit immediately on contention); a
+/// lock whose recorded PID is no longer running is broken with a warning
+/// and retried without counting against `wait_secs`.
+pub fn acquire(path: &Path, wait_secs: u64) -> Result<LockGuard, LockError> {
+    let deadline = Instant::now() + Duration::from_secs(wait_secs);
+    loop {
+        match create_and_write_pid(path) {
+            Ok(()) => return Ok(LockGuard { path: path.to_path_buf() }),
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                match holder_pid(path) {
+                    Some(pid) if process_is_alive(pid) => {
+                        if Instant::now() >= deadline {
+                            return Err(LockError::Held { path: path.display().to_string(), pid });
+                        }
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                    Some(pid) => {
+                        eprintln!("lock file '{}': pid {pid} is no longer running; breaking stale lock", path.display());
+                        let _ = fs::remove_file(path);
+                    }
+                    None => {
+                        eprintln!("lock file '{}': contents unreadable; breaking stale lock", path.display());
+                        let _ = fs::remove_file(path);
+                    }
+                }
+            }
+            Err(source) => return Err(LockError::Io { path: path.display().to_string(), source }),
+        }
+    }
+}
+
+fn create_and_write_pid(path: &Path) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().write(true).create_new(true).open(path)?;
+    write!(file, "{}", std::process::id())
+}
+
+fn holder_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Whether `pid` still names a running process. On Linux, `/proc/<pid>`
+/// disappears the moment the process exits, which is all a stale-lock check
+/// needs - no `libc`/`sysinfo` dependency required for a check this simple.
+/// There's no non-Linux implementation yet (nothing else in this crate has
+/// needed one - see the `cfg(windows)` carve-out for `--service`), so
+/// elsewhere this conservatively assumes the PID is alive and leaves the
+/// lock in place rather than risk breaking one still in use.
+#[cfg(target_os = "linux")]
+fn process_is_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_is_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    /// A scratch directory unique to this test process and call, so
+    /// parallel `cargo test` runs never collide on the same path.
+    fn scratch_dir() -> PathBuf {
+        static COUNTER: AtomicU32 = AtomicU32::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("lockfile-test-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn default_path_is_alongside_the_state_file() {
+        assert_eq!(default_lock_file_path("network-monitor-state.json"), "network-monitor-state.json.lock");
+    }
+
+    #[test]
+    fn second_run_is_rejected_immediately_by_default_while_the_first_holds_the_lock() {
+        let path = scratch_dir().join("run.lock");
+
+        let first = acquire(&path, 0).expect("first run should acquire the lock");
+        assert!(path.exists());
+
+        let err = acquire(&path, 0).expect_err("second run should not acquire an already-held lock");
+        assert_eq!(err.exit_code(), LOCK_HELD_EXIT_CODE);
+        assert!(matches!(err, LockError::Held { pid, .. } if pid == std::process::id()));
+
+        drop(first);
+        acquire(&path, 0).expect("lock is free again once the first run's guard is dropped");
+    }
+
+    #[test]
+    fn second_run_waits_for_lock_wait_secs_then_succeeds_once_released() {
+        let path = scratch_dir().join("run.lock");
+        let first = acquire(&path, 0).expect("first run should acquire the lock");
+
+        let released_path = path.clone();
+        let releaser = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            drop(first);
+            let _ = &released_path;
+        });
+
+        let second = acquire(&path, 2).expect("second run should wait for the lock and then acquire it");
+        releaser.join().unwrap();
+        drop(second);
+    }
+
+    #[test]
+    fn a_lock_left_behind_by_a_pid_that_is_not_running_is_broken_and_reacquired() {
+        let path = scratch_dir().join("run.lock");
+        fs::write(&path, "999999999").unwrap();
+
+        let guard = acquire(&path, 0).expect("a stale lock should be broken and reacquired");
+        assert_eq!(fs::read_to_string(&path).unwrap(), std::process::id().to_string());
+        drop(guard);
+    }
+
+    #[test]
+    fn a_lock_file_with_unparseable_contents_is_treated_as_stale() {
+        let path = scratch_dir().join("run.lock");
+        fs::write(&path, "not-a-pid").unwrap();
+
+        let guard = acquire(&path, 0).expect("an unparseable lock should be broken and reacquired");
+        drop(guard);
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+
+use crate::vm::VMResourceStatus;
+
+/// Quotes `s` if it contains a space, `=`, `"`, or is empty - logfmt treats
+/// unquoted whitespace/`=` as field separators - and backslash-escapes any
+/// embedded `"` or `\` so the quoted value round-trips through a logfmt
+/// parser.
+fn logfmt_value(s: &str) -> String {
+    if s.is_empty() || s.contains(' ') || s.contains('=') || s.contains('"') {
+        let escaped = s.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"{escaped}\"")
+    } else {
+        s.to_string()
+    }
+}
+
+fn logfmt_line(vm: &VMResourceStatus) -> String {
+    let issues = vm
+        .issues
+        .iter()
+        .map(|i| i.issue_type.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        "vm={} host={} cluster={} power={} cpu={:.1} mem={:.1} health={:.1} issues={}\n",
+        logfmt_value(&vm.name),
+        logfmt_value(&vm.host),
+        logfmt_value(&vm.cluster),
+        vm.power_state,
+        vm.cpu_usage_pct,
+        vm.memory_usage_pct,
+        vm.health_score,
+        logfmt_value(&issues),
+    )
+}
+
+/// Renders one logfmt (`key=value`) line per VM.
+pub fn render_logfmt(statuses: &[VMResourceStatus]) -> String {
+    statuses.iter().map(logfmt_line).collect()
+}
+
+/// `--logfmt-output`: additionally writes the fleet snapshot as logfmt
+/// lines to `path`, reusing the same in-memory results as `--output` - no
+/// extra vCenter query. Composes with `--output`/`--format`, which are
+/// unaffected.
+pub fn write_logfmt_output(path: &str, statuses: &[VMResourceStatus]) -> Result<()> {
+    std::fs::write(path, render_logfmt(statuses)).with_context(|| format!("writing logfmt output to {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, MetricsSourceStatus, PowerState, VMIssueType};
+
+    fn vm(name: &str, host: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: host.to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 30.0,
+            memory_usage_pct: 50.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 3600.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            issues,
+            health_score: 100.0,
+            change_version: 0,
+        }
+    }
+
+    #[test]
+    fn one_line_per_vm_with_unquoted_simple_fields() {
+        let rendered = render_logfmt(&[vm("db-01", "esxi-01", vec![])]);
+        assert_eq!(rendered, "vm=db-01 host=esxi-01 cluster=cluster-a power=poweredOn cpu=30.0 mem=50.0 health=100.0 issues=\"\"\n");
+    }
+
+    #[test]
+    fn single_issue_code_is_left_unquoted() {
+        let rendered = render_logfmt(&[vm("db-01", "esxi-01", vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")])]);
+        assert!(rendered.contains("issues=HIGH_CPU_USAGE"));
+    }
+
+    #[test]
+    fn values_with_spaces_are_quoted_and_embedded_quotes_are_escaped() {
+        let rendered = logfmt_value("vm \"prod\"");
+        assert_eq!(rendered, "\"vm \\\"prod\\\"\"");
+    }
+
+    #[test]
+    fn plain_values_are_left_unquoted() {
+        assert_eq!(logfmt_value("db-01"), "db-01");
+    }
+}
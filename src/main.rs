@@ -0,0 +1,1055 @@
+mod acknowledge;
+mod aggregate;
+mod alerting;
+mod api_rate_log;
+mod auth;
+mod bootevents;
+mod bootstorm;
+mod check_timing;
+mod cli;
+mod content_type;
+mod dashboard;
+mod datastore;
+mod demo;
+mod drs;
+mod fingerprint;
+mod hotadd;
+mod inspect;
+mod interrupt;
+mod inventory;
+mod lockfile;
+mod logfmt;
+mod maintenance;
+mod metrics;
+mod metrics_provider;
+mod migration;
+mod notifier;
+mod planner;
+mod preview;
+mod reachability;
+mod recommend;
+mod replay;
+mod report;
+mod request_budget;
+mod rightsize;
+mod routing;
+mod run_id;
+mod sanitycheck;
+mod scoring;
+mod select;
+mod service;
+mod sessions;
+mod sink;
+mod site_config;
+mod sparkline;
+mod strict_json;
+mod strict_parsing;
+mod suspendevents;
+mod template;
+mod thresholds;
+mod ticket;
+mod topology;
+mod validate;
+mod vcenter;
+mod vm;
+mod vmc;
+mod watch;
+
+use std::fs;
+use std::io::IsTerminal;
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+
+use cli::{Args, OutputFormat};
+use planner::RunState;
+use routing::RouteConfig;
+use sink::OutputSink;
+use service::notify::Notifier;
+use vcenter::{SimulatedClient, VCenterClient};
+use vm::{VMIssueType, VMResourceStatus};
+
+/// Applies `--time-budget`, if set, to `statuses`: picks which VMs the
+/// analysis phase has time for (prioritizing last run's problem VMs, then
+/// rotating through the rest), persists the rotation state for next run,
+/// and returns the VMs to report on plus the names deferred this run.
+fn apply_time_budget(
+    args: &Args,
+    statuses: Vec<VMResourceStatus>,
+    fetch_elapsed_secs: f64,
+    run_id: &str,
+) -> Result<(Vec<VMResourceStatus>, Vec<String>)> {
+    let Some(time_budget) = args.time_budget else {
+        return Ok((statuses, Vec::new()));
+    };
+    if statuses.is_empty() {
+        return Ok((statuses, Vec::new()));
+    }
+
+    let state = RunState::load(&args.state_file);
+    let budgets = planner::split_budget(time_budget);
+    let per_vm_latency_secs = fetch_elapsed_secs / statuses.len() as f64;
+    let all_vm_names: Vec<String> = statuses.iter().map(|v| v.name.clone()).collect();
+    let plan = planner::plan_analysis_batch(
+        &all_vm_names,
+        &state.previous_issue_vms,
+        state.rotation_offset,
+        per_vm_latency_secs,
+        budgets.analysis_secs,
+    );
+
+    let processed: Vec<VMResourceStatus> = statuses
+        .into_iter()
+        .filter(|v| plan.to_process.contains(&v.name))
+        .collect();
+
+    if !plan.deferred.is_empty() {
+        eprintln!(
+            "[{run_id}] time-budget: deferred {} VM(s) to a later run: {}",
+            plan.deferred.len(),
+            plan.deferred.join(", ")
+        );
+    }
+
+    RunState {
+        previous_issue_vms: processed
+            .iter()
+            .filter(|v| v.has_issues())
+            .map(|v| v.name.clone())
+            .collect(),
+        rotation_offset: plan.next_rotation_offset,
+        last_run_id: Some(run_id.to_string()),
+        vm_change_versions: state.vm_change_versions,
+        vm_last_status: state.vm_last_status,
+        last_output_hash: state.last_output_hash,
+        issue_first_seen: state.issue_first_seen,
+    }
+    .save(&args.state_file)?;
+
+    Ok((processed, plan.deferred))
+}
+
+/// Applies `--since-last-run`: a VM whose `change_version` still matches
+/// `--state-file`'s last-seen value for it has its prior, fully-analyzed
+/// status carried forward in place of this run's freshly fetched one,
+/// trading that VM's performance-metric freshness for not having to
+/// re-analyze a fleet that mostly hasn't changed. A VM seen for the first
+/// time, or any VM at all with `--force-full` set, is always analyzed
+/// fresh. The updated markers/statuses are persisted by the caller once the
+/// boot storm, hot-add, DRS and scoring steps have run, before
+/// recommendations, maintenance downgrades and fingerprinting.
+fn apply_since_last_run(args: &Args, statuses: Vec<VMResourceStatus>) -> Vec<VMResourceStatus> {
+    if !args.since_last_run || args.force_full {
+        return statuses;
+    }
+    let state = RunState::load(&args.state_file);
+    statuses
+        .into_iter()
+        .map(|vm| {
+            let unchanged = state.vm_change_versions.get(&vm.name) == Some(&vm.change_version);
+            if unchanged {
+                if let Some(prior) = state.vm_last_status.get(&vm.name) {
+                    return prior.clone();
+                }
+            }
+            vm
+        })
+        .collect()
+}
+
+/// Persists `--since-last-run`'s per-VM markers and fully-analyzed statuses
+/// for next run, once scoring has run (see [`apply_since_last_run`]). A no-op without
+/// `--since-last-run`, so a run that never enabled it never pays for a
+/// `--state-file` write it doesn't need.
+fn save_since_last_run_state(args: &Args, statuses: &[VMResourceStatus]) -> Result<()> {
+    if !args.since_last_run {
+        return Ok(());
+    }
+    let mut state = RunState::load(&args.state_file);
+    state.vm_change_versions = statuses.iter().map(|v| (v.name.clone(), v.change_version)).collect();
+    state.vm_last_status = statuses.iter().cloned().map(|v| (v.name.clone(), v)).collect();
+    state.save(&args.state_file)
+}
+
+/// Persists `fingerprint::annotate`'s per-issue first-seen timestamps for
+/// next run. Always on, unlike `--since-last-run` - ticket age isn't an
+/// opt-in feature the way analysis carry-forward is.
+fn save_issue_first_seen_state(args: &Args, issue_first_seen: &std::collections::BTreeMap<String, chrono::DateTime<chrono::Utc>>) -> Result<()> {
+    let mut state = RunState::load(&args.state_file);
+    state.issue_first_seen = issue_first_seen.clone();
+    state.save(&args.state_file)
+}
+
+/// Reads `--vm-list-stdin`'s VM names from stdin, one per line, ignoring
+/// blank lines and lines starting with `#` the same way a hand-edited list
+/// would be commented.
+fn read_vm_list_stdin() -> Result<std::collections::HashSet<String>> {
+    use std::io::Read;
+    let mut raw = String::new();
+    std::io::stdin().read_to_string(&mut raw)?;
+    Ok(raw
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Applies `--select` to a fetched fleet, printing `--explain-selection`'s
+/// per-VM exclusion reason for any VM it drops before filtering. A no-op
+/// when `--select` wasn't given.
+fn apply_selection(args: &Args, run_id: &str, expr: Option<&select::Expr>, statuses: Vec<VMResourceStatus>) -> Vec<VMResourceStatus> {
+    let Some(expr) = expr else {
+        return statuses;
+    };
+    if args.explain_selection {
+        for vm in &statuses {
+            if let Some(reason) = select::explain(expr, vm) {
+                eprintln!("[{run_id}] --select excluded {}: {reason}", vm.name);
+            }
+        }
+    }
+    statuses.into_iter().filter(|vm| select::evaluate(expr, vm)).collect()
+}
+
+/// Resolves `--lock-file`'s effective path: the explicit flag if given,
+/// otherwise [`lockfile::default_lock_file_path`] when a `--state-file`-
+/// dependent feature is actually in play, otherwise no lock at all - an
+/// ad-hoc run against no persisted state has nothing to race on.
+fn lock_file_path(args: &Args) -> Option<String> {
+    if let Some(path) = &args.lock_file {
+        return Some(path.clone());
+    }
+    if args.since_last_run || args.time_budget.is_some() {
+        return Some(lockfile::default_lock_file_path(&args.state_file));
+    }
+    None
+}
+
+pub(crate) fn route_and_print(args: &Args, statuses: &[VMResourceStatus], run_id: &str) -> Result<()> {
+    let Some(attribute_key) = &args.route_by_attribute else {
+        return Ok(());
+    };
+    let config_path = args
+        .route_config
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("--route-by-attribute requires --route-config"))?;
+    let config = RouteConfig::load(config_path, args.strict_json)?;
+    let grouped = routing::route_problem_vms(statuses, attribute_key, &config);
+    for (channel, vms) in grouped {
+        let names: Vec<&str> = vms.iter().map(|v| v.name.as_str()).collect();
+        eprintln!("[{run_id}] route[{channel}]: {}", names.join(", "));
+    }
+    Ok(())
+}
+
+/// `--fail-on-issues`: exits the process with [`vm::ISSUE_ERROR_EXIT_CODE`]
+/// if `statuses` carries an error-tier issue (after `warning_overrides`
+/// downgrades), otherwise a no-op - warning-tier issues already appear in
+/// the rendered report and don't need a separate announcement. A no-op
+/// entirely when `--fail-on-issues` wasn't set.
+fn enforce_fail_on_issues(args: &Args, statuses: &[VMResourceStatus], warning_overrides: &std::collections::HashSet<VMIssueType>, run_id: &str) {
+    if !args.fail_on_issues {
+        return;
+    }
+    if vm::has_error_tier_issue(statuses, warning_overrides) {
+        eprintln!("[{run_id}] --fail-on-issues: an error-tier issue was detected this run");
+        std::process::exit(vm::ISSUE_ERROR_EXIT_CODE);
+    }
+}
+
+/// Distinct from [`vm::ISSUE_ERROR_EXIT_CODE`], [`lockfile::LOCK_HELD_EXIT_CODE`],
+/// [`interrupt::INTERRUPTED_EXIT_CODE`], and every [`auth::AuthError`] code, so
+/// a script driving this tool can tell "the run was aborted before anything
+/// was written" apart from "the run finished and found a problem" or any
+/// auth failure. See `tests::every_top_level_exit_code_is_unique` below -
+/// update it when adding another exit code anywhere in the crate.
+const ATOMIC_ABORT_EXIT_CODE: i32 = 9;
+
+/// `--atomic`: if `deferred` names more VMs than `--atomic-max-deferred`
+/// tolerates, exits the process with [`ATOMIC_ABORT_EXIT_CODE`] before the
+/// caller writes the report, ticket export, any of the side outputs, or a
+/// state file - a no-op otherwise. Must run before the first of those
+/// writes, not after.
+fn enforce_atomic(args: &Args, deferred: &[String], run_id: &str) {
+    if !args.atomic {
+        return;
+    }
+    if deferred.len() > args.atomic_max_deferred {
+        eprintln!(
+            "[{run_id}] --atomic: {} VM(s) deferred from analysis, above --atomic-max-deferred {}; aborting before writing any output",
+            deferred.len(),
+            args.atomic_max_deferred
+        );
+        std::process::exit(ATOMIC_ABORT_EXIT_CODE);
+    }
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    env_logger::Builder::new().filter_level(args.log_level_filter()).init();
+    let disabled_issues = args
+        .disabled_issue_types()
+        .map_err(|err| anyhow::anyhow!("disable-issues: {err}"))?;
+    let issue_threshold_warnings = args
+        .issue_threshold_warnings()
+        .map_err(|err| anyhow::anyhow!("issue-threshold-warnings: {err}"))?;
+    let names_for_issue_type = args
+        .names_for_issue_type()
+        .map_err(|err| anyhow::anyhow!("names-for-issue: {err}"))?;
+    let score_weights = match &args.score_weights {
+        Some(path) => scoring::load_weight_overrides(path, args.strict_json)?,
+        None => std::collections::HashMap::new(),
+    };
+    let vm_list_names = if args.vm_list_stdin { read_vm_list_stdin()? } else { std::collections::HashSet::new() };
+    let selection = args.selection().map_err(|err| anyhow::anyhow!("select: {err}"))?;
+    let proposed_thresholds = args.preview_thresholds().map_err(|err| anyhow::anyhow!("preview-thresholds: {err}"))?;
+    let run_id = run_id::resolve(args.run_id.as_deref());
+
+    if args.service {
+        #[cfg(windows)]
+        {
+            service::windows::run()?;
+            return Ok(());
+        }
+        #[cfg(not(windows))]
+        {
+            anyhow::bail!("--service is only supported on Windows");
+        }
+    }
+
+    if args.config_validate {
+        let result = validate::run_config_validate(&args);
+        for warning in &result.warnings {
+            eprintln!("warning: {warning}");
+        }
+        if result.errors.is_empty() {
+            println!("config OK");
+            return Ok(());
+        }
+        for error in &result.errors {
+            eprintln!("{error}");
+        }
+        anyhow::bail!("{} config error(s)", result.errors.len());
+    }
+
+    if args.print_effective_config {
+        let site_config = match &args.site_config {
+            Some(path) => Some(site_config::SiteConfig::load(path, args.strict_json)?),
+            None => None,
+        };
+        let effective = site_config::resolve_effective_config(
+            args.site.as_deref(),
+            args.clock_skew_threshold_secs,
+            args.underuse_threshold,
+            args.check_reachability,
+            &args.disable_issues,
+            site_config.as_ref(),
+        );
+        println!("{}", serde_json::to_string_pretty(&effective)?);
+        return Ok(());
+    }
+
+    if args.test_notifiers {
+        let path = args
+            .notifier_config
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--test-notifiers requires --notifier-config"))?;
+        let registry = notifier::NotifierRegistry::from_config(notifier::NotifierRegistryConfig::load(path, args.strict_json)?);
+        let result = registry.test_all();
+        print!("{}", result.render_section());
+        if !result.failures.is_empty() {
+            anyhow::bail!("{} notifier(s) failed the test message", result.failures.len());
+        }
+        return Ok(());
+    }
+
+    if args.suggest_thresholds {
+        let samples = thresholds::load_history(&args.history, args.lookback_days)?;
+        let (suggestions, insufficient) = thresholds::suggest_thresholds(&samples);
+        print!("{}", thresholds::render_text(&suggestions, &insufficient));
+        let snippet = thresholds::render_overrides_snippet(&suggestions);
+        println!("{snippet}");
+        if let Some(path) = &args.apply {
+            fs::write(path, &snippet)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(glob_or_dir) = &args.aggregate {
+        let paths = aggregate::resolve_report_paths(glob_or_dir)?;
+        let snapshots = aggregate::load_reports(&paths)?;
+        let result = aggregate::aggregate(&snapshots);
+        print!("{}", aggregate::render_text(&result));
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        return Ok(());
+    }
+
+    let _lock_guard = match lock_file_path(&args) {
+        Some(path) => match lockfile::acquire(std::path::Path::new(&path), args.lock_wait_secs.unwrap_or(0)) {
+            Ok(guard) => Some(guard),
+            Err(err) => {
+                eprintln!("[{run_id}] {err}");
+                std::process::exit(err.exit_code());
+            }
+        },
+        None => None,
+    };
+
+    if let Some(path) = &args.replay {
+        let outcome = replay::replay(path, &args.detection_options(), args.strict_parsing)?;
+        if !outcome.failed_analyses.is_empty() {
+            eprintln!(
+                "[{run_id}] --strict-parsing: {} VM(s) excluded from this run due to field errors: {}",
+                outcome.failed_analyses.len(),
+                outcome.failed_analyses.join("; ")
+            );
+        } else if outcome.fallbacks.count() > 0 {
+            eprintln!(
+                "[{run_id}] data quality: {} field-level fallback(s) taken this run; rerun with --strict-parsing to treat these as errors",
+                outcome.fallbacks.count()
+            );
+        }
+        enforce_atomic(&args, &outcome.failed_analyses, &run_id);
+        let mut statuses = outcome.statuses;
+        vm::strip_disabled_issues(&mut statuses, &disabled_issues);
+        let statuses = apply_selection(&args, &run_id, selection.as_ref(), statuses);
+        let (mut statuses, not_found) = vm::resolve_name_list(statuses, &vm_list_names);
+        let acknowledgements = acknowledge::apply_acknowledgements(&mut statuses, chrono::Local::now().date_naive());
+        scoring::annotate_health_scores(&mut statuses, &score_weights);
+        let host_metrics = std::collections::BTreeMap::new();
+        recommend::annotate_recommendations(&mut statuses, &host_metrics, !args.no_recommendations);
+        maintenance::annotate_maintenance_downgrades(&mut statuses, &host_metrics, !args.no_respect_maintenance_mode);
+        let include_stats = !args.no_stats;
+        let metrics_degraded = statuses.iter().any(|vm| vm.metrics_source == vm::MetricsSourceStatus::Unavailable);
+        for note in vmc::disabled_check_notes(args.vmc_profile, args.check_host_state, args.check_host_health) {
+            eprintln!("[{run_id}] vmc-profile: {note}");
+        }
+        if args.sanity_check_thresholds {
+            for warning in sanitycheck::unapproached_thresholds(&statuses, &args.detection_options()) {
+                eprintln!("[{run_id}] sanity-check-thresholds: {warning}");
+            }
+        }
+        if let Some(issue_type) = names_for_issue_type {
+            for name in report::names_for_issue(&statuses, issue_type) {
+                println!("{name}");
+            }
+            return Ok(());
+        }
+        let preview = proposed_thresholds.as_ref().map(|proposed| preview::preview_threshold_changes(&statuses, proposed));
+        let rendered = match args.format {
+            OutputFormat::Text => report::generate_report(
+                &statuses,
+                include_stats,
+                &[],
+                &not_found,
+                &host_metrics,
+                None,
+                None,
+                None,
+                args.exclude_powered_off_from_stats,
+                args.uptime_format.into(),
+                &run_id,
+                None,
+                None,
+                None,
+                args.group_by.map(Into::into),
+                args.site.as_deref(),
+                &acknowledgements,
+                None,
+                preview.as_ref(),
+                metrics_degraded,
+            ),
+            OutputFormat::Json => report::export_json_report(
+                &statuses,
+                include_stats,
+                &[],
+                &not_found,
+                args.json_schema_version.into(),
+                &[],
+                &host_metrics,
+                None,
+                None,
+                None,
+                args.exclude_powered_off_from_stats,
+                &run_id,
+                None,
+                None,
+                None,
+                args.site.as_deref(),
+                &acknowledgements,
+                args.compact_json,
+                preview.as_ref(),
+                metrics_degraded,
+                &[],
+            )?,
+            OutputFormat::Csv => report::export_csv_report(&statuses, &run_id, args.site.as_deref()),
+        };
+        sink::sink_for(&args).write(&rendered)?;
+        enforce_fail_on_issues(&args, &statuses, &issue_threshold_warnings, &run_id);
+        if let Some(threshold) = args.fail_below_score {
+            if let Some(score) = scoring::run_score(&statuses) {
+                if score < threshold {
+                    anyhow::bail!("run health score {score:.1} is below --fail-below-score {threshold:.1}");
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    if args.demo {
+        if args.notifier_config.is_some() && !args.demo_allow_notify {
+            anyhow::bail!(
+                "--demo refuses to run alongside --notifier-config unless --demo-allow-notify is also set, \
+                 to avoid paging anyone with synthetic alerts"
+            );
+        }
+        eprintln!("[{run_id}] {}", demo::DEMO_WATERMARK);
+
+        let (mut statuses, host_metrics) = demo::load_demo_fleet(&args.detection_options())?;
+        vm::strip_disabled_issues(&mut statuses, &disabled_issues);
+        let statuses = apply_selection(&args, &run_id, selection.as_ref(), statuses);
+        let (mut statuses, not_found) = vm::resolve_name_list(statuses, &vm_list_names);
+        let acknowledgements = acknowledge::apply_acknowledgements(&mut statuses, chrono::Local::now().date_naive());
+        scoring::annotate_health_scores(&mut statuses, &score_weights);
+        recommend::annotate_recommendations(&mut statuses, &host_metrics, !args.no_recommendations);
+        maintenance::annotate_maintenance_downgrades(&mut statuses, &host_metrics, !args.no_respect_maintenance_mode);
+        let include_stats = !args.no_stats;
+
+        let notify_result = if args.demo_allow_notify {
+            if let Some(path) = &args.notifier_config {
+                let registry = notifier::NotifierRegistry::from_config(notifier::NotifierRegistryConfig::load(path, args.strict_json)?);
+                let summary = notifier::RunSummary::from(&report::compute_statistics(&statuses, false))
+                    .with_run_id(Some(run_id.clone()))
+                    .with_site(args.site.clone());
+                Some(registry.notify_all(&summary, &statuses, &std::collections::BTreeSet::new()))
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        for note in vmc::disabled_check_notes(args.vmc_profile, args.check_host_state, args.check_host_health) {
+            eprintln!("[{run_id}] vmc-profile: {note}");
+        }
+        if args.sanity_check_thresholds {
+            for warning in sanitycheck::unapproached_thresholds(&statuses, &args.detection_options()) {
+                eprintln!("[{run_id}] sanity-check-thresholds: {warning}");
+            }
+        }
+        if let Some(issue_type) = names_for_issue_type {
+            for name in report::names_for_issue(&statuses, issue_type) {
+                println!("{name}");
+            }
+            return Ok(());
+        }
+        let preview = proposed_thresholds.as_ref().map(|proposed| preview::preview_threshold_changes(&statuses, proposed));
+        let rendered = match args.format {
+            OutputFormat::Text => demo::watermark_text(&report::generate_report(
+                &statuses,
+                include_stats,
+                &[],
+                &not_found,
+                &host_metrics,
+                notify_result.as_ref(),
+                None,
+                None,
+                args.exclude_powered_off_from_stats,
+                args.uptime_format.into(),
+                &run_id,
+                None,
+                None,
+                None,
+                args.group_by.map(Into::into),
+                args.site.as_deref(),
+                &acknowledgements,
+                None,
+                preview.as_ref(),
+                false,
+            )),
+            OutputFormat::Json => demo::watermark_json(&report::export_json_report(
+                &statuses,
+                include_stats,
+                &[],
+                &not_found,
+                args.json_schema_version.into(),
+                &[],
+                &host_metrics,
+                notify_result.as_ref(),
+                None,
+                None,
+                args.exclude_powered_off_from_stats,
+                &run_id,
+                None,
+                None,
+                None,
+                args.site.as_deref(),
+                &acknowledgements,
+                args.compact_json,
+                preview.as_ref(),
+                false,
+                &[],
+            )?)?,
+            OutputFormat::Csv => demo::watermark_csv(&report::export_csv_report(&statuses, &run_id, args.site.as_deref())),
+        };
+        sink::sink_for(&args).write(&rendered)?;
+
+        if let Some(result) = &notify_result {
+            if args.fail_on_notify_error && !result.failures.is_empty() {
+                anyhow::bail!("{} notifier(s) failed to deliver", result.failures.len());
+            }
+        }
+        enforce_fail_on_issues(&args, &statuses, &issue_threshold_warnings, &run_id);
+        if let Some(threshold) = args.fail_below_score {
+            if let Some(score) = scoring::run_score(&statuses) {
+                if score < threshold {
+                    anyhow::bail!("run health score {score:.1} is below --fail-below-score {threshold:.1}");
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    let session = match auth::authenticate_from_args(&args) {
+        Ok(session) => session,
+        Err(err) => {
+            eprintln!("[{run_id}] authentication failed: {err}");
+            std::process::exit(err.exit_code());
+        }
+    };
+    eprintln!("[{run_id}] {}", session.describe());
+    eprintln!("[{run_id}] vCenter version: {}", session.version.describe());
+
+    let mut detection_options = args.detection_options();
+    if detection_options.check_host_state && !session.version.at_least(6, 5) {
+        eprintln!(
+            "[{run_id}] check-host-state: host connection/maintenance-mode state isn't exposed below vCenter 6.5 ({}); skipping for this run",
+            session.version.describe()
+        );
+        detection_options.check_host_state = false;
+    } else if args.check_host_state && !session.version.is_recognized() {
+        eprintln!(
+            "[{run_id}] warning: unrecognized vCenter version {}; proceeding with --check-host-state enabled",
+            session.version.describe()
+        );
+    }
+
+    let metrics_provider: Box<dyn metrics_provider::MetricsProvider> = match args.metrics_source {
+        cli::MetricsSourceArg::Simulated => Box::new(metrics_provider::SimulatedMetricsProvider),
+        cli::MetricsSourceArg::Soap => Box::new(metrics_provider::SoapMetricsProvider::new()),
+    };
+
+    let vcenter_version = session.version.clone();
+    let client = SimulatedClient::new(session, args.vm_count, detection_options)
+        .with_api_rate_log(args.api_rate_log)
+        .with_timing(args.timing || args.budget_hint.is_some())
+        .with_max_total_requests(args.max_total_requests)
+        .with_metrics_provider(metrics_provider);
+
+    if let Some(query) = &args.inspect {
+        if args.vm_list_stdin {
+            anyhow::bail!("--vm-list-stdin: not supported with --inspect");
+        }
+        return inspect::run_inspect(&args, &client, query, &run_id);
+    }
+
+    if args.dashboard {
+        if args.vm_list_stdin {
+            anyhow::bail!("--vm-list-stdin: not supported with --dashboard");
+        }
+        return dashboard::run_dashboard(&args, &client);
+    }
+
+    if args.watch {
+        if args.vm_list_stdin {
+            anyhow::bail!("--vm-list-stdin: not supported with --watch");
+        }
+        let notifier = Notifier::from_env();
+        return watch::run_watch_mode(&args, &client, &notifier, &run_id);
+    }
+
+    let collected = interrupt::new_collected();
+    interrupt::install(collected.clone(), args.clone(), client.session.clone(), run_id.clone())?;
+
+    let own_sessions = client.own_sessions();
+    let session_count = own_sessions.as_ref().map(|sessions| sessions.len() as u32);
+    if let Some(count) = session_count {
+        if count >= args.session_count_warn {
+            eprintln!(
+                "[{run_id}] warning: {count} concurrent vCenter session(s) for {}, at or above --session-count-warn {}",
+                client.session.username, args.session_count_warn
+            );
+        }
+    } else {
+        eprintln!("[{run_id}] session-count-warn: insufficient privilege to list sessions for {}; skipping", client.session.username);
+    }
+    let reaped_sessions: Vec<String> = match (args.reap_stale_sessions, &own_sessions) {
+        (Some(idle_minutes), Some(sessions)) => {
+            let stale = sessions::stale_sessions(sessions, idle_minutes);
+            client.reap_sessions(&stale);
+            stale.into_iter().map(|s| s.id.clone()).collect()
+        }
+        _ => Vec::new(),
+    };
+    let session_limit_report = sessions::SessionLimitReport {
+        count: session_count,
+        warn_threshold: args.session_count_warn,
+        reaped: reaped_sessions,
+    };
+
+    let password_expiry_days = client.session.password_expiry_days();
+    if let Some(days) = password_expiry_days {
+        if days <= args.password_expiry_warn_days {
+            eprintln!(
+                "[{run_id}] warning: account password for {} expires in {days} day(s), at or below --password-expiry-warn-days {}",
+                client.session.username, args.password_expiry_warn_days
+            );
+        }
+    }
+    let password_expiry_report = auth::PasswordExpiryReport {
+        days_remaining: password_expiry_days,
+        warn_threshold_days: args.password_expiry_warn_days,
+    };
+
+    let fetch_started = Instant::now();
+    let mut statuses = client.fetch_vm_statuses()?;
+    vm::strip_disabled_issues(&mut statuses, &disabled_issues);
+    let statuses = apply_selection(&args, &run_id, selection.as_ref(), statuses);
+    let (statuses, not_found) = vm::resolve_name_list(statuses, &vm_list_names);
+
+    let (statuses, mut deferred) = apply_time_budget(&args, statuses, fetch_started.elapsed().as_secs_f64(), &run_id)?;
+    deferred.extend(client.request_budget().deferred());
+    deferred.extend(client.timed_out());
+    enforce_atomic(&args, &deferred, &run_id);
+
+    if args.rightsizing_report {
+        let samples = if args.history.is_empty() {
+            rightsize::live_samples(&statuses)
+        } else {
+            thresholds::load_history(&args.history, args.lookback_days)?
+        };
+        let report = rightsize::build_report(&statuses, &samples, args.underuse_threshold, &args.rightsize_exempt_attribute);
+        let rendered = match args.format {
+            OutputFormat::Text => rightsize::render_text(&report),
+            OutputFormat::Csv => rightsize::render_csv(&report),
+            OutputFormat::Json => {
+                if args.compact_json {
+                    serde_json::to_string(&report)?
+                } else {
+                    serde_json::to_string_pretty(&report)?
+                }
+            }
+        };
+        sink::sink_for(&args).write(&rendered)?;
+        return Ok(());
+    }
+
+    let mut statuses = apply_since_last_run(&args, statuses);
+    let acknowledgements = acknowledge::apply_acknowledgements(&mut statuses, chrono::Local::now().date_naive());
+    *collected.lock().unwrap() = Some((statuses.clone(), client.metrics_degraded()));
+
+    let boot_storm_finding = if args.check_boot_storm {
+        let threshold = args
+            .boot_storm_threshold()
+            .map_err(|err| anyhow::anyhow!("boot-storm-threshold: {err}"))?;
+        bootstorm::detect_from_statuses(&statuses, threshold)
+    } else {
+        None
+    };
+    if let Some(finding) = &boot_storm_finding {
+        eprintln!(
+            "[{run_id}] boot-storm: {} VM(s) across {} host(s) rebooted within a {:.0}s window: {}",
+            finding.vm_names.len(),
+            finding.hosts.len(),
+            finding.window_end_secs_ago - finding.window_start_secs_ago,
+            finding.vm_names.join(", ")
+        );
+        if args.suppress_individual_boot_storm_alerts {
+            bootstorm::suppress_clustered_alerts(&mut statuses, finding);
+        }
+    }
+
+    if args.require_hot_add {
+        let scope = match &args.hot_add_scope {
+            Some(path) => Some(hotadd::HotAddScope::load(path, args.strict_json)?),
+            None => None,
+        };
+        hotadd::flag_disabled(&mut statuses, scope.as_ref());
+    }
+
+    let compliance = if args.check_drs_rules {
+        let path = args
+            .drs_rules
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--check-drs-rules requires --drs-rules"))?;
+        let config = drs::DrsRuleConfig::load(path, args.strict_json)?;
+        let placements: std::collections::HashMap<String, String> =
+            statuses.iter().map(|vm| (vm.name.clone(), vm.host.clone())).collect();
+        let report = drs::evaluate(&config.rules, &placements);
+        drs::flag_violations(&mut statuses, &report);
+        Some(report)
+    } else {
+        None
+    };
+
+    scoring::annotate_health_scores(&mut statuses, &score_weights);
+    save_since_last_run_state(&args, &statuses)?;
+
+    let api_rate_log = client.api_rate_log().summaries();
+    let host_metrics: std::collections::BTreeMap<_, _> = client.host_metrics().into_iter().collect();
+    recommend::annotate_recommendations(&mut statuses, &host_metrics, !args.no_recommendations);
+    maintenance::annotate_maintenance_downgrades(&mut statuses, &host_metrics, !args.no_respect_maintenance_mode);
+    let mut issue_first_seen = planner::RunState::load(&args.state_file).issue_first_seen;
+    let run_started_at = chrono::Utc::now();
+    fingerprint::annotate(&mut statuses, &args.host, &mut issue_first_seen, run_started_at);
+    save_issue_first_seen_state(&args, &issue_first_seen)?;
+    if let Some(path) = &args.ticket_export {
+        let issue_types = args.ticket_issue_types().map_err(|err| anyhow::anyhow!("ticket-issue-types: {err}"))?;
+        let tickets = ticket::build_tickets(
+            &statuses,
+            &issue_types,
+            args.ticket_only_new,
+            run_started_at,
+            args.ticket_runbook_link.as_deref(),
+        );
+        ticket::write_ticket_export(path, &tickets)?;
+    }
+    let include_stats = !args.no_stats;
+
+    let notify_result = if let Some(path) = &args.notifier_config {
+        let registry = notifier::NotifierRegistry::from_config(notifier::NotifierRegistryConfig::load(path, args.strict_json)?);
+        let previously_had_issues = planner::RunState::load(&args.state_file).previous_issue_vms;
+        let summary = notifier::RunSummary::from(&report::compute_statistics(&statuses, false))
+            .with_version(Some(vcenter_version.clone()))
+            .with_run_id(Some(run_id.clone()))
+            .with_site(args.site.clone())
+            .with_password_expiry_warning(
+                password_expiry_days.filter(|days| *days <= args.password_expiry_warn_days),
+            )
+            .with_metrics_degraded(client.metrics_degraded());
+        match &boot_storm_finding {
+            Some(finding) => {
+                let mut notify_candidates = statuses.clone();
+                notify_candidates.push(bootstorm::synthetic_boot_storm_vm(finding));
+                Some(registry.notify_all(&summary, &notify_candidates, &previously_had_issues))
+            }
+            None => Some(registry.notify_all(&summary, &statuses, &previously_had_issues)),
+        }
+    } else {
+        None
+    };
+
+    let request_budget_report = client.request_budget().report();
+    let sparkline_history = if args.sparklines && std::io::stdout().is_terminal() {
+        Some(if args.history.is_empty() {
+            std::collections::BTreeMap::new()
+        } else {
+            thresholds::load_history(&args.history, args.lookback_days)?
+        })
+    } else {
+        None
+    };
+    for note in vmc::disabled_check_notes(args.vmc_profile, args.check_host_state, args.check_host_health) {
+        eprintln!("[{run_id}] vmc-profile: {note}");
+    }
+    if args.sanity_check_thresholds {
+        for warning in sanitycheck::unapproached_thresholds(&statuses, &args.detection_options()) {
+            eprintln!("[{run_id}] sanity-check-thresholds: {warning}");
+        }
+    }
+    if let Some(issue_type) = names_for_issue_type {
+        for name in report::names_for_issue(&statuses, issue_type) {
+            println!("{name}");
+        }
+        return Ok(());
+    }
+    let preview = proposed_thresholds.as_ref().map(|proposed| preview::preview_threshold_changes(&statuses, proposed));
+    let rendered = match args.format {
+        OutputFormat::Text => {
+            report::generate_report(
+                &statuses,
+                include_stats,
+                &deferred,
+                &not_found,
+                &host_metrics,
+                notify_result.as_ref(),
+                compliance.as_ref(),
+                Some(&vcenter_version),
+                args.exclude_powered_off_from_stats,
+                args.uptime_format.into(),
+                &run_id,
+                request_budget_report.as_ref(),
+                Some(&session_limit_report),
+                Some(&password_expiry_report),
+                args.group_by.map(Into::into),
+                args.site.as_deref(),
+                &acknowledgements,
+                sparkline_history.as_ref(),
+                preview.as_ref(),
+                client.metrics_degraded(),
+            )
+        }
+        OutputFormat::Json => report::export_json_report(
+            &statuses,
+            include_stats,
+            &deferred,
+            &not_found,
+            args.json_schema_version.into(),
+            &api_rate_log,
+            &host_metrics,
+            notify_result.as_ref(),
+            compliance.as_ref(),
+            Some(&vcenter_version),
+            args.exclude_powered_off_from_stats,
+            &run_id,
+            request_budget_report.as_ref(),
+            Some(&session_limit_report),
+            Some(&password_expiry_report),
+            args.site.as_deref(),
+            &acknowledgements,
+            args.compact_json,
+            preview.as_ref(),
+            client.metrics_degraded(),
+            &client.timing().summaries(),
+        )?,
+        OutputFormat::Csv => report::export_csv_report(&statuses, &run_id, args.site.as_deref()),
+    };
+    if args.output_on_change {
+        use std::hash::{Hash, Hasher};
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        rendered.hash(&mut hasher);
+        let hash = hasher.finish();
+        let mut state = planner::RunState::load(&args.state_file);
+        if state.last_output_hash == Some(hash) {
+            eprintln!("[{run_id}] --output-on-change: no change, skipping write");
+        } else {
+            sink::sink_for(&args).write(&rendered)?;
+            state.last_output_hash = Some(hash);
+            state.save(&args.state_file)?;
+        }
+    } else {
+        sink::sink_for(&args).write(&rendered)?;
+    }
+    if let Some(path) = &args.summary_output {
+        sink::FileSink::new(path.clone()).write(&report::generate_summary_report(&statuses))?;
+    }
+    if let Some(path) = &args.topology_output {
+        topology::write_topology_output(path, &statuses, args.topology_context, args.topology_max_nodes)?;
+    }
+    if let Some(path) = &args.openmetrics_output {
+        metrics::write_openmetrics_output(path, &statuses, &host_metrics, args.site.as_deref())?;
+    }
+    if let Some(path) = &args.logfmt_output {
+        logfmt::write_logfmt_output(path, &statuses)?;
+    }
+    if let Some(path) = &args.template {
+        // Always the v2 shape, regardless of `--json-schema-version`, so a
+        // template always sees full issue detail and doesn't need to track
+        // schema versions meant for the primary `--format json` consumer.
+        let context_json = report::export_json_report(
+            &statuses,
+            include_stats,
+            &deferred,
+            &not_found,
+            report::JsonSchemaVersion::V2,
+            &api_rate_log,
+            &host_metrics,
+            notify_result.as_ref(),
+            compliance.as_ref(),
+            Some(&vcenter_version),
+            args.exclude_powered_off_from_stats,
+            &run_id,
+            request_budget_report.as_ref(),
+            Some(&session_limit_report),
+            Some(&password_expiry_report),
+            args.site.as_deref(),
+            &acknowledgements,
+            args.compact_json,
+            preview.as_ref(),
+            client.metrics_degraded(),
+            &client.timing().summaries(),
+        )?;
+        let context = serde_json::from_str(&context_json).context("parsing report context for --template")?;
+        let template_source = fs::read_to_string(path).with_context(|| format!("reading --template file '{path}'"))?;
+        let rendered = template::render(&template_source, &context)?;
+        let output_path = args.template_output.as_ref().context("--template-output is required by --template")?;
+        sink::FileSink::new(output_path.clone()).write(&rendered)?;
+    }
+    route_and_print(&args, &statuses, &run_id)?;
+    if args.api_rate_log {
+        eprint!("{}", client.api_rate_log().render_table());
+    }
+    if args.timing {
+        eprint!("{}", client.timing().render_table());
+    }
+    if let Some(budget_secs) = args.budget_hint {
+        let hint = check_timing::budget_hint(&client.timing().summaries(), budget_secs);
+        if hint.disable.is_empty() {
+            eprintln!("[{run_id}] --budget-hint: already within {budget_secs:.1} s ({:.3} s measured)", hint.projected_secs);
+        } else {
+            eprintln!(
+                "[{run_id}] --budget-hint: disable {} to fit {budget_secs:.1} s (projected {:.3} s)",
+                hint.disable.join(", "),
+                hint.projected_secs
+            );
+        }
+    }
+
+    if let Some(result) = &notify_result {
+        if args.fail_on_notify_error && !result.failures.is_empty() {
+            anyhow::bail!("{} notifier(s) failed to deliver", result.failures.len());
+        }
+    }
+    enforce_fail_on_issues(&args, &statuses, &issue_threshold_warnings, &run_id);
+    if let Some(threshold) = args.fail_below_score {
+        if let Some(score) = scoring::run_score(&statuses) {
+            if score < threshold {
+                anyhow::bail!("run health score {score:.1} is below --fail-below-score {threshold:.1}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// There's no central exit-code registry - each module picks its own
+    /// constant (or `AuthError::exit_code` match arm) without visibility
+    /// into the others', which has already produced two collisions
+    /// (`ATOMIC_ABORT_EXIT_CODE` vs `AuthError::SsoTokenRejected`,
+    /// `AuthError::UnexpectedContentType` vs `lockfile::LOCK_HELD_EXIT_CODE`).
+    /// This is the one place that lists every top-level exit code and
+    /// asserts they're pairwise distinct - add a new code here whenever one
+    /// is introduced anywhere in the crate.
+    #[test]
+    fn every_top_level_exit_code_is_unique() {
+        let codes = vec![
+            vm::ISSUE_ERROR_EXIT_CODE,
+            lockfile::LOCK_HELD_EXIT_CODE,
+            interrupt::INTERRUPTED_EXIT_CODE,
+            ATOMIC_ABORT_EXIT_CODE,
+            auth::AuthError::BadCredentials { username: String::new() }.exit_code(),
+            auth::AuthError::AccountLockedOrExpired { username: String::new() }.exit_code(),
+            auth::AuthError::NetworkFailure { host: String::new(), detail: String::new() }.exit_code(),
+            auth::AuthError::UnexpectedContentType {
+                host: String::new(),
+                source: content_type::UnexpectedContentType {
+                    status: 200,
+                    content_type: String::new(),
+                    body_snippet: String::new(),
+                    looks_like_login_page: false,
+                },
+            }
+            .exit_code(),
+            auth::AuthError::SsoTokenRejected { host: String::new(), detail: String::new() }.exit_code(),
+            auth::AuthError::CloudCspTokenRejected { host: String::new(), detail: String::new() }.exit_code(),
+            auth::AuthError::InvalidCredentialCombination { detail: String::new() }.exit_code(),
+        ];
+        let unique: std::collections::HashSet<i32> = codes.iter().copied().collect();
+        assert_eq!(unique.len(), codes.len(), "exit codes must be pairwise distinct: {codes:?}");
+    }
+}
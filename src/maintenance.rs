@@ -0,0 +1,142 @@
+//! Downgrades issue severity for VMs whose host is in maintenance mode.
+//! `PoweredOff`/`Suspended`/`UptimeShort` are all routine side effects of a
+//! host being drained for maintenance, not something worth paging on, but
+//! they're still worth keeping in the report - just at
+//! [`Severity::Informational`] instead of their usual tier. Gated by
+//! `--no-respect-maintenance-mode`, on by default.
+
+use std::collections::BTreeMap;
+
+use crate::vm::{HostMetrics, Severity, VMIssueType, VMResourceStatus};
+
+/// Issue types expected as a side effect of planned host maintenance.
+const DOWNGRADED_ON_MAINTENANCE: [VMIssueType; 3] = [VMIssueType::PoweredOff, VMIssueType::Suspended, VMIssueType::UptimeShort];
+
+/// For each VM whose host is in maintenance mode (per `host_metrics`),
+/// downgrades any [`DOWNGRADED_ON_MAINTENANCE`] issue to
+/// [`Severity::Informational`], recording the issue's prior severity in
+/// `original_severity` and noting the reason in its detail, so a report or
+/// audit can still see what the issue would otherwise have been. A no-op
+/// when `enabled` is false, or for a VM whose host has no entry in
+/// `host_metrics` (e.g. under `--replay`, which doesn't persist host
+/// state).
+pub fn annotate_maintenance_downgrades(statuses: &mut [VMResourceStatus], host_metrics: &BTreeMap<String, HostMetrics>, enabled: bool) {
+    if !enabled {
+        return;
+    }
+    for vm in statuses {
+        let in_maintenance = host_metrics.get(&vm.host).is_some_and(|host| host.in_maintenance_mode);
+        if !in_maintenance {
+            continue;
+        }
+        for issue in &mut vm.issues {
+            if !DOWNGRADED_ON_MAINTENANCE.contains(&issue.issue_type) {
+                continue;
+            }
+            issue.original_severity = Some(issue.severity);
+            issue.severity = Severity::Informational;
+            match &mut issue.detail {
+                Some(detail) => detail.push_str(" (host in maintenance)"),
+                None => issue.detail = Some("host in maintenance".to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, HostConnectionState, PowerState};
+
+    fn vm(issue_type: VMIssueType) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOff,
+            cpu_usage_pct: 0.0,
+            memory_usage_pct: 0.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: false,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: vec![DetectedIssue::new(issue_type, "powered off")],
+        }
+    }
+
+    fn host_metrics(in_maintenance_mode: bool) -> BTreeMap<String, HostMetrics> {
+        let mut map = BTreeMap::new();
+        map.insert(
+            "esxi-01".to_string(),
+            HostMetrics {
+                cpu_usage_pct: 10.0,
+                memory_usage_pct: 10.0,
+                physical_cores: 32,
+                connection_state: HostConnectionState::Connected,
+                in_maintenance_mode,
+                sensor_status: crate::vm::HostSensorStatus::Green,
+                failing_sensor: None,
+            },
+        );
+        map
+    }
+
+    #[test]
+    fn downgrades_expected_issues_for_a_host_in_maintenance() {
+        let mut statuses = vec![vm(VMIssueType::PoweredOff)];
+        annotate_maintenance_downgrades(&mut statuses, &host_metrics(true), true);
+        let issue = &statuses[0].issues[0];
+        assert_eq!(issue.severity, Severity::Informational);
+        assert_eq!(issue.original_severity, Some(Severity::Warning));
+        assert!(issue.detail.as_deref().unwrap().contains("host in maintenance"));
+    }
+
+    #[test]
+    fn leaves_other_issue_types_untouched() {
+        let mut statuses = vec![vm(VMIssueType::ToolsNotRunning)];
+        annotate_maintenance_downgrades(&mut statuses, &host_metrics(true), true);
+        let issue = &statuses[0].issues[0];
+        assert_eq!(issue.severity, Severity::Critical);
+        assert_eq!(issue.original_severity, None);
+    }
+
+    #[test]
+    fn is_a_no_op_when_the_host_is_not_in_maintenance() {
+        let mut statuses = vec![vm(VMIssueType::PoweredOff)];
+        annotate_maintenance_downgrades(&mut statuses, &host_metrics(false), true);
+        assert_eq!(statuses[0].issues[0].original_severity, None);
+    }
+
+    #[test]
+    fn is_a_no_op_when_disabled() {
+        let mut statuses = vec![vm(VMIssueType::PoweredOff)];
+        annotate_maintenance_downgrades(&mut statuses, &host_metrics(true), false);
+        assert_eq!(statuses[0].issues[0].original_severity, None);
+    }
+}
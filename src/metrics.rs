@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::vm::{HostMetrics, VMResourceStatus};
+
+/// One exposed time series within a [`MetricFamily`]: its labels and current
+/// value. Built once per fleet snapshot; kept separate from rendering so a
+/// plain Prometheus text exporter could reuse the same series later without
+/// duplicating the fleet walk - `--openmetrics-output` is the only renderer
+/// today.
+struct Sample {
+    labels: Vec<(&'static str, String)>,
+    value: f64,
+}
+
+/// A metric family: name, HELP text, OpenMetrics `TYPE`, optional `UNIT`
+/// suffix, and the samples collected for it this run. `name` already carries
+/// the unit suffix (`_percent`, `_seconds`) per the OpenMetrics spec, which
+/// requires it to match `unit` exactly.
+struct MetricFamily {
+    name: &'static str,
+    help: &'static str,
+    metric_type: &'static str,
+    unit: Option<&'static str>,
+    samples: Vec<Sample>,
+}
+
+fn vm_labels(vm: &VMResourceStatus, site: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut labels = vec![("name", vm.name.clone()), ("host", vm.host.clone()), ("cluster", vm.cluster.clone())];
+    if let Some(site) = site {
+        labels.push(("site", site.to_string()));
+    }
+    labels
+}
+
+fn host_labels(host: &str, site: Option<&str>) -> Vec<(&'static str, String)> {
+    let mut labels = vec![("host", host.to_string())];
+    if let Some(site) = site {
+        labels.push(("site", site.to_string()));
+    }
+    labels
+}
+
+/// Builds every metric family from the fleet snapshot: per-VM CPU/memory
+/// usage, uptime, and issue count, plus per-host CPU/memory usage from
+/// `--api-rate-log`-style host metrics (empty when they weren't collected,
+/// in which case the host families are simply omitted). `site` is `--site`'s
+/// label, attached to every sample when set.
+fn build_metric_families(statuses: &[VMResourceStatus], host_metrics: &BTreeMap<String, HostMetrics>, site: Option<&str>) -> Vec<MetricFamily> {
+    let mut families = vec![
+        MetricFamily {
+            name: "vm_cpu_usage_percent",
+            help: "Guest CPU usage as a percentage of provisioned vCPU.",
+            metric_type: "gauge",
+            unit: Some("percent"),
+            samples: statuses.iter().map(|vm| Sample { labels: vm_labels(vm, site), value: vm.cpu_usage_pct }).collect(),
+        },
+        MetricFamily {
+            name: "vm_memory_usage_percent",
+            help: "Guest memory usage as a percentage of provisioned memory.",
+            metric_type: "gauge",
+            unit: Some("percent"),
+            samples: statuses.iter().map(|vm| Sample { labels: vm_labels(vm, site), value: vm.memory_usage_pct }).collect(),
+        },
+        MetricFamily {
+            name: "vm_uptime_seconds",
+            help: "Seconds since the VM's last power-on.",
+            metric_type: "gauge",
+            unit: Some("seconds"),
+            samples: statuses.iter().map(|vm| Sample { labels: vm_labels(vm, site), value: vm.uptime_secs }).collect(),
+        },
+        MetricFamily {
+            name: "vm_issues",
+            help: "Number of detected issues currently affecting the VM.",
+            metric_type: "gauge",
+            unit: None,
+            samples: statuses.iter().map(|vm| Sample { labels: vm_labels(vm, site), value: vm.issues.len() as f64 }).collect(),
+        },
+    ];
+
+    if !host_metrics.is_empty() {
+        families.push(MetricFamily {
+            name: "host_cpu_usage_percent",
+            help: "Host CPU usage as a percentage, from the host's own perf counters.",
+            metric_type: "gauge",
+            unit: Some("percent"),
+            samples: host_metrics
+                .iter()
+                .map(|(host, metrics)| Sample { labels: host_labels(host, site), value: metrics.cpu_usage_pct })
+                .collect(),
+        });
+        families.push(MetricFamily {
+            name: "host_memory_usage_percent",
+            help: "Host memory usage as a percentage, from the host's own perf counters.",
+            metric_type: "gauge",
+            unit: Some("percent"),
+            samples: host_metrics
+                .iter()
+                .map(|(host, metrics)| Sample { labels: host_labels(host, site), value: metrics.memory_usage_pct })
+                .collect(),
+        });
+    }
+
+    families
+}
+
+/// Escapes a label value per the OpenMetrics text format: backslash and
+/// quote are escaped, and a literal newline can't appear in a label value at all.
+fn escape_label_value(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn render_labels(labels: &[(&'static str, String)]) -> String {
+    labels
+        .iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", escape_label_value(value)))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Renders the fleet snapshot as spec-compliant OpenMetrics text exposition
+/// format: `HELP`/`TYPE`/`UNIT` lines per metric family, then one sample line
+/// per VM (or host), terminated by the mandatory `# EOF` line.
+fn render_openmetrics(statuses: &[VMResourceStatus], host_metrics: &BTreeMap<String, HostMetrics>, site: Option<&str>) -> String {
+    let mut out = String::new();
+    for family in build_metric_families(statuses, host_metrics, site) {
+        out.push_str(&format!("# HELP {} {}\n", family.name, family.help));
+        out.push_str(&format!("# TYPE {} {}\n", family.name, family.metric_type));
+        if let Some(unit) = family.unit {
+            out.push_str(&format!("# UNIT {} {}\n", family.name, unit));
+        }
+        for sample in &family.samples {
+            out.push_str(&format!("{}{{{}}} {}\n", family.name, render_labels(&sample.labels), sample.value));
+        }
+    }
+    out.push_str("# EOF\n");
+    out
+}
+
+/// `--openmetrics-output`: additionally writes the fleet snapshot as
+/// OpenMetrics text exposition to `path`, reusing the same in-memory results
+/// as `--output` - no extra vCenter query. Composes with `--output`/
+/// `--format`, which are unaffected. `site` is `--site`'s label, attached to
+/// every sample when set.
+pub fn write_openmetrics_output(
+    path: &str,
+    statuses: &[VMResourceStatus],
+    host_metrics: &BTreeMap<String, HostMetrics>,
+    site: Option<&str>,
+) -> Result<()> {
+    std::fs::write(path, render_openmetrics(statuses, host_metrics, site)).with_context(|| format!("writing OpenMetrics output to {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::PowerState;
+
+    fn vm(name: &str) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 42.0,
+            memory_usage_pct: 55.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 3600.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            issues: Vec::new(),
+            health_score: 100.0,
+            change_version: 0,
+        }
+    }
+
+    #[test]
+    fn output_ends_with_the_mandatory_eof_line() {
+        let rendered = render_openmetrics(&[vm("vm-01")], &BTreeMap::new(), None);
+        assert!(rendered.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn metric_names_carry_their_declared_unit_suffix() {
+        let rendered = render_openmetrics(&[vm("vm-01")], &BTreeMap::new(), None);
+        assert!(rendered.contains("# UNIT vm_cpu_usage_percent percent"));
+        assert!(rendered.contains("vm_cpu_usage_percent{name=\"vm-01\",host=\"esxi-01\",cluster=\"cluster-a\"} 42"));
+    }
+
+    #[test]
+    fn host_families_are_omitted_when_no_host_metrics_were_collected() {
+        let rendered = render_openmetrics(&[vm("vm-01")], &BTreeMap::new(), None);
+        assert!(!rendered.contains("host_cpu_usage_percent"));
+    }
+
+    #[test]
+    fn host_families_appear_when_host_metrics_are_present() {
+        let mut host_metrics = BTreeMap::new();
+        host_metrics.insert(
+            "esxi-01".to_string(),
+            HostMetrics {
+                cpu_usage_pct: 80.0,
+                memory_usage_pct: 60.0,
+                physical_cores: 32,
+                connection_state: crate::vm::HostConnectionState::Connected,
+                in_maintenance_mode: false,
+                sensor_status: crate::vm::HostSensorStatus::Green,
+                failing_sensor: None,
+            },
+        );
+        let rendered = render_openmetrics(&[vm("vm-01")], &host_metrics, None);
+        assert!(rendered.contains("host_cpu_usage_percent{host=\"esxi-01\"} 80"));
+    }
+
+    #[test]
+    fn label_values_with_quotes_and_backslashes_are_escaped() {
+        let mut v = vm("vm-\"01\"");
+        v.host = "esxi\\01".to_string();
+        let rendered = render_openmetrics(&[v], &BTreeMap::new(), None);
+        assert!(rendered.contains("name=\"vm-\\\"01\\\"\""));
+        assert!(rendered.contains("host=\"esxi\\\\01\""));
+    }
+
+    #[test]
+    fn site_label_is_attached_to_vm_and_host_samples_when_set() {
+        let mut host_metrics = BTreeMap::new();
+        host_metrics.insert(
+            "esxi-01".to_string(),
+            HostMetrics {
+                cpu_usage_pct: 80.0,
+                memory_usage_pct: 60.0,
+                physical_cores: 32,
+                connection_state: crate::vm::HostConnectionState::Connected,
+                in_maintenance_mode: false,
+                sensor_status: crate::vm::HostSensorStatus::Green,
+                failing_sensor: None,
+            },
+        );
+        let rendered = render_openmetrics(&[vm("vm-01")], &host_metrics, Some("us-east-1"));
+        assert!(rendered.contains("vm_cpu_usage_percent{name=\"vm-01\",host=\"esxi-01\",cluster=\"cluster-a\",site=\"us-east-1\"} 42"));
+        assert!(rendered.contains("host_cpu_usage_percent{host=\"esxi-01\",site=\"us-east-1\"} 80"));
+    }
+
+    #[test]
+    fn site_label_is_omitted_when_unset() {
+        let rendered = render_openmetrics(&[vm("vm-01")], &BTreeMap::new(), None);
+        assert!(!rendered.contains("site="));
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use crate::vm::PowerState;
+
+/// Sources a VM's point-in-time performance counters, decoupling
+/// [`crate::vcenter::SimulatedClient`] from any one stats backend -
+/// simulated today, a real `PerformanceManager` (SOAP) or its REST
+/// successor once one exists. `Ok(None)` means no sample is available for
+/// this VM right now (real vCenter has no perf counters for a VM that
+/// isn't powered on), which callers should treat as "use a default", not
+/// an error - same as [`crate::reachability::ReachabilityProbe`] is the
+/// swappable edge for reachability checks. `Err` is reserved for a
+/// connection-level failure (the collector itself is unreachable) rather
+/// than this one VM having nothing to report - see [`MetricsFetchError`].
+///
+/// Keyed by counter name (`"cpu_usage_pct"`, `"memory_usage_pct"`) rather
+/// than dedicated struct fields so a provider can report counters this
+/// binary doesn't know about yet without a breaking trait change - the
+/// real `PerformanceManager.QueryPerf` result is shaped the same way, one
+/// entry per requested counter.
+pub trait MetricsProvider: Send + Sync {
+    fn vm_performance_metrics(&self, vm_name: &str, power_state: PowerState) -> Result<Option<HashMap<String, f64>>, MetricsFetchError>;
+}
+
+/// A VM-level call returning this means the collector's connection itself
+/// is down, not that this one VM lacks data - e.g. the SOAP
+/// `PerformanceManager` endpoint (`/sdk`) is unreachable while the REST API
+/// stays healthy, which happens during vCenter patching. Distinguishing
+/// this from `Ok(None)` is what lets
+/// [`crate::vcenter::SimulatedClient::prefetch_vm_metrics`] mark every
+/// affected VM's `metrics_source` as
+/// [`crate::vm::MetricsSourceStatus::Unavailable`] and stop retrying for
+/// the rest of the run, instead of treating a real outage as "every VM is
+/// idle".
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricsFetchError;
+
+/// The counter key [`MetricsProvider`] implementations report CPU usage
+/// under.
+pub const CPU_USAGE_PCT: &str = "cpu_usage_pct";
+/// The counter key [`MetricsProvider`] implementations report memory usage
+/// under.
+pub const MEMORY_USAGE_PCT: &str = "memory_usage_pct";
+
+/// The default provider: synthesizes plausible-looking CPU/memory usage in
+/// memory, without any network access, same as the rest of
+/// [`crate::vcenter::SimulatedClient`]'s fleet generation. Never reports
+/// [`MetricsFetchError`] - there's nothing here that can go "unreachable".
+pub struct SimulatedMetricsProvider;
+
+impl MetricsProvider for SimulatedMetricsProvider {
+    fn vm_performance_metrics(&self, _vm_name: &str, power_state: PowerState) -> Result<Option<HashMap<String, f64>>, MetricsFetchError> {
+        if power_state != PowerState::PoweredOn {
+            // Real vCenter has nothing to report for a VM that isn't
+            // running; callers fall back to 0% rather than treating this
+            // as missing data.
+            return Ok(None);
+        }
+        let mut rng = rand::thread_rng();
+        let mut metrics = HashMap::new();
+        metrics.insert(CPU_USAGE_PCT.to_string(), rng.gen_range(0.0..100.0));
+        metrics.insert(MEMORY_USAGE_PCT.to_string(), rng.gen_range(0.0..100.0));
+        Ok(Some(metrics))
+    }
+}
+
+/// A `PerformanceManager.QueryPerf` (SOAP) backed provider, for
+/// `--metrics-source soap`. There's no real SOAP client wired up yet (see
+/// [`crate::auth`] for the same caveat on the session endpoint), so this
+/// falls back to the same synthesis [`SimulatedMetricsProvider`] does -
+/// logged once per process so it's never mistaken for the real thing -
+/// rather than fail every fetch outright until one lands. Never reports
+/// [`MetricsFetchError`] either, for the same reason: there's no real
+/// `/sdk` endpoint here to go down. A provider that actually talks to one
+/// is what [`crate::vcenter::SimulatedClient::prefetch_vm_metrics`]'s
+/// connection-down handling is for.
+pub struct SoapMetricsProvider {
+    warned: std::sync::atomic::AtomicBool,
+}
+
+impl SoapMetricsProvider {
+    pub fn new() -> Self {
+        Self { warned: std::sync::atomic::AtomicBool::new(false) }
+    }
+}
+
+impl Default for SoapMetricsProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsProvider for SoapMetricsProvider {
+    fn vm_performance_metrics(&self, vm_name: &str, power_state: PowerState) -> Result<Option<HashMap<String, f64>>, MetricsFetchError> {
+        if !self.warned.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            eprintln!("metrics-source soap: no SOAP PerformanceManager client is wired up yet, falling back to simulated metrics");
+        }
+        SimulatedMetricsProvider.vm_performance_metrics(vm_name, power_state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulated_provider_reports_both_counters_when_powered_on() {
+        let metrics = SimulatedMetricsProvider.vm_performance_metrics("vm-0001", PowerState::PoweredOn).unwrap().unwrap();
+        assert!(metrics.contains_key(CPU_USAGE_PCT));
+        assert!(metrics.contains_key(MEMORY_USAGE_PCT));
+    }
+
+    #[test]
+    fn simulated_provider_has_nothing_to_report_when_not_powered_on() {
+        assert!(SimulatedMetricsProvider.vm_performance_metrics("vm-0001", PowerState::PoweredOff).unwrap().is_none());
+        assert!(SimulatedMetricsProvider.vm_performance_metrics("vm-0001", PowerState::Suspended).unwrap().is_none());
+    }
+
+    #[test]
+    fn soap_provider_falls_back_to_simulated_metrics() {
+        let metrics = SoapMetricsProvider::new().vm_performance_metrics("vm-0001", PowerState::PoweredOn).unwrap().unwrap();
+        assert!(metrics.contains_key(CPU_USAGE_PCT));
+        assert!(metrics.contains_key(MEMORY_USAGE_PCT));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::vm::LastMigration;
+
+/// A single result row from the (simulated) vCenter event query, before
+/// it's been confirmed to be a migration event. vCenter's event query isn't
+/// type-filtered as tightly as the event-type name suggests, so this is
+/// kept separate from [`MigrationEvent`] and checked by [`parse_event`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawMigrationEvent {
+    pub event_type: String,
+    pub vm_name: String,
+    /// How long before "now" the migration completed. Kept relative rather
+    /// than as a wall-clock timestamp so bucketing against
+    /// `--migration-window-hours` needs no notion of the current time.
+    pub hours_ago: f64,
+    pub from_host: String,
+    pub to_host: String,
+}
+
+/// A confirmed `VmMigratedEvent`/`DrsVmMigratedEvent`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MigrationEvent {
+    pub vm_name: String,
+    pub hours_ago: f64,
+    pub from_host: String,
+    pub to_host: String,
+}
+
+/// Keeps only `VmMigratedEvent`/`DrsVmMigratedEvent` rows, discarding
+/// everything else the event query might return.
+pub fn parse_event(raw: &RawMigrationEvent) -> Option<MigrationEvent> {
+    if raw.event_type != "VmMigratedEvent" && raw.event_type != "DrsVmMigratedEvent" {
+        return None;
+    }
+    Some(MigrationEvent {
+        vm_name: raw.vm_name.clone(),
+        hours_ago: raw.hours_ago,
+        from_host: raw.from_host.clone(),
+        to_host: raw.to_host.clone(),
+    })
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MigrationSummary {
+    pub count: u32,
+    pub last: Option<LastMigration>,
+}
+
+/// Buckets a flat event-query result by VM, client-side - one
+/// `VmMigratedEvent`/`DrsVmMigratedEvent` query covers `window_hours` for
+/// the whole fleet, rather than querying per VM.
+pub fn bucket_migrations_by_vm(events: &[MigrationEvent], window_hours: f64) -> HashMap<String, MigrationSummary> {
+    let mut by_vm: HashMap<String, MigrationSummary> = HashMap::new();
+    let mut most_recent_hours_ago: HashMap<String, f64> = HashMap::new();
+
+    for event in events.iter().filter(|e| e.hours_ago <= window_hours) {
+        let summary = by_vm.entry(event.vm_name.clone()).or_default();
+        summary.count += 1;
+
+        let is_most_recent_so_far = most_recent_hours_ago
+            .get(&event.vm_name)
+            .map(|&prev_hours_ago| event.hours_ago < prev_hours_ago)
+            .unwrap_or(true);
+        if is_most_recent_so_far {
+            most_recent_hours_ago.insert(event.vm_name.clone(), event.hours_ago);
+            summary.last = Some(LastMigration {
+                from_host: event.from_host.clone(),
+                to_host: event.to_host.clone(),
+            });
+        }
+    }
+    by_vm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(event_type: &str, vm_name: &str, hours_ago: f64, from_host: &str, to_host: &str) -> RawMigrationEvent {
+        RawMigrationEvent {
+            event_type: event_type.to_string(),
+            vm_name: vm_name.to_string(),
+            hours_ago,
+            from_host: from_host.to_string(),
+            to_host: to_host.to_string(),
+        }
+    }
+
+    #[test]
+    fn parse_event_accepts_both_migration_event_types_and_rejects_others() {
+        assert!(parse_event(&raw("VmMigratedEvent", "vm-0001", 1.0, "esxi-01", "esxi-02")).is_some());
+        assert!(parse_event(&raw("DrsVmMigratedEvent", "vm-0001", 1.0, "esxi-01", "esxi-02")).is_some());
+        assert!(parse_event(&raw("VmPoweredOnEvent", "vm-0001", 1.0, "esxi-01", "esxi-02")).is_none());
+    }
+
+    #[test]
+    fn bucket_counts_per_vm_and_excludes_events_outside_the_window() {
+        let events = vec![
+            MigrationEvent { vm_name: "vm-0001".to_string(), hours_ago: 1.0, from_host: "esxi-01".to_string(), to_host: "esxi-02".to_string() },
+            MigrationEvent { vm_name: "vm-0001".to_string(), hours_ago: 30.0, from_host: "esxi-02".to_string(), to_host: "esxi-03".to_string() },
+            MigrationEvent { vm_name: "vm-0002".to_string(), hours_ago: 2.0, from_host: "esxi-04".to_string(), to_host: "esxi-05".to_string() },
+        ];
+
+        let buckets = bucket_migrations_by_vm(&events, 24.0);
+        assert_eq!(buckets.get("vm-0001").unwrap().count, 1, "the 30h-ago event is outside the 24h window");
+        assert_eq!(buckets.get("vm-0002").unwrap().count, 1);
+    }
+
+    #[test]
+    fn bucket_tracks_the_most_recent_migration_regardless_of_input_order() {
+        let events = vec![
+            MigrationEvent { vm_name: "vm-0001".to_string(), hours_ago: 10.0, from_host: "esxi-01".to_string(), to_host: "esxi-02".to_string() },
+            MigrationEvent { vm_name: "vm-0001".to_string(), hours_ago: 2.0, from_host: "esxi-02".to_string(), to_host: "esxi-03".to_string() },
+        ];
+
+        let buckets = bucket_migrations_by_vm(&events, 24.0);
+        let summary = buckets.get("vm-0001").unwrap();
+        assert_eq!(summary.count, 2);
+        let last = summary.last.as_ref().unwrap();
+        assert_eq!(last.from_host, "esxi-02");
+        assert_eq!(last.to_host, "esxi-03");
+    }
+}
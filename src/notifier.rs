@@ -0,0 +1,561 @@
+//! Plugin-style notifier registry: each configured backend (Slack, Teams,
+//! email, webhook, PagerDuty, ...) implements [`Notifier`], declares its own
+//! filter, and is driven from [`NotifierRegistry::notify_all`]. A failing
+//! notifier is recorded, not fatal - one broken webhook must never keep the
+//! others, or the run itself, from going out. The filtering logic lives on
+//! [`NotifierFilter`] so it's written, and unit-tested, exactly once instead
+//! of once per backend.
+//!
+//! Real delivery (an HTTPS POST to Slack, an SMTP send, a PagerDuty event)
+//! isn't wired up yet; [`LogNotifier`] simulates it by writing a line to
+//! stderr, the same way [`crate::auth::authenticate`] simulates vCenter's
+//! login call, so the registry/filtering/reporting plumbing can be built and
+//! tested against a stable contract ahead of a real backend landing.
+
+use std::collections::BTreeSet;
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::auth::VCenterVersion;
+use crate::report::Statistics;
+use crate::vm::{Severity, VMIssueType, VMResourceStatus};
+
+/// What a [`Notifier`] is told about the run as a whole. The VMs it should
+/// consider are passed separately to [`Notifier::notify`], already narrowed
+/// by that notifier's [`NotifierFilter`].
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub total_vms: usize,
+    pub vms_with_issues: usize,
+    pub powered_off: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vcenter_version: Option<VCenterVersion>,
+    /// See [`crate::run_id`]. Carried on every webhook/Slack/etc. payload so
+    /// a delivered notification can be joined back up with the run and
+    /// report it came from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// Days until the authenticated account's password expires, only set
+    /// when that's at or below `--password-expiry-warn-days` - a password
+    /// quietly expiring has taken monitoring down silently before, so this
+    /// has to reach every configured notifier, not just the report.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password_expiry_warning_days: Option<u32>,
+    /// `--site`'s geographic/DC label, so a central system aggregating
+    /// alerts from multiple sites can group by site without parsing the
+    /// vCenter hostname. Unset (and omitted) unless `--site` was passed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    /// Whether the SOAP `PerformanceManager` connection went down at any
+    /// point this run, per [`crate::vcenter::SimulatedClient::metrics_degraded`] -
+    /// every configured notifier needs this, not just the report, since a
+    /// run with partial metrics silently looking clean is exactly the kind
+    /// of thing alerting exists to catch. Omitted (not just `false`) when
+    /// metrics collection stayed healthy, so existing payloads don't grow a
+    /// field nobody needs to read.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub metrics_degraded: bool,
+}
+
+impl From<&Statistics> for RunSummary {
+    fn from(stats: &Statistics) -> Self {
+        Self {
+            total_vms: stats.total_vms,
+            vms_with_issues: stats.vms_with_issues,
+            powered_off: stats.powered_off,
+            vcenter_version: None,
+            run_id: None,
+            password_expiry_warning_days: None,
+            site: None,
+            metrics_degraded: false,
+        }
+    }
+}
+
+impl RunSummary {
+    /// Attaches the session's detected vCenter version so every notifier
+    /// payload carries it alongside the run's stats.
+    pub fn with_version(mut self, version: Option<VCenterVersion>) -> Self {
+        self.vcenter_version = version;
+        self
+    }
+
+    /// Attaches this run's correlation ID so every notifier payload carries
+    /// it alongside the run's stats. See [`crate::run_id`].
+    pub fn with_run_id(mut self, run_id: Option<String>) -> Self {
+        self.run_id = run_id;
+        self
+    }
+
+    /// Attaches `--password-expiry-warn-days`'s outcome; pass `None` when
+    /// the expiry is unknown or comfortably above the threshold, so a
+    /// notifier payload only ever carries this when it's actually worth
+    /// acting on.
+    pub fn with_password_expiry_warning(mut self, days_remaining: Option<u32>) -> Self {
+        self.password_expiry_warning_days = days_remaining;
+        self
+    }
+
+    /// Attaches `--site`'s label so every notifier payload carries it
+    /// alongside the run's stats.
+    pub fn with_site(mut self, site: Option<String>) -> Self {
+        self.site = site;
+        self
+    }
+
+    /// Attaches whether the metrics collector's connection went down this
+    /// run, per [`crate::vcenter::SimulatedClient::metrics_degraded`].
+    pub fn with_metrics_degraded(mut self, metrics_degraded: bool) -> Self {
+        self.metrics_degraded = metrics_degraded;
+        self
+    }
+}
+
+/// Result of one notifier's delivery attempt.
+#[derive(Debug, Clone, Serialize)]
+pub struct NotifyOutcome {
+    pub notifier: String,
+    pub vms_notified: usize,
+}
+
+/// A single notification backend. `notify` should report a failed delivery
+/// as `Err`, not panic or retry internally - [`NotifierRegistry::notify_all`]
+/// records the error and moves on to the next notifier.
+pub trait Notifier {
+    fn name(&self) -> &str;
+    fn notify(&self, summary: &RunSummary, issues: &[VMResourceStatus]) -> Result<NotifyOutcome>;
+}
+
+/// Narrows which VMs a notifier is told about. Shared across every backend so
+/// the matching rules are consistent and tested once, not reimplemented per
+/// notifier.
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(default)]
+pub struct NotifierFilter {
+    pub min_severity: Option<Severity>,
+    pub issue_types: Option<Vec<VMIssueType>>,
+    /// Only VMs whose name contains this substring (e.g. `"prod-"`). Plain
+    /// substring match, not a glob/regex, to avoid a new dependency for what
+    /// configs use as a prefix/suffix match in practice.
+    pub vm_name_contains: Option<String>,
+    /// Only notify about a VM the run it first develops issues, not every
+    /// run it continues to have them. `previously_had_issues` (the last
+    /// run's problem VMs, as tracked by [`crate::planner::RunState`]) is
+    /// what makes this possible.
+    pub only_on_transition: bool,
+}
+
+impl NotifierFilter {
+    fn matches(&self, vm: &VMResourceStatus, previously_had_issues: &BTreeSet<String>) -> bool {
+        if !vm.has_issues() {
+            return false;
+        }
+        if let Some(min_severity) = self.min_severity {
+            if vm.worst_severity().is_none_or(|s| s < min_severity) {
+                return false;
+            }
+        }
+        if let Some(issue_types) = &self.issue_types {
+            if !vm.issues.iter().any(|i| issue_types.contains(&i.issue_type)) {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.vm_name_contains {
+            if !vm.name.contains(pattern.as_str()) {
+                return false;
+            }
+        }
+        if self.only_on_transition && previously_had_issues.contains(&vm.name) {
+            return false;
+        }
+        true
+    }
+
+    /// The subset of `statuses` this filter lets through.
+    pub fn apply<'a>(
+        &self,
+        statuses: &'a [VMResourceStatus],
+        previously_had_issues: &BTreeSet<String>,
+    ) -> Vec<&'a VMResourceStatus> {
+        statuses.iter().filter(|vm| self.matches(vm, previously_had_issues)).collect()
+    }
+}
+
+/// One backend's entry in `--notifier-config`'s JSON file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NotifierEntry {
+    pub name: String,
+    /// Backend kind (`"slack"`, `"teams"`, `"email"`, `"webhook"`,
+    /// `"pagerduty"`). Delivery is simulated today (see module docs); `kind`
+    /// only affects the logged line's label until a real backend lands.
+    pub kind: String,
+    #[serde(default)]
+    pub filter: NotifierFilter,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotifierRegistryConfig {
+    pub notifiers: Vec<NotifierEntry>,
+}
+
+impl NotifierRegistryConfig {
+    pub fn load(path: &str, strict_json: bool) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading notifier config {path}"))?;
+        crate::strict_json::parse(&raw, &format!("notifier config {path}"), strict_json, &["notifiers"])
+    }
+}
+
+/// Simulates delivery to a configured backend by writing a line to stderr.
+/// Stands in for the real Slack/Teams/email/webhook/PagerDuty clients (see
+/// module docs) so the registry and its filtering/reporting are exercised
+/// end-to-end ahead of those landing.
+struct LogNotifier {
+    name: String,
+    kind: String,
+}
+
+impl Notifier for LogNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn notify(&self, summary: &RunSummary, issues: &[VMResourceStatus]) -> Result<NotifyOutcome> {
+        eprintln!(
+            "notify[{}/{}]: {} of {} VMs have issues, sending {} to this backend",
+            self.name,
+            self.kind,
+            summary.vms_with_issues,
+            summary.total_vms,
+            issues.len()
+        );
+        if let Some(days) = summary.password_expiry_warning_days {
+            eprintln!(
+                "notify[{}/{}]: WARNING - authentication account password expires in {days} day(s)",
+                self.name, self.kind
+            );
+        }
+        Ok(NotifyOutcome {
+            notifier: self.name.clone(),
+            vms_notified: issues.len(),
+        })
+    }
+}
+
+struct RegisteredNotifier {
+    notifier: Box<dyn Notifier>,
+    filter: NotifierFilter,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotifyFailure {
+    pub notifier: String,
+    pub error: String,
+}
+
+/// Outcome of one pass over every configured notifier: `failures` is never
+/// allowed to short-circuit `outcomes` - every notifier runs regardless of
+/// whether an earlier one failed.
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct NotifyRunResult {
+    pub outcomes: Vec<NotifyOutcome>,
+    pub failures: Vec<NotifyFailure>,
+}
+
+impl NotifyRunResult {
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.is_empty() && self.failures.is_empty()
+    }
+
+    /// Renders the text report's "NOTIFICATIONS" section.
+    pub fn render_section(&self) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let mut out = String::from("NOTIFICATIONS:\n");
+        for outcome in &self.outcomes {
+            out.push_str(&format!("  {}: notified {} VM(s)\n", outcome.notifier, outcome.vms_notified));
+        }
+        for failure in &self.failures {
+            out.push_str(&format!("  {}: FAILED - {}\n", failure.notifier, failure.error));
+        }
+        out
+    }
+}
+
+/// A single-VM stand-in used by `--test-notifiers` so every filter still has
+/// something to match against without a real run's inventory.
+fn synthetic_test_vm() -> VMResourceStatus {
+    VMResourceStatus {
+        name: "test-notifier-vm".to_string(),
+        host: "test-notifier-host".to_string(),
+        cluster: "test-notifier-cluster".to_string(),
+        inventory_path: "/unknown".to_string(),
+        power_state: crate::vm::PowerState::PoweredOn,
+        cpu_usage_pct: 0.0,
+        memory_usage_pct: 0.0,
+        raw_metrics: std::collections::HashMap::new(),
+        metrics_source: crate::vm::MetricsSourceStatus::Available,
+        cpu_count: 1,
+        cores_per_socket: 1,
+        memory_gb: 16.0,
+        hardware_version: "vmx-19".to_string(),
+        cpu_hot_add_enabled: true,
+        memory_hot_add_enabled: true,
+        guest_visible_memory_mb: None,
+        guest_visible_cpu_count: None,
+        disk_allocated_gb: 100.0,
+        disk_used_gb: Some(50.0),
+        usage_basis: crate::vm::UsageBasis::Configured,
+        tools_running: false,
+        clock_skew_secs: None,
+        guest_ip: None,
+        reachable: None,
+        running_processes: Vec::new(),
+        attributes: std::collections::HashMap::new(),
+        notes: None,
+        migration_count_24h: 0,
+        last_migration: None,
+        uptime_secs: 30.0 * 86400.0,
+        created_recently: false,
+        power_on_count: 0,
+        last_power_on_secs_ago: None,
+        suspended_duration_secs: None,
+        health_score: 100.0,
+        change_version: 0,
+        issues: vec![crate::vm::DetectedIssue::new(
+            VMIssueType::Unresponsive,
+            "synthetic test message from --test-notifiers",
+        )],
+    }
+}
+
+/// Drives every configured notifier in turn, applying its own filter first.
+pub struct NotifierRegistry {
+    entries: Vec<RegisteredNotifier>,
+}
+
+impl NotifierRegistry {
+    pub fn from_config(config: NotifierRegistryConfig) -> Self {
+        let entries = config
+            .notifiers
+            .into_iter()
+            .map(|entry| RegisteredNotifier {
+                notifier: Box::new(LogNotifier {
+                    name: entry.name,
+                    kind: entry.kind,
+                }),
+                filter: entry.filter,
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Runs every notifier against the VMs its filter lets through. Fail-soft:
+    /// a notifier that returns `Err` is recorded in the result's `failures`
+    /// and never stops the rest from running.
+    pub fn notify_all(
+        &self,
+        summary: &RunSummary,
+        statuses: &[VMResourceStatus],
+        previously_had_issues: &BTreeSet<String>,
+    ) -> NotifyRunResult {
+        let mut result = NotifyRunResult::default();
+        for entry in &self.entries {
+            let matched = entry.filter.apply(statuses, previously_had_issues);
+            let matched: Vec<VMResourceStatus> = matched.into_iter().cloned().collect();
+            match entry.notifier.notify(summary, &matched) {
+                Ok(outcome) => result.outcomes.push(outcome),
+                Err(err) => result.failures.push(NotifyFailure {
+                    notifier: entry.notifier.name().to_string(),
+                    error: err.to_string(),
+                }),
+            }
+        }
+        result
+    }
+
+    /// `--test-notifiers`: sends one synthetic message through every
+    /// configured backend, bypassing filters, so a deploy can confirm every
+    /// backend is reachable before relying on it during a real run.
+    pub fn test_all(&self) -> NotifyRunResult {
+        let summary = RunSummary {
+            total_vms: 1,
+            vms_with_issues: 1,
+            powered_off: 0,
+            vcenter_version: None,
+            run_id: None,
+            password_expiry_warning_days: None,
+            site: None,
+            metrics_degraded: false,
+        };
+        let test_vm = synthetic_test_vm();
+        let mut result = NotifyRunResult::default();
+        for entry in &self.entries {
+            match entry.notifier.notify(&summary, std::slice::from_ref(&test_vm)) {
+                Ok(outcome) => result.outcomes.push(outcome),
+                Err(err) => result.failures.push(NotifyFailure {
+                    notifier: entry.notifier.name().to_string(),
+                    error: err.to_string(),
+                }),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState};
+
+    fn vm(name: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn filter_requires_issues_and_respects_min_severity() {
+        let filter = NotifierFilter {
+            min_severity: Some(Severity::Critical),
+            ..Default::default()
+        };
+        let healthy = vm("vm-healthy", vec![]);
+        let warning = vm("vm-warning", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]);
+        let critical = vm("vm-critical", vec![DetectedIssue::new(VMIssueType::Unresponsive, "x")]);
+        let statuses = vec![healthy, warning, critical];
+
+        let matched = filter.apply(&statuses, &BTreeSet::new());
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "vm-critical");
+    }
+
+    #[test]
+    fn filter_issue_types_and_name_pattern() {
+        let by_type = NotifierFilter {
+            issue_types: Some(vec![VMIssueType::ClockSkew]),
+            ..Default::default()
+        };
+        let statuses = vec![
+            vm("vm-1", vec![DetectedIssue::new(VMIssueType::ClockSkew, "x")]),
+            vm("vm-2", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+        ];
+        let matched = by_type.apply(&statuses, &BTreeSet::new());
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "vm-1");
+
+        let by_name = NotifierFilter {
+            vm_name_contains: Some("prod-".to_string()),
+            ..Default::default()
+        };
+        let statuses = vec![
+            vm("prod-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("dev-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+        ];
+        let matched = by_name.apply(&statuses, &BTreeSet::new());
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "prod-1");
+    }
+
+    #[test]
+    fn only_on_transition_skips_vms_already_flagged_last_run() {
+        let filter = NotifierFilter {
+            only_on_transition: true,
+            ..Default::default()
+        };
+        let statuses = vec![
+            vm("vm-new", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("vm-ongoing", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+        ];
+        let previously_had_issues = BTreeSet::from(["vm-ongoing".to_string()]);
+
+        let matched = filter.apply(&statuses, &previously_had_issues);
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "vm-new");
+    }
+
+    struct AlwaysFails;
+    impl Notifier for AlwaysFails {
+        fn name(&self) -> &str {
+            "always-fails"
+        }
+
+        fn notify(&self, _summary: &RunSummary, _issues: &[VMResourceStatus]) -> Result<NotifyOutcome> {
+            anyhow::bail!("simulated delivery failure")
+        }
+    }
+
+    #[test]
+    fn a_failing_notifier_is_recorded_and_does_not_block_the_others() {
+        let registry = NotifierRegistry {
+            entries: vec![
+                RegisteredNotifier {
+                    notifier: Box::new(AlwaysFails),
+                    filter: NotifierFilter::default(),
+                },
+                RegisteredNotifier {
+                    notifier: Box::new(LogNotifier {
+                        name: "ok-notifier".to_string(),
+                        kind: "webhook".to_string(),
+                    }),
+                    filter: NotifierFilter::default(),
+                },
+            ],
+        };
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let summary = RunSummary {
+            total_vms: 1,
+            vms_with_issues: 1,
+            powered_off: 1,
+            vcenter_version: None,
+            run_id: None,
+            password_expiry_warning_days: None,
+            site: None,
+            metrics_degraded: false,
+        };
+
+        let result = registry.notify_all(&summary, &statuses, &BTreeSet::new());
+        assert_eq!(result.failures.len(), 1);
+        assert_eq!(result.failures[0].notifier, "always-fails");
+        assert_eq!(result.outcomes.len(), 1);
+        assert_eq!(result.outcomes[0].notifier, "ok-notifier");
+    }
+}
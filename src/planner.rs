@@ -0,0 +1,225 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::vm::VMResourceStatus;
+
+/// Share of `--time-budget` allotted to each phase. Inventory and analysis
+/// dominate a real run; reporting and notification are comparatively cheap.
+const INVENTORY_FRACTION: f64 = 0.35;
+const ANALYSIS_FRACTION: f64 = 0.45;
+const REPORTING_FRACTION: f64 = 0.15;
+const NOTIFICATION_FRACTION: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhaseBudgets {
+    pub inventory_secs: f64,
+    pub analysis_secs: f64,
+    pub reporting_secs: f64,
+    pub notification_secs: f64,
+}
+
+/// Splits `--time-budget` across the inventory/analysis/reporting/notification
+/// phases of a run, so a slow vCenter can't let one phase eat the whole
+/// monitoring window and overrun into the next schedule slot.
+pub fn split_budget(total_secs: u64) -> PhaseBudgets {
+    let total = total_secs as f64;
+    PhaseBudgets {
+        inventory_secs: total * INVENTORY_FRACTION,
+        analysis_secs: total * ANALYSIS_FRACTION,
+        reporting_secs: total * REPORTING_FRACTION,
+        notification_secs: total * NOTIFICATION_FRACTION,
+    }
+}
+
+/// Which VMs the analysis phase has time to cover this run. `deferred` must
+/// always be surfaced (report text + JSON metadata) rather than dropped
+/// silently, since a deferred VM's issues simply won't be seen this cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnalysisPlan {
+    pub to_process: Vec<String>,
+    pub deferred: Vec<String>,
+    pub next_rotation_offset: usize,
+}
+
+/// Picks which VMs (by name, in `all_vm_names`'s order) the analysis phase
+/// can cover within `analysis_budget_secs` at `per_vm_latency_secs` each.
+/// VMs that had issues on the previous run are prioritized first; remaining
+/// capacity is filled by a slice of the rest starting at `rotation_offset`
+/// and wrapping around, so repeated runs eventually cover every VM instead
+/// of starving whichever ones sort last.
+pub fn plan_analysis_batch(
+    all_vm_names: &[String],
+    previous_issue_vms: &BTreeSet<String>,
+    rotation_offset: usize,
+    per_vm_latency_secs: f64,
+    analysis_budget_secs: f64,
+) -> AnalysisPlan {
+    let n = all_vm_names.len();
+    if n == 0 || per_vm_latency_secs <= 0.0 {
+        return AnalysisPlan {
+            to_process: all_vm_names.to_vec(),
+            deferred: Vec::new(),
+            next_rotation_offset: rotation_offset,
+        };
+    }
+
+    let capacity = ((analysis_budget_secs / per_vm_latency_secs).floor() as usize).min(n);
+    let mut to_process = Vec::with_capacity(capacity);
+    let mut selected: BTreeSet<&str> = BTreeSet::new();
+
+    for name in all_vm_names {
+        if to_process.len() >= capacity {
+            break;
+        }
+        if previous_issue_vms.contains(name) {
+            to_process.push(name.clone());
+            selected.insert(name.as_str());
+        }
+    }
+
+    let offset = rotation_offset % n;
+    for i in 0..n {
+        if to_process.len() >= capacity {
+            break;
+        }
+        let name = &all_vm_names[(offset + i) % n];
+        if selected.insert(name.as_str()) {
+            to_process.push(name.clone());
+        }
+    }
+
+    let deferred = all_vm_names
+        .iter()
+        .filter(|name| !selected.contains(name.as_str()))
+        .cloned()
+        .collect();
+
+    AnalysisPlan {
+        to_process,
+        deferred,
+        next_rotation_offset: (offset + capacity) % n,
+    }
+}
+
+/// Rotation position and previous-run issue set, persisted between runs so
+/// `--time-budget` prioritization and coverage rotation survive a restart.
+/// `last_run_id` is the `--run-id` (or generated UUID) of whichever run last
+/// wrote this file, so a state file found on disk can be joined back up with
+/// the report/notifications it came from; `#[serde(default)]` keeps state
+/// files written before this field existed loading cleanly.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RunState {
+    pub previous_issue_vms: BTreeSet<String>,
+    pub rotation_offset: usize,
+    #[serde(default)]
+    pub last_run_id: Option<String>,
+    /// Last-seen `change_version` per VM, from `--since-last-run`.
+    /// `#[serde(default)]` keeps state files written before this field
+    /// existed loading cleanly, the same way `last_run_id` does.
+    #[serde(default)]
+    pub vm_change_versions: BTreeMap<String, u64>,
+    /// Last fully-analyzed status per VM, carried forward by
+    /// `--since-last-run` for a VM whose `vm_change_versions` entry is
+    /// still current instead of re-analyzing it this run.
+    #[serde(default)]
+    pub vm_last_status: BTreeMap<String, VMResourceStatus>,
+    /// Hash of the last report `--output-on-change` actually wrote, so a
+    /// later run can tell whether this run's report is a repeat without
+    /// keeping the whole prior report around. `#[serde(default)]` keeps
+    /// state files written before this field existed loading cleanly, the
+    /// same way `last_run_id` does.
+    #[serde(default)]
+    pub last_output_hash: Option<u64>,
+    /// Per-fingerprint first-seen timestamp, carried forward by
+    /// `crate::fingerprint::annotate` so a recurring issue's age survives
+    /// process restarts. `#[serde(default)]` keeps state files written
+    /// before this field existed loading cleanly, the same way
+    /// `last_run_id` does.
+    #[serde(default)]
+    pub issue_first_seen: BTreeMap<String, chrono::DateTime<chrono::Utc>>,
+}
+
+impl RunState {
+    /// Missing or unparsable state (e.g. first run) is treated as empty
+    /// rather than an error, since there's nothing meaningful to recover.
+    pub fn load(path: &str) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|raw| serde_json::from_str(&raw).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        let raw = serde_json::to_string_pretty(self).context("serializing run state")?;
+        fs::write(path, raw).with_context(|| format!("writing state file {path}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("vm-{i:04}")).collect()
+    }
+
+    #[test]
+    fn splits_budget_across_phases() {
+        let budgets = split_budget(600);
+        assert_eq!(budgets.inventory_secs, 210.0);
+        assert_eq!(budgets.analysis_secs, 270.0);
+        assert_eq!(budgets.reporting_secs, 90.0);
+        assert_eq!(budgets.notification_secs, 30.0);
+    }
+
+    #[test]
+    fn prioritizes_previous_issue_vms_then_rotates() {
+        let all = names(10);
+        let previous: BTreeSet<String> = ["vm-0007".to_string(), "vm-0003".to_string()].into_iter().collect();
+        // Budget/latency gives capacity for exactly 4 VMs.
+        let plan = plan_analysis_batch(&all, &previous, 0, 1.0, 4.0);
+
+        assert_eq!(plan.to_process.len(), 4);
+        assert!(plan.to_process.contains(&"vm-0003".to_string()));
+        assert!(plan.to_process.contains(&"vm-0007".to_string()));
+        // Remaining two slots filled by rotation starting at offset 0,
+        // skipping the already-selected priority VMs.
+        assert!(plan.to_process.contains(&"vm-0000".to_string()));
+        assert!(plan.to_process.contains(&"vm-0001".to_string()));
+        assert_eq!(plan.deferred.len(), 6);
+        assert_eq!(plan.next_rotation_offset, 4);
+    }
+
+    #[test]
+    fn rotation_wraps_around_on_next_run() {
+        let all = names(5);
+        let previous = BTreeSet::new();
+        let first = plan_analysis_batch(&all, &previous, 3, 1.0, 2.0);
+        assert_eq!(first.to_process, vec!["vm-0003".to_string(), "vm-0004".to_string()]);
+        assert_eq!(first.next_rotation_offset, 0);
+
+        let second = plan_analysis_batch(&all, &previous, first.next_rotation_offset, 1.0, 2.0);
+        assert_eq!(second.to_process, vec!["vm-0000".to_string(), "vm-0001".to_string()]);
+    }
+
+    #[test]
+    fn zero_budget_defers_everything() {
+        let all = names(3);
+        let plan = plan_analysis_batch(&all, &BTreeSet::new(), 0, 1.0, 0.0);
+        assert!(plan.to_process.is_empty());
+        assert_eq!(plan.deferred.len(), 3);
+    }
+
+    #[test]
+    fn unbounded_latency_of_zero_processes_everything() {
+        // A per-VM latency we haven't measured yet (first run) shouldn't
+        // defer anything; there's no observed rate to plan against.
+        let all = names(3);
+        let plan = plan_analysis_batch(&all, &BTreeSet::new(), 0, 0.0, 10.0);
+        assert_eq!(plan.to_process, all);
+        assert!(plan.deferred.is_empty());
+    }
+}
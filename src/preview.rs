@@ -0,0 +1,262 @@
+//! `--preview-thresholds`: a read-only "what would newly alert" comparison
+//! against proposed CPU/memory thresholds, computed straight from the
+//! already-collected [`VMResourceStatus`] list - no second `detect_issues`
+//! pass, no extra vCenter call, and no effect on `issues`, notifications, or
+//! `--fail-on-issues`/`--fail-below-score`. Only `cpu` and `memory` are
+//! supported; a `disk` key is rejected with a clear message rather than
+//! silently accepted, since this tree has no disk-usage check or
+//! `VMIssueType::DiskHigh` to preview against - see the module doc on
+//! [`crate::recommend`].
+
+use serde::Serialize;
+
+use crate::vcenter::{CPU_HIGH_THRESHOLD_PCT, MEMORY_HIGH_THRESHOLD_PCT};
+use crate::vm::VMResourceStatus;
+
+/// Proposed replacement for [`CPU_HIGH_THRESHOLD_PCT`]/[`MEMORY_HIGH_THRESHOLD_PCT`],
+/// parsed from `--preview-thresholds`. A metric the flag doesn't mention
+/// keeps the run's actual threshold, so `--preview-thresholds cpu=70`
+/// previews CPU alone without touching memory.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProposedThresholds {
+    pub cpu_pct: f64,
+    pub memory_pct: f64,
+}
+
+impl Default for ProposedThresholds {
+    fn default() -> Self {
+        Self { cpu_pct: CPU_HIGH_THRESHOLD_PCT, memory_pct: MEMORY_HIGH_THRESHOLD_PCT }
+    }
+}
+
+/// Parses `--preview-thresholds`' comma-delimited `key=value` tokens (e.g.
+/// `cpu=70,memory=80`). `disk` is rejected by name rather than silently
+/// dropped - see the module doc. `Err` names the bad token.
+pub fn parse_proposed_thresholds(tokens: &[String]) -> Result<ProposedThresholds, String> {
+    let mut proposed = ProposedThresholds::default();
+    for token in tokens {
+        let (key, value) = token.split_once('=').ok_or_else(|| format!("'{token}' is not in key=value form"))?;
+        match key {
+            "cpu" => proposed.cpu_pct = value.parse().map_err(|_| format!("'{token}': '{value}' is not a number"))?,
+            "memory" => proposed.memory_pct = value.parse().map_err(|_| format!("'{token}': '{value}' is not a number"))?,
+            "disk" => {
+                return Err(
+                    "disk usage isn't tracked in this tree yet (no VMIssueType::DiskHigh or collected data) - only cpu and memory can be previewed".to_string(),
+                )
+            }
+            other => return Err(format!("unknown threshold '{other}' (expected cpu or memory)")),
+        }
+    }
+    Ok(proposed)
+}
+
+/// One VM whose CPU and/or memory alert status would change under
+/// `proposed` - only metrics that actually flip are listed.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreviewDelta {
+    pub vm_name: String,
+    pub newly_alerting: Vec<&'static str>,
+    pub newly_clearing: Vec<&'static str>,
+}
+
+/// `--preview-thresholds`' comparison section: how many VMs would newly
+/// alert or clear under `proposed`, and the full per-VM delta list.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PreviewReport {
+    pub proposed_cpu_pct: f64,
+    pub proposed_memory_pct: f64,
+    pub newly_alerting_count: usize,
+    pub newly_clearing_count: usize,
+    pub deltas: Vec<PreviewDelta>,
+}
+
+/// Whether `value` crosses `threshold` the same way [`crate::vcenter::detect_issues`]
+/// checks `HighCpuUsage`/`HighMemoryUsage`: strictly greater than.
+fn alerts(value: f64, threshold: f64) -> bool {
+    value > threshold
+}
+
+/// Computes [`PreviewReport`] from `statuses` as already collected this run,
+/// comparing each VM's `cpu_usage_pct`/`memory_usage_pct` against the active
+/// thresholds ([`CPU_HIGH_THRESHOLD_PCT`]/[`MEMORY_HIGH_THRESHOLD_PCT`]) and
+/// `proposed`. Pure and read-only: it never touches `statuses`, never calls
+/// vCenter, and has no bearing on the real run's issues, notifications, or
+/// exit code.
+pub fn preview_threshold_changes(statuses: &[VMResourceStatus], proposed: &ProposedThresholds) -> PreviewReport {
+    let mut deltas = Vec::new();
+    let mut newly_alerting_count = 0;
+    let mut newly_clearing_count = 0;
+    for vm in statuses {
+        let mut newly_alerting = Vec::new();
+        let mut newly_clearing = Vec::new();
+        for (name, value, active, proposed) in [
+            ("cpu", vm.cpu_usage_pct, CPU_HIGH_THRESHOLD_PCT, proposed.cpu_pct),
+            ("memory", vm.memory_usage_pct, MEMORY_HIGH_THRESHOLD_PCT, proposed.memory_pct),
+        ] {
+            match (alerts(value, active), alerts(value, proposed)) {
+                (false, true) => newly_alerting.push(name),
+                (true, false) => newly_clearing.push(name),
+                _ => {}
+            }
+        }
+        if !newly_alerting.is_empty() || !newly_clearing.is_empty() {
+            newly_alerting_count += newly_alerting.len();
+            newly_clearing_count += newly_clearing.len();
+            deltas.push(PreviewDelta { vm_name: vm.name.clone(), newly_alerting, newly_clearing });
+        }
+    }
+    PreviewReport {
+        proposed_cpu_pct: proposed.cpu_pct,
+        proposed_memory_pct: proposed.memory_pct,
+        newly_alerting_count,
+        newly_clearing_count,
+        deltas,
+    }
+}
+
+impl PreviewReport {
+    /// Renders the text report's trailing PREVIEW section.
+    pub fn render_section(&self) -> String {
+        let mut out = format!(
+            "PREVIEW (cpu={:.0}%, memory={:.0}%): {} newly alerting, {} newly clearing\n",
+            self.proposed_cpu_pct, self.proposed_memory_pct, self.newly_alerting_count, self.newly_clearing_count
+        );
+        for delta in &self.deltas {
+            out.push_str(&format!(
+                "  - {}: alerting [{}], clearing [{}]\n",
+                delta.vm_name,
+                delta.newly_alerting.join(", "),
+                delta.newly_clearing.join(", ")
+            ));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::PowerState;
+
+    fn vm(name: &str, cpu_usage_pct: f64, memory_usage_pct: f64) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct,
+            memory_usage_pct,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_cpu_and_memory_tokens() {
+        let proposed = parse_proposed_thresholds(&["cpu=70".to_string(), "memory=80".to_string()]).unwrap();
+        assert_eq!(proposed, ProposedThresholds { cpu_pct: 70.0, memory_pct: 80.0 });
+    }
+
+    #[test]
+    fn unset_metric_keeps_the_active_threshold() {
+        let proposed = parse_proposed_thresholds(&["cpu=70".to_string()]).unwrap();
+        assert_eq!(proposed.memory_pct, MEMORY_HIGH_THRESHOLD_PCT);
+    }
+
+    #[test]
+    fn disk_is_rejected_by_name() {
+        let err = parse_proposed_thresholds(&["disk=85".to_string()]).unwrap_err();
+        assert!(err.contains("disk usage isn't tracked"), "{err}");
+    }
+
+    #[test]
+    fn unknown_key_is_rejected() {
+        let err = parse_proposed_thresholds(&["bogus=1".to_string()]).unwrap_err();
+        assert!(err.contains("unknown threshold 'bogus'"), "{err}");
+    }
+
+    #[test]
+    fn malformed_token_is_rejected() {
+        assert!(parse_proposed_thresholds(&["cpu".to_string()]).is_err());
+        assert!(parse_proposed_thresholds(&["cpu=not-a-number".to_string()]).is_err());
+    }
+
+    #[test]
+    fn straddling_inventory_produces_expected_deltas() {
+        // 95/95: alerts under both threshold sets - no delta.
+        // 75/75: below the active 90% but above the tightened 70% -
+        //        newly alerts under both metrics.
+        // 50/50: below both threshold sets - no delta.
+        let statuses = vec![vm("vm-already-alerting", 95.0, 95.0), vm("vm-newly-alerting", 75.0, 75.0), vm("vm-never-alerts", 50.0, 50.0)];
+        let proposed = ProposedThresholds { cpu_pct: 70.0, memory_pct: 70.0 };
+
+        let report = preview_threshold_changes(&statuses, &proposed);
+
+        assert_eq!(report.newly_alerting_count, 2);
+        assert_eq!(report.newly_clearing_count, 0);
+        assert_eq!(report.deltas.len(), 1);
+        assert_eq!(report.deltas[0].vm_name, "vm-newly-alerting");
+        assert_eq!(report.deltas[0].newly_alerting, vec!["cpu", "memory"]);
+    }
+
+    #[test]
+    fn raising_a_threshold_newly_clears_vms_that_currently_alert() {
+        // 92% is above the active 90% threshold (so it alerts today) but
+        // below a loosened 95% proposal - it would clear.
+        let statuses = vec![vm("vm-0001", 92.0, 10.0)];
+        let proposed = ProposedThresholds { cpu_pct: 95.0, memory_pct: MEMORY_HIGH_THRESHOLD_PCT };
+
+        let report = preview_threshold_changes(&statuses, &proposed);
+
+        assert_eq!(report.newly_clearing_count, 1);
+        assert_eq!(report.deltas[0].newly_clearing, vec!["cpu"]);
+    }
+
+    #[test]
+    fn unaffected_vms_produce_no_delta_entry() {
+        let statuses = vec![vm("vm-0001", 10.0, 10.0)];
+        let proposed = ProposedThresholds { cpu_pct: 70.0, memory_pct: 80.0 };
+
+        let report = preview_threshold_changes(&statuses, &proposed);
+
+        assert!(report.deltas.is_empty());
+        assert_eq!(report.newly_alerting_count, 0);
+    }
+
+    #[test]
+    fn render_section_names_the_proposed_thresholds() {
+        let report = preview_threshold_changes(&[vm("vm-0001", 75.0, 10.0)], &ProposedThresholds { cpu_pct: 70.0, memory_pct: 80.0 });
+        let rendered = report.render_section();
+        assert!(rendered.contains("cpu=70%"));
+        assert!(rendered.contains("vm-0001"));
+    }
+}
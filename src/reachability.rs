@@ -0,0 +1,21 @@
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// Checks whether a guest is reachable over the network. ICMP needs raw
+/// sockets (root), so the real implementation settles for "can we open a TCP
+/// connection" — a timeout means unreachable, a refused connection still
+/// counts as reachable since *something* answered.
+pub trait ReachabilityProbe {
+    fn is_reachable(&self, host: &str, port: u16, timeout: Duration) -> bool;
+}
+
+pub struct TcpProbe;
+
+impl ReachabilityProbe for TcpProbe {
+    fn is_reachable(&self, host: &str, port: u16, timeout: Duration) -> bool {
+        let Ok(mut addrs) = (host, port).to_socket_addrs() else {
+            return false;
+        };
+        addrs.any(|addr| TcpStream::connect_timeout(&addr, timeout).is_ok())
+    }
+}
@@ -0,0 +1,258 @@
+//! Turns a [`DetectedIssue`] plus the VM/host data a run already collected
+//! into zero or more actionable [`Recommendation`]s, attached to the issue
+//! for the text/JSON report. Disabled wholesale with `--no-recommendations`.
+//!
+//! Each rule is a pure function over data this tree actually has. A rule
+//! never guesses at a measurement it wasn't given - missing inputs mean no
+//! recommendation, not a wrong one. That's why `SnapshotOld`/`SnapshotLarge`/
+//! `DiskHigh` have no rule below: this tree has no snapshot or disk-usage
+//! check at all (no `VMIssueType` variant, no collected data), so there is
+//! nothing a rule could honestly compute yet; add one once
+//! `--check-snapshots`/`--check-disk-usage` exist with real data behind them.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::vm::{DetectedIssue, HostMetrics, VMIssueType, VMResourceStatus};
+
+/// How much a recommendation's rule trusts its own inputs. `High` when
+/// everything the rule wanted was a direct measurement; `Medium`/`Low`
+/// when part of the call was made from a proxy (e.g. host-level usage as a
+/// stand-in for actual free capacity, which this tree doesn't track).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Confidence {
+    Low,
+    Medium,
+    High,
+}
+
+/// One actionable suggestion attached to a [`DetectedIssue`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Recommendation {
+    pub action: String,
+    pub rationale: String,
+    pub confidence: Confidence,
+}
+
+impl Recommendation {
+    fn new(action: impl Into<String>, rationale: impl Into<String>, confidence: Confidence) -> Self {
+        Self { action: action.into(), rationale: rationale.into(), confidence }
+    }
+}
+
+/// `HighCpuUsage`: recommends adding vCPUs when the host itself has
+/// headroom to give them, or migrating off the host when it doesn't -
+/// requires the issue's measured value/threshold and the VM's host metrics.
+pub fn recommend_high_cpu_usage(issue: &DetectedIssue, vm: &VMResourceStatus, host: Option<&HostMetrics>) -> Option<Recommendation> {
+    let (measured, threshold) = (issue.measured_value?, issue.threshold?);
+    let host = host?;
+    let host_headroom_pct = 100.0 - host.cpu_usage_pct;
+    if host_headroom_pct >= 20.0 {
+        Some(Recommendation::new(
+            format!("increase {}'s vCPU count from {}", vm.name, vm.cpu_count),
+            format!(
+                "guest CPU usage is {measured:.0}% (threshold {threshold:.0}%); host {} has {host_headroom_pct:.0}% CPU headroom to give it more",
+                vm.host
+            ),
+            Confidence::Medium,
+        ))
+    } else {
+        Some(Recommendation::new(
+            format!("migrate {} to a less-loaded host", vm.name),
+            format!(
+                "guest CPU usage is {measured:.0}% (threshold {threshold:.0}%), but host {} is itself at {:.0}% CPU - adding vCPUs here would not help",
+                vm.host, host.cpu_usage_pct
+            ),
+            Confidence::Medium,
+        ))
+    }
+}
+
+/// `HighMemoryUsage`: same shape as [`recommend_high_cpu_usage`], for
+/// memory instead of CPU.
+pub fn recommend_high_memory_usage(issue: &DetectedIssue, vm: &VMResourceStatus, host: Option<&HostMetrics>) -> Option<Recommendation> {
+    let (measured, threshold) = (issue.measured_value?, issue.threshold?);
+    let host = host?;
+    let host_headroom_pct = 100.0 - host.memory_usage_pct;
+    if host_headroom_pct >= 20.0 {
+        Some(Recommendation::new(
+            format!("increase {}'s memory allocation", vm.name),
+            format!(
+                "guest memory usage is {measured:.0}% (threshold {threshold:.0}%); host {} has {host_headroom_pct:.0}% memory headroom to give it more",
+                vm.host
+            ),
+            Confidence::Medium,
+        ))
+    } else {
+        Some(Recommendation::new(
+            format!("migrate {} to a less-loaded host", vm.name),
+            format!(
+                "guest memory usage is {measured:.0}% (threshold {threshold:.0}%), but host {} is itself at {:.0}% memory - adding memory here would not help",
+                vm.host, host.memory_usage_pct
+            ),
+            Confidence::Medium,
+        ))
+    }
+}
+
+/// `ToolsNotRunning`: a direct fix, no host data needed.
+pub fn recommend_tools_not_running(vm: &VMResourceStatus) -> Option<Recommendation> {
+    Some(Recommendation::new(
+        format!("reinstall or restart VMware Tools on {}", vm.name),
+        "VMware Tools is not running, so the guest can't report clock skew, reachability, or its process list".to_string(),
+        Confidence::High,
+    ))
+}
+
+/// Dispatches one issue to its rule, if it has one. An issue type with no
+/// rule below (including `SnapshotOld`/`SnapshotLarge`/`DiskHigh`, which
+/// don't exist as `VMIssueType` variants in this tree - see the module doc)
+/// simply produces nothing.
+pub fn recommendations_for(issue: &DetectedIssue, vm: &VMResourceStatus, host: Option<&HostMetrics>) -> Vec<Recommendation> {
+    let recommendation = match issue.issue_type {
+        VMIssueType::HighCpuUsage => recommend_high_cpu_usage(issue, vm, host),
+        VMIssueType::HighMemoryUsage => recommend_high_memory_usage(issue, vm, host),
+        VMIssueType::ToolsNotRunning => recommend_tools_not_running(vm),
+        _ => None,
+    };
+    recommendation.into_iter().collect()
+}
+
+/// Fills in every VM's issues' `recommendations` in place. Must run after
+/// every issue-mutating step for the run has finished, same ordering
+/// requirement as [`crate::scoring::annotate_health_scores`]. A no-op when
+/// `enabled` is `false` (`--no-recommendations`).
+pub fn annotate_recommendations(statuses: &mut [VMResourceStatus], host_metrics: &BTreeMap<String, HostMetrics>, enabled: bool) {
+    if !enabled {
+        return;
+    }
+    for vm in statuses {
+        let host = host_metrics.get(&vm.host).cloned();
+        let recommendations: Vec<Vec<Recommendation>> = vm.issues.iter().map(|issue| recommendations_for(issue, vm, host.as_ref())).collect();
+        for (issue, recs) in vm.issues.iter_mut().zip(recommendations) {
+            issue.recommendations = recs;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{HostConnectionState, PowerState};
+
+    fn vm() -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 95.0,
+            memory_usage_pct: 95.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    fn host(usage_pct: f64) -> HostMetrics {
+        HostMetrics {
+            cpu_usage_pct: usage_pct,
+            memory_usage_pct: usage_pct,
+            physical_cores: 32,
+            connection_state: HostConnectionState::Connected,
+            in_maintenance_mode: false,
+            sensor_status: crate::vm::HostSensorStatus::Green,
+            failing_sensor: None,
+        }
+    }
+
+    #[test]
+    fn high_cpu_usage_recommends_more_vcpus_when_host_has_headroom() {
+        let issue = DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x");
+        let rec = recommend_high_cpu_usage(&issue, &vm(), Some(&host(50.0))).unwrap();
+        assert!(rec.action.contains("increase"));
+    }
+
+    #[test]
+    fn high_cpu_usage_recommends_migration_when_host_is_also_loaded() {
+        let issue = DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x");
+        let rec = recommend_high_cpu_usage(&issue, &vm(), Some(&host(90.0))).unwrap();
+        assert!(rec.action.contains("migrate"));
+    }
+
+    #[test]
+    fn high_cpu_usage_produces_nothing_without_host_metrics() {
+        let issue = DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x");
+        assert!(recommend_high_cpu_usage(&issue, &vm(), None).is_none());
+    }
+
+    #[test]
+    fn high_cpu_usage_produces_nothing_without_a_measured_value() {
+        let issue = DetectedIssue::new(VMIssueType::HighCpuUsage, "x");
+        assert!(recommend_high_cpu_usage(&issue, &vm(), Some(&host(50.0))).is_none());
+    }
+
+    #[test]
+    fn high_memory_usage_recommends_more_memory_when_host_has_headroom() {
+        let issue = DetectedIssue::measured(VMIssueType::HighMemoryUsage, 95.0, 90.0, "x");
+        let rec = recommend_high_memory_usage(&issue, &vm(), Some(&host(50.0))).unwrap();
+        assert!(rec.action.contains("memory"));
+    }
+
+    #[test]
+    fn tools_not_running_always_recommends_reinstalling_tools() {
+        assert!(recommend_tools_not_running(&vm()).is_some());
+    }
+
+    #[test]
+    fn unhandled_issue_types_produce_no_recommendations() {
+        let issue = DetectedIssue::new(VMIssueType::PoweredOff, "x");
+        assert!(recommendations_for(&issue, &vm(), Some(&host(50.0))).is_empty());
+    }
+
+    #[test]
+    fn annotate_recommendations_is_a_no_op_when_disabled() {
+        let mut statuses = vec![vm()];
+        statuses[0].issues.push(DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x"));
+        annotate_recommendations(&mut statuses, &BTreeMap::new(), false);
+        assert!(statuses[0].issues[0].recommendations.is_empty());
+    }
+
+    #[test]
+    fn annotate_recommendations_fills_in_each_issues_recommendations() {
+        let mut statuses = vec![vm()];
+        statuses[0].issues.push(DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x"));
+        let mut host_metrics = BTreeMap::new();
+        host_metrics.insert("esxi-01".to_string(), host(50.0));
+        annotate_recommendations(&mut statuses, &host_metrics, true);
+        assert_eq!(statuses[0].issues[0].recommendations.len(), 1);
+    }
+}
@@ -0,0 +1,404 @@
+//! `--replay`: detection already runs as a pure pass over already-collected
+//! facts plus [`DetectionOptions`] (which doubles as the detection
+//! thresholds) - [`detect_issues`] takes raw numbers/states in, not a live
+//! vCenter connection, and every field it needs is already persisted in a
+//! `--format json` `v2` report via [`VMResourceStatus`]. This module is the
+//! offline half of that split: load a prior report's facts back out
+//! ([`RawVm`]/[`resolve_vm`]) and rerun detection against them with
+//! different options ([`replay_statuses`]), without a fresh vCenter query -
+//! recomputing issues, health scores and exit codes from `--cpu-threshold`
+//! or any other `--check-*` flag as if the run had used it originally.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::strict_parsing::{self, FallbackReport};
+use crate::vcenter::{detect_issues, DetectionOptions};
+use crate::vm::{DetectedIssue, HostMetrics, LastMigration, MetricsSourceStatus, PowerState, VMResourceStatus};
+
+/// Shape of a `--format json` `v2` report, as produced by
+/// [`crate::report::export_json_report`] - only the fields `--replay` needs
+/// to reconstruct each VM's stored metrics. `v1`'s bare type-name `issues`
+/// and smaller `VMResourceStatus` shape don't carry what detection needs, so
+/// replaying a `v1` report fails to parse rather than silently under-detecting.
+#[derive(Debug, Deserialize)]
+struct ReplayInput {
+    vms: Vec<RawVm>,
+    #[serde(default)]
+    host_metrics: BTreeMap<String, HostMetrics>,
+}
+
+/// Mirrors [`VMResourceStatus`], except the measurement fields that a
+/// vCenter schema change has historically renamed or reshaped
+/// (`cpu_usage_pct`, `memory_usage_pct`, `cpu_count`, `cores_per_socket`,
+/// `migration_count_24h`, `uptime_secs`) are kept as raw [`Value`]s rather
+/// than failing the whole parse when one is missing or the wrong type.
+/// [`resolve_vm`] turns these into real fields, via [`strict_parsing`] so
+/// `--strict-parsing` can govern what happens when one is absent.
+#[derive(Debug, Deserialize)]
+struct RawVm {
+    name: String,
+    host: String,
+    cluster: String,
+    #[serde(default = "crate::vm::default_inventory_path")]
+    inventory_path: String,
+    power_state: PowerState,
+    #[serde(default)]
+    cpu_usage_pct: Option<Value>,
+    #[serde(default)]
+    memory_usage_pct: Option<Value>,
+    #[serde(default = "crate::vm::default_metrics_source")]
+    metrics_source: MetricsSourceStatus,
+    #[serde(default)]
+    cpu_count: Option<Value>,
+    #[serde(default)]
+    cores_per_socket: Option<Value>,
+    #[serde(default = "crate::vm::default_memory_gb")]
+    memory_gb: f64,
+    #[serde(default = "crate::vm::default_hardware_version")]
+    hardware_version: String,
+    #[serde(default = "crate::vm::default_hot_add_enabled")]
+    cpu_hot_add_enabled: bool,
+    #[serde(default = "crate::vm::default_hot_add_enabled")]
+    memory_hot_add_enabled: bool,
+    #[serde(default)]
+    guest_visible_memory_mb: Option<f64>,
+    #[serde(default)]
+    guest_visible_cpu_count: Option<u32>,
+    #[serde(default)]
+    disk_allocated_gb: f64,
+    #[serde(default)]
+    disk_used_gb: Option<f64>,
+    #[serde(default = "crate::vm::default_usage_basis")]
+    usage_basis: crate::vm::UsageBasis,
+    tools_running: bool,
+    clock_skew_secs: Option<f64>,
+    guest_ip: Option<String>,
+    reachable: Option<bool>,
+    #[serde(default)]
+    running_processes: Vec<String>,
+    #[serde(default)]
+    attributes: HashMap<String, String>,
+    notes: Option<String>,
+    #[serde(default)]
+    migration_count_24h: Option<Value>,
+    last_migration: Option<LastMigration>,
+    #[serde(default)]
+    uptime_secs: Option<Value>,
+    #[serde(default)]
+    created_recently: bool,
+    #[serde(default)]
+    power_on_count: u32,
+    #[serde(default)]
+    last_power_on_secs_ago: Option<f64>,
+    #[serde(default)]
+    suspended_duration_secs: Option<f64>,
+    #[serde(default = "crate::vm::default_change_version")]
+    change_version: u64,
+    #[serde(default)]
+    issues: Vec<DetectedIssue>,
+    #[serde(default)]
+    raw_metrics: HashMap<String, f64>,
+}
+
+/// Resolves `raw`'s measurement fields against `report`/`strict`, returning
+/// `Err(reason)` in `--strict-parsing` mode instead of a [`VMResourceStatus`]
+/// when one is missing or the wrong type - that VM's analysis fails outright
+/// rather than proceeding on a guessed default.
+fn resolve_vm(raw: RawVm, strict: bool, report: &mut FallbackReport) -> Result<VMResourceStatus, String> {
+    let cpu_usage_pct = strict_parsing::f64_field(raw.cpu_usage_pct.as_ref(), "cpu_usage_pct", 0.0, &raw.name, strict, report)?;
+    let memory_usage_pct = strict_parsing::f64_field(raw.memory_usage_pct.as_ref(), "memory_usage_pct", 0.0, &raw.name, strict, report)?;
+    let cpu_count = strict_parsing::u32_field(raw.cpu_count.as_ref(), "cpu_count", 1, &raw.name, strict, report)?;
+    let cores_per_socket = strict_parsing::u32_field(raw.cores_per_socket.as_ref(), "cores_per_socket", 1, &raw.name, strict, report)?;
+    let migration_count_24h = strict_parsing::u32_field(raw.migration_count_24h.as_ref(), "migration_count_24h", 0, &raw.name, strict, report)?;
+    let uptime_secs = strict_parsing::f64_field(raw.uptime_secs.as_ref(), "uptime_secs", 30.0 * 86400.0, &raw.name, strict, report)?;
+
+    Ok(VMResourceStatus {
+        name: raw.name,
+        host: raw.host,
+        cluster: raw.cluster,
+        inventory_path: raw.inventory_path,
+        power_state: raw.power_state,
+        cpu_usage_pct,
+        memory_usage_pct,
+        metrics_source: raw.metrics_source,
+        cpu_count,
+        cores_per_socket,
+        memory_gb: raw.memory_gb,
+        hardware_version: raw.hardware_version,
+        cpu_hot_add_enabled: raw.cpu_hot_add_enabled,
+        memory_hot_add_enabled: raw.memory_hot_add_enabled,
+        guest_visible_memory_mb: raw.guest_visible_memory_mb,
+        guest_visible_cpu_count: raw.guest_visible_cpu_count,
+        disk_allocated_gb: raw.disk_allocated_gb,
+        disk_used_gb: raw.disk_used_gb,
+        usage_basis: raw.usage_basis,
+        tools_running: raw.tools_running,
+        clock_skew_secs: raw.clock_skew_secs,
+        guest_ip: raw.guest_ip,
+        reachable: raw.reachable,
+        running_processes: raw.running_processes,
+        attributes: raw.attributes,
+        notes: raw.notes,
+        migration_count_24h,
+        last_migration: raw.last_migration,
+        uptime_secs,
+        created_recently: raw.created_recently,
+        power_on_count: raw.power_on_count,
+        last_power_on_secs_ago: raw.last_power_on_secs_ago,
+        suspended_duration_secs: raw.suspended_duration_secs,
+        health_score: 100.0,
+        change_version: raw.change_version,
+        issues: raw.issues,
+        raw_metrics: raw.raw_metrics,
+    })
+}
+
+/// Outcome of a `--replay` run: the recomputed statuses, how many
+/// measurement-field fallbacks were taken (always tracked, regardless of
+/// `--strict-parsing`), and the names of any VMs `--strict-parsing` refused
+/// to analyze, with the reason.
+pub struct ReplayOutcome {
+    pub statuses: Vec<VMResourceStatus>,
+    pub fallbacks: FallbackReport,
+    pub failed_analyses: Vec<String>,
+}
+
+/// Reruns every detector against `vms`' already-stored metrics using
+/// `options`, discarding whatever issues they were originally saved with.
+/// Independent of the filesystem so it can be unit tested on its own.
+pub(crate) fn replay_statuses(vms: Vec<VMResourceStatus>, host_metrics: &BTreeMap<String, HostMetrics>, options: &DetectionOptions) -> Vec<VMResourceStatus> {
+    vms.into_iter()
+        .map(|vm| {
+            let issues = detect_issues(
+                vm.power_state,
+                vm.cpu_usage_pct,
+                vm.memory_usage_pct,
+                vm.metrics_source,
+                vm.cpu_count,
+                vm.cores_per_socket,
+                &vm.hardware_version,
+                vm.tools_running,
+                vm.clock_skew_secs,
+                vm.reachable,
+                &vm.running_processes,
+                host_metrics.get(&vm.host),
+                vm.migration_count_24h,
+                vm.uptime_secs,
+                vm.created_recently,
+                vm.power_on_count,
+                vm.cpu_hot_add_enabled,
+                vm.memory_hot_add_enabled,
+                vm.suspended_duration_secs,
+                vm.disk_allocated_gb,
+                vm.disk_used_gb,
+                options,
+            );
+            VMResourceStatus { issues, ..vm }
+        })
+        .collect()
+}
+
+/// Loads `path` (a prior `--format json` `v2` report) and reruns detection
+/// against its stored metrics with `options`, e.g. to ask "what if the CPU
+/// threshold were 70 instead of 90?" without a fresh vCenter query.
+/// `strict` is `--strict-parsing`: when set, a VM with a missing or
+/// wrongly-typed measurement field is excluded and named in
+/// `failed_analyses` instead of analyzed against a guessed default.
+pub fn replay(path: &str, options: &DetectionOptions, strict: bool) -> Result<ReplayOutcome> {
+    let raw = crate::sink::read_to_string(path).with_context(|| format!("reading replay report {path}"))?;
+    let input: ReplayInput = serde_json::from_str(&raw)
+        .with_context(|| format!("parsing replay report {path} (requires --json-schema-version v2)"))?;
+
+    let mut fallbacks = FallbackReport::default();
+    let mut vms = Vec::new();
+    let mut failed_analyses = Vec::new();
+    for raw_vm in input.vms {
+        match resolve_vm(raw_vm, strict, &mut fallbacks) {
+            Ok(vm) => vms.push(vm),
+            Err(reason) => failed_analyses.push(reason),
+        }
+    }
+
+    Ok(ReplayOutcome {
+        statuses: replay_statuses(vms, &input.host_metrics, options),
+        fallbacks,
+        failed_analyses,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::VMIssueType;
+    use serde_json::json;
+
+    fn vm(cpu_usage_pct: f64) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct,
+            memory_usage_pct: 10.0,
+            raw_metrics: HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn replay_discards_stored_issues_and_recomputes_from_stored_metrics() {
+        let stored = vec![vm(75.0)];
+        let host_metrics = BTreeMap::new();
+
+        let lenient = replay_statuses(stored.clone(), &host_metrics, &DetectionOptions::default());
+        assert!(!lenient[0].has_issues(), "75% is under the built-in 90% CPU threshold");
+
+        // A saved report's issues are never trusted: replay must recompute
+        // even when the original run flagged nothing for this VM.
+        let mut saved_with_issue = stored[0].clone();
+        saved_with_issue.issues = vec![crate::vm::DetectedIssue::measured(VMIssueType::HighCpuUsage, 75.0, 60.0, "stale")];
+        let recomputed = replay_statuses(vec![saved_with_issue], &host_metrics, &DetectionOptions::default());
+        assert!(!recomputed[0].has_issues(), "stale stored issue must not survive replay");
+    }
+
+    #[test]
+    fn replay_recomputes_issues_against_an_overridden_cpu_threshold() {
+        let stored = vec![vm(75.0)];
+        let host_metrics = BTreeMap::new();
+
+        let default_threshold = replay_statuses(stored.clone(), &host_metrics, &DetectionOptions::default());
+        assert!(!default_threshold[0].has_issues(), "75% is under the built-in 90% CPU threshold");
+
+        let lower_threshold = DetectionOptions { cpu_high_threshold_pct: 70.0, ..Default::default() };
+        let recomputed = replay_statuses(stored, &host_metrics, &lower_threshold);
+        assert!(recomputed[0].has_issues(), "75% is over a 70% threshold asked for at replay time, not collection time");
+        assert_eq!(recomputed[0].issues[0].issue_type, VMIssueType::HighCpuUsage);
+    }
+
+    fn raw_vm_json(memory_usage_pct: Option<f64>) -> Value {
+        let mut obj = json!({
+            "name": "vm-0001",
+            "host": "esxi-01",
+            "cluster": "cluster-a",
+            "power_state": "PoweredOn",
+            "cpu_usage_pct": 10.0,
+            "cpu_count": 2,
+            "cores_per_socket": 1,
+            "tools_running": true,
+            "clock_skew_secs": null,
+            "guest_ip": null,
+            "reachable": null,
+            "running_processes": [],
+            "attributes": {},
+            "notes": null,
+            "migration_count_24h": 0,
+            "last_migration": null,
+            "uptime_secs": 2592000.0,
+            "issues": [],
+        });
+        if let Some(pct) = memory_usage_pct {
+            obj["memory_usage_pct"] = json!(pct);
+        }
+        obj
+    }
+
+    #[test]
+    fn missing_measurement_field_falls_back_and_is_counted_by_default() {
+        let raw: RawVm = serde_json::from_value(raw_vm_json(None)).unwrap();
+        let mut fallbacks = FallbackReport::default();
+        let vm = resolve_vm(raw, false, &mut fallbacks).unwrap();
+        assert_eq!(vm.memory_usage_pct, 0.0, "a vCenter field rename should default, not panic or abort the run");
+        assert_eq!(fallbacks.count(), 1);
+    }
+
+    #[test]
+    fn missing_measurement_field_fails_that_vm_under_strict_parsing() {
+        let raw: RawVm = serde_json::from_value(raw_vm_json(None)).unwrap();
+        let mut fallbacks = FallbackReport::default();
+        let err = resolve_vm(raw, true, &mut fallbacks).unwrap_err();
+        assert!(err.contains("vm-0001"));
+        assert!(err.contains("memory_usage_pct"));
+        assert_eq!(fallbacks.count(), 0, "strict-mode failures are not fallbacks");
+    }
+
+    #[test]
+    fn present_measurement_field_needs_no_fallback_in_either_mode() {
+        let mut fallbacks = FallbackReport::default();
+        let strict: RawVm = serde_json::from_value(raw_vm_json(Some(55.0))).unwrap();
+        let vm = resolve_vm(strict, true, &mut fallbacks).unwrap();
+        assert_eq!(vm.memory_usage_pct, 55.0);
+        assert_eq!(fallbacks.count(), 0);
+    }
+
+    #[test]
+    fn replay_excludes_strict_parsing_failures_but_still_analyzes_the_rest() {
+        use std::io::Write;
+
+        let tmp = std::env::temp_dir().join("network-monitor-strict-parsing-test.json");
+        let good = raw_vm_json(Some(55.0));
+        let mut bad = raw_vm_json(None);
+        bad["name"] = json!("vm-0002");
+        let report = json!({ "vms": [good, bad], "host_metrics": {} });
+        std::fs::File::create(&tmp).unwrap().write_all(report.to_string().as_bytes()).unwrap();
+
+        let outcome = replay(tmp.to_str().unwrap(), &DetectionOptions::default(), true).unwrap();
+        assert_eq!(outcome.statuses.len(), 1);
+        assert_eq!(outcome.statuses[0].name, "vm-0001");
+        assert_eq!(outcome.failed_analyses.len(), 1);
+        assert!(outcome.failed_analyses[0].contains("vm-0002"));
+
+        std::fs::remove_file(&tmp).ok();
+    }
+
+    #[test]
+    fn replay_transparently_decompresses_a_gzipped_report() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let tmp = std::env::temp_dir().join("network-monitor-gzip-replay-test.json.gz");
+        let report = json!({ "vms": [raw_vm_json(Some(95.0))], "host_metrics": {} });
+        let mut encoder = GzEncoder::new(std::fs::File::create(&tmp).unwrap(), Compression::default());
+        encoder.write_all(report.to_string().as_bytes()).unwrap();
+        encoder.finish().unwrap();
+
+        let outcome = replay(tmp.to_str().unwrap(), &DetectionOptions::default(), false).unwrap();
+        assert_eq!(outcome.statuses.len(), 1);
+        assert!(outcome.statuses[0].has_issues(), "95% CPU should still trip the default threshold after decompression");
+
+        std::fs::remove_file(&tmp).ok();
+    }
+}
@@ -0,0 +1,1182 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::api_rate_log::EndpointSummary;
+use crate::auth::{PasswordExpiryReport, VCenterVersion};
+use crate::drs::ComplianceReport;
+use crate::notifier::NotifyRunResult;
+use crate::request_budget::RequestBudgetReport;
+use crate::sessions::SessionLimitReport;
+use crate::vm::{format_uptime, DetectedIssue, HostMetrics, UptimeFormat, VMIssueType, VMResourceStatus};
+
+/// Selects `--group-by`'s grouping of the text report's per-VM issue
+/// listing. `Folder` groups by `inventory_path`'s immediate parent folder
+/// instead of the default flat list. See [`crate::cli::GroupByArg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Folder,
+}
+
+/// `inventory_path`'s parent folder, e.g. `/DC1/vm/cluster-a/team-2` for
+/// `/DC1/vm/cluster-a/team-2/vm-0001`. Falls back to the whole path if it
+/// has no `/` to split on (shouldn't happen - every path starts with one).
+pub(crate) fn folder_of(inventory_path: &str) -> &str {
+    inventory_path.rsplit_once('/').map(|(parent, _)| parent).unwrap_or(inventory_path)
+}
+
+/// Renders `--sparklines`' cpu/mem gauges for one flagged VM, or nothing
+/// when it's off. `history` is `--history`'s per-VM pooled `(cpu, mem)`
+/// samples (see [`crate::thresholds::load_history`]). A VM missing from it,
+/// or `history` being `None` because `--history` wasn't given, falls back
+/// to [`crate::sparkline::render`]'s single-block gauge for the current
+/// reading.
+fn render_sparklines(vm: &VMResourceStatus, history: Option<&BTreeMap<String, Vec<(f64, f64)>>>, indent: &str) -> String {
+    let samples = history.and_then(|h| h.get(&vm.name));
+    let cpu_samples: Vec<f64> = samples.map(|s| s.iter().map(|(cpu, _)| *cpu).collect()).unwrap_or_default();
+    let mem_samples: Vec<f64> = samples.map(|s| s.iter().map(|(_, mem)| *mem).collect()).unwrap_or_default();
+    format!(
+        "{indent}    cpu {} mem {}\n",
+        crate::sparkline::render(&cpu_samples, vm.cpu_usage_pct),
+        crate::sparkline::render(&mem_samples, vm.memory_usage_pct)
+    )
+}
+
+/// Renders one VM's issue detail (and last migration, if any) at the given
+/// indent, shared by the flat and `--group-by folder` listings.
+fn render_vm_issue_detail(vm: &VMResourceStatus, uptime_format: UptimeFormat, indent: &str) -> String {
+    let mut out = String::new();
+    for issue in &vm.issues {
+        out.push_str(&format!("{indent}    {}\n", format_issue(issue, uptime_format)));
+        for recommendation in &issue.recommendations {
+            let confidence = match recommendation.confidence {
+                crate::recommend::Confidence::Low => "low",
+                crate::recommend::Confidence::Medium => "medium",
+                crate::recommend::Confidence::High => "high",
+            };
+            out.push_str(&format!(
+                "{indent}      -> {} ({confidence} confidence): {}\n",
+                recommendation.action, recommendation.rationale
+            ));
+        }
+    }
+    if let Some(last_migration) = &vm.last_migration {
+        out.push_str(&format!(
+            "{indent}    last migration: {} -> {}\n",
+            last_migration.from_host, last_migration.to_host
+        ));
+    }
+    out
+}
+
+/// Renders a single issue for the text report/notifications: the measured
+/// value against its threshold when the issue has one, the freeform detail
+/// otherwise. `UPTIME_SHORT`'s measured value/threshold are both uptimes, so
+/// they're rendered per `--uptime-format` instead of the generic `N > M`.
+pub(crate) fn format_issue(issue: &DetectedIssue, uptime_format: UptimeFormat) -> String {
+    let base = match (issue.measured_value, issue.threshold) {
+        (Some(measured), Some(threshold)) if issue.issue_type == VMIssueType::UptimeShort => format!(
+            "{} (uptime {}, threshold {})",
+            issue.issue_type,
+            format_uptime(measured, uptime_format),
+            format_uptime(threshold, uptime_format)
+        ),
+        (Some(measured), Some(threshold)) => {
+            format!("{} ({measured:.1} > {threshold:.1})", issue.issue_type)
+        }
+        _ => format!("{}: {}", issue.issue_type, issue.detail.as_deref().unwrap_or("-")),
+    };
+    match issue.first_seen {
+        Some(first_seen) => {
+            let days_open = (chrono::Utc::now() - first_seen).num_days();
+            format!("{base} [open {days_open}d]")
+        }
+        None => base,
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Statistics {
+    pub total_vms: usize,
+    pub vms_with_issues: usize,
+    pub powered_off: usize,
+    /// VMs whose power state came back as [`crate::vm::PowerState::Unknown`],
+    /// typically a vCenter detail call that didn't report one of the three
+    /// real states. Counted on its own rather than folded into `powered_off`,
+    /// since that bucket doesn't mean anything for a VM whose state isn't
+    /// actually known. Still included in `total_vms`/`vms_with_issues` like
+    /// any other VM - unlike `powered_off`, there's no flag to exclude it.
+    pub state_unknown: usize,
+    /// VMs carrying [`VMIssueType::HotAddDisabled`], from `--require-hot-add`.
+    /// `0` when the check didn't run, same as any other opt-in detector.
+    pub hot_add_disabled: usize,
+    /// Powered-on VMs with no resolvable host, per [`vms_with_no_detected_host`].
+    pub vms_with_no_detected_host: usize,
+    /// Issues downgraded to `Informational` by
+    /// [`crate::maintenance::annotate_maintenance_downgrades`] because their
+    /// VM's host was in maintenance mode. `0` when
+    /// `--no-respect-maintenance-mode` was set or no host was draining.
+    pub maintenance_downgraded: usize,
+    /// The run's weighted health score: the average `health_score` across
+    /// powered-on VMs (see [`crate::scoring::run_score`]). `None` when there
+    /// are no powered-on VMs to average.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub run_health_score: Option<f64>,
+}
+
+/// Single pass over `statuses` computing the counts shown in both the text and
+/// JSON reports. Callers that pass `--no-stats` skip this entirely, since it's
+/// a full scan of the status vector on top of the one the issue listing does.
+///
+/// `exclude_powered_off` (`--exclude-powered-off-from-stats`) scopes
+/// `total_vms`/`vms_with_issues` to powered-on VMs only, so idle capacity
+/// doesn't skew the issue ratio; `powered_off` always counts over every VM
+/// regardless, since it's the one figure that needs the excluded VMs to mean
+/// anything. `run_health_score` always scopes to powered-on VMs regardless of
+/// `exclude_powered_off`, since a powered-off VM isn't part of the fleet's
+/// operational health either way.
+pub fn compute_statistics(statuses: &[VMResourceStatus], exclude_powered_off: bool) -> Statistics {
+    let powered_off = statuses
+        .iter()
+        .filter(|v| v.power_state == crate::vm::PowerState::PoweredOff)
+        .count();
+    let state_unknown = statuses
+        .iter()
+        .filter(|v| v.power_state == crate::vm::PowerState::Unknown)
+        .count();
+    let in_scope: Vec<&VMResourceStatus> = if exclude_powered_off {
+        statuses
+            .iter()
+            .filter(|v| v.power_state != crate::vm::PowerState::PoweredOff)
+            .collect()
+    } else {
+        statuses.iter().collect()
+    };
+    Statistics {
+        total_vms: in_scope.len(),
+        vms_with_issues: in_scope.iter().filter(|v| v.has_issues()).count(),
+        powered_off,
+        state_unknown,
+        hot_add_disabled: in_scope
+            .iter()
+            .filter(|v| v.issues.iter().any(|i| i.issue_type == VMIssueType::HotAddDisabled))
+            .count(),
+        vms_with_no_detected_host: in_scope.iter().filter(|v| has_no_detected_host(v)).count(),
+        maintenance_downgraded: in_scope
+            .iter()
+            .flat_map(|v| &v.issues)
+            .filter(|i| i.original_severity.is_some())
+            .count(),
+        run_health_score: crate::scoring::run_score(statuses),
+    }
+}
+
+/// Whether `vm` is powered on with no resolvable host - an unresolvable
+/// host MOID, which can mean an orphaned VM or a vCenter API gap. A
+/// powered-off VM's host is routinely stale/irrelevant, so only powered-on
+/// VMs are flagged.
+fn has_no_detected_host(vm: &VMResourceStatus) -> bool {
+    vm.power_state == crate::vm::PowerState::PoweredOn && vm.host.is_empty()
+}
+
+/// Renders the per-host CPU/memory table shown under the statistics line
+/// when host metrics were collected (see [`crate::vcenter::SimulatedClient::host_metrics`]).
+/// Hosts are rendered in name order, not collection order, so the table is
+/// stable across runs.
+fn render_host_utilization_table(host_metrics: &BTreeMap<String, HostMetrics>) -> String {
+    let mut out = String::new();
+    for (host, metrics) in host_metrics {
+        out.push_str(&format!(
+            "    {host}: {:.0}% cpu, {:.0}% memory\n",
+            metrics.cpu_usage_pct, metrics.memory_usage_pct
+        ));
+    }
+    out
+}
+
+/// Renders the human-readable text report shown on stdout and written to
+/// `--output` files with `--format text`. Pass `include_stats = false`
+/// (`--no-stats`) to skip the statistics line. `deferred` lists VMs deferred
+/// this run by `--time-budget`, `--max-total-requests` or
+/// `--per-vm-timeout-ms`; it must never be
+/// silently dropped, so it's always appended when non-empty. `host_metrics`
+/// is `--api-rate-log`-style opt-in host-level data; empty when it wasn't
+/// collected (e.g. in watch mode), in which case the table is simply omitted.
+/// `notify_result` is `--notifier-config`'s delivery outcome, rendered as a
+/// trailing NOTIFICATIONS section when `--notifier-config` was set.
+/// `compliance` is `--check-drs-rules`'s evaluation, rendered as a trailing
+/// DRS RULE COMPLIANCE section when it found anything to report.
+/// `vcenter_version` is the site's detected version (`None` when there's no
+/// real session behind the run, e.g. `--replay`/`--demo`), printed in the
+/// header after the run ID (and the `--site` line, when set) so reports from
+/// different sites can be told apart.
+/// `exclude_powered_off_from_stats` is `--exclude-powered-off-from-stats`;
+/// see [`compute_statistics`]. The statistics block also carries the run's
+/// weighted health score (see [`crate::scoring`]), printed right below the
+/// VM counts. `uptime_format` is `--uptime-format`, applied to `UPTIME_SHORT`
+/// issues; see [`format_issue`]. `run_id` is `--run-id` (or a generated UUID
+/// when it's unset), printed as the first line so it can be joined back up
+/// with this run's notifier payloads and state file entry; see
+/// [`crate::run_id`]. `request_budget` is `--max-total-requests`'s outcome,
+/// rendered as a trailing REQUEST BUDGET section when the flag was set.
+/// `not_found` lists `--vm-list-stdin` names that weren't among the VMs this
+/// run fetched (see [`crate::vm::resolve_name_list`]) - a typo'd or
+/// decommissioned VM must show up here rather than just vanishing from the
+/// report with no trace. `session_limit` is `--session-count-warn`/
+/// `--reap-stale-sessions`'s outcome, rendered as a trailing SESSIONS
+/// section only when there's something worth saying; see
+/// [`crate::sessions::SessionLimitReport::render_section`].
+/// `password_expiry` is `--password-expiry-warn-days`'s outcome, rendered
+/// as a trailing PASSWORD EXPIRY section only when the account's password
+/// is at or below the threshold; see
+/// [`crate::auth::PasswordExpiryReport::render_section`].
+/// `group_by` is `--group-by`: `None` (the default) lists VMs with issues
+/// flat, `Some(GroupBy::Folder)` buckets them under their `inventory_path`
+/// folder instead. `site` is `--site`'s geographic/DC label, rendered in the
+/// header only when set. `acknowledgements` is `crate::acknowledge`'s
+/// outcome - `monitor:ignore=...` directives read from VM notes - rendered
+/// as a trailing ACKNOWLEDGED ISSUES section only when there's something to
+/// say. `sparklines` is `--sparklines`' per-VM cpu/mem gauges - `None` when
+/// the flag is off or stdout isn't a TTY, `Some` (possibly empty)
+/// otherwise; see [`render_sparklines`]. Powered-on VMs with no resolvable
+/// host are always listed in a trailing "No detected host" section and
+/// counted in the statistics block; see [`has_no_detected_host`]. `preview`
+/// is `--preview-thresholds`' comparison, rendered as a trailing PREVIEW
+/// section when set; see [`crate::preview`]. `metrics_degraded` is
+/// [`crate::vcenter::SimulatedClient::metrics_degraded`] - whether the SOAP
+/// `PerformanceManager` connection went down at any point this run - noted
+/// in the header so a partial-metrics run is never mistaken for a clean one.
+#[allow(clippy::too_many_arguments)]
+pub fn generate_report(
+    statuses: &[VMResourceStatus],
+    include_stats: bool,
+    deferred: &[String],
+    not_found: &[String],
+    host_metrics: &BTreeMap<String, HostMetrics>,
+    notify_result: Option<&NotifyRunResult>,
+    compliance: Option<&ComplianceReport>,
+    vcenter_version: Option<&VCenterVersion>,
+    exclude_powered_off_from_stats: bool,
+    uptime_format: UptimeFormat,
+    run_id: &str,
+    request_budget: Option<&RequestBudgetReport>,
+    session_limit: Option<&SessionLimitReport>,
+    password_expiry: Option<&PasswordExpiryReport>,
+    group_by: Option<GroupBy>,
+    site: Option<&str>,
+    acknowledgements: &crate::acknowledge::AcknowledgementReport,
+    sparklines: Option<&BTreeMap<String, Vec<(f64, f64)>>>,
+    preview: Option<&crate::preview::PreviewReport>,
+    metrics_degraded: bool,
+) -> String {
+    let mut out = format!("Run ID: {run_id}\n");
+    if let Some(site) = site {
+        out.push_str(&format!("Site: {site}\n"));
+    }
+    if let Some(version) = vcenter_version {
+        out.push_str(&format!("vCenter: {}\n", version.describe()));
+    }
+    if metrics_degraded {
+        out.push_str("Metrics collection degraded: the SOAP PerformanceManager connection went down during this run; affected VMs' CPU/memory usage is unavailable, not genuinely idle\n");
+    }
+    if include_stats {
+        let stats = compute_statistics(statuses, exclude_powered_off_from_stats);
+        out.push_str(&format!(
+            "{} VMs, {} with issues, {} powered off\n",
+            stats.total_vms, stats.vms_with_issues, stats.powered_off
+        ));
+        if let Some(score) = stats.run_health_score {
+            out.push_str(&format!("Run health score: {score:.1}/100\n"));
+        }
+        if stats.hot_add_disabled > 0 {
+            out.push_str(&format!("Hot-add disabled: {}\n", stats.hot_add_disabled));
+        }
+        if stats.vms_with_no_detected_host > 0 {
+            out.push_str(&format!("No detected host: {}\n", stats.vms_with_no_detected_host));
+        }
+        if stats.maintenance_downgraded > 0 {
+            out.push_str(&format!("Downgraded for host maintenance: {}\n", stats.maintenance_downgraded));
+        }
+        if stats.state_unknown > 0 {
+            out.push_str(&format!("Power state unknown: {}\n", stats.state_unknown));
+        }
+        if !host_metrics.is_empty() {
+            out.push_str("  host utilization:\n");
+            out.push_str(&render_host_utilization_table(host_metrics));
+        }
+    }
+    match group_by {
+        Some(GroupBy::Folder) => {
+            let mut by_folder: std::collections::BTreeMap<&str, Vec<&VMResourceStatus>> = std::collections::BTreeMap::new();
+            for vm in statuses.iter().filter(|v| v.has_issues()) {
+                by_folder.entry(folder_of(&vm.inventory_path)).or_default().push(vm);
+            }
+            for (folder, vms) in by_folder {
+                out.push_str(&format!("Folder {folder}:\n"));
+                for vm in vms {
+                    out.push_str(&format!("  - {} ({}):\n", vm.name, vm.host));
+                    if let Some(history) = sparklines {
+                        out.push_str(&render_sparklines(vm, Some(history), "  "));
+                    }
+                    out.push_str(&render_vm_issue_detail(vm, uptime_format, "  "));
+                }
+            }
+        }
+        None => {
+            for vm in statuses.iter().filter(|v| v.has_issues()) {
+                out.push_str(&format!("- {} ({}):\n", vm.name, vm.host));
+                if let Some(history) = sparklines {
+                    out.push_str(&render_sparklines(vm, Some(history), ""));
+                }
+                out.push_str(&render_vm_issue_detail(vm, uptime_format, ""));
+            }
+        }
+    }
+    if !deferred.is_empty() {
+        out.push_str(&format!(
+            "Deferred {} VM(s) this run (--time-budget and/or --max-total-requests and/or --per-vm-timeout-ms): {}\n",
+            deferred.len(),
+            deferred.join(", ")
+        ));
+    }
+    if !not_found.is_empty() {
+        out.push_str(&format!(
+            "Not found {} VM(s) from --vm-list-stdin: {}\n",
+            not_found.len(),
+            not_found.join(", ")
+        ));
+    }
+    let no_host_names: Vec<&str> = statuses.iter().filter(|v| has_no_detected_host(v)).map(|v| v.name.as_str()).collect();
+    if !no_host_names.is_empty() {
+        out.push_str(&format!("No detected host for {} VM(s): {}\n", no_host_names.len(), no_host_names.join(", ")));
+    }
+    if let Some(result) = notify_result {
+        out.push_str(&result.render_section());
+    }
+    if let Some(compliance) = compliance {
+        out.push_str(&compliance.render_section());
+    }
+    if let Some(request_budget) = request_budget {
+        out.push_str(&request_budget.render_section());
+    }
+    if let Some(session_limit) = session_limit {
+        out.push_str(&session_limit.render_section());
+    }
+    if let Some(password_expiry) = password_expiry {
+        out.push_str(&password_expiry.render_section());
+    }
+    if let Some(preview) = preview {
+        out.push_str(&preview.render_section());
+    }
+    out.push_str(&acknowledgements.render_section());
+    out
+}
+
+/// Renders the condensed report for `--summary-output`: overall stats plus
+/// a per-cluster issue count, with no per-VM detail. Meant for an audience
+/// (management, a dashboard) that wants the health headline, not the list
+/// the engineering-facing `--output`/`--format` report gives.
+pub fn generate_summary_report(statuses: &[VMResourceStatus]) -> String {
+    let stats = compute_statistics(statuses, false);
+    let mut out = format!(
+        "{} VMs, {} with issues, {} powered off\n",
+        stats.total_vms, stats.vms_with_issues, stats.powered_off
+    );
+
+    let mut by_cluster: std::collections::BTreeMap<&str, usize> = std::collections::BTreeMap::new();
+    for vm in statuses.iter().filter(|v| v.has_issues()) {
+        *by_cluster.entry(vm.cluster.as_str()).or_default() += 1;
+    }
+    for (cluster, count) in by_cluster {
+        out.push_str(&format!("- {cluster}: {count} VM(s) with issues\n"));
+    }
+    out
+}
+
+/// `--names-for-issue`: the bare `vm_name`s carrying `issue_type`, in fleet
+/// order, for a remediation script that wants "every VM with this issue"
+/// without parsing the full report. Empty, not an error, when nothing
+/// carries it.
+pub fn names_for_issue(statuses: &[VMResourceStatus], issue_type: VMIssueType) -> Vec<String> {
+    statuses
+        .iter()
+        .filter(|v| v.issues.iter().any(|i| i.issue_type == issue_type))
+        .map(|v| v.name.clone())
+        .collect()
+}
+
+/// `--json-schema-version`: v2 (default) includes full [`DetectedIssue`]
+/// detail (measured value, threshold, severity); v1 serializes `issues` as
+/// bare type-name strings, for consumers still on the original schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonSchemaVersion {
+    V1,
+    V2,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonReport<'a> {
+    run_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    statistics: Option<Statistics>,
+    vms: &'a [VMResourceStatus],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    deferred_vms: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    vms_not_found: &'a [String],
+    /// Powered-on VMs with no resolvable host, v2-only like `session_limit`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    vms_with_no_detected_host: Vec<&'a str>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    api_rate_log: &'a [EndpointSummary],
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    host_metrics: &'a BTreeMap<String, HostMetrics>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notifications: Option<&'a NotifyRunResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    drs_compliance: Option<&'a ComplianceReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vcenter_version: Option<&'a VCenterVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_budget: Option<&'a RequestBudgetReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    session_limit: Option<&'a SessionLimitReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password_expiry: Option<&'a PasswordExpiryReport>,
+    /// `--preview-thresholds`' comparison, v2-only like `session_limit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    preview: Option<&'a crate::preview::PreviewReport>,
+    /// `--site`'s geographic/DC label, v2-only like `session_limit`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    site: Option<&'a str>,
+    /// `crate::acknowledge`'s outcome, v2-only like `session_limit`.
+    #[serde(skip_serializing_if = "crate::acknowledge::AcknowledgementReport::is_empty")]
+    acknowledgements: &'a crate::acknowledge::AcknowledgementReport,
+    /// [`crate::vcenter::SimulatedClient::metrics_degraded`], v2-only like
+    /// `session_limit`. Omitted (not just `false`) when metrics collection
+    /// stayed healthy this run.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    metrics_degraded: bool,
+    /// `--timing`'s per-check cost summary, v2-only like `session_limit`.
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    check_timing: &'a [crate::check_timing::CheckCost],
+}
+
+/// v1-schema mirror of [`VMResourceStatus`], with `issues` collapsed to bare
+/// type-name strings instead of full [`DetectedIssue`] objects.
+#[derive(Debug, Serialize)]
+struct LegacyVmResourceStatus<'a> {
+    name: &'a str,
+    host: &'a str,
+    cluster: &'a str,
+    power_state: crate::vm::PowerState,
+    cpu_usage_pct: f64,
+    memory_usage_pct: f64,
+    cpu_count: u32,
+    cores_per_socket: u32,
+    tools_running: bool,
+    clock_skew_secs: Option<f64>,
+    guest_ip: Option<&'a str>,
+    reachable: Option<bool>,
+    running_processes: &'a [String],
+    attributes: &'a std::collections::HashMap<String, String>,
+    notes: Option<&'a str>,
+    issues: Vec<String>,
+}
+
+impl<'a> From<&'a VMResourceStatus> for LegacyVmResourceStatus<'a> {
+    fn from(vm: &'a VMResourceStatus) -> Self {
+        Self {
+            name: &vm.name,
+            host: &vm.host,
+            cluster: &vm.cluster,
+            power_state: vm.power_state,
+            cpu_usage_pct: vm.cpu_usage_pct,
+            memory_usage_pct: vm.memory_usage_pct,
+            cpu_count: vm.cpu_count,
+            cores_per_socket: vm.cores_per_socket,
+            tools_running: vm.tools_running,
+            clock_skew_secs: vm.clock_skew_secs,
+            guest_ip: vm.guest_ip.as_deref(),
+            reachable: vm.reachable,
+            running_processes: &vm.running_processes,
+            attributes: &vm.attributes,
+            notes: vm.notes.as_deref(),
+            issues: vm.issues.iter().map(|i| i.issue_type.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LegacyJsonReport<'a> {
+    run_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    statistics: Option<Statistics>,
+    vms: Vec<LegacyVmResourceStatus<'a>>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    deferred_vms: &'a [String],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    vms_not_found: &'a [String],
+}
+
+/// Renders the `--format json` report. Pass `include_stats = false`
+/// (`--no-stats`) to omit the `statistics` field entirely. `deferred` lists
+/// VMs the `--time-budget` planner couldn't get to this run. `schema_version`
+/// picks between the full v2 issue detail and the bare-type-name v1 shape.
+/// `api_rate_log` is `--api-rate-log`'s per-endpoint summary, `host_metrics`
+/// the per-host CPU/memory counters, `notify_result` `--notifier-config`'s
+/// delivery outcome, `compliance` `--check-drs-rules`'s evaluation, and
+/// `vcenter_version` the site's detected version - all included only in v2
+/// (v1 consumers expect the original, smaller shape). `request_budget` is
+/// `--max-total-requests`'s outcome, also v2-only. `exclude_powered_off_from_stats`
+/// is `--exclude-powered-off-from-stats`; see [`compute_statistics`]. `run_id`
+/// is carried in both schema versions, so it can join this report back up
+/// with the same run's notifier payloads and state file entry regardless of
+/// `--json-schema-version`; see [`crate::run_id`]. `not_found` is
+/// `--vm-list-stdin`'s unmatched names, carried in both schema versions for
+/// the same reason `deferred_vms` is; see [`generate_report`].
+/// `session_limit` is `--session-count-warn`/`--reap-stale-sessions`'s
+/// outcome, v2-only like `request_budget`. `password_expiry` is
+/// `--password-expiry-warn-days`'s outcome, also v2-only. `site` is
+/// `--site`'s label, also v2-only. `acknowledgements` is `crate::acknowledge`'s
+/// outcome, also v2-only. `compact` is `--compact-json`: `false` (the
+/// default) pretty-prints for human inspection, `true` switches to
+/// `serde_json::to_string`'s single-line output, which noticeably shrinks
+/// multi-thousand-VM reports for consumers that parse rather than read them.
+/// `preview` is `--preview-thresholds`' comparison, carried as the `preview`
+/// field, also v2-only; see [`crate::preview`]. `metrics_degraded` is
+/// [`crate::vcenter::SimulatedClient::metrics_degraded`], also v2-only.
+/// `check_timing` is `--timing`'s per-check cost summary, carried as the
+/// `check_timing` field, also v2-only; see [`crate::check_timing`].
+#[allow(clippy::too_many_arguments)]
+pub fn export_json_report(
+    statuses: &[VMResourceStatus],
+    include_stats: bool,
+    deferred: &[String],
+    not_found: &[String],
+    schema_version: JsonSchemaVersion,
+    api_rate_log: &[EndpointSummary],
+    host_metrics: &BTreeMap<String, HostMetrics>,
+    notify_result: Option<&NotifyRunResult>,
+    compliance: Option<&ComplianceReport>,
+    vcenter_version: Option<&VCenterVersion>,
+    exclude_powered_off_from_stats: bool,
+    run_id: &str,
+    request_budget: Option<&RequestBudgetReport>,
+    session_limit: Option<&SessionLimitReport>,
+    password_expiry: Option<&PasswordExpiryReport>,
+    site: Option<&str>,
+    acknowledgements: &crate::acknowledge::AcknowledgementReport,
+    compact: bool,
+    preview: Option<&crate::preview::PreviewReport>,
+    metrics_degraded: bool,
+    check_timing: &[crate::check_timing::CheckCost],
+) -> serde_json::Result<String> {
+    match schema_version {
+        JsonSchemaVersion::V2 => {
+            let report = JsonReport {
+                run_id,
+                statistics: include_stats.then(|| compute_statistics(statuses, exclude_powered_off_from_stats)),
+                vms: statuses,
+                deferred_vms: deferred,
+                vms_not_found: not_found,
+                vms_with_no_detected_host: statuses.iter().filter(|v| has_no_detected_host(v)).map(|v| v.name.as_str()).collect(),
+                api_rate_log,
+                host_metrics,
+                notifications: notify_result,
+                drs_compliance: compliance,
+                vcenter_version,
+                request_budget,
+                session_limit,
+                password_expiry,
+                preview,
+                site,
+                acknowledgements,
+                metrics_degraded,
+                check_timing,
+            };
+            if compact {
+                serde_json::to_string(&report)
+            } else {
+                serde_json::to_string_pretty(&report)
+            }
+        }
+        JsonSchemaVersion::V1 => {
+            let report = LegacyJsonReport {
+                run_id,
+                statistics: include_stats.then(|| compute_statistics(statuses, exclude_powered_off_from_stats)),
+                vms: statuses.iter().map(LegacyVmResourceStatus::from).collect(),
+                deferred_vms: deferred,
+                vms_not_found: not_found,
+            };
+            if compact {
+                serde_json::to_string(&report)
+            } else {
+                serde_json::to_string_pretty(&report)
+            }
+        }
+    }
+}
+
+pub(crate) fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') || s.contains('\n') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Renders the `--format csv` report, with the VM's `Owner` attribute and
+/// notes broken out into their own columns for alert routing. `run_id` is
+/// carried as a leading `#`-comment line (CSV has no metadata block to put
+/// it in otherwise), the same convention the Ctrl-C partial report already
+/// uses for its "rapport partiel" marker. `site` is `--site`'s label,
+/// carried the same way and omitted when unset.
+pub fn export_csv_report(statuses: &[VMResourceStatus], run_id: &str, site: Option<&str>) -> String {
+    let mut out = format!("# run_id: {run_id}\n");
+    if let Some(site) = site {
+        out.push_str(&format!("# site: {site}\n"));
+    }
+    out.push_str("name,host,cluster,power_state,cpu_usage_pct,memory_usage_pct,owner,notes,health_score,issues,inventory_path\n");
+    for vm in statuses {
+        let owner = vm.attributes.get("Owner").map(String::as_str).unwrap_or("");
+        let notes = vm.notes.as_deref().unwrap_or("");
+        let issues = vm
+            .issues
+            .iter()
+            .map(|i| i.issue_type.to_string())
+            .collect::<Vec<_>>()
+            .join(";");
+        out.push_str(&format!(
+            "{},{},{},{},{:.1},{:.1},{},{},{:.1},{},{}\n",
+            csv_escape(&vm.name),
+            csv_escape(&vm.host),
+            csv_escape(&vm.cluster),
+            vm.power_state,
+            vm.cpu_usage_pct,
+            vm.memory_usage_pct,
+            csv_escape(owner),
+            csv_escape(notes),
+            vm.health_score,
+            csv_escape(&issues),
+            csv_escape(&vm.inventory_path),
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::PowerState;
+
+    fn vm(power_state: PowerState, issues: Vec<crate::vm::DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn group_by_folder_buckets_issues_under_their_inventory_path_parent() {
+        let mut in_team_a = vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")]);
+        in_team_a.name = "vm-team-a".to_string();
+        in_team_a.inventory_path = "/DC1/vm/cluster-a/team-1/vm-team-a".to_string();
+        let mut in_team_b = vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")]);
+        in_team_b.name = "vm-team-b".to_string();
+        in_team_b.inventory_path = "/DC1/vm/cluster-a/team-2/vm-team-b".to_string();
+        let statuses = vec![in_team_a, in_team_b];
+
+        let flat = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!flat.contains("Folder "));
+
+        let grouped = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None,
+            Some(GroupBy::Folder), None, &Default::default(), None, None, false);
+        assert!(grouped.contains("Folder /DC1/vm/cluster-a/team-1:\n  - vm-team-a"));
+        assert!(grouped.contains("Folder /DC1/vm/cluster-a/team-2:\n  - vm-team-b"));
+    }
+
+    #[test]
+    fn no_stats_omits_statistics_block() {
+        let statuses = vec![vm(PowerState::PoweredOff, vec![])];
+        assert!(generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false).contains("1 VMs"));
+        assert!(!generate_report(&statuses, false, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false).contains("1 VMs"));
+
+        let with_stats: serde_json::Value =
+            serde_json::from_str(&export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap()).unwrap();
+        assert!(with_stats.get("statistics").is_some());
+
+        let without_stats: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, false, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert!(without_stats.get("statistics").is_none());
+    }
+
+    #[test]
+    fn exclude_powered_off_from_stats_scopes_counts_to_powered_on_vms_only() {
+        let statuses = vec![
+            vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")]),
+            vm(PowerState::PoweredOff, vec![]),
+        ];
+
+        let included = compute_statistics(&statuses, false);
+        assert_eq!((included.total_vms, included.vms_with_issues, included.powered_off), (2, 1, 1));
+
+        let excluded = compute_statistics(&statuses, true);
+        assert_eq!((excluded.total_vms, excluded.vms_with_issues, excluded.powered_off), (1, 1, 1));
+    }
+
+    #[test]
+    fn summary_report_breaks_down_issues_by_cluster() {
+        let mut has_issue = vm(
+            PowerState::PoweredOn,
+            vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")],
+        );
+        has_issue.cluster = "cluster-b".to_string();
+        let statuses = vec![has_issue, vm(PowerState::PoweredOn, vec![])];
+
+        let summary = generate_summary_report(&statuses);
+        assert!(summary.starts_with("2 VMs, 1 with issues"));
+        assert!(summary.contains("cluster-b: 1 VM(s) with issues"));
+    }
+
+    #[test]
+    fn run_id_is_carried_in_every_report_format() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "run-abc-123", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(text.starts_with("Run ID: run-abc-123\n"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "run-abc-123", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["run_id"], "run-abc-123");
+
+        let legacy_json: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "run-abc-123", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(legacy_json["run_id"], "run-abc-123");
+
+        let csv = export_csv_report(&statuses, "run-abc-123", None);
+        assert!(csv.starts_with("# run_id: run-abc-123\n"));
+    }
+
+    #[test]
+    fn deferred_vms_are_never_silently_dropped() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        let deferred = vec!["vm-0002".to_string()];
+        assert!(generate_report(&statuses, true, &deferred, &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false).contains("vm-0002"));
+
+        let report: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &deferred, &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(report["deferred_vms"][0], "vm-0002");
+    }
+
+    #[test]
+    fn not_found_vms_are_never_silently_dropped() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        let not_found = vec!["vm-typo".to_string()];
+        assert!(generate_report(&statuses, true, &[], &not_found, &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false).contains("vm-typo"));
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &not_found, JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["vms_not_found"][0], "vm-typo");
+
+        let v1: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &not_found, JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v1["vms_not_found"][0], "vm-typo");
+    }
+
+    #[test]
+    fn powered_on_vm_with_no_host_is_listed_and_counted() {
+        let mut orphaned = vm(PowerState::PoweredOn, vec![]);
+        orphaned.host = String::new();
+        let statuses = vec![orphaned, vm(PowerState::PoweredOn, vec![])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(text.contains("No detected host: 1\n"));
+        assert!(text.contains("No detected host for 1 VM(s): vm-0001\n"));
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["statistics"]["vms_with_no_detected_host"], 1);
+        assert_eq!(v2["vms_with_no_detected_host"][0], "vm-0001");
+    }
+
+    #[test]
+    fn powered_off_vm_with_no_host_is_not_flagged() {
+        let mut off = vm(PowerState::PoweredOff, vec![]);
+        off.host = String::new();
+        let statuses = vec![off];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("No detected host"));
+    }
+
+    #[test]
+    fn session_limit_section_is_v2_only() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        let session_limit = SessionLimitReport { count: Some(25), warn_threshold: 20, reaped: vec!["sess-001".to_string()] };
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None,
+            Some(&session_limit), None, None, None, &Default::default(), None, None, false);
+        assert!(text.contains("SESSIONS:"));
+        assert!(text.contains("sess-001"));
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None,
+                Some(&session_limit), None, None, &Default::default(), false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["session_limit"]["count"], 25);
+
+        let v1: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None,
+                Some(&session_limit), None, None, &Default::default(), false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(v1.get("session_limit").is_none());
+    }
+
+    #[test]
+    fn v1_schema_serializes_issues_as_bare_type_names() {
+        let statuses = vec![vm(
+            PowerState::PoweredOn,
+            vec![crate::vm::DetectedIssue::measured(
+                crate::vm::VMIssueType::HighCpuUsage,
+                95.0,
+                90.0,
+                "CPU usage at 95.0%",
+            )],
+        )];
+        let report: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, false, &[], &[], JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(report["vms"][0]["issues"][0], "HIGH_CPU_USAGE");
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, false, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["vms"][0]["issues"][0]["measured_value"], 95.0);
+    }
+
+    #[test]
+    fn host_utilization_table_shown_under_statistics_when_present() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        let mut host_metrics = BTreeMap::new();
+        host_metrics.insert(
+            "esxi-01".to_string(),
+            HostMetrics {
+                cpu_usage_pct: 95.0,
+                memory_usage_pct: 40.0,
+                physical_cores: 32,
+                connection_state: crate::vm::HostConnectionState::Connected,
+                in_maintenance_mode: false,
+                sensor_status: crate::vm::HostSensorStatus::Green,
+                failing_sensor: None,
+            },
+        );
+
+        let report = generate_report(&statuses, true, &[], &[], &host_metrics, None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(report.contains("esxi-01: 95% cpu, 40% memory"));
+        assert!(!generate_report(&statuses, false, &[], &[], &host_metrics, None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false).contains("esxi-01"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &host_metrics, None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["host_metrics"]["esxi-01"]["cpu_usage_pct"], 95.0);
+
+        let v1: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V1, &[], &host_metrics, None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert!(v1.get("host_metrics").is_none());
+    }
+
+    #[test]
+    fn csv_report_includes_owner_and_escapes_commas() {
+        let mut with_owner = vm(PowerState::PoweredOn, vec![]);
+        with_owner
+            .attributes
+            .insert("Owner".to_string(), "team, ops".to_string());
+        let csv = export_csv_report(&[with_owner], "test-run-id", None);
+        assert!(csv.contains("\"team, ops\""));
+    }
+
+    #[test]
+    fn csv_report_includes_health_score_column() {
+        let mut vm = vm(PowerState::PoweredOn, vec![]);
+        vm.health_score = 85.0;
+        let csv = export_csv_report(&[vm], "test-run-id", None);
+        assert!(csv.contains("name,host,cluster,power_state,cpu_usage_pct,memory_usage_pct,owner,notes,health_score,issues"));
+        assert!(csv.contains(",85.0,"));
+    }
+
+    #[test]
+    fn run_health_score_shown_in_stats_line_and_json_metadata() {
+        let mut healthy = vm(PowerState::PoweredOn, vec![]);
+        healthy.health_score = 100.0;
+        let statuses = vec![healthy];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(text.contains("Run health score: 100.0/100"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(json["statistics"]["run_health_score"], 100.0);
+    }
+
+    #[test]
+    fn run_health_score_absent_with_no_powered_on_vms() {
+        let statuses = vec![vm(PowerState::PoweredOff, vec![])];
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("Run health score"));
+    }
+
+    #[test]
+    fn site_is_rendered_in_the_text_header_and_csv_comment_when_set() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None,
+            Some("us-east-1"), &Default::default(), None, None, false);
+        assert!(text.contains("Site: us-east-1\n"));
+
+        let csv = export_csv_report(&statuses, "test-run-id", Some("us-east-1"));
+        assert!(csv.contains("# site: us-east-1\n"));
+    }
+
+    #[test]
+    fn site_is_omitted_from_every_format_when_unset() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("Site:"));
+
+        let csv = export_csv_report(&statuses, "test-run-id", None);
+        assert!(!csv.contains("# site:"));
+
+        let json: serde_json::Value = serde_json::from_str(
+            &export_json_report(&statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[]).unwrap(),
+        )
+        .unwrap();
+        assert!(json.get("site").is_none());
+    }
+
+    #[test]
+    fn site_appears_in_v2_json_but_not_v1() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None,
+                Some("us-east-1"), &Default::default(), false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["site"], "us-east-1");
+
+        let v1: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None,
+                Some("us-east-1"), &Default::default(), false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(v1.get("site").is_none());
+    }
+
+    #[test]
+    fn acknowledged_issues_section_is_rendered_when_there_is_something_to_say() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        let acknowledgements = crate::acknowledge::AcknowledgementReport {
+            acknowledged: vec![crate::acknowledge::AcknowledgedIssue {
+                vm: "vm-0001".to_string(),
+                issue_type: crate::vm::VMIssueType::HighCpuUsage,
+                until: None,
+                reason: Some("batch week".to_string()),
+            }],
+            stale: vec![],
+        };
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &acknowledgements, None, None, false);
+        assert!(text.contains("ACKNOWLEDGED ISSUES:"));
+        assert!(text.contains("batch week"));
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &acknowledgements, false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(v2["acknowledgements"]["acknowledged"][0]["vm"], "vm-0001");
+
+        let v1: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V1, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &acknowledgements, false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(v1.get("acknowledgements").is_none());
+    }
+
+    #[test]
+    fn acknowledgements_are_omitted_from_every_format_when_empty() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("ACKNOWLEDGED ISSUES:"));
+
+        let v2: serde_json::Value = serde_json::from_str(
+            &export_json_report(
+                &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[])
+            .unwrap(),
+        )
+        .unwrap();
+        assert!(v2.get("acknowledgements").is_none());
+    }
+
+    #[test]
+    fn compact_json_drops_the_whitespace_pretty_json_uses() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+
+        let pretty = export_json_report(
+            &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), false, None, false, &[])
+        .unwrap();
+        assert!(pretty.contains('\n'));
+
+        let compact = export_json_report(
+            &statuses, true, &[], &[], JsonSchemaVersion::V2, &[], &BTreeMap::new(), None, None, None, false, "test-run-id", None, None, None, None, &Default::default(), true, None, false, &[])
+        .unwrap();
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&pretty).unwrap()
+        );
+    }
+
+    #[test]
+    fn sparklines_are_omitted_when_not_requested() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("cpu "));
+    }
+
+    #[test]
+    fn sparklines_use_history_when_the_vm_has_samples() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")])];
+        let mut history = BTreeMap::new();
+        history.insert("vm-0001".to_string(), vec![(0.0, 0.0), (100.0, 100.0)]);
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(),
+            Some(&history), None, false);
+        assert!(text.contains(&format!("cpu {}{} mem {}{}", '\u{2581}', '\u{2588}', '\u{2581}', '\u{2588}')));
+    }
+
+    #[test]
+    fn sparklines_fall_back_to_a_single_block_when_the_vm_has_no_history() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")])];
+
+        let text = generate_report(
+            &statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(),
+            Some(&BTreeMap::new()), None, false);
+        assert!(text.contains(&format!("cpu {} mem {}", '\u{2582}', '\u{2582}')));
+    }
+
+    #[test]
+    fn a_first_seen_issue_shows_how_long_it_has_been_open() {
+        let mut issue = crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x");
+        issue.first_seen = Some(chrono::Utc::now() - chrono::Duration::days(3));
+        let statuses = vec![vm(PowerState::PoweredOn, vec![issue])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(text.contains("[open 3d]"));
+    }
+
+    #[test]
+    fn an_issue_with_no_first_seen_omits_the_age() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")])];
+
+        let text = generate_report(&statuses, true, &[], &[], &BTreeMap::new(), None, None, None, false, UptimeFormat::Human, "test-run-id", None, None, None, None, None, &Default::default(), None, None, false);
+        assert!(!text.contains("[open"));
+    }
+
+    #[test]
+    fn names_for_issue_lists_only_vms_carrying_that_issue_type() {
+        let mut has_it = vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::ToolsNotRunning, "x")]);
+        has_it.name = "vm-with-issue".to_string();
+        let mut lacks_it = vm(PowerState::PoweredOn, vec![crate::vm::DetectedIssue::new(crate::vm::VMIssueType::HighCpuUsage, "x")]);
+        lacks_it.name = "vm-without-issue".to_string();
+        let statuses = vec![has_it, lacks_it];
+
+        let names = names_for_issue(&statuses, crate::vm::VMIssueType::ToolsNotRunning);
+        assert_eq!(names, vec!["vm-with-issue".to_string()]);
+    }
+
+    #[test]
+    fn names_for_issue_is_empty_when_nothing_carries_it() {
+        let statuses = vec![vm(PowerState::PoweredOn, vec![])];
+        assert!(names_for_issue(&statuses, crate::vm::VMIssueType::ToolsNotRunning).is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+//! Enforces `--max-total-requests`: a hard per-run ceiling on outgoing
+//! vCenter calls, so a misconfigured run (every `--check-*` flag on, a huge
+//! `--vm-count`, no bulk fetch) can't hammer vCenter with tens of thousands
+//! of requests - VI admins treat that as an incident. This simulated
+//! vCenter integration has no snapshot/cdrom/network-inventory calls to
+//! degrade (there's no `--check-snapshots` etc. in this tree); the per-VM
+//! optional checks billed against the ceiling here are the ones this tree
+//! actually models as extra per-VM lookups - reachability, required-process,
+//! and clock-skew - so those are what degrade, in
+//! [`DEGRADE_PRIORITY`] order. Past 80% of the ceiling,
+//! [`RequestBudget::maybe_degrade`] starts disabling them one at a time for
+//! every VM not yet processed, logging each degradation; once the ceiling
+//! itself is hit, [`RequestBudget::is_exhausted`] tells the caller to defer
+//! remaining VMs instead of analyzing them, the same shape `--time-budget`
+//! already uses. See [`crate::vcenter::SimulatedClient`]'s per-VM loop for
+//! where this gets consulted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use serde::Serialize;
+
+/// Per-VM optional checks billed against `--max-total-requests`, in the
+/// order [`RequestBudget::maybe_degrade`] disables them as the ceiling
+/// approaches - most dispensable first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DegradableCheck {
+    Reachability,
+    Process,
+    ClockSkew,
+}
+
+pub const DEGRADE_PRIORITY: [DegradableCheck; 3] =
+    [DegradableCheck::Reachability, DegradableCheck::Process, DegradableCheck::ClockSkew];
+
+impl DegradableCheck {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DegradableCheck::Reachability => "reachability",
+            DegradableCheck::Process => "process",
+            DegradableCheck::ClockSkew => "clock-skew",
+        }
+    }
+}
+
+/// Tracks consumed requests against `--max-total-requests` for one run.
+/// `ceiling = None` (the default, `--max-total-requests` unset) disables
+/// enforcement entirely - every method becomes a no-op and `report()`
+/// returns `None`.
+pub struct RequestBudget {
+    ceiling: Option<u64>,
+    consumed: AtomicU64,
+    degraded: Mutex<Vec<DegradableCheck>>,
+    deferred: Mutex<Vec<String>>,
+}
+
+impl RequestBudget {
+    pub fn new(ceiling: Option<u64>) -> Self {
+        Self {
+            ceiling,
+            consumed: AtomicU64::new(0),
+            degraded: Mutex::new(Vec::new()),
+            deferred: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn degradation_threshold(&self) -> Option<u64> {
+        self.ceiling.map(|c| (c as f64 * 0.8).round() as u64)
+    }
+
+    /// Adds `n` to the consumed count.
+    pub fn record(&self, n: u64) {
+        self.consumed.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn consumed(&self) -> u64 {
+        self.consumed.load(Ordering::Relaxed)
+    }
+
+    /// True once the ceiling itself has been reached; callers should defer
+    /// rather than analyze any VM from this point on.
+    pub fn is_exhausted(&self) -> bool {
+        self.ceiling.is_some_and(|ceiling| self.consumed() >= ceiling)
+    }
+
+    pub fn is_degraded(&self, check: DegradableCheck) -> bool {
+        self.degraded.lock().unwrap().contains(&check)
+    }
+
+    /// Past 80% of the ceiling, disables the next not-yet-degraded check in
+    /// `active` (evaluated in [`DEGRADE_PRIORITY`] order) and logs it.
+    /// No-op below the threshold, once every `active` check is already
+    /// degraded, or with no ceiling set.
+    pub fn maybe_degrade(&self, active: &[DegradableCheck]) {
+        let Some(threshold) = self.degradation_threshold() else { return };
+        if self.consumed() < threshold {
+            return;
+        }
+        let mut degraded = self.degraded.lock().unwrap();
+        for check in DEGRADE_PRIORITY {
+            if active.contains(&check) && !degraded.contains(&check) {
+                degraded.push(check);
+                eprintln!(
+                    "max-total-requests: {}/{} requests consumed, disabling {} for remaining VMs",
+                    self.consumed(),
+                    self.ceiling.unwrap(),
+                    check.label()
+                );
+                return;
+            }
+        }
+    }
+
+    /// Records that `vm_name` was skipped outright because the ceiling was
+    /// already exhausted when its turn came up.
+    pub fn defer(&self, vm_name: &str) {
+        self.deferred.lock().unwrap().push(vm_name.to_string());
+    }
+
+    pub fn deferred(&self) -> Vec<String> {
+        self.deferred.lock().unwrap().clone()
+    }
+
+    /// `None` when `--max-total-requests` wasn't set, so callers can skip
+    /// attaching a section to the report/JSON metadata entirely.
+    pub fn report(&self) -> Option<RequestBudgetReport> {
+        let ceiling = self.ceiling?;
+        Some(RequestBudgetReport {
+            ceiling,
+            consumed: self.consumed(),
+            degraded: self.degraded.lock().unwrap().iter().map(|check| check.label().to_string()).collect(),
+        })
+    }
+}
+
+/// `--max-total-requests`'s run-level outcome, surfaced in the text report
+/// and JSON metadata.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequestBudgetReport {
+    pub ceiling: u64,
+    pub consumed: u64,
+    pub degraded: Vec<String>,
+}
+
+impl RequestBudgetReport {
+    pub fn render_section(&self) -> String {
+        let mut out = format!("REQUEST BUDGET: {}/{} requests consumed\n", self.consumed, self.ceiling);
+        if !self.degraded.is_empty() {
+            out.push_str(&format!("  degraded checks: {}\n", self.degraded.join(", ")));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_ceiling_never_exhausts_or_degrades() {
+        let budget = RequestBudget::new(None);
+        budget.record(1_000_000);
+        budget.maybe_degrade(&DEGRADE_PRIORITY);
+        assert!(!budget.is_exhausted());
+        assert!(!budget.is_degraded(DegradableCheck::Reachability));
+        assert!(budget.report().is_none());
+    }
+
+    #[test]
+    fn degrades_in_priority_order_once_past_80_percent() {
+        let budget = RequestBudget::new(Some(10));
+        let active = DEGRADE_PRIORITY.to_vec();
+
+        budget.record(7);
+        budget.maybe_degrade(&active);
+        assert!(!budget.is_degraded(DegradableCheck::Reachability), "below the 80% threshold");
+
+        budget.record(1);
+        budget.maybe_degrade(&active);
+        assert!(budget.is_degraded(DegradableCheck::Reachability));
+        assert!(!budget.is_degraded(DegradableCheck::Process));
+
+        budget.record(1);
+        budget.maybe_degrade(&active);
+        assert!(budget.is_degraded(DegradableCheck::Process));
+        assert!(!budget.is_degraded(DegradableCheck::ClockSkew));
+    }
+
+    #[test]
+    fn only_active_checks_are_candidates_for_degradation() {
+        let budget = RequestBudget::new(Some(10));
+        budget.record(9);
+        budget.maybe_degrade(&[DegradableCheck::ClockSkew]);
+        assert!(!budget.is_degraded(DegradableCheck::Reachability));
+        assert!(budget.is_degraded(DegradableCheck::ClockSkew));
+    }
+
+    #[test]
+    fn is_exhausted_exactly_at_the_ceiling_not_before() {
+        let budget = RequestBudget::new(Some(5));
+        budget.record(4);
+        assert!(!budget.is_exhausted());
+        budget.record(1);
+        assert!(budget.is_exhausted());
+    }
+
+    #[test]
+    fn report_is_none_without_a_ceiling() {
+        assert!(RequestBudget::new(None).report().is_none());
+    }
+
+    #[test]
+    fn report_carries_consumed_and_degraded_labels() {
+        let budget = RequestBudget::new(Some(10));
+        budget.record(8);
+        budget.maybe_degrade(&DEGRADE_PRIORITY);
+        let report = budget.report().unwrap();
+        assert_eq!(report.ceiling, 10);
+        assert_eq!(report.consumed, 8);
+        assert_eq!(report.degraded, vec!["reachability".to_string()]);
+    }
+}
@@ -0,0 +1,340 @@
+//! `--rightsizing-report`: the opposite of `--check-vcpu-allocation`/the
+//! high-usage issue types, for finance rather than on-call - which VMs are
+//! grossly *over*-provisioned for what they actually use. A VM qualifies
+//! when its peak CPU and memory usage over the lookback both stayed below
+//! `--underuse-threshold`; [`build_report`] then suggests a halved (with a
+//! floor) vCPU/memory size and totals up what the estate could reclaim.
+//!
+//! Peak usage comes from `--history` reports when given (same pooling
+//! [`crate::thresholds::load_history`] does), or from this run's own single
+//! live sample otherwise - "using either the live sample or the history DB
+//! when present", per the request this shipped for. A VM with neither is
+//! listed in [`RightsizingReport::insufficient_data`] rather than guessed
+//! at; one carrying `--rightsize-exempt-attribute` is listed in
+//! [`RightsizingReport::exempt`] instead, regardless of how idle it looks -
+//! deliberately oversized (DR standby, compliance hold) is not the same
+//! thing as forgotten.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::vm::VMResourceStatus;
+
+/// Default for `--underuse-threshold`: a VM whose peak CPU and memory usage
+/// both stayed below this is a rightsizing candidate.
+pub const DEFAULT_UNDERUSE_THRESHOLD_PCT: f64 = 20.0;
+
+/// Default for `--rightsize-exempt-attribute`: the custom attribute key
+/// whose mere presence (any value) exempts a VM from recommendations.
+pub const DEFAULT_EXEMPT_ATTRIBUTE: &str = "RightsizeExempt";
+
+/// Never suggest sizing a VM down below this many vCPUs.
+const FLOOR_VCPUS: u32 = 1;
+
+/// Never suggest sizing a VM down below this many GB of memory.
+const FLOOR_MEMORY_GB: f64 = 2.0;
+
+/// Halves `current`, rounding to the nearest whole vCPU, floored at
+/// [`FLOOR_VCPUS`] and capped at `current` itself so a VM already at or
+/// below the floor is never "sized up" by this suggestion.
+fn suggested_vcpus(current: u32) -> u32 {
+    let halved = (current as f64 / 2.0).round() as u32;
+    halved.max(FLOOR_VCPUS).min(current)
+}
+
+/// Same as [`suggested_vcpus`], for memory in GB.
+fn suggested_memory_gb(current: f64) -> f64 {
+    (current / 2.0).round().max(FLOOR_MEMORY_GB).min(current)
+}
+
+/// Builds `--rightsizing-report`'s `samples_by_vm` input from this run's
+/// own fetch: one `(cpu_usage_pct, memory_usage_pct)` sample per VM, used
+/// when `--history` wasn't given.
+pub fn live_samples(statuses: &[VMResourceStatus]) -> BTreeMap<String, Vec<(f64, f64)>> {
+    statuses.iter().map(|vm| (vm.name.clone(), vec![(vm.cpu_usage_pct, vm.memory_usage_pct)])).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RightsizingCandidate {
+    pub name: String,
+    pub sample_count: usize,
+    pub peak_cpu_pct: f64,
+    pub peak_memory_pct: f64,
+    pub current_vcpus: u32,
+    pub current_memory_gb: f64,
+    pub suggested_vcpus: u32,
+    pub suggested_memory_gb: f64,
+    pub reclaimable_vcpus: u32,
+    pub reclaimable_memory_gb: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RightsizingReport {
+    pub candidates: Vec<RightsizingCandidate>,
+    /// VMs with no usable sample (absent from `--history` and from this
+    /// run's live fetch), in name order.
+    pub insufficient_data: Vec<String>,
+    /// VMs carrying `--rightsize-exempt-attribute`, in name order.
+    pub exempt: Vec<String>,
+    pub total_reclaimable_vcpus: u32,
+    pub total_reclaimable_memory_gb: f64,
+}
+
+/// Builds the rightsizing report for `statuses` against `samples_by_vm`
+/// (either pooled `--history` samples or [`live_samples`]). Exemption is
+/// checked before underuse, so an exempt VM that's also short on data is
+/// simply exempt - there's no need to also explain why it has no
+/// suggestion.
+pub fn build_report(
+    statuses: &[VMResourceStatus],
+    samples_by_vm: &BTreeMap<String, Vec<(f64, f64)>>,
+    underuse_threshold_pct: f64,
+    exempt_attribute: &str,
+) -> RightsizingReport {
+    let mut candidates = Vec::new();
+    let mut insufficient_data = Vec::new();
+    let mut exempt = Vec::new();
+
+    for vm in statuses {
+        if vm.attributes.contains_key(exempt_attribute) {
+            exempt.push(vm.name.clone());
+            continue;
+        }
+        let samples = match samples_by_vm.get(&vm.name) {
+            Some(samples) if !samples.is_empty() => samples,
+            _ => {
+                insufficient_data.push(vm.name.clone());
+                continue;
+            }
+        };
+        let peak_cpu_pct = samples.iter().map(|(cpu, _)| *cpu).fold(f64::MIN, f64::max);
+        let peak_memory_pct = samples.iter().map(|(_, mem)| *mem).fold(f64::MIN, f64::max);
+        if peak_cpu_pct >= underuse_threshold_pct || peak_memory_pct >= underuse_threshold_pct {
+            continue;
+        }
+
+        let current_vcpus = vm.cpu_count * vm.cores_per_socket;
+        let current_memory_gb = vm.memory_gb;
+        let suggested_vcpus = suggested_vcpus(current_vcpus);
+        let suggested_memory_gb = suggested_memory_gb(current_memory_gb);
+        candidates.push(RightsizingCandidate {
+            name: vm.name.clone(),
+            sample_count: samples.len(),
+            peak_cpu_pct,
+            peak_memory_pct,
+            current_vcpus,
+            current_memory_gb,
+            suggested_vcpus,
+            suggested_memory_gb,
+            reclaimable_vcpus: current_vcpus - suggested_vcpus,
+            reclaimable_memory_gb: current_memory_gb - suggested_memory_gb,
+        });
+    }
+
+    candidates.sort_by(|a, b| a.name.cmp(&b.name));
+    insufficient_data.sort();
+    exempt.sort();
+
+    let total_reclaimable_vcpus = candidates.iter().map(|c| c.reclaimable_vcpus).sum();
+    let total_reclaimable_memory_gb = candidates.iter().map(|c| c.reclaimable_memory_gb).sum();
+
+    RightsizingReport {
+        candidates,
+        insufficient_data,
+        exempt,
+        total_reclaimable_vcpus,
+        total_reclaimable_memory_gb,
+    }
+}
+
+/// Human-readable rendering for `--rightsizing-report --format text`.
+pub fn render_text(report: &RightsizingReport) -> String {
+    let mut out = format!(
+        "{} rightsizing candidate(s), {} exempt, {} with insufficient data\n",
+        report.candidates.len(),
+        report.exempt.len(),
+        report.insufficient_data.len()
+    );
+    for c in &report.candidates {
+        out.push_str(&format!(
+            "- {} ({} sample(s)): peak cpu={:.1}% mem={:.1}% -> {} vCPU(s) -> {} ({}->{} GB), reclaiming {} vCPU(s) + {:.0} GB\n",
+            c.name,
+            c.sample_count,
+            c.peak_cpu_pct,
+            c.peak_memory_pct,
+            c.current_vcpus,
+            c.suggested_vcpus,
+            c.current_memory_gb,
+            c.suggested_memory_gb,
+            c.reclaimable_vcpus,
+            c.reclaimable_memory_gb
+        ));
+    }
+    if !report.exempt.is_empty() {
+        out.push_str(&format!("Exempt: {}\n", report.exempt.join(", ")));
+    }
+    if !report.insufficient_data.is_empty() {
+        out.push_str(&format!("Insufficient data: {}\n", report.insufficient_data.join(", ")));
+    }
+    out.push_str(&format!(
+        "Estate-wide reclaimable: {} vCPU(s), {:.0} GB memory\n",
+        report.total_reclaimable_vcpus, report.total_reclaimable_memory_gb
+    ));
+    out
+}
+
+/// Renders `--rightsizing-report --format csv`: one row per candidate,
+/// same escaping as [`crate::report::export_csv_report`].
+pub fn render_csv(report: &RightsizingReport) -> String {
+    let mut out = String::from("name,sample_count,peak_cpu_pct,peak_memory_pct,current_vcpus,current_memory_gb,suggested_vcpus,suggested_memory_gb,reclaimable_vcpus,reclaimable_memory_gb\n");
+    for c in &report.candidates {
+        out.push_str(&format!(
+            "{},{},{:.1},{:.1},{},{},{},{},{},{}\n",
+            crate::report::csv_escape(&c.name),
+            c.sample_count,
+            c.peak_cpu_pct,
+            c.peak_memory_pct,
+            c.current_vcpus,
+            c.current_memory_gb,
+            c.suggested_vcpus,
+            c.suggested_memory_gb,
+            c.reclaimable_vcpus,
+            c.reclaimable_memory_gb
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::PowerState;
+    use std::collections::HashMap;
+
+    fn vm(name: &str, cpu_count: u32, cores_per_socket: u32, memory_gb: f64, attributes: HashMap<String, String>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 5.0,
+            memory_usage_pct: 5.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count,
+            cores_per_socket,
+            memory_gb,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes,
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 100.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn suggested_vcpus_halves_and_rounds_to_nearest() {
+        assert_eq!(suggested_vcpus(8), 4);
+        assert_eq!(suggested_vcpus(5), 3, "2.5 rounds up to 3, same as f64::round");
+        assert_eq!(suggested_vcpus(4), 2);
+        assert_eq!(suggested_vcpus(3), 2, "1.5 rounds up to 2");
+    }
+
+    #[test]
+    fn suggested_vcpus_never_goes_below_the_floor_or_above_current() {
+        assert_eq!(suggested_vcpus(1), 1, "already at the floor, nothing to reclaim");
+        assert_eq!(suggested_vcpus(2), 1);
+    }
+
+    #[test]
+    fn suggested_memory_gb_halves_and_floors() {
+        assert_eq!(suggested_memory_gb(16.0), 8.0);
+        assert_eq!(suggested_memory_gb(4.0), 2.0, "halves to exactly the floor");
+        assert_eq!(suggested_memory_gb(3.0), 2.0, "1.5 rounds up to 2, which is also the floor");
+        assert_eq!(suggested_memory_gb(2.0), 2.0, "already at the floor");
+        assert_eq!(suggested_memory_gb(1.0), 1.0, "below the floor already - left alone, not rounded up to it");
+    }
+
+    #[test]
+    fn underused_vm_with_enough_samples_is_a_candidate() {
+        let statuses = vec![vm("vm-idle", 4, 1, 16.0, HashMap::new())];
+        let mut samples = BTreeMap::new();
+        samples.insert("vm-idle".to_string(), vec![(5.0, 10.0), (8.0, 12.0)]);
+
+        let report = build_report(&statuses, &samples, DEFAULT_UNDERUSE_THRESHOLD_PCT, DEFAULT_EXEMPT_ATTRIBUTE);
+        assert!(report.insufficient_data.is_empty());
+        assert!(report.exempt.is_empty());
+        assert_eq!(report.candidates.len(), 1);
+        let c = &report.candidates[0];
+        assert_eq!(c.current_vcpus, 4);
+        assert_eq!(c.suggested_vcpus, 2);
+        assert_eq!(c.suggested_memory_gb, 8.0);
+        assert_eq!(report.total_reclaimable_vcpus, 2);
+        assert_eq!(report.total_reclaimable_memory_gb, 8.0);
+    }
+
+    #[test]
+    fn vm_with_peak_usage_at_or_above_threshold_is_not_a_candidate() {
+        let statuses = vec![vm("vm-busy", 4, 1, 16.0, HashMap::new())];
+        let mut samples = BTreeMap::new();
+        samples.insert("vm-busy".to_string(), vec![(5.0, 10.0), (25.0, 12.0)]);
+
+        let report = build_report(&statuses, &samples, DEFAULT_UNDERUSE_THRESHOLD_PCT, DEFAULT_EXEMPT_ATTRIBUTE);
+        assert!(report.candidates.is_empty());
+        assert!(report.insufficient_data.is_empty());
+        assert!(report.exempt.is_empty());
+    }
+
+    #[test]
+    fn vm_with_no_samples_is_insufficient_data_not_a_candidate() {
+        let statuses = vec![vm("vm-unknown", 4, 1, 16.0, HashMap::new())];
+        let samples = BTreeMap::new();
+
+        let report = build_report(&statuses, &samples, DEFAULT_UNDERUSE_THRESHOLD_PCT, DEFAULT_EXEMPT_ATTRIBUTE);
+        assert!(report.candidates.is_empty());
+        assert_eq!(report.insufficient_data, vec!["vm-unknown".to_string()]);
+    }
+
+    #[test]
+    fn exempt_vm_is_listed_separately_even_when_it_would_otherwise_qualify() {
+        let mut attrs = HashMap::new();
+        attrs.insert(DEFAULT_EXEMPT_ATTRIBUTE.to_string(), "dr-standby".to_string());
+        let statuses = vec![vm("vm-standby", 4, 1, 16.0, attrs)];
+        let mut samples = BTreeMap::new();
+        samples.insert("vm-standby".to_string(), vec![(1.0, 1.0)]);
+
+        let report = build_report(&statuses, &samples, DEFAULT_UNDERUSE_THRESHOLD_PCT, DEFAULT_EXEMPT_ATTRIBUTE);
+        assert!(report.candidates.is_empty());
+        assert!(report.insufficient_data.is_empty());
+        assert_eq!(report.exempt, vec!["vm-standby".to_string()]);
+    }
+
+    #[test]
+    fn live_samples_builds_one_sample_per_vm_from_the_current_fetch() {
+        let statuses = vec![vm("vm-a", 2, 1, 8.0, HashMap::new())];
+        let samples = live_samples(&statuses);
+        assert_eq!(samples.get("vm-a"), Some(&vec![(5.0, 5.0)]));
+    }
+}
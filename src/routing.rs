@@ -0,0 +1,131 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::vm::VMResourceStatus;
+
+/// Maps attribute values (e.g. `Owner` names) to notification channels, so
+/// notifier backends (Slack/email/webhook) can deliver each owner only their
+/// own problem VMs. VMs missing the attribute, or whose value isn't in
+/// `mapping`, fall back to `default_channel`.
+#[derive(Debug, Deserialize)]
+pub struct RouteConfig {
+    pub mapping: BTreeMap<String, String>,
+    pub default_channel: String,
+}
+
+impl RouteConfig {
+    pub fn load(path: &str, strict_json: bool) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading route config {path}"))?;
+        crate::strict_json::parse(&raw, &format!("route config {path}"), strict_json, &["mapping", "default_channel"])
+    }
+
+    fn channel_for(&self, attribute_value: Option<&str>) -> &str {
+        attribute_value
+            .and_then(|v| self.mapping.get(v))
+            .map(String::as_str)
+            .unwrap_or(&self.default_channel)
+    }
+}
+
+/// Groups VMs that currently have issues by the notification channel their
+/// `attribute_key` (e.g. `"Owner"`) attribute routes to, per `config`.
+/// Independent of any real notifier so it can be unit tested on its own.
+pub fn route_problem_vms<'a>(
+    statuses: &'a [VMResourceStatus],
+    attribute_key: &str,
+    config: &RouteConfig,
+) -> BTreeMap<String, Vec<&'a VMResourceStatus>> {
+    let mut grouped: BTreeMap<String, Vec<&VMResourceStatus>> = BTreeMap::new();
+    for vm in statuses.iter().filter(|v| v.has_issues()) {
+        let attribute_value = vm.attributes.get(attribute_key).map(String::as_str);
+        let channel = config.channel_for(attribute_value);
+        grouped.entry(channel.to_string()).or_default().push(vm);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState, VMIssueType};
+    use std::collections::HashMap;
+
+    fn vm(name: &str, owner: Option<&str>, has_issue: bool) -> VMResourceStatus {
+        let mut attributes = HashMap::new();
+        if let Some(owner) = owner {
+            attributes.insert("Owner".to_string(), owner.to_string());
+        }
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes,
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: if has_issue {
+                vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")]
+            } else {
+                vec![]
+            },
+        }
+    }
+
+    fn config() -> RouteConfig {
+        RouteConfig {
+            mapping: BTreeMap::from([
+                ("alice".to_string(), "#team-alice".to_string()),
+                ("bob".to_string(), "#team-bob".to_string()),
+            ]),
+            default_channel: "#default".to_string(),
+        }
+    }
+
+    #[test]
+    fn groups_by_owner_and_falls_back_to_default() {
+        let statuses = vec![
+            vm("vm-1", Some("alice"), true),
+            vm("vm-2", Some("carol"), true),
+            vm("vm-3", None, true),
+            vm("vm-4", Some("alice"), false),
+        ];
+        let grouped = route_problem_vms(&statuses, "Owner", &config());
+
+        assert_eq!(grouped.get("#team-alice").unwrap().len(), 1);
+        assert_eq!(grouped.get("#default").unwrap().len(), 2);
+        assert!(!grouped.contains_key("#team-bob"));
+        assert_eq!(grouped.values().map(|v| v.len()).sum::<usize>(), 3);
+    }
+}
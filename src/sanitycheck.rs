@@ -0,0 +1,152 @@
+//! `--sanity-check-thresholds`: after a run, flags any enabled,
+//! user-configured threshold that never flagged a single VM - the
+//! signature of a threshold set so loose it's a no-op (a typo'd
+//! `--min-hw-version 0`, a `--max-migrations` nobody could ever exceed)
+//! rather than proof the fleet is actually clean. Purely advisory: it
+//! reads `issues` already attached by `crate::vcenter::detect_issues` and
+//! never changes them, `--fail-on-issues`, or `--fail-below-score`.
+//!
+//! `--max-suspend-hours` is deliberately not covered here - a quiet run
+//! just as often means nothing was suspended at all, which isn't a sign of
+//! a misconfigured threshold.
+
+use crate::vcenter::DetectionOptions;
+use crate::vm::{VMIssueType, VMResourceStatus};
+
+struct ThresholdCheck {
+    flag: &'static str,
+    issue_type: VMIssueType,
+    enabled: bool,
+    configured_value: String,
+}
+
+/// One line per enabled threshold in `options` that no VM in `statuses`
+/// ever triggered, naming the flag and the value it's set to.
+pub fn unapproached_thresholds(statuses: &[VMResourceStatus], options: &DetectionOptions) -> Vec<String> {
+    let checks = [
+        ThresholdCheck {
+            flag: "--clock-skew-threshold-secs",
+            issue_type: VMIssueType::ClockSkew,
+            enabled: options.check_clock,
+            configured_value: options.clock_skew_threshold_secs.to_string(),
+        },
+        ThresholdCheck {
+            flag: "--max-vcpu-ratio",
+            issue_type: VMIssueType::OverAllocatedCpu,
+            enabled: options.check_vcpu_allocation,
+            configured_value: options.max_vcpu_ratio.to_string(),
+        },
+        ThresholdCheck {
+            flag: "--max-migrations",
+            issue_type: VMIssueType::ExcessiveMigrations,
+            enabled: options.check_migrations,
+            configured_value: options.max_migrations.to_string(),
+        },
+        ThresholdCheck {
+            flag: "--short-uptime-threshold-secs",
+            issue_type: VMIssueType::UptimeShort,
+            enabled: options.check_uptime,
+            configured_value: options.short_uptime_threshold_secs.to_string(),
+        },
+        ThresholdCheck {
+            flag: "--min-hw-version",
+            issue_type: VMIssueType::HardwareVersionOld,
+            enabled: options.check_hw_version,
+            configured_value: options.min_hw_version.to_string(),
+        },
+    ];
+
+    checks
+        .into_iter()
+        .filter(|check| check.enabled)
+        .filter(|check| !statuses.iter().any(|vm| vm.issues.iter().any(|issue| issue.issue_type == check.issue_type)))
+        .map(|check| format!("{} is set to {} but never flagged a VM this run - double check it isn't too loose to catch anything", check.flag, check.configured_value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState};
+    use std::collections::HashMap;
+
+    fn vm_with_issues(issues: Vec<VMIssueType>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues: issues.into_iter().map(|issue_type| DetectedIssue::measured(issue_type, 0.0, 0.0, "x")).collect(),
+        }
+    }
+
+    #[test]
+    fn disabled_checks_are_never_reported() {
+        let warnings = unapproached_thresholds(&[vm_with_issues(Vec::new())], &DetectionOptions::default());
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn enabled_check_that_never_fired_is_reported() {
+        let options = DetectionOptions { check_hw_version: true, min_hw_version: 999, ..Default::default() };
+        let warnings = unapproached_thresholds(&[vm_with_issues(Vec::new())], &options);
+        assert!(warnings.iter().any(|w| w.starts_with("--min-hw-version")), "{warnings:?}");
+    }
+
+    #[test]
+    fn enabled_check_that_fired_at_least_once_is_not_reported() {
+        let options = DetectionOptions { check_hw_version: true, min_hw_version: 999, ..Default::default() };
+        let warnings = unapproached_thresholds(&[vm_with_issues(vec![VMIssueType::HardwareVersionOld])], &options);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn max_suspend_hours_is_not_among_the_checks() {
+        let options = DetectionOptions { max_suspend_hours: Some(1.0), ..Default::default() };
+        let warnings = unapproached_thresholds(&[vm_with_issues(Vec::new())], &options);
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn multiple_enabled_unfired_checks_are_all_reported() {
+        let options = DetectionOptions {
+            check_clock: true,
+            check_migrations: true,
+            ..Default::default()
+        };
+        let warnings = unapproached_thresholds(&[vm_with_issues(Vec::new())], &options);
+        assert_eq!(warnings.len(), 2);
+    }
+}
@@ -0,0 +1,181 @@
+//! A single weighted health number per VM and per run, for management-style
+//! consumers that want one figure rather than an issue list. Each
+//! [`VMIssueType`] carries a [`default_weight`] (overridable via
+//! `--score-weights`); a VM's score is 100 minus the sum of weights of its
+//! current issues, floored at 0. [`annotate_health_scores`] must run after
+//! every issue-mutating step (disabled-issue stripping, DRS/boot-storm
+//! flagging) so [`VMResourceStatus::health_score`] reflects the issues the
+//! rest of the report actually shows.
+
+use std::collections::HashMap;
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::vm::{DetectedIssue, PowerState, Severity, VMIssueType, VMResourceStatus};
+
+/// Default per-issue-type weight, absent an override from `--score-weights`:
+/// tied to [`VMIssueType::severity`] so a single critical issue dents the
+/// score hard while an informational advisory barely moves it.
+pub fn default_weight(issue_type: VMIssueType) -> f64 {
+    match issue_type.severity() {
+        Severity::Critical => 30.0,
+        Severity::Warning => 15.0,
+        Severity::Informational => 5.0,
+    }
+}
+
+/// `--score-weights` config: overrides [`default_weight`] for the issue
+/// types it names, using the same codes `--disable-issues` does (e.g.
+/// `"HIGH_CPU_USAGE"`); every type it doesn't name keeps its default.
+#[derive(Debug, Deserialize)]
+struct RawScoreWeightsConfig {
+    #[serde(default)]
+    weights: HashMap<String, f64>,
+}
+
+/// Loads `--score-weights`'s config at `path`, resolving its codes against
+/// [`VMIssueType::from_str`]. An unknown code is an error rather than a
+/// silently-ignored override.
+pub fn load_weight_overrides(path: &str, strict_json: bool) -> Result<HashMap<VMIssueType, f64>> {
+    let raw = fs::read_to_string(path).with_context(|| format!("reading score weights config {path}"))?;
+    let parsed: RawScoreWeightsConfig = crate::strict_json::parse(&raw, &format!("score weights config {path}"), strict_json, &["weights"])?;
+    parsed
+        .weights
+        .into_iter()
+        .map(|(code, weight)| {
+            code.parse::<VMIssueType>()
+                .map(|issue_type| (issue_type, weight))
+                .map_err(|err| anyhow::anyhow!("score weights config {path}: {err}"))
+        })
+        .collect()
+}
+
+/// A single VM's health score: 100 minus the sum of `issues`' weights
+/// (`overrides` first, [`default_weight`] otherwise), floored at 0. Pure and
+/// deterministic - the same issues and overrides always score the same, so
+/// it's safe to recompute against `--replay` output and compare across runs.
+pub fn vm_score(issues: &[DetectedIssue], overrides: &HashMap<VMIssueType, f64>) -> f64 {
+    let penalty: f64 = issues
+        .iter()
+        .map(|issue| overrides.get(&issue.issue_type).copied().unwrap_or_else(|| default_weight(issue.issue_type)))
+        .sum();
+    (100.0 - penalty).max(0.0)
+}
+
+/// Sets every VM's `health_score` from its current `issues`. Run this once,
+/// after every issue-mutating step for the run has finished - anything that
+/// adds or strips issues after this point leaves `health_score` stale.
+pub fn annotate_health_scores(statuses: &mut [VMResourceStatus], overrides: &HashMap<VMIssueType, f64>) {
+    for vm in statuses {
+        vm.health_score = vm_score(&vm.issues, overrides);
+    }
+}
+
+/// Run-level score: the average `health_score` across powered-on VMs (a
+/// powered-off VM isn't part of the fleet's operational health). `None` when
+/// there are no powered-on VMs to average. VMs must already be annotated via
+/// [`annotate_health_scores`]; this only aggregates.
+pub fn run_score(statuses: &[VMResourceStatus]) -> Option<f64> {
+    let scores: Vec<f64> = statuses
+        .iter()
+        .filter(|vm| vm.power_state == PowerState::PoweredOn)
+        .map(|vm| vm.health_score)
+        .collect();
+    if scores.is_empty() {
+        None
+    } else {
+        Some(scores.iter().sum::<f64>() / scores.len() as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_healthy_vm_scores_100() {
+        assert_eq!(vm_score(&[], &HashMap::new()), 100.0);
+    }
+
+    #[test]
+    fn everything_broken_vm_floors_at_0() {
+        let issues = vec![
+            DetectedIssue::new(VMIssueType::ToolsNotRunning, "x"),
+            DetectedIssue::new(VMIssueType::Unresponsive, "x"),
+            DetectedIssue::new(VMIssueType::BootStorm, "x"),
+            DetectedIssue::new(VMIssueType::DrsRuleViolation, "x"),
+        ];
+        assert_eq!(vm_score(&issues, &HashMap::new()), 0.0);
+    }
+
+    #[test]
+    fn overrides_replace_the_default_weight_for_named_types() {
+        let issues = vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")];
+        let overrides = HashMap::from([(VMIssueType::HighCpuUsage, 40.0)]);
+        assert_eq!(vm_score(&issues, &overrides), 60.0);
+    }
+
+    fn vm(power_state: PowerState, health_score: f64) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: std::collections::HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score,
+            change_version: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn run_score_averages_only_powered_on_vms() {
+        let statuses = vec![vm(PowerState::PoweredOn, 80.0), vm(PowerState::PoweredOn, 60.0), vm(PowerState::PoweredOff, 0.0)];
+        assert_eq!(run_score(&statuses), Some(70.0));
+    }
+
+    #[test]
+    fn run_score_is_none_with_no_powered_on_vms() {
+        let statuses = vec![vm(PowerState::PoweredOff, 0.0)];
+        assert_eq!(run_score(&statuses), None);
+    }
+
+    #[test]
+    fn annotate_health_scores_sets_every_vms_score() {
+        let mut statuses = vec![vm(PowerState::PoweredOn, 0.0)];
+        statuses[0].issues.push(DetectedIssue::new(VMIssueType::HighCpuUsage, "x"));
+        annotate_health_scores(&mut statuses, &HashMap::new());
+        assert_eq!(statuses[0].health_score, 85.0);
+    }
+}
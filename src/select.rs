@@ -0,0 +1,631 @@
+//! `--select`: a small boolean expression language for picking VMs out of
+//! the fetched fleet by `cluster`, `power` (`on`/`off`), `name`, `folder`,
+//! or `tag`, e.g. `cluster == "prod-b" && tag contains "web" && !(power == off)`.
+//!
+//! There's no distinct vCenter tag-assignment model in this inventory (see
+//! [`crate::vm::VMResourceStatus::attributes`]) - `tag` matches against the
+//! *values* of a VM's custom attributes rather than a real tag list, since
+//! that's the closest thing this codebase simulates.
+//!
+//! [`parse`] builds an [`Expr`] once; [`evaluate`] runs it against each VM.
+//! [`explain`] is `--explain-selection`'s diagnostic: for a VM the
+//! expression excludes, it names the specific comparison that failed.
+
+use std::fmt;
+
+use regex::Regex;
+
+use crate::report::folder_of;
+use crate::vm::{PowerState, VMResourceStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Cluster,
+    Tag,
+    Power,
+    Name,
+    Folder,
+}
+
+impl Field {
+    fn from_ident(s: &str) -> Option<Field> {
+        match s {
+            "cluster" => Some(Field::Cluster),
+            "tag" => Some(Field::Tag),
+            "power" => Some(Field::Power),
+            "name" => Some(Field::Name),
+            "folder" => Some(Field::Folder),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Field::Cluster => "cluster",
+            Field::Tag => "tag",
+            Field::Power => "power",
+            Field::Name => "name",
+            Field::Folder => "folder",
+        };
+        write!(f, "{s}")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Contains,
+    Matches,
+}
+
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Contains => "contains",
+            CompareOp::Matches => "=~",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// A parsed `--select` expression. Built by [`parse`]; evaluated per-VM by
+/// [`evaluate`]/[`explain`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Compare { field: Field, op: CompareOp, value: String },
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Compare { field: Field::Power, op, value } => write!(f, "{field} {op} {value}", field = Field::Power),
+            Expr::Compare { field, op, value } => write!(f, "{field} {op} \"{value}\""),
+            Expr::And(l, r) => write!(f, "{l} && {r}"),
+            Expr::Or(l, r) => write!(f, "{l} || {r}"),
+            Expr::Not(e) => write!(f, "!({e})"),
+        }
+    }
+}
+
+/// A `--select` syntax or semantic error, with the 0-based character
+/// position it was found at so the CLI can point at the offending spot in
+/// the expression rather than just naming a token.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectError {
+    pub message: String,
+    pub position: usize,
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at character {})", self.message, self.position + 1)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Ident(String),
+    Str(String),
+    EqEq,
+    NotEq,
+    AndAnd,
+    OrOr,
+    Not,
+    TildeMatch,
+    LParen,
+    RParen,
+    Eof,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    position: usize,
+}
+
+fn lex(input: &str) -> Result<Vec<Token>, SelectError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\n' | '\r' => i += 1,
+            '(' => {
+                tokens.push(Token { kind: TokenKind::LParen, position: i });
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token { kind: TokenKind::RParen, position: i });
+                i += 1;
+            }
+            '"' => {
+                let start = i;
+                i += 1;
+                let mut value = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => return Err(SelectError { message: "unterminated string literal".to_string(), position: start }),
+                        Some('"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                            value.push('"');
+                            i += 2;
+                        }
+                        Some(ch) => {
+                            value.push(*ch);
+                            i += 1;
+                        }
+                    }
+                }
+                tokens.push(Token { kind: TokenKind::Str(value), position: start });
+            }
+            '=' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token { kind: TokenKind::EqEq, position: i });
+                i += 2;
+            }
+            '=' if chars.get(i + 1) == Some(&'~') => {
+                tokens.push(Token { kind: TokenKind::TildeMatch, position: i });
+                i += 2;
+            }
+            '!' if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token { kind: TokenKind::NotEq, position: i });
+                i += 2;
+            }
+            '!' => {
+                tokens.push(Token { kind: TokenKind::Not, position: i });
+                i += 1;
+            }
+            '&' if chars.get(i + 1) == Some(&'&') => {
+                tokens.push(Token { kind: TokenKind::AndAnd, position: i });
+                i += 2;
+            }
+            '|' if chars.get(i + 1) == Some(&'|') => {
+                tokens.push(Token { kind: TokenKind::OrOr, position: i });
+                i += 2;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                let mut ident = String::new();
+                while let Some(&c) = chars.get(i) {
+                    if c.is_alphanumeric() || c == '_' || c == '-' {
+                        ident.push(c);
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token { kind: TokenKind::Ident(ident), position: start });
+            }
+            other => return Err(SelectError { message: format!("unexpected character '{other}'"), position: i }),
+        }
+    }
+    tokens.push(Token { kind: TokenKind::Eof, position: chars.len() });
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.pos].clone();
+        if self.pos < self.tokens.len() - 1 {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_and()?;
+        while matches!(self.peek().kind, TokenKind::OrOr) {
+            self.advance();
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, SelectError> {
+        let mut left = self.parse_unary()?;
+        while matches!(self.peek().kind, TokenKind::AndAnd) {
+            self.advance();
+            let right = self.parse_unary()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, SelectError> {
+        if matches!(self.peek().kind, TokenKind::Not) {
+            self.advance();
+            let inner = self.parse_unary()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, SelectError> {
+        if matches!(self.peek().kind, TokenKind::LParen) {
+            self.advance();
+            let inner = self.parse_or()?;
+            match self.peek().kind {
+                TokenKind::RParen => {
+                    self.advance();
+                    Ok(inner)
+                }
+                _ => Err(SelectError { message: "expected ')'".to_string(), position: self.peek().position }),
+            }
+        } else {
+            self.parse_compare()
+        }
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, SelectError> {
+        let field_token = self.advance();
+        let field = match &field_token.kind {
+            TokenKind::Ident(s) => Field::from_ident(s).ok_or_else(|| SelectError {
+                message: format!("unknown field '{s}' (expected cluster, tag, power, name, or folder)"),
+                position: field_token.position,
+            })?,
+            _ => {
+                return Err(SelectError {
+                    message: "expected a field name (cluster, tag, power, name, or folder)".to_string(),
+                    position: field_token.position,
+                })
+            }
+        };
+
+        let op_token = self.advance();
+        let op = match &op_token.kind {
+            TokenKind::EqEq => CompareOp::Eq,
+            TokenKind::NotEq => CompareOp::Ne,
+            TokenKind::TildeMatch => CompareOp::Matches,
+            TokenKind::Ident(s) if s == "contains" => CompareOp::Contains,
+            _ => {
+                return Err(SelectError {
+                    message: "expected a comparison operator (==, !=, contains, =~)".to_string(),
+                    position: op_token.position,
+                })
+            }
+        };
+
+        let value_token = self.advance();
+        let value = match &value_token.kind {
+            TokenKind::Str(s) => s.clone(),
+            TokenKind::Ident(s) => s.clone(),
+            _ => {
+                return Err(SelectError { message: "expected a value".to_string(), position: value_token.position });
+            }
+        };
+
+        if field == Field::Power {
+            if !matches!(op, CompareOp::Eq | CompareOp::Ne) {
+                return Err(SelectError {
+                    message: format!("'{op}' is not valid for field 'power' (use == or !=)"),
+                    position: op_token.position,
+                });
+            }
+            if value != "on" && value != "off" {
+                return Err(SelectError {
+                    message: format!("power: value must be 'on' or 'off', got '{value}'"),
+                    position: value_token.position,
+                });
+            }
+        }
+
+        if op == CompareOp::Matches {
+            if let Err(err) = Regex::new(&value) {
+                return Err(SelectError { message: format!("invalid regex '{value}': {err}"), position: value_token.position });
+            }
+        }
+
+        Ok(Expr::Compare { field, op, value })
+    }
+
+    fn expect_eof(&self) -> Result<(), SelectError> {
+        match self.peek().kind {
+            TokenKind::Eof => Ok(()),
+            _ => Err(SelectError { message: "unexpected trailing input".to_string(), position: self.peek().position }),
+        }
+    }
+}
+
+/// Parses a `--select` expression. `Err` points at the exact character the
+/// expression is invalid at.
+pub fn parse(input: &str) -> Result<Expr, SelectError> {
+    let tokens = lex(input)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+    parser.expect_eof()?;
+    Ok(expr)
+}
+
+fn compare_str(op: CompareOp, actual: &str, value: &str) -> bool {
+    match op {
+        CompareOp::Eq => actual == value,
+        CompareOp::Ne => actual != value,
+        CompareOp::Contains => actual.contains(value),
+        CompareOp::Matches => Regex::new(value).expect("regex validated at parse time").is_match(actual),
+    }
+}
+
+fn evaluate_compare(field: Field, op: CompareOp, value: &str, vm: &VMResourceStatus) -> bool {
+    match field {
+        Field::Cluster => compare_str(op, &vm.cluster, value),
+        Field::Name => compare_str(op, &vm.name, value),
+        Field::Folder => compare_str(op, folder_of(&vm.inventory_path), value),
+        Field::Power => {
+            let is_on = vm.power_state == PowerState::PoweredOn;
+            match op {
+                CompareOp::Eq => is_on == (value == "on"),
+                CompareOp::Ne => is_on != (value == "on"),
+                CompareOp::Contains | CompareOp::Matches => unreachable!("parse rejects contains/=~ for field 'power'"),
+            }
+        }
+        Field::Tag => match op {
+            CompareOp::Eq => vm.attributes.values().any(|v| v == value),
+            CompareOp::Ne => !vm.attributes.values().any(|v| v == value),
+            CompareOp::Contains => vm.attributes.values().any(|v| v.contains(value)),
+            CompareOp::Matches => {
+                let re = Regex::new(value).expect("regex validated at parse time");
+                vm.attributes.values().any(|v| re.is_match(v))
+            }
+        },
+    }
+}
+
+/// Whether `vm` is included by `expr`.
+pub fn evaluate(expr: &Expr, vm: &VMResourceStatus) -> bool {
+    match expr {
+        Expr::Compare { field, op, value } => evaluate_compare(*field, *op, value, vm),
+        Expr::And(l, r) => evaluate(l, vm) && evaluate(r, vm),
+        Expr::Or(l, r) => evaluate(l, vm) || evaluate(r, vm),
+        Expr::Not(e) => !evaluate(e, vm),
+    }
+}
+
+fn first_failing_clause(expr: &Expr, vm: &VMResourceStatus) -> String {
+    match expr {
+        Expr::And(l, r) => {
+            if !evaluate(l, vm) {
+                first_failing_clause(l, vm)
+            } else {
+                first_failing_clause(r, vm)
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+/// `--explain-selection`'s diagnostic: `None` when `expr` includes `vm`,
+/// otherwise the most specific sub-clause (narrowed through `&&`) that
+/// excluded it.
+pub fn explain(expr: &Expr, vm: &VMResourceStatus) -> Option<String> {
+    if evaluate(expr, vm) {
+        None
+    } else {
+        Some(first_failing_clause(expr, vm))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn vm() -> VMResourceStatus {
+        VMResourceStatus {
+            name: "web-01".to_string(),
+            host: "esx-1.example.com".to_string(),
+            cluster: "prod-a".to_string(),
+            inventory_path: "/DC1/vm/prod-a/team-web/web-01".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 8.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::from([("Owner".to_string(), "team-web".to_string())]),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 100000.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            issues: Vec::new(),
+            health_score: 100.0,
+            change_version: 0,
+        }
+    }
+
+    #[test]
+    fn parses_and_evaluates_a_simple_equality() {
+        let expr = parse(r#"cluster == "prod-a""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse(r#"cluster == "prod-b""#).unwrap();
+        assert!(!evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn not_equal_is_the_inverse_of_equal() {
+        let expr = parse(r#"cluster != "prod-b""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn tag_matches_an_attribute_value_not_a_key() {
+        let expr = parse(r#"tag == "team-web""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse(r#"tag == "Owner""#).unwrap();
+        assert!(!evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn tag_contains_is_an_attribute_value_substring_match() {
+        let expr = parse(r#"tag contains "web""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn name_contains_is_a_substring_match() {
+        let expr = parse(r#"name contains "eb-0""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn power_on_and_off_match_the_power_state() {
+        let expr = parse("power == on").unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse("power == off").unwrap();
+        assert!(!evaluate(&expr, &vm()));
+        let expr = parse("power != off").unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn folder_is_derived_from_the_inventory_path() {
+        let expr = parse(r#"folder == "/DC1/vm/prod-a/team-web""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn regex_match_tests_against_a_compiled_pattern() {
+        let expr = parse(r#"name =~ "^web-[0-9]+$""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse(r#"name =~ "^db-""#).unwrap();
+        assert!(!evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn and_requires_both_sides() {
+        let expr = parse(r#"cluster == "prod-a" && power == on"#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse(r#"cluster == "prod-a" && power == off"#).unwrap();
+        assert!(!evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn or_requires_either_side() {
+        let expr = parse(r#"cluster == "prod-b" || power == on"#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn not_inverts_a_parenthesized_expression() {
+        let expr = parse(r#"!(cluster == "prod-b")"#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        // If || bound tighter this would be cluster == "prod-b" && (power == on || name == "web-01"),
+        // which is false because the cluster doesn't match; with && binding
+        // tighter it is (cluster == "prod-b" && power == on) || name == "web-01",
+        // which holds through the `name` clause.
+        let expr = parse(r#"cluster == "prod-b" && power == on || name == "web-01""#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn parentheses_override_default_precedence() {
+        let expr = parse(r#"(cluster == "prod-b" || name == "web-01") && power == on"#).unwrap();
+        assert!(evaluate(&expr, &vm()));
+        let expr = parse(r#"cluster == "prod-b" || (name == "web-01" && power == off)"#).unwrap();
+        assert!(!evaluate(&expr, &vm()));
+    }
+
+    #[test]
+    fn unknown_field_is_rejected_with_its_position() {
+        let err = parse(r#"region == "us-east""#).unwrap_err();
+        assert!(err.message.contains("unknown field 'region'"));
+        assert_eq!(err.position, 0);
+    }
+
+    #[test]
+    fn missing_operator_is_rejected_with_its_position() {
+        let err = parse(r#"cluster "prod-a""#).unwrap_err();
+        assert_eq!(err.position, 8);
+    }
+
+    #[test]
+    fn unterminated_string_is_rejected_at_the_opening_quote() {
+        let err = parse(r#"cluster == "prod-a"#).unwrap_err();
+        assert_eq!(err.position, 11);
+    }
+
+    #[test]
+    fn invalid_regex_is_rejected_at_the_value() {
+        let err = parse(r#"name =~ "(unclosed""#).unwrap_err();
+        assert_eq!(err.position, 8);
+    }
+
+    #[test]
+    fn power_value_must_be_on_or_off() {
+        let err = parse(r#"power == "sideways""#).unwrap_err();
+        assert!(err.message.contains("must be 'on' or 'off'"));
+    }
+
+    #[test]
+    fn contains_is_not_valid_for_power() {
+        let err = parse(r#"power contains "on""#).unwrap_err();
+        assert!(err.message.contains("not valid for field 'power'"));
+    }
+
+    #[test]
+    fn unexpected_trailing_input_is_rejected() {
+        let err = parse(r#"cluster == "prod-a" )"#).unwrap_err();
+        assert_eq!(err.position, 20);
+    }
+
+    #[test]
+    fn explain_is_none_when_the_vm_is_included() {
+        let expr = parse(r#"cluster == "prod-a""#).unwrap();
+        assert_eq!(explain(&expr, &vm()), None);
+    }
+
+    #[test]
+    fn explain_names_the_failing_clause_in_an_and_chain() {
+        let expr = parse(r#"cluster == "prod-a" && power == off"#).unwrap();
+        assert_eq!(explain(&expr, &vm()), Some("power == off".to_string()));
+    }
+
+    #[test]
+    fn explain_names_the_whole_clause_when_it_is_not_an_and_chain() {
+        let expr = parse(r#"cluster == "prod-b" || power == off"#).unwrap();
+        assert_eq!(explain(&expr, &vm()), Some(r#"cluster == "prod-b" || power == off"#.to_string()));
+    }
+}
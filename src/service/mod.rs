@@ -0,0 +1,9 @@
+//! OS service integration: systemd readiness/liveness notifications on Linux,
+//! and Windows Service Control Manager registration on Windows. Both are
+//! no-ops when the process isn't actually running under the respective
+//! supervisor.
+
+pub mod notify;
+
+#[cfg(windows)]
+pub mod windows;
@@ -0,0 +1,114 @@
+//! systemd readiness/liveness signaling for watch mode.
+//!
+//! Sends `READY=1` after the first successful authentication, `WATCHDOG=1` after every
+//! completed poll (so `WatchdogSec=` in the unit file can restart a hung process), and a
+//! `STATUS=` line with the latest summary. All of this is a no-op when `NOTIFY_SOCKET` is
+//! unset, i.e. when not running under systemd, or when the `systemd` feature is disabled.
+
+#[cfg(feature = "systemd")]
+mod imp {
+    use std::env;
+    use std::os::unix::net::UnixDatagram;
+
+    use sd_notify::NotifyState;
+
+    /// Thin wrapper around `sd_notify::notify` that can be pointed at an explicit
+    /// socket path, so tests don't need a real systemd supervisor.
+    pub struct Notifier {
+        socket_path: Option<String>,
+    }
+
+    impl Notifier {
+        /// Picks up `NOTIFY_SOCKET` from the environment, as systemd sets it.
+        pub fn from_env() -> Self {
+            Self {
+                socket_path: env::var("NOTIFY_SOCKET").ok(),
+            }
+        }
+
+        /// Used by tests to bypass the environment and write to a known socket.
+        #[cfg(test)]
+        pub fn with_socket_path(path: impl Into<String>) -> Self {
+            Self {
+                socket_path: Some(path.into()),
+            }
+        }
+
+        fn send_raw(&self, payload: &str) -> anyhow::Result<()> {
+            let Some(path) = &self.socket_path else {
+                return Ok(());
+            };
+            let socket = UnixDatagram::unbound()?;
+            socket.send_to(payload.as_bytes(), path)?;
+            Ok(())
+        }
+
+        pub fn ready(&self) -> anyhow::Result<()> {
+            self.send_raw(&NotifyState::Ready.to_string())
+        }
+
+        pub fn watchdog(&self) -> anyhow::Result<()> {
+            self.send_raw(&NotifyState::Watchdog.to_string())
+        }
+
+        pub fn status(&self, message: &str) -> anyhow::Result<()> {
+            self.send_raw(&NotifyState::Status(message).to_string())
+        }
+    }
+}
+
+#[cfg(not(feature = "systemd"))]
+mod imp {
+    /// No-op stand-in when the `systemd` feature is disabled, so callers don't
+    /// need to sprinkle `#[cfg(feature = "systemd")]` everywhere.
+    pub struct Notifier;
+
+    impl Notifier {
+        pub fn from_env() -> Self {
+            Self
+        }
+
+        pub fn ready(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        pub fn watchdog(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        pub fn status(&self, _message: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+}
+
+pub use imp::Notifier;
+
+#[cfg(all(test, feature = "systemd", unix))]
+mod tests {
+    use super::imp::Notifier;
+    use std::os::unix::net::UnixDatagram;
+
+    #[test]
+    fn ready_and_watchdog_are_sent_to_injected_socket() {
+        let dir = std::env::temp_dir().join(format!("network-notify-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket_path = dir.join("notify.sock");
+        let listener = UnixDatagram::bind(&socket_path).unwrap();
+
+        let notifier = Notifier::with_socket_path(socket_path.to_str().unwrap());
+        notifier.ready().unwrap();
+        notifier.watchdog().unwrap();
+        notifier.status("312 VMs, 17 issues, last poll 14:02").unwrap();
+
+        let mut buf = [0u8; 256];
+        let (n, _) = listener.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"READY=1");
+        let (n, _) = listener.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"WATCHDOG=1");
+        let (n, _) = listener.recv_from(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"STATUS=312 VMs, 17 issues, last poll 14:02");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
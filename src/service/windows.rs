@@ -0,0 +1,81 @@
+//! Windows Service Control Manager integration for `--service` mode.
+//!
+//! Registers the monitor as a service so SCM can stop it cleanly: SCM sends a stop
+//! control, we flip an `AtomicBool` that the service's wait loop polls, and the
+//! service then reports itself stopped. That loop runs no vCenter polls;
+//! [`crate::watch::run_watch_mode`] is not started under `--service`.
+
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use windows_service::service::{
+    ServiceControl, ServiceControlAccept, ServiceExitCode, ServiceState, ServiceStatus,
+    ServiceType,
+};
+use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+use windows_service::{define_windows_service, service_dispatcher};
+
+const SERVICE_NAME: &str = "network-monitor";
+const SERVICE_TYPE: ServiceType = ServiceType::OWN_PROCESS;
+
+define_windows_service!(ffi_service_main, service_main);
+
+/// Entry point used by `--service`. Blocks for the lifetime of the service;
+/// returns once SCM has told us to stop.
+pub fn run() -> windows_service::Result<()> {
+    service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+}
+
+fn service_main(_arguments: Vec<OsString>) {
+    if let Err(err) = run_service() {
+        eprintln!("network-monitor service failed: {err}");
+    }
+}
+
+fn run_service() -> windows_service::Result<()> {
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    let stop_requested_handler = stop_requested.clone();
+
+    let event_handler = move |control_event| -> ServiceControlHandlerResult {
+        match control_event {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                stop_requested_handler.store(true, Ordering::SeqCst);
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        }
+    };
+
+    let status_handle = service_control_handler::register(SERVICE_NAME, event_handler)?;
+
+    status_handle.set_service_status(ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state: ServiceState::Running,
+        controls_accepted: ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN,
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })?;
+
+    while !stop_requested.load(Ordering::SeqCst) {
+        // The actual poll loop lives in `crate::watch::run_watch_mode`; this
+        // stand-in just demonstrates the clean-stop wiring SCM needs.
+        std::thread::sleep(Duration::from_millis(250));
+    }
+
+    status_handle.set_service_status(ServiceStatus {
+        service_type: SERVICE_TYPE,
+        current_state: ServiceState::Stopped,
+        controls_accepted: ServiceControlAccept::empty(),
+        exit_code: ServiceExitCode::Win32(0),
+        checkpoint: 0,
+        wait_hint: Duration::default(),
+        process_id: None,
+    })?;
+
+    Ok(())
+}
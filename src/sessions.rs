@@ -0,0 +1,121 @@
+//! `--session-count-warn`/`--reap-stale-sessions`: the monitoring account
+//! ("svc-monitoring" in practice) is shared across several tools and keeps
+//! bumping into vCenter's per-user concurrent session limit, so right after
+//! authenticating, [`crate::vcenter::SimulatedClient::own_sessions`] counts
+//! that account's sessions and the report warns once the count gets close
+//! to the limit. Reaping idle sessions is opt-in and, per
+//! `SessionManager.TerminateSession`'s own restriction, never targets the
+//! session currently making the call.
+
+use serde::Serialize;
+
+/// One entry from the vCenter session list (`GET /api/session` or SOAP
+/// `SessionManager.sessionList`), scoped down to just what warning and
+/// reaping decisions need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionRecord {
+    pub id: String,
+    pub idle_minutes: u64,
+    pub is_current: bool,
+}
+
+/// Selects sessions eligible for `--reap-stale-sessions`: this user's own
+/// sessions idle at least `idle_minutes_threshold` minutes. The current
+/// session is never eligible, however idle it looks, since
+/// `SessionManager.TerminateSession` can't be used to log itself out
+/// mid-call.
+pub fn stale_sessions(sessions: &[SessionRecord], idle_minutes_threshold: u64) -> Vec<&SessionRecord> {
+    sessions
+        .iter()
+        .filter(|s| !s.is_current && s.idle_minutes >= idle_minutes_threshold)
+        .collect()
+}
+
+/// `--session-count-warn`/`--reap-stale-sessions`'s run-level outcome,
+/// surfaced in the text report and JSON metadata. `count` is `None` when
+/// the session list couldn't be read (insufficient privilege, or any other
+/// failure) - the run proceeds either way, just without this section
+/// having anything concrete to say.
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionLimitReport {
+    pub count: Option<u32>,
+    pub warn_threshold: u32,
+    pub reaped: Vec<String>,
+}
+
+impl SessionLimitReport {
+    /// `false` when there's nothing worth a line: the count is known and
+    /// under the warning threshold, and nothing was reaped.
+    fn has_anything_to_say(&self) -> bool {
+        self.count.is_none_or(|count| count >= self.warn_threshold) || !self.reaped.is_empty()
+    }
+
+    pub fn render_section(&self) -> String {
+        if !self.has_anything_to_say() {
+            return String::new();
+        }
+        let mut out = String::from("SESSIONS: ");
+        out.push_str(&match self.count {
+            Some(count) if count >= self.warn_threshold => {
+                format!("{count} concurrent session(s) for this account, at or above --session-count-warn {}\n", self.warn_threshold)
+            }
+            Some(count) => format!("{count} concurrent session(s) for this account\n"),
+            None => "session count unknown (insufficient privilege to list sessions)\n".to_string(),
+        });
+        if !self.reaped.is_empty() {
+            out.push_str(&format!("  reaped {} stale session(s): {}\n", self.reaped.len(), self.reaped.join(", ")));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(id: &str, idle_minutes: u64, is_current: bool) -> SessionRecord {
+        SessionRecord { id: id.to_string(), idle_minutes, is_current }
+    }
+
+    #[test]
+    fn stale_sessions_excludes_the_current_session_regardless_of_idle_time() {
+        let sessions = vec![session("current", 10_000, true), session("other", 30, false)];
+        let stale = stale_sessions(&sessions, 30);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, "other");
+    }
+
+    #[test]
+    fn stale_sessions_respects_the_idle_threshold() {
+        let sessions = vec![session("fresh", 10, false), session("stale", 60, false)];
+        let stale = stale_sessions(&sessions, 30);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].id, "stale");
+    }
+
+    #[test]
+    fn stale_sessions_is_empty_with_nothing_over_the_threshold() {
+        let sessions = vec![session("fresh", 5, false)];
+        assert!(stale_sessions(&sessions, 30).is_empty());
+    }
+
+    #[test]
+    fn render_section_is_empty_below_the_warn_threshold_with_nothing_reaped() {
+        let report = SessionLimitReport { count: Some(5), warn_threshold: 20, reaped: Vec::new() };
+        assert!(report.render_section().is_empty());
+    }
+
+    #[test]
+    fn render_section_warns_at_or_above_the_threshold() {
+        let report = SessionLimitReport { count: Some(20), warn_threshold: 20, reaped: Vec::new() };
+        assert!(report.render_section().contains("at or above --session-count-warn 20"));
+    }
+
+    #[test]
+    fn render_section_mentions_unknown_count_on_insufficient_privilege() {
+        let report = SessionLimitReport { count: None, warn_threshold: 20, reaped: vec!["sess-001".to_string()] };
+        let section = report.render_section();
+        assert!(section.contains("unknown"));
+        assert!(section.contains("sess-001"));
+    }
+}
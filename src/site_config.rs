@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// One `--site-config` entry's overrides for a single `--site` label,
+/// layered on top of the process-wide CLI flags (see [`resolve_effective_config`]).
+/// Every field is optional: an unset field falls through to the global
+/// flag's value, which is itself already either a user-supplied flag or
+/// clap's compiled-in default - this binary has no separate config file
+/// for the "global" tier, so that collapse happens before this code ever
+/// runs. `None` anywhere below means "nothing to override here", not zero.
+///
+/// This binary takes `--host`/`--username`/`--password` as per-invocation
+/// CLI flags, not stored config, so there's no per-site credential or TLS
+/// scoping here - that would mean teaching this tool to hold multiple
+/// vCenter sessions open at once, a materially different architecture to
+/// the one this run makes a single connection against. Same reasoning
+/// applies to TLS settings: there's no TLS client in this binary to
+/// configure (see [`crate::auth`]'s simulated session).
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct SiteOverrides {
+    pub clock_skew_threshold_secs: Option<f64>,
+    pub underuse_threshold: Option<f64>,
+    pub check_reachability: Option<bool>,
+    pub disable_issues: Option<Vec<String>>,
+}
+
+/// `--site-config`'s file shape: one [`SiteOverrides`] per `--site` label.
+/// A site with no entry here just runs on the global flags unchanged.
+#[derive(Debug, Deserialize)]
+pub struct SiteConfig {
+    pub sites: BTreeMap<String, SiteOverrides>,
+}
+
+impl SiteConfig {
+    pub fn load(path: &str, strict_json: bool) -> Result<Self> {
+        let raw = fs::read_to_string(path).with_context(|| format!("reading site config {path}"))?;
+        crate::strict_json::parse(&raw, &format!("site config {path}"), strict_json, &["sites"])
+    }
+}
+
+/// This run's fully-merged settings for `--print-effective-config`: what
+/// would actually be used once `--site-config`'s per-site overrides are
+/// layered on. There are no credentials or TLS settings tracked anywhere
+/// in this binary's config (see [`SiteOverrides`]), so there's nothing to
+/// redact before printing - this field list is the redacted view already.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EffectiveConfig {
+    pub site: Option<String>,
+    pub clock_skew_threshold_secs: f64,
+    pub underuse_threshold: f64,
+    pub check_reachability: bool,
+    pub disable_issues: Vec<String>,
+}
+
+/// Precedence rule shared by every overridable setting: a site entry's
+/// value, if set, beats the global (flag-or-default) value. Generic so
+/// [`resolve_effective_config`] doesn't reimplement this per field.
+fn resolve<T>(site_value: Option<T>, global_value: T) -> T {
+    site_value.unwrap_or(global_value)
+}
+
+/// Merges the process-wide CLI flags with `site_config`'s entry for `site`
+/// (if any), per [`resolve`]'s precedence. `site_config` is `None` when
+/// `--site-config` wasn't passed, and `site` is `None` when `--site`
+/// wasn't - either way this just echoes the global values back.
+#[allow(clippy::too_many_arguments)]
+pub fn resolve_effective_config(
+    site: Option<&str>,
+    clock_skew_threshold_secs: f64,
+    underuse_threshold: f64,
+    check_reachability: bool,
+    disable_issues: &[String],
+    site_config: Option<&SiteConfig>,
+) -> EffectiveConfig {
+    let overrides = site.and_then(|site| site_config.and_then(|c| c.sites.get(site)));
+    EffectiveConfig {
+        site: site.map(str::to_string),
+        clock_skew_threshold_secs: resolve(overrides.and_then(|o| o.clock_skew_threshold_secs), clock_skew_threshold_secs),
+        underuse_threshold: resolve(overrides.and_then(|o| o.underuse_threshold), underuse_threshold),
+        check_reachability: resolve(overrides.and_then(|o| o.check_reachability), check_reachability),
+        disable_issues: resolve(overrides.and_then(|o| o.disable_issues.clone()), disable_issues.to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overrides(
+        clock_skew_threshold_secs: Option<f64>,
+        underuse_threshold: Option<f64>,
+        check_reachability: Option<bool>,
+        disable_issues: Option<Vec<String>>,
+    ) -> SiteOverrides {
+        SiteOverrides { clock_skew_threshold_secs, underuse_threshold, check_reachability, disable_issues }
+    }
+
+    fn site_config(entries: Vec<(&str, SiteOverrides)>) -> SiteConfig {
+        SiteConfig { sites: entries.into_iter().map(|(name, o)| (name.to_string(), o)).collect() }
+    }
+
+    /// Global values standing in for "whatever the CLI flags resolved to",
+    /// arbitrary but distinct from every override used below so a test
+    /// can't pass by accident.
+    const GLOBAL_CLOCK_SKEW: f64 = 5.0;
+    const GLOBAL_UNDERUSE: f64 = 20.0;
+    const GLOBAL_CHECK_REACHABILITY: bool = false;
+
+    fn global_disable_issues() -> Vec<String> {
+        vec!["HOT_ADD_DISABLED".to_string()]
+    }
+
+    fn resolve_for(site: Option<&str>, config: Option<&SiteConfig>) -> EffectiveConfig {
+        resolve_effective_config(
+            site,
+            GLOBAL_CLOCK_SKEW,
+            GLOBAL_UNDERUSE,
+            GLOBAL_CHECK_REACHABILITY,
+            &global_disable_issues(),
+            config,
+        )
+    }
+
+    #[test]
+    fn no_site_config_falls_through_to_global_flags_unchanged() {
+        let effective = resolve_for(Some("dc-east"), None);
+        assert_eq!(effective.clock_skew_threshold_secs, GLOBAL_CLOCK_SKEW);
+        assert_eq!(effective.underuse_threshold, GLOBAL_UNDERUSE);
+        assert_eq!(effective.check_reachability, GLOBAL_CHECK_REACHABILITY);
+        assert_eq!(effective.disable_issues, global_disable_issues());
+    }
+
+    #[test]
+    fn site_with_no_matching_entry_falls_through_to_global_flags() {
+        let config = site_config(vec![("dc-west", overrides(Some(1.0), None, None, None))]);
+        let effective = resolve_for(Some("dc-east"), Some(&config));
+        assert_eq!(effective.clock_skew_threshold_secs, GLOBAL_CLOCK_SKEW);
+    }
+
+    #[test]
+    fn unset_site_has_no_site_entry_to_look_up() {
+        let config = site_config(vec![("dc-east", overrides(Some(1.0), None, None, None))]);
+        let effective = resolve_for(None, Some(&config));
+        assert_eq!(effective.clock_skew_threshold_secs, GLOBAL_CLOCK_SKEW);
+    }
+
+    /// Table-driven: one row per overridable field, each checked against
+    /// its own matching site entry. The precedence rule silently breaking
+    /// on one field while the others still pass is exactly the regression
+    /// a single happy-path test wouldn't catch.
+    #[test]
+    fn site_override_beats_global_per_field() {
+        struct Case {
+            name: &'static str,
+            config: SiteConfig,
+            expected_clock_skew: f64,
+            expected_underuse: f64,
+            expected_check_reachability: bool,
+            expected_disable_issues: Vec<String>,
+        }
+
+        let cases = vec![
+            Case {
+                name: "clock skew overridden",
+                config: site_config(vec![("dc-east", overrides(Some(42.0), None, None, None))]),
+                expected_clock_skew: 42.0,
+                expected_underuse: GLOBAL_UNDERUSE,
+                expected_check_reachability: GLOBAL_CHECK_REACHABILITY,
+                expected_disable_issues: global_disable_issues(),
+            },
+            Case {
+                name: "underuse threshold overridden",
+                config: site_config(vec![("dc-east", overrides(None, Some(50.0), None, None))]),
+                expected_clock_skew: GLOBAL_CLOCK_SKEW,
+                expected_underuse: 50.0,
+                expected_check_reachability: GLOBAL_CHECK_REACHABILITY,
+                expected_disable_issues: global_disable_issues(),
+            },
+            Case {
+                name: "check-reachability overridden",
+                config: site_config(vec![("dc-east", overrides(None, None, Some(true), None))]),
+                expected_clock_skew: GLOBAL_CLOCK_SKEW,
+                expected_underuse: GLOBAL_UNDERUSE,
+                expected_check_reachability: true,
+                expected_disable_issues: global_disable_issues(),
+            },
+            Case {
+                name: "disable-issues overridden",
+                config: site_config(vec![("dc-east", overrides(None, None, None, Some(vec!["CLOCK_SKEW".to_string()])))]),
+                expected_clock_skew: GLOBAL_CLOCK_SKEW,
+                expected_underuse: GLOBAL_UNDERUSE,
+                expected_check_reachability: GLOBAL_CHECK_REACHABILITY,
+                expected_disable_issues: vec!["CLOCK_SKEW".to_string()],
+            },
+        ];
+
+        for case in cases {
+            let effective = resolve_for(Some("dc-east"), Some(&case.config));
+            assert_eq!(effective.clock_skew_threshold_secs, case.expected_clock_skew, "{}", case.name);
+            assert_eq!(effective.underuse_threshold, case.expected_underuse, "{}", case.name);
+            assert_eq!(effective.check_reachability, case.expected_check_reachability, "{}", case.name);
+            assert_eq!(effective.disable_issues, case.expected_disable_issues, "{}", case.name);
+        }
+    }
+
+    #[test]
+    fn load_parses_a_site_config_file() {
+        let dir = std::env::temp_dir().join(format!("network-monitor-site-config-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("sites.json");
+        fs::write(&path, r#"{"sites":{"dc-east":{"clock_skew_threshold_secs":1.5}}}"#).unwrap();
+
+        let config = SiteConfig::load(path.to_str().unwrap(), false).unwrap();
+        assert_eq!(config.sites["dc-east"].clock_skew_threshold_secs, Some(1.5));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,70 @@
+/// Eight Unicode block characters, empty to full, for the `▁▂▃▄▅▆▇█`-style
+/// sparklines `--sparklines` prints next to a flagged VM's cpu/mem percent
+/// in the terminal report.
+const BLOCKS: [char; 8] = ['\u{2581}', '\u{2582}', '\u{2583}', '\u{2584}', '\u{2585}', '\u{2586}', '\u{2587}', '\u{2588}'];
+
+/// How many of `--history`'s most recent samples a sparkline shows - enough
+/// to see a trend at a glance without the line wrapping alongside
+/// everything else a flagged VM's entry already prints.
+const MAX_HISTORY_POINTS: usize = 8;
+
+/// Maps a 0-100 percentage onto one of [`BLOCKS`], clamping out-of-range
+/// values instead of panicking - a stale or synthetic sample shouldn't take
+/// the whole report down with it.
+fn block_for(value_pct: f64) -> char {
+    let clamped = value_pct.clamp(0.0, 100.0);
+    let index = ((clamped / 100.0) * (BLOCKS.len() - 1) as f64).round() as usize;
+    BLOCKS[index.min(BLOCKS.len() - 1)]
+}
+
+/// Renders `samples` (oldest to newest, 0-100 percentages pooled from
+/// `--history`) as a compact sparkline, one block per sample, capped to
+/// the most recent [`MAX_HISTORY_POINTS`]. An empty `samples` - no
+/// `--history` file covered this VM, or `--history` wasn't given at all -
+/// falls back to a single block proportional to `current_pct`: still a
+/// useful at-a-glance gauge, just without a trend to show.
+pub fn render(samples: &[f64], current_pct: f64) -> String {
+    if samples.is_empty() {
+        return block_for(current_pct).to_string();
+    }
+    let start = samples.len().saturating_sub(MAX_HISTORY_POINTS);
+    samples[start..].iter().map(|&v| block_for(v)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_for_spans_the_full_range() {
+        assert_eq!(block_for(0.0), BLOCKS[0]);
+        assert_eq!(block_for(100.0), BLOCKS[7]);
+        assert_eq!(block_for(50.0), BLOCKS[4]);
+    }
+
+    #[test]
+    fn block_for_clamps_out_of_range_values() {
+        assert_eq!(block_for(-10.0), BLOCKS[0]);
+        assert_eq!(block_for(150.0), BLOCKS[7]);
+    }
+
+    #[test]
+    fn empty_history_falls_back_to_a_single_proportional_block() {
+        assert_eq!(render(&[], 0.0), BLOCKS[0].to_string());
+        assert_eq!(render(&[], 100.0), BLOCKS[7].to_string());
+    }
+
+    #[test]
+    fn render_emits_one_block_per_sample_in_order() {
+        assert_eq!(render(&[0.0, 50.0, 100.0], 0.0), format!("{}{}{}", BLOCKS[0], BLOCKS[4], BLOCKS[7]));
+    }
+
+    #[test]
+    fn render_caps_to_the_most_recent_history_points() {
+        let samples: Vec<f64> = (0..20).map(|n| n as f64).collect();
+        let sparkline = render(&samples, 0.0);
+        assert_eq!(sparkline.chars().count(), MAX_HISTORY_POINTS, "must not grow unbounded with a long history");
+        // The most recent samples (12..=19), not the oldest (0..=7).
+        assert_eq!(sparkline, render(&samples[12..], 0.0));
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+
+/// Parses `raw` (from `source`, used only for the error message) into `T`.
+///
+/// In lenient mode (the default) this is a plain `serde_json::from_str`:
+/// fields the config format has grown past are ignored, same as today.
+/// In `--strict-json` mode, any top-level field `raw` has that isn't in
+/// `known_fields` is rejected by name before `T` ever sees it - for
+/// validation runs after a config format (or a vCenter schema it mirrors)
+/// may have changed shape out from under a silently-ignored field. Nested
+/// objects (e.g. a notifier entry's `filter`) aren't checked; this catches
+/// the common case of a renamed or dropped top-level field, not every
+/// level of a deeply nested config.
+pub fn parse<T: DeserializeOwned>(raw: &str, source: &str, strict: bool, known_fields: &[&str]) -> Result<T> {
+    if strict {
+        let value: serde_json::Value = serde_json::from_str(raw).with_context(|| format!("parsing {source}"))?;
+        if let serde_json::Value::Object(fields) = &value {
+            for key in fields.keys() {
+                if !known_fields.contains(&key.as_str()) {
+                    anyhow::bail!(
+                        "{source}: unexpected field '{key}' (--strict-json); expected one of: {}",
+                        known_fields.join(", ")
+                    );
+                }
+            }
+        }
+        serde_json::from_value(value).with_context(|| format!("parsing {source} (--strict-json)"))
+    } else {
+        serde_json::from_str(raw).with_context(|| format!("parsing {source}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Example {
+        name: String,
+        #[serde(default)]
+        count: u32,
+    }
+
+    const FIELDS: &[&str] = &["name", "count"];
+
+    #[test]
+    fn lenient_mode_ignores_unknown_fields() {
+        let raw = r#"{"name": "a", "count": 1, "extra": "ignored"}"#;
+        let parsed: Example = parse(raw, "test", false, FIELDS).unwrap();
+        assert_eq!(parsed, Example { name: "a".to_string(), count: 1 });
+    }
+
+    #[test]
+    fn strict_mode_accepts_known_fields() {
+        let raw = r#"{"name": "a", "count": 1}"#;
+        let parsed: Example = parse(raw, "test", true, FIELDS).unwrap();
+        assert_eq!(parsed, Example { name: "a".to_string(), count: 1 });
+    }
+
+    #[test]
+    fn strict_mode_names_the_unexpected_field() {
+        let raw = r#"{"name": "a", "surprize": "typo"}"#;
+        let err = parse::<Example>(raw, "test.json", true, FIELDS).unwrap_err();
+        assert!(err.to_string().contains("'surprize'"), "{err}");
+        assert!(err.to_string().contains("test.json"), "{err}");
+    }
+}
@@ -0,0 +1,87 @@
+use serde_json::Value;
+
+/// Counts every field-level fallback taken while resolving a `--replay`
+/// report's measurement fields, so schema drift (e.g. a renamed vCenter
+/// field landing as `0.0` for every VM) is visible instead of masked.
+/// Surfaced as a data-quality warning when `--strict-parsing` is off.
+#[derive(Debug, Clone, Default)]
+pub struct FallbackReport {
+    /// `(vm_name, field_name)` pairs, one per fallback taken, in the order
+    /// they were encountered.
+    pub fallbacks: Vec<(String, String)>,
+}
+
+impl FallbackReport {
+    pub fn count(&self) -> usize {
+        self.fallbacks.len()
+    }
+}
+
+/// Resolves a single measurement field as an `f64`. In `--strict-parsing`
+/// mode a missing or non-numeric `value` is returned as an `Err` naming
+/// `vm_name` and `field`, for the caller to turn into a per-VM analysis
+/// failure; otherwise it falls back to `default` and the fallback is
+/// recorded in `report`. Centralizing this here, rather than an
+/// `.and_then(...).unwrap_or(...)` chain at each call site, means every
+/// fallback taken during a `--replay` run is counted in exactly one place.
+pub fn f64_field(value: Option<&Value>, field: &str, default: f64, vm_name: &str, strict: bool, report: &mut FallbackReport) -> Result<f64, String> {
+    match value.and_then(Value::as_f64) {
+        Some(v) => Ok(v),
+        None if strict => Err(format!("{vm_name}: missing or non-numeric field '{field}'")),
+        None => {
+            report.fallbacks.push((vm_name.to_string(), field.to_string()));
+            Ok(default)
+        }
+    }
+}
+
+/// As [`f64_field`], but for measurement fields that should be a
+/// non-negative integer (`cpu_count`, `cores_per_socket`, `migration_count_24h`).
+pub fn u32_field(value: Option<&Value>, field: &str, default: u32, vm_name: &str, strict: bool, report: &mut FallbackReport) -> Result<u32, String> {
+    match value.and_then(Value::as_u64) {
+        Some(v) => Ok(v as u32),
+        None if strict => Err(format!("{vm_name}: missing or non-numeric field '{field}'")),
+        None => {
+            report.fallbacks.push((vm_name.to_string(), field.to_string()));
+            Ok(default)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn present_field_is_used_without_a_fallback() {
+        let mut report = FallbackReport::default();
+        let value = json!(42.0);
+        assert_eq!(f64_field(Some(&value), "cpu_usage_pct", 0.0, "vm-1", false, &mut report).unwrap(), 42.0);
+        assert_eq!(report.count(), 0);
+    }
+
+    #[test]
+    fn missing_field_falls_back_and_is_recorded_in_non_strict_mode() {
+        let mut report = FallbackReport::default();
+        assert_eq!(f64_field(None, "memory_usage_pct", 0.0, "vm-1", false, &mut report).unwrap(), 0.0);
+        assert_eq!(report.fallbacks, vec![("vm-1".to_string(), "memory_usage_pct".to_string())]);
+    }
+
+    #[test]
+    fn missing_field_is_an_error_in_strict_mode() {
+        let mut report = FallbackReport::default();
+        let err = f64_field(None, "memory_usage_pct", 0.0, "vm-1", true, &mut report).unwrap_err();
+        assert!(err.contains("vm-1"));
+        assert!(err.contains("memory_usage_pct"));
+        assert_eq!(report.count(), 0, "strict-mode errors are not fallbacks");
+    }
+
+    #[test]
+    fn wrongly_typed_field_is_treated_the_same_as_missing() {
+        let mut report = FallbackReport::default();
+        let value = json!("four");
+        assert_eq!(u32_field(Some(&value), "cpu_count", 1, "vm-1", false, &mut report).unwrap(), 1);
+        assert_eq!(report.count(), 1);
+    }
+}
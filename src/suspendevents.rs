@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// A single result row from the (simulated) vCenter event query, before
+/// it's been confirmed to be a `VmSuspendedEvent`. Kept separate from
+/// [`SuspendEvent`] and checked by [`parse_event`], same split as
+/// [`crate::bootevents::RawBootEvent`]/[`crate::bootevents::BootEvent`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct RawSuspendEvent {
+    pub event_type: String,
+    pub vm_name: String,
+    /// How long before "now" the event happened, same relative-not-absolute
+    /// convention as [`crate::bootevents::RawBootEvent::hours_ago`].
+    pub hours_ago: f64,
+}
+
+/// A confirmed `VmSuspendedEvent` for one VM.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SuspendEvent {
+    pub vm_name: String,
+    pub hours_ago: f64,
+}
+
+/// Keeps only `VmSuspendedEvent` rows, discarding everything else the event
+/// query might return.
+pub fn parse_event(raw: &RawSuspendEvent) -> Option<SuspendEvent> {
+    if raw.event_type != "VmSuspendedEvent" {
+        return None;
+    }
+    Some(SuspendEvent {
+        vm_name: raw.vm_name.clone(),
+        hours_ago: raw.hours_ago,
+    })
+}
+
+/// Buckets a flat event-query result by VM, client-side - same shape as
+/// [`crate::bootevents::bucket_boot_history_by_vm`]. A VM can only be
+/// suspended once at a time, but the event log may carry more than one
+/// `VmSuspendedEvent` for it (suspended, resumed, suspended again); the
+/// most recent one - the smallest `hours_ago` - is the one that matters.
+pub fn bucket_suspend_time_by_vm(events: &[SuspendEvent]) -> HashMap<String, f64> {
+    let mut by_vm: HashMap<String, f64> = HashMap::new();
+    for event in events {
+        let secs_ago = event.hours_ago * 3600.0;
+        by_vm
+            .entry(event.vm_name.clone())
+            .and_modify(|existing| *existing = secs_ago.min(*existing))
+            .or_insert(secs_ago);
+    }
+    by_vm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn raw(event_type: &str, vm_name: &str, hours_ago: f64) -> RawSuspendEvent {
+        RawSuspendEvent {
+            event_type: event_type.to_string(),
+            vm_name: vm_name.to_string(),
+            hours_ago,
+        }
+    }
+
+    #[test]
+    fn parse_event_accepts_suspended_and_rejects_others() {
+        assert_eq!(
+            parse_event(&raw("VmSuspendedEvent", "vm-0001", 2.0)).unwrap(),
+            SuspendEvent { vm_name: "vm-0001".to_string(), hours_ago: 2.0 }
+        );
+        assert!(parse_event(&raw("VmPoweredOnEvent", "vm-0001", 2.0)).is_none());
+    }
+
+    #[test]
+    fn bucket_converts_hours_ago_to_seconds() {
+        let events = vec![SuspendEvent { vm_name: "vm-0001".to_string(), hours_ago: 3.0 }];
+        let by_vm = bucket_suspend_time_by_vm(&events);
+        assert_eq!(by_vm.get("vm-0001"), Some(&(3.0 * 3600.0)));
+    }
+
+    #[test]
+    fn bucket_keeps_the_most_recent_suspend_event_per_vm() {
+        let events = vec![
+            SuspendEvent { vm_name: "vm-0001".to_string(), hours_ago: 48.0 },
+            SuspendEvent { vm_name: "vm-0001".to_string(), hours_ago: 5.0 },
+        ];
+        let by_vm = bucket_suspend_time_by_vm(&events);
+        assert_eq!(by_vm.get("vm-0001"), Some(&(5.0 * 3600.0)), "the most recent suspend, not the oldest");
+    }
+
+    #[test]
+    fn vm_with_no_events_has_no_entry() {
+        assert!(bucket_suspend_time_by_vm(&[]).is_empty());
+    }
+}
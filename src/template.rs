@@ -0,0 +1,165 @@
+//! Dependency-free renderer for `--template`/`--template-output`. A real
+//! Tera or Handlebars template language is a parser, an expression
+//! evaluator, and (for Tera) its own stdlib of filters - more than a single
+//! report-shaping flag earns, the same call this repo already made for
+//! simple name matching (see [`crate::aggregate::glob_match`]'s hand-rolled
+//! `*`-only glob instead of the `glob` crate, and
+//! [`crate::notifier::NotifierFilter::vm_name_contains`]'s substring match).
+//!
+//! This implements the subset of Handlebars syntax that covers reporting
+//! templates in practice: `{{dotted.path}}` variable substitution and
+//! `{{#each array.path}}...{{/each}}` repetition (which may nest, so a
+//! template can walk into each VM's `issues` array), both resolved against
+//! the same JSON document [`crate::report::export_json_report`] already
+//! produces. So the available context keys are that report's v2 fields,
+//! among them `run_id`,`statistics.*` (present unless `--no-stats`), `vms`
+//! (each with `name`, `host`, `cluster`, `power_state`, `cpu_usage_pct`,
+//! `memory_usage_pct`, `health_score`, `issues[]` with `issue_type`,
+//! `severity`, `detail`, ..., and the rest of [`crate::vm::VMResourceStatus`]'s
+//! serialized fields), `deferred_vms`, `api_rate_log`, `host_metrics`,
+//! `notifications`, `drs_compliance`, and `vcenter_version`. Inside an
+//! `{{#each}}` block, bare names resolve against the current item first,
+//! falling back to the enclosing scope. Conditionals and helpers are out of
+//! scope; pipe `--format json` into a real templating tool if a template
+//! needs those.
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+fn lookup<'a>(scopes: &[&'a Value], path: &str) -> Option<&'a Value> {
+    for scope in scopes.iter().rev() {
+        let mut current = Some(*scope);
+        for part in path.split('.') {
+            current = current.and_then(|v| v.get(part));
+        }
+        if let Some(found) = current {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Finds the `{{/each}}` matching the `{{#each ...}}` that was just consumed,
+/// accounting for any `{{#each ...}}` blocks nested inside it.
+fn find_each_close(rest: &str) -> Result<usize> {
+    let mut depth = 1usize;
+    let mut pos = 0usize;
+    loop {
+        let next_close = rest[pos..].find("{{/each}}").context("unterminated {{#each}} block")?;
+        let close_at = pos + next_close;
+        match rest[pos..close_at].find("{{#each") {
+            Some(open_rel) => {
+                depth += 1;
+                pos += open_rel + "{{#each".len();
+            }
+            None => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(close_at);
+                }
+                pos = close_at + "{{/each}}".len();
+            }
+        }
+    }
+}
+
+fn render_scoped(template: &str, scopes: &mut Vec<&Value>) -> Result<String> {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+        let end = after_open.find("}}").context("unterminated {{ tag in template")?;
+        let tag = after_open[..end].trim();
+        rest = &after_open[end + 2..];
+
+        if let Some(array_path) = tag.strip_prefix("#each ") {
+            let array_path = array_path.trim();
+            let close_at = find_each_close(rest)?;
+            let block = &rest[..close_at];
+            rest = &rest[close_at + "{{/each}}".len()..];
+
+            if let Some(items) = lookup(scopes, array_path).and_then(Value::as_array) {
+                for item in items {
+                    scopes.push(item);
+                    out.push_str(&render_scoped(block, scopes)?);
+                    scopes.pop();
+                }
+            }
+        } else if tag.starts_with('/') || tag.starts_with('#') {
+            bail!("unsupported template tag '{{{{{tag}}}}}' - only {{{{#each ...}}}} blocks and {{{{path}}}} variables are supported");
+        } else {
+            if let Some(value) = lookup(scopes, tag) {
+                out.push_str(&display(value));
+            }
+        }
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Renders `template` against `context` (the parsed v2 JSON report
+/// document). See the module doc comment for the available keys.
+pub fn render(template: &str, context: &Value) -> Result<String> {
+    render_scoped(template, &mut vec![context])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn substitutes_dotted_paths() {
+        let context = json!({"run_id": "abc123", "statistics": {"total_vms": 5}});
+        let out = render("run {{run_id}}: {{statistics.total_vms}} VM(s)", &context).unwrap();
+        assert_eq!(out, "run abc123: 5 VM(s)");
+    }
+
+    #[test]
+    fn each_repeats_block_per_item() {
+        let context = json!({"vms": [{"name": "web-01"}, {"name": "web-02"}]});
+        let out = render("{{#each vms}}{{name}} {{/each}}", &context).unwrap();
+        assert_eq!(out, "web-01 web-02 ");
+    }
+
+    #[test]
+    fn each_falls_back_to_enclosing_scope() {
+        let context = json!({"run_id": "xyz", "vms": [{"name": "web-01"}]});
+        let out = render("{{#each vms}}{{name}}/{{run_id}} {{/each}}", &context).unwrap();
+        assert_eq!(out, "web-01/xyz ");
+    }
+
+    #[test]
+    fn each_nests_for_per_vm_issue_lists() {
+        let context = json!({
+            "vms": [
+                {"name": "web-01", "issues": [{"issue_type": "OVER_ALLOCATED_CPU"}, {"issue_type": "TOOLS_NOT_RUNNING"}]},
+                {"name": "web-02", "issues": []},
+            ]
+        });
+        let out = render("{{#each vms}}{{name}}: {{#each issues}}{{issue_type}},{{/each}} {{/each}}", &context).unwrap();
+        assert_eq!(out, "web-01: OVER_ALLOCATED_CPU,TOOLS_NOT_RUNNING, web-02:  ");
+    }
+
+    #[test]
+    fn missing_key_renders_as_empty() {
+        let context = json!({});
+        let out = render("[{{nope}}]", &context).unwrap();
+        assert_eq!(out, "[]");
+    }
+
+    #[test]
+    fn unterminated_each_is_an_error() {
+        let context = json!({"vms": []});
+        assert!(render("{{#each vms}}no close", &context).is_err());
+    }
+}
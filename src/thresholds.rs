@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+/// A VM needs at least this many historical samples before a suggestion is
+/// trusted; below it, the VM is listed separately instead of given a
+/// suggestion computed from too little data to mean anything.
+const MIN_SAMPLES_FOR_SUGGESTION: usize = 5;
+
+/// Suggested threshold is p99 plus this many points of headroom, so the
+/// alert fires on a genuine excursion rather than the single worst sample
+/// already seen.
+const SUGGESTION_PAD_PCT: f64 = 5.0;
+
+/// Suggestions are clamped to this range regardless of what the data says -
+/// below the floor isn't worth alerting on, above the ceiling leaves no
+/// room to notice anything got worse before the VM is already maxed out.
+const MIN_SUGGESTED_THRESHOLD_PCT: f64 = 50.0;
+const MAX_SUGGESTED_THRESHOLD_PCT: f64 = 95.0;
+
+/// Linear-interpolation percentile, the same definition used by most
+/// monitoring systems (e.g. Prometheus' `histogram_quantile`). `samples`
+/// need not be sorted or evenly spaced in time - each history file is just
+/// one more irregularly-spaced data point per VM. Returns `0.0` for an
+/// empty slice; callers gate on [`MIN_SAMPLES_FOR_SUGGESTION`] before this
+/// matters.
+fn percentile(samples: &[f64], p: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    if sorted.len() == 1 {
+        return sorted[0];
+    }
+    let rank = (p / 100.0) * (sorted.len() - 1) as f64;
+    let lower = rank.floor() as usize;
+    let upper = rank.ceil() as usize;
+    if lower == upper {
+        return sorted[lower];
+    }
+    let frac = rank - lower as f64;
+    sorted[lower] + (sorted[upper] - sorted[lower]) * frac
+}
+
+fn clamp_suggestion(value: f64) -> f64 {
+    value.clamp(MIN_SUGGESTED_THRESHOLD_PCT, MAX_SUGGESTED_THRESHOLD_PCT)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ThresholdSuggestion {
+    pub name: String,
+    pub sample_count: usize,
+    pub cpu_p95: f64,
+    pub cpu_p99: f64,
+    pub suggested_cpu_threshold_pct: f64,
+    pub mem_p95: f64,
+    pub mem_p99: f64,
+    pub suggested_mem_threshold_pct: f64,
+}
+
+/// Computes per-VM threshold suggestions from `samples_by_vm` (CPU%, memory%
+/// pairs keyed by VM name). VMs with fewer than [`MIN_SAMPLES_FOR_SUGGESTION`]
+/// samples are returned by name in the second vector instead of being given
+/// a suggestion the data can't support.
+pub fn suggest_thresholds(samples_by_vm: &BTreeMap<String, Vec<(f64, f64)>>) -> (Vec<ThresholdSuggestion>, Vec<String>) {
+    let mut suggestions = Vec::new();
+    let mut insufficient = Vec::new();
+
+    for (name, samples) in samples_by_vm {
+        if samples.len() < MIN_SAMPLES_FOR_SUGGESTION {
+            insufficient.push(name.clone());
+            continue;
+        }
+        let cpu_samples: Vec<f64> = samples.iter().map(|(cpu, _)| *cpu).collect();
+        let mem_samples: Vec<f64> = samples.iter().map(|(_, mem)| *mem).collect();
+        let cpu_p95 = percentile(&cpu_samples, 95.0);
+        let cpu_p99 = percentile(&cpu_samples, 99.0);
+        let mem_p95 = percentile(&mem_samples, 95.0);
+        let mem_p99 = percentile(&mem_samples, 99.0);
+        suggestions.push(ThresholdSuggestion {
+            name: name.clone(),
+            sample_count: samples.len(),
+            cpu_p95,
+            cpu_p99,
+            suggested_cpu_threshold_pct: clamp_suggestion(cpu_p99 + SUGGESTION_PAD_PCT),
+            mem_p95,
+            mem_p99,
+            suggested_mem_threshold_pct: clamp_suggestion(mem_p99 + SUGGESTION_PAD_PCT),
+        });
+    }
+
+    (suggestions, insufficient)
+}
+
+/// Human-readable rendering for `--suggest-thresholds`: one line per VM with
+/// enough data, then the VMs that don't have enough yet.
+pub fn render_text(suggestions: &[ThresholdSuggestion], insufficient: &[String]) -> String {
+    let mut out = format!(
+        "{} VM(s) with enough history, {} with too few samples (< {MIN_SAMPLES_FOR_SUGGESTION})\n",
+        suggestions.len(),
+        insufficient.len()
+    );
+    for s in suggestions {
+        out.push_str(&format!(
+            "- {} ({} samples): cpu p95={:.1}% p99={:.1}% -> suggest {:.1}%, mem p95={:.1}% p99={:.1}% -> suggest {:.1}%\n",
+            s.name,
+            s.sample_count,
+            s.cpu_p95,
+            s.cpu_p99,
+            s.suggested_cpu_threshold_pct,
+            s.mem_p95,
+            s.mem_p99,
+            s.suggested_mem_threshold_pct
+        ));
+    }
+    if !insufficient.is_empty() {
+        out.push_str(&format!("Too few samples to suggest: {}\n", insufficient.join(", ")));
+    }
+    out
+}
+
+#[derive(Debug, Serialize)]
+struct OverrideEntry<'a> {
+    vm: &'a str,
+    cpu_high_threshold_pct: f64,
+    memory_high_threshold_pct: f64,
+}
+
+/// Renders `suggestions` as a ready-to-paste JSON document. This repo's
+/// alert thresholds are currently global flags (`--check-clock`'s
+/// `--clock-skew-threshold-secs` and friends), not a per-VM override file,
+/// so there's nothing to merge this into yet - it's an advisory snippet for
+/// whoever adds that, not a format this binary reads back in.
+pub fn render_overrides_snippet(suggestions: &[ThresholdSuggestion]) -> String {
+    let overrides: Vec<OverrideEntry> = suggestions
+        .iter()
+        .map(|s| OverrideEntry {
+            vm: &s.name,
+            cpu_high_threshold_pct: s.suggested_cpu_threshold_pct,
+            memory_high_threshold_pct: s.suggested_mem_threshold_pct,
+        })
+        .collect();
+    serde_json::to_string_pretty(&overrides).expect("suggestion overrides are always serializable")
+}
+
+/// Just the fields `--suggest-thresholds` needs out of a prior `--format
+/// json` `v2` report (see [`crate::replay::replay`] for the same shape).
+#[derive(Debug, Deserialize)]
+struct HistorySnapshot {
+    vms: Vec<HistoryVm>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryVm {
+    name: String,
+    cpu_usage_pct: f64,
+    memory_usage_pct: f64,
+}
+
+/// Loads `--history` report files newer than `lookback_days`, pooling each
+/// VM's CPU/memory reading from every file it appears in. Each file is one
+/// sample round; there's no finer-grained timestamp recorded inside a
+/// report today, so recency is judged by the file's mtime.
+pub fn load_history(paths: &[String], lookback_days: u64) -> Result<BTreeMap<String, Vec<(f64, f64)>>> {
+    let cutoff = SystemTime::now()
+        .checked_sub(Duration::from_secs(lookback_days * 86_400))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut samples: BTreeMap<String, Vec<(f64, f64)>> = BTreeMap::new();
+    for path in paths {
+        let modified = fs::metadata(path)
+            .with_context(|| format!("reading history file {path}"))?
+            .modified()
+            .with_context(|| format!("reading mtime of history file {path}"))?;
+        if modified < cutoff {
+            continue;
+        }
+        let raw = fs::read_to_string(path).with_context(|| format!("reading history file {path}"))?;
+        let snapshot: HistorySnapshot = serde_json::from_str(&raw)
+            .with_context(|| format!("parsing history file {path} (requires --json-schema-version v2)"))?;
+        for vm in snapshot.vms {
+            samples.entry(vm.name).or_default().push((vm.cpu_usage_pct, vm.memory_usage_pct));
+        }
+    }
+    Ok(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_matches_known_values_on_evenly_spaced_data() {
+        let samples: Vec<f64> = (1..=100).map(|n| n as f64).collect();
+        assert_eq!(percentile(&samples, 0.0), 1.0);
+        assert_eq!(percentile(&samples, 100.0), 100.0);
+        assert!((percentile(&samples, 50.0) - 50.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn percentile_handles_sparse_and_unsorted_samples() {
+        assert_eq!(percentile(&[42.0], 95.0), 42.0, "single sample is its own every percentile");
+        assert_eq!(percentile(&[], 95.0), 0.0, "no data, no crash");
+
+        let unsorted = vec![30.0, 10.0, 50.0, 20.0, 40.0];
+        assert_eq!(percentile(&unsorted, 0.0), 10.0);
+        assert_eq!(percentile(&unsorted, 100.0), 50.0);
+    }
+
+    #[test]
+    fn vms_below_minimum_samples_are_listed_separately() {
+        let mut samples = BTreeMap::new();
+        samples.insert("vm-thin".to_string(), vec![(10.0, 10.0), (20.0, 20.0)]);
+        samples.insert(
+            "vm-thick".to_string(),
+            vec![(10.0, 5.0), (20.0, 10.0), (30.0, 15.0), (40.0, 20.0), (90.0, 25.0)],
+        );
+
+        let (suggestions, insufficient) = suggest_thresholds(&samples);
+        assert_eq!(insufficient, vec!["vm-thin".to_string()]);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].name, "vm-thick");
+        assert_eq!(suggestions[0].sample_count, 5);
+    }
+
+    #[test]
+    fn suggestion_is_p99_plus_pad_clamped_to_sane_bounds() {
+        let mut samples = BTreeMap::new();
+        // p99 of this set lands near 99, so +5 padding should get clamped
+        // down to the ceiling rather than suggesting a threshold above it.
+        samples.insert(
+            "vm-hot".to_string(),
+            (1..=100).map(|n| (n as f64, 10.0)).collect::<Vec<_>>(),
+        );
+        // A consistently idle VM should suggest the floor, not a threshold
+        // that tracks its near-zero usage.
+        samples.insert("vm-idle".to_string(), vec![(1.0, 1.0); 10]);
+
+        let (suggestions, insufficient) = suggest_thresholds(&samples);
+        assert!(insufficient.is_empty());
+
+        let hot = suggestions.iter().find(|s| s.name == "vm-hot").unwrap();
+        assert_eq!(hot.suggested_cpu_threshold_pct, MAX_SUGGESTED_THRESHOLD_PCT);
+
+        let idle = suggestions.iter().find(|s| s.name == "vm-idle").unwrap();
+        assert_eq!(idle.suggested_cpu_threshold_pct, MIN_SUGGESTED_THRESHOLD_PCT);
+    }
+}
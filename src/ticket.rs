@@ -0,0 +1,228 @@
+//! `--ticket-export`: turns issues into a normalized, ticket-ready JSON
+//! payload for a downstream Jira/ServiceNow importer, so that importer
+//! doesn't have to regex-scrape the text report. Reuses
+//! [`crate::fingerprint`]'s per-issue dedup key rather than inventing a
+//! second one.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::vm::{DetectedIssue, Severity, VMIssueType, VMResourceStatus};
+
+/// Ticket priority, derived one-to-one from [`Severity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TicketPriority {
+    P1,
+    P2,
+    P3,
+}
+
+impl TicketPriority {
+    fn from_severity(severity: Severity) -> Self {
+        match severity {
+            Severity::Critical => TicketPriority::P1,
+            Severity::Warning => TicketPriority::P2,
+            Severity::Informational => TicketPriority::P3,
+        }
+    }
+}
+
+/// One ticket-ready payload entry. Field names are part of the stable
+/// contract downstream importers parse against - renaming any of them is a
+/// breaking change for `--ticket-export`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ticket {
+    pub summary: String,
+    pub description: String,
+    pub priority: TicketPriority,
+    pub fingerprint: String,
+    pub labels: Vec<String>,
+}
+
+/// `--ticket-issue-types`' default. This tree doesn't track VM snapshots at
+/// all (no `SnapshotOld`/`SnapshotLarge` - see [`crate::datastore`] for
+/// what it tracks instead: the backing VMX/suspend files, not snapshot
+/// chains), so the closest analogs stand in: two issue types that are
+/// themselves about something stale going unnoticed.
+pub fn default_ticket_issue_types() -> Vec<String> {
+    vec!["SUSPENDED_TOO_LONG".to_string(), "HARDWARE_VERSION_OLD".to_string()]
+}
+
+/// Renders one issue's ticket description. `owner` comes from the `Owner`
+/// VM attribute (see [`crate::routing`], which keys off the same
+/// attribute) - `"unknown"` when it isn't set.
+fn render_description(vm: &VMResourceStatus, issue: &DetectedIssue, runbook_link: Option<&str>) -> String {
+    let owner = vm.attributes.get("Owner").map(String::as_str).unwrap_or("unknown");
+    let measurement = match (issue.measured_value, issue.threshold) {
+        (Some(measured), Some(threshold)) => format!("measured {measured:.1} against a threshold of {threshold:.1}"),
+        _ => "no numeric measurement recorded for this issue".to_string(),
+    };
+    let detail = issue.detail.as_deref().unwrap_or("no further detail");
+    let runbook = runbook_link.unwrap_or("none configured");
+    format!("VM: {}\nOwner: {owner}\n{measurement}\nDetail: {detail}\nRunbook: {runbook}", vm.name)
+}
+
+/// Builds one ticket per issue in `statuses` whose type is in
+/// `issue_types`. `only_new` restricts the export to issues first seen in
+/// this exact run (`issue.first_seen == Some(now)`, set by
+/// [`crate::fingerprint::annotate`] just before this runs) - a recurring
+/// issue a ticket was already opened for in a previous run is skipped.
+pub fn build_tickets(
+    statuses: &[VMResourceStatus],
+    issue_types: &HashSet<VMIssueType>,
+    only_new: bool,
+    now: DateTime<Utc>,
+    runbook_link: Option<&str>,
+) -> Vec<Ticket> {
+    let mut tickets = Vec::new();
+    for vm in statuses {
+        for issue in &vm.issues {
+            if !issue_types.contains(&issue.issue_type) {
+                continue;
+            }
+            if only_new && issue.first_seen != Some(now) {
+                continue;
+            }
+            tickets.push(Ticket {
+                summary: format!("{}: {:?} severity on {}", issue.issue_type, issue.severity, vm.name),
+                description: render_description(vm, issue, runbook_link),
+                priority: TicketPriority::from_severity(issue.severity),
+                fingerprint: issue.fingerprint.clone(),
+                labels: vec![issue.issue_type.to_string(), vm.host.clone(), vm.cluster.clone()],
+            });
+        }
+    }
+    tickets
+}
+
+/// Writes `tickets` to `path` as pretty-printed JSON.
+pub fn write_ticket_export(path: &str, tickets: &[Ticket]) -> anyhow::Result<()> {
+    use anyhow::Context;
+    let json = serde_json::to_string_pretty(tickets)?;
+    std::fs::write(path, json).with_context(|| format!("writing ticket export to {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{MetricsSourceStatus, PowerState, UsageBasis};
+    use std::collections::HashMap;
+
+    fn vm(name: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    fn issue(issue_type: VMIssueType, first_seen: Option<DateTime<Utc>>) -> DetectedIssue {
+        let mut issue = DetectedIssue::measured(issue_type, 42.0, 10.0, "x");
+        issue.fingerprint = format!("{issue_type}-fp");
+        issue.first_seen = first_seen;
+        issue
+    }
+
+    #[test]
+    fn only_matching_issue_types_are_exported() {
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+        let statuses = vec![vm(
+            "vm-0001",
+            vec![
+                issue(VMIssueType::HardwareVersionOld, Some(now)),
+                issue(VMIssueType::HighCpuUsage, Some(now)),
+            ],
+        )];
+        let issue_types = HashSet::from([VMIssueType::HardwareVersionOld]);
+        let tickets = build_tickets(&statuses, &issue_types, false, now, None);
+        assert_eq!(tickets.len(), 1);
+        assert_eq!(tickets[0].labels[0], "HARDWARE_VERSION_OLD");
+    }
+
+    #[test]
+    fn only_new_skips_issues_first_seen_in_an_earlier_run() {
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+        let earlier: DateTime<Utc> = "2026-08-01T00:00:00Z".parse().unwrap();
+        let statuses = vec![vm(
+            "vm-0001",
+            vec![issue(VMIssueType::HardwareVersionOld, Some(now)), issue(VMIssueType::SuspendedTooLong, Some(earlier))],
+        )];
+        let issue_types = HashSet::from([VMIssueType::HardwareVersionOld, VMIssueType::SuspendedTooLong]);
+
+        let all = build_tickets(&statuses, &issue_types, false, now, None);
+        assert_eq!(all.len(), 2);
+
+        let only_new = build_tickets(&statuses, &issue_types, true, now, None);
+        assert_eq!(only_new.len(), 1);
+        assert_eq!(only_new[0].labels[0], "HARDWARE_VERSION_OLD");
+    }
+
+    #[test]
+    fn priority_is_derived_from_severity() {
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+        let statuses = vec![vm("vm-0001", vec![issue(VMIssueType::HardwareVersionOld, Some(now))])];
+        let issue_types = HashSet::from([VMIssueType::HardwareVersionOld]);
+        let tickets = build_tickets(&statuses, &issue_types, false, now, None);
+        assert_eq!(tickets[0].priority, TicketPriority::from_severity(VMIssueType::HardwareVersionOld.severity()));
+    }
+
+    #[test]
+    fn description_includes_owner_measurement_detail_and_runbook() {
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+        let mut v = vm("vm-0001", vec![issue(VMIssueType::HardwareVersionOld, Some(now))]);
+        v.attributes.insert("Owner".to_string(), "team-storage".to_string());
+        let issue_types = HashSet::from([VMIssueType::HardwareVersionOld]);
+        let tickets = build_tickets(&[v], &issue_types, false, now, Some("https://runbooks.example.com/hw-version"));
+        let description = &tickets[0].description;
+        assert!(description.contains("team-storage"));
+        assert!(description.contains("measured 42.0 against a threshold of 10.0"));
+        assert!(description.contains("Detail: x"));
+        assert!(description.contains("https://runbooks.example.com/hw-version"));
+    }
+
+    #[test]
+    fn fingerprint_is_carried_through_unchanged() {
+        let now: DateTime<Utc> = "2026-08-08T00:00:00Z".parse().unwrap();
+        let statuses = vec![vm("vm-0001", vec![issue(VMIssueType::HardwareVersionOld, Some(now))])];
+        let issue_types = HashSet::from([VMIssueType::HardwareVersionOld]);
+        let tickets = build_tickets(&statuses, &issue_types, false, now, None);
+        assert_eq!(tickets[0].fingerprint, "HARDWARE_VERSION_OLD-fp");
+    }
+}
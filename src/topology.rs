@@ -0,0 +1,293 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+use crate::vm::{Severity, VMResourceStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TopologyFormat {
+    Dot,
+    Mermaid,
+}
+
+impl TopologyFormat {
+    /// Picks DOT or Mermaid syntax from the output file extension, as
+    /// `--topology-output` documents.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = path.rsplit('.').next()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "dot" | "gv" => Some(TopologyFormat::Dot),
+            "mmd" => Some(TopologyFormat::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+fn severity_color(severity: Option<Severity>) -> &'static str {
+    match severity {
+        Some(Severity::Critical) => "#d32f2f",
+        Some(Severity::Warning) => "#f9a825",
+        Some(Severity::Informational) => "#1976d2",
+        None => "#9e9e9e",
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(s: &str) -> String {
+    s.replace('"', "&quot;")
+}
+
+struct Node<'a> {
+    vm: &'a VMResourceStatus,
+    dimmed: bool,
+}
+
+/// Builds the cluster -> host -> VM graph, limited to VMs with issues, plus
+/// their healthy host-mates (dimmed) when `context` is set. Stops adding
+/// nodes once `max_nodes` VM nodes have been collected, and returns the nodes
+/// together with the count of VMs that were dropped so callers can report
+/// what was left out.
+fn collect_nodes(statuses: &[VMResourceStatus], context: bool, max_nodes: usize) -> (Vec<Node<'_>>, usize) {
+    let problem_hosts: std::collections::BTreeSet<&str> =
+        statuses.iter().filter(|vm| vm.has_issues()).map(|vm| vm.host.as_str()).collect();
+    let mut nodes = Vec::new();
+    let mut dropped = 0usize;
+    for vm in statuses {
+        if !vm.has_issues() && !(context && problem_hosts.contains(vm.host.as_str())) {
+            continue;
+        }
+        if nodes.len() >= max_nodes {
+            dropped += 1;
+            continue;
+        }
+        nodes.push(Node {
+            vm,
+            dimmed: !vm.has_issues(),
+        });
+    }
+    (nodes, dropped)
+}
+
+fn render_dot(nodes: &[Node<'_>]) -> String {
+    let mut clusters: BTreeMap<&str, BTreeMap<&str, Vec<&Node<'_>>>> = BTreeMap::new();
+    for node in nodes {
+        clusters
+            .entry(node.vm.cluster.as_str())
+            .or_default()
+            .entry(node.vm.host.as_str())
+            .or_default()
+            .push(node);
+    }
+
+    let mut out = String::from("digraph topology {\n  rankdir=LR;\n");
+    for (cluster, hosts) in &clusters {
+        out.push_str(&format!(
+            "  subgraph \"cluster_{c}\" {{ label=\"{c}\";\n",
+            c = escape_dot(cluster)
+        ));
+        for (host, vms) in hosts {
+            out.push_str(&format!("    \"{h}\" [shape=box];\n", h = escape_dot(host)));
+            for node in vms {
+                let name = escape_dot(&node.vm.name);
+                let color = if node.dimmed { "#e0e0e0" } else { severity_color(node.vm.worst_severity()) };
+                let style = if node.dimmed { "style=dashed," } else { "style=filled," };
+                out.push_str(&format!(
+                    "    \"{name}\" [{style} fillcolor=\"{color}\"];\n    \"{h}\" -> \"{name}\" [label=\"cpu={cpu:.0}% mem={mem:.0}%\"];\n",
+                    h = escape_dot(host),
+                    cpu = node.vm.cpu_usage_pct,
+                    mem = node.vm.memory_usage_pct,
+                ));
+            }
+        }
+        out.push_str("  }\n");
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn render_mermaid(nodes: &[Node<'_>]) -> String {
+    let mut clusters: BTreeMap<&str, BTreeMap<&str, Vec<&Node<'_>>>> = BTreeMap::new();
+    for node in nodes {
+        clusters
+            .entry(node.vm.cluster.as_str())
+            .or_default()
+            .entry(node.vm.host.as_str())
+            .or_default()
+            .push(node);
+    }
+
+    let mut out = String::from("graph LR\n");
+    for (cluster, hosts) in &clusters {
+        out.push_str(&format!("  subgraph {}\n", escape_mermaid(cluster)));
+        for (host, vms) in hosts {
+            for node in vms {
+                let name = escape_mermaid(&node.vm.name);
+                let color = if node.dimmed { "#e0e0e0" } else { severity_color(node.vm.worst_severity()) };
+                out.push_str(&format!(
+                    "    {h}[\"{h}\"] -->|\"cpu={cpu:.0}% mem={mem:.0}%\"| {name}[\"{name}\"]\n    style {name} fill:{color}\n",
+                    h = escape_mermaid(host),
+                    cpu = node.vm.cpu_usage_pct,
+                    mem = node.vm.memory_usage_pct,
+                ));
+            }
+        }
+        out.push_str("  end\n");
+    }
+    out
+}
+
+/// Renders the topology graph for `--topology-output`. VMs beyond `max_nodes`
+/// are silently capped to keep the graph readable; the caller should log how
+/// many were dropped if it cares.
+pub fn render_topology(
+    statuses: &[VMResourceStatus],
+    format: TopologyFormat,
+    context: bool,
+    max_nodes: usize,
+) -> (String, usize) {
+    let (nodes, dropped) = collect_nodes(statuses, context, max_nodes);
+    let rendered = match format {
+        TopologyFormat::Dot => render_dot(&nodes),
+        TopologyFormat::Mermaid => render_mermaid(&nodes),
+    };
+    (rendered, dropped)
+}
+
+/// Renders and writes the topology graph to `path`, per `--topology-output`.
+pub fn write_topology_output(
+    path: &str,
+    statuses: &[VMResourceStatus],
+    context: bool,
+    max_nodes: usize,
+) -> Result<()> {
+    let format = TopologyFormat::from_path(path)
+        .with_context(|| format!("--topology-output {path}: unrecognized extension, expected .dot, .gv or .mmd"))?;
+    let (rendered, dropped) = render_topology(statuses, format, context, max_nodes);
+    if dropped > 0 {
+        eprintln!("topology: dropped {dropped} VM(s) beyond --topology-max-nodes");
+    }
+    std::fs::write(path, rendered).with_context(|| format!("writing topology output to {path}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::{DetectedIssue, PowerState, VMIssueType};
+
+    fn sample() -> Vec<VMResourceStatus> {
+        vec![
+            VMResourceStatus {
+                name: "vm-\"quoted\"".to_string(),
+                host: "esxi-01".to_string(),
+                cluster: "cluster-a".to_string(),
+                inventory_path: "/unknown".to_string(),
+                power_state: PowerState::PoweredOn,
+                cpu_usage_pct: 95.0,
+                memory_usage_pct: 50.0,
+                raw_metrics: std::collections::HashMap::new(),
+                metrics_source: crate::vm::MetricsSourceStatus::Available,
+                cpu_count: 2,
+                cores_per_socket: 1,
+                memory_gb: 16.0,
+                hardware_version: "vmx-19".to_string(),
+                cpu_hot_add_enabled: true,
+                memory_hot_add_enabled: true,
+                guest_visible_memory_mb: None,
+                guest_visible_cpu_count: None,
+                disk_allocated_gb: 100.0,
+                disk_used_gb: Some(50.0),
+                usage_basis: crate::vm::UsageBasis::Configured,
+                tools_running: true,
+                clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+                attributes: std::collections::HashMap::new(),
+                notes: None,
+                migration_count_24h: 0,
+                last_migration: None,
+                uptime_secs: 30.0 * 86400.0,
+                created_recently: false,
+                power_on_count: 0,
+                last_power_on_secs_ago: None,
+                suspended_duration_secs: None,
+                health_score: 100.0,
+                change_version: 0,
+                issues: vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "CPU usage at 95.0%")],
+            },
+            VMResourceStatus {
+                name: "vm-healthy".to_string(),
+                host: "esxi-01".to_string(),
+                cluster: "cluster-a".to_string(),
+                inventory_path: "/unknown".to_string(),
+                power_state: PowerState::PoweredOn,
+                cpu_usage_pct: 10.0,
+                memory_usage_pct: 10.0,
+                raw_metrics: std::collections::HashMap::new(),
+                metrics_source: crate::vm::MetricsSourceStatus::Available,
+                cpu_count: 2,
+                cores_per_socket: 1,
+                memory_gb: 16.0,
+                hardware_version: "vmx-19".to_string(),
+                cpu_hot_add_enabled: true,
+                memory_hot_add_enabled: true,
+                guest_visible_memory_mb: None,
+                guest_visible_cpu_count: None,
+                disk_allocated_gb: 100.0,
+                disk_used_gb: Some(50.0),
+                usage_basis: crate::vm::UsageBasis::Configured,
+                tools_running: true,
+                clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+                attributes: std::collections::HashMap::new(),
+                notes: None,
+                migration_count_24h: 0,
+                last_migration: None,
+                uptime_secs: 30.0 * 86400.0,
+                created_recently: false,
+                power_on_count: 0,
+                last_power_on_secs_ago: None,
+                suspended_duration_secs: None,
+                health_score: 100.0,
+                change_version: 0,
+                issues: vec![],
+            },
+        ]
+    }
+
+    #[test]
+    fn dot_golden_without_context() {
+        let (dot, dropped) = render_topology(&sample(), TopologyFormat::Dot, false, 50);
+        assert_eq!(dropped, 0);
+        assert_eq!(
+            dot,
+            "digraph topology {\n  rankdir=LR;\n  subgraph \"cluster_cluster-a\" { label=\"cluster-a\";\n    \"esxi-01\" [shape=box];\n    \"vm-\\\"quoted\\\"\" [style=filled, fillcolor=\"#f9a825\"];\n    \"esxi-01\" -> \"vm-\\\"quoted\\\"\" [label=\"cpu=95% mem=50%\"];\n  }\n}\n"
+        );
+    }
+
+    #[test]
+    fn mermaid_golden_with_context() {
+        let (mmd, dropped) = render_topology(&sample(), TopologyFormat::Mermaid, true, 50);
+        assert_eq!(dropped, 0);
+        assert!(mmd.contains("fill:#e0e0e0"));
+        assert!(mmd.contains("vm-healthy"));
+    }
+
+    #[test]
+    fn max_nodes_caps_and_reports_drops() {
+        let (_, dropped) = render_topology(&sample(), TopologyFormat::Dot, false, 0);
+        assert_eq!(dropped, 1);
+    }
+
+    #[test]
+    fn format_from_path() {
+        assert_eq!(TopologyFormat::from_path("out.dot"), Some(TopologyFormat::Dot));
+        assert_eq!(TopologyFormat::from_path("out.mmd"), Some(TopologyFormat::Mermaid));
+        assert_eq!(TopologyFormat::from_path("out.txt"), None);
+    }
+}
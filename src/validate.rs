@@ -0,0 +1,808 @@
+use crate::cli::{Args, OutputFormat};
+use crate::topology::TopologyFormat;
+use std::fmt;
+
+/// Whether a [`ValidationIssue`] blocks `--config-validate` (and every other
+/// entry point into this crate) or is merely a suspicious-but-legal
+/// combination worth surfacing without failing the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationSeverity {
+    Error,
+    Warning,
+}
+
+/// One finding from [`validate_args_detailed`]. `code` is the machine-readable
+/// field name the finding is about - stable across releases, so CI in the
+/// config repo can match on it instead of parsing prose. `Display` renders
+/// `"{code}: {message}"`, the same shape `validate_args` has always returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub code: &'static str,
+    pub message: String,
+    pub severity: ValidationSeverity,
+}
+
+impl ValidationIssue {
+    fn error(code: &'static str, message: impl Into<String>) -> Self {
+        ValidationIssue { code, message: message.into(), severity: ValidationSeverity::Error }
+    }
+
+    fn warning(code: &'static str, message: impl Into<String>) -> Self {
+        ValidationIssue { code, message: message.into(), severity: ValidationSeverity::Warning }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+/// Checks thresholds and flag combinations for obviously bad or merely
+/// suspicious values without touching the network. Used by
+/// `--config-validate` and run unconditionally before any report is
+/// generated, so a bad deploy fails fast with a clear per-field list instead
+/// of a confusing downstream panic or bail. Every entry here is produced up
+/// front, not short-circuited on the first hit - a config can be wrong in
+/// more than one field at once, and seeing all of them in one pass saves a
+/// round trip through CI.
+pub fn validate_args_detailed(args: &Args) -> Vec<ValidationIssue> {
+    let mut issues = Vec::new();
+
+    match (&args.sso_token, &args.cloud_csp_token, &args.username, &args.password) {
+        (Some(_), None, None, None) => {}
+        (None, Some(_), None, None) => {}
+        (None, None, Some(_), Some(_)) => {}
+        (Some(_), Some(_), _, _) => issues.push(ValidationIssue::error("cloud-csp-token", "mutually exclusive with --sso-token")),
+        (Some(_), _, _, _) => issues.push(ValidationIssue::error("sso-token", "mutually exclusive with --username/--password")),
+        (_, Some(_), _, _) => issues.push(ValidationIssue::error("cloud-csp-token", "mutually exclusive with --username/--password")),
+        (None, None, None, None) => issues.push(ValidationIssue::error("username/password", "required unless --sso-token or --cloud-csp-token is set")),
+        (None, None, _, _) => issues.push(ValidationIssue::error("username/password", "both must be set together")),
+    }
+    if args.clock_skew_threshold_secs < 0.0 {
+        issues.push(ValidationIssue::error("clock-skew-threshold-secs", "must not be negative"));
+    }
+    if args.check_reachability && args.reachability_timeout_ms == 0 {
+        issues.push(ValidationIssue::error("reachability-timeout-ms", "must be greater than 0"));
+    }
+    if args.vm_count == 0 {
+        issues.push(ValidationIssue::error("vm-count", "must be greater than 0"));
+    }
+    if args.route_by_attribute.is_some() && args.route_config.is_none() {
+        issues.push(ValidationIssue::error("route-config", "required when --route-by-attribute is set"));
+    }
+    if args.time_budget == Some(0) {
+        issues.push(ValidationIssue::error("time-budget", "must be greater than 0"));
+    }
+    if args.watch && args.dashboard {
+        issues.push(ValidationIssue::error("watch", "mutually exclusive with --dashboard"));
+    }
+    if args.inspect.is_some() && args.dashboard {
+        issues.push(ValidationIssue::error("inspect", "mutually exclusive with --dashboard"));
+    }
+    if args.inspect.is_some() && args.format == OutputFormat::Csv {
+        issues.push(ValidationIssue::error("inspect", "--format csv is not supported, use text or json"));
+    }
+    if args.read_only_assert && args.apply.is_some() {
+        issues.push(ValidationIssue::error("read-only-assert", "mutually exclusive with --apply"));
+    }
+    if let Some(path) = &args.topology_output {
+        if TopologyFormat::from_path(path).is_none() {
+            issues.push(ValidationIssue::error(
+                "topology-output",
+                format!("unrecognized extension in '{path}' (expected .dot, .gv, or .mmd)"),
+            ));
+        }
+    }
+    let disabled_issue_types = args.disabled_issue_types();
+    if let Err(err) = &disabled_issue_types {
+        issues.push(ValidationIssue::error("disable-issues", err.clone()));
+    }
+    let issue_threshold_warnings = args.issue_threshold_warnings();
+    if let Err(err) = &issue_threshold_warnings {
+        issues.push(ValidationIssue::error("issue-threshold-warnings", err.clone()));
+    }
+    if let (Ok(disabled), Ok(overridden)) = (&disabled_issue_types, &issue_threshold_warnings) {
+        let mut dead_overrides: Vec<_> = overridden.intersection(disabled).map(|t| t.to_string()).collect();
+        dead_overrides.sort();
+        if !dead_overrides.is_empty() {
+            issues.push(ValidationIssue::warning(
+                "issue-threshold-warnings",
+                format!(
+                    "{} {} also in --disable-issues, so the override never takes effect",
+                    dead_overrides.join(", "),
+                    if dead_overrides.len() == 1 { "is" } else { "are" }
+                ),
+            ));
+        }
+    }
+    if args.demo && args.notifier_config.is_some() && !args.demo_allow_notify {
+        issues.push(ValidationIssue::error("demo-allow-notify", "required when --demo is combined with --notifier-config"));
+    }
+    if args.alert_cooldown == Some(0) {
+        issues.push(ValidationIssue::error("alert-cooldown", "must be greater than 0"));
+    }
+    if args.check_boot_storm && !args.check_uptime {
+        issues.push(ValidationIssue::error("check-boot-storm", "requires --check-uptime"));
+    }
+    if let Err(err) = args.boot_storm_threshold() {
+        issues.push(ValidationIssue::error("boot-storm-threshold", err));
+    }
+    if args.check_drs_rules && args.drs_rules.is_none() {
+        issues.push(ValidationIssue::error("drs-rules", "required when --check-drs-rules is set"));
+    }
+    if args.vm_list_stdin && (args.watch || args.dashboard) {
+        issues.push(ValidationIssue::error("vm-list-stdin", "not supported with --watch or --dashboard"));
+    }
+    if args.template.is_some() && args.template_output.is_none() {
+        issues.push(ValidationIssue::error("template-output", "required when --template is set"));
+    }
+    if args.max_total_requests == Some(0) {
+        issues.push(ValidationIssue::error("max-total-requests", "must be greater than 0"));
+    }
+    if args.per_vm_timeout_ms == Some(0) {
+        issues.push(ValidationIssue::error("per-vm-timeout-ms", "must be greater than 0"));
+    }
+    if args.session_count_warn == 0 {
+        issues.push(ValidationIssue::error("session-count-warn", "must be greater than 0"));
+    }
+    if args.max_file_checks == Some(0) {
+        issues.push(ValidationIssue::error("max-file-checks", "must be greater than 0"));
+    }
+    if args.host_concurrency == Some(0) {
+        issues.push(ValidationIssue::error("host-concurrency", "must be greater than 0"));
+    }
+    if !(0.0..=100.0).contains(&args.underuse_threshold) {
+        issues.push(ValidationIssue::error("underuse-threshold", "must be between 0 and 100"));
+    }
+    if args.output_on_change && args.output.is_none() {
+        issues.push(ValidationIssue::error("output-on-change", "requires --output"));
+    }
+    if args.site_config.is_some() && args.site.is_none() {
+        issues.push(ValidationIssue::error("site-config", "has no effect without --site"));
+    }
+    if let Err(err) = args.selection() {
+        issues.push(ValidationIssue::error("select", err));
+    }
+    if args.explain_selection && args.select.is_none() {
+        issues.push(ValidationIssue::error("explain-selection", "requires --select"));
+    }
+    if args.max_suspend_hours == Some(0.0) {
+        issues.push(ValidationIssue::error("max-suspend-hours", "must be greater than 0"));
+    }
+    if let Err(err) = args.ticket_issue_types() {
+        issues.push(ValidationIssue::error("ticket-issue-types", err));
+    }
+    if args.ticket_only_new && args.ticket_export.is_none() {
+        issues.push(ValidationIssue::error("ticket-only-new", "requires --ticket-export"));
+    }
+    if args.ticket_runbook_link.is_some() && args.ticket_export.is_none() {
+        issues.push(ValidationIssue::error("ticket-runbook-link", "has no effect without --ticket-export"));
+    }
+    if !args.ticket_issue_types.is_empty() && args.ticket_export.is_none() {
+        issues.push(ValidationIssue::error("ticket-issue-types", "has no effect without --ticket-export"));
+    }
+    match args.preview_thresholds() {
+        Err(err) => issues.push(ValidationIssue::error("preview-thresholds", err)),
+        Ok(Some(proposed)) => {
+            if proposed.cpu_pct == crate::vcenter::CPU_HIGH_THRESHOLD_PCT && proposed.memory_pct == crate::vcenter::MEMORY_HIGH_THRESHOLD_PCT {
+                issues.push(ValidationIssue::warning(
+                    "preview-thresholds",
+                    format!(
+                        "proposed cpu={:.0} memory={:.0} match the current thresholds - preview would show no deltas",
+                        proposed.cpu_pct, proposed.memory_pct
+                    ),
+                ));
+            }
+        }
+        Ok(None) => {}
+    }
+
+    issues
+}
+
+/// `validate_args_detailed`, filtered to the blocking entries and formatted
+/// as `"{code}: {message}"` strings - the stable public shape this function
+/// has always returned. Warnings are available via `validate_args_detailed`
+/// for callers that want to surface them separately.
+pub fn validate_args(args: &Args) -> Vec<String> {
+    validate_args_detailed(args)
+        .into_iter()
+        .filter(|issue| issue.severity == ValidationSeverity::Error)
+        .map(|issue| issue.to_string())
+        .collect()
+}
+
+/// Result of [`run_config_validate`]: `errors` block `--config-validate`
+/// (and exit it non-zero); `warnings` never do, but are still worth printing
+/// so a suspicious-but-legal config doesn't silently ship.
+pub struct ConfigValidation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// Runs the `--config-validate` check: validates flags, then (since `--host`
+/// plus either `--username`/`--password`, `--sso-token`, or
+/// `--cloud-csp-token` make authentication implicit) confirms the
+/// credentials actually work against vCenter. Empty `errors` means the
+/// config is good to deploy. Skips the credential check entirely when the
+/// username/password/sso-token/cloud-csp-token combination is already
+/// invalid - `validate_args` above already reported it, and there's no
+/// point letting `auth::authenticate_from_args` report the same thing
+/// again as a credential failure.
+pub fn run_config_validate(args: &Args) -> ConfigValidation {
+    let mut errors = validate_args(args);
+    let warnings: Vec<String> = validate_args_detailed(args)
+        .into_iter()
+        .filter(|issue| issue.severity == ValidationSeverity::Warning)
+        .map(|issue| issue.to_string())
+        .collect();
+    let credentials_are_well_formed = matches!(
+        (&args.sso_token, &args.cloud_csp_token, &args.username, &args.password),
+        (Some(_), None, None, None) | (None, Some(_), None, None) | (None, None, Some(_), Some(_))
+    );
+    if credentials_are_well_formed {
+        if let Err(err) = crate::auth::authenticate_from_args(args) {
+            errors.push(format!("credentials: {err}"));
+        }
+    }
+    ConfigValidation { errors, warnings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args() -> Args {
+        Args {
+            host: "vcenter.example.com".to_string(),
+            username: Some("tester".to_string()),
+            password: Some("secret".to_string()),
+            sso_token: None,
+            cloud_csp_token: None,
+            vmc_profile: false,
+            format: crate::cli::OutputFormat::Text,
+            output: None,
+            watch: false,
+            interval_secs: 60,
+            vm_count: 50,
+            service: false,
+            no_stats: false,
+            topology_output: None,
+            topology_context: false,
+            topology_max_nodes: 200,
+            cpu_threshold: crate::vcenter::CPU_HIGH_THRESHOLD_PCT,
+            memory_threshold: crate::vcenter::MEMORY_HIGH_THRESHOLD_PCT,
+            check_clock: false,
+            clock_skew_threshold_secs: 5.0,
+            route_by_attribute: None,
+            route_config: None,
+            dashboard: false,
+            inspect: None,
+            check_reachability: false,
+            reachability_port: 443,
+            reachability_timeout_ms: 1000,
+            check_process: Vec::new(),
+            config_validate: false,
+            time_budget: None,
+            state_file: "network-monitor-state.json".to_string(),
+            summary_output: None,
+            logfmt_output: None,
+            json_schema_version: crate::cli::JsonSchemaVersionArg::V2,
+            api_rate_log: false,
+            timing: false,
+            budget_hint: None,
+            single_threaded: false,
+            host_concurrency: None,
+            read_only_assert: false,
+            check_vcpu_allocation: false,
+            max_vcpu_ratio: 1.0,
+            notifier_config: None,
+            test_notifiers: false,
+            fail_on_notify_error: false,
+            replay: None,
+            strict_parsing: false,
+            suggest_thresholds: false,
+            history: Vec::new(),
+            lookback_days: 30,
+            sparklines: false,
+            rightsizing_report: false,
+            underuse_threshold: 20.0,
+            rightsize_exempt_attribute: "RightsizeExempt".to_string(),
+            apply: None,
+            strict_json: false,
+            output_rotate: crate::cli::OutputRotationArg::Overwrite,
+            output_keep_n: 5,
+            output_dir: None,
+            output_on_change: false,
+            aggregate: None,
+            check_migrations: false,
+            migration_window_hours: 24.0,
+            max_migrations: 5,
+            disable_issues: Vec::new(),
+            names_for_issue: None,
+            score_weights: None,
+            fail_below_score: None,
+            demo: false,
+            demo_allow_notify: false,
+            alert_cooldown: None,
+            check_uptime: false,
+            short_uptime_threshold_secs: 900.0,
+            boot_history_window_hours: 1.0,
+            reboot_loop_count: 3,
+            uptime_format: crate::cli::UptimeFormatArg::Human,
+            run_id: None,
+            check_boot_storm: false,
+            boot_storm_threshold: "10%".to_string(),
+            suppress_individual_boot_storm_alerts: false,
+            check_host_state: false,
+            check_host_health: false,
+            check_hw_version: false,
+            min_hw_version: 15,
+            delta_only: false,
+            full_every: 0,
+            check_drs_rules: false,
+            drs_rules: None,
+            require_hot_add: false,
+            hot_add_scope: None,
+            vm_list_stdin: false,
+            exclude_powered_off_from_stats: false,
+            template: None,
+            template_output: None,
+            max_total_requests: None,
+            per_vm_timeout_ms: None,
+            fail_on_issues: false,
+            issue_threshold_warnings: Vec::new(),
+            atomic: false,
+            atomic_max_deferred: 0,
+            session_count_warn: 20,
+            reap_stale_sessions: None,
+            openmetrics_output: None,
+            password_expiry_warn_days: 14,
+            group_by: None,
+            since_last_run: false,
+            force_full: false,
+            check_vm_files: false,
+            max_file_checks: None,
+            verbose: 0,
+            verbose_legacy: false,
+            quiet: false,
+            no_recommendations: false,
+            no_respect_maintenance_mode: false,
+            site: None,
+            site_config: None,
+            print_effective_config: false,
+            check_hotadd: false,
+            check_guest_resource_mismatch: false,
+            check_storage_waste: false,
+            lock_file: None,
+            lock_wait_secs: None,
+            compact_json: false,
+            metrics_source: crate::cli::MetricsSourceArg::Simulated,
+            select: None,
+            explain_selection: false,
+            max_suspend_hours: None,
+            include_raw_metrics: false,
+            preview_thresholds: Vec::new(),
+            sanity_check_thresholds: false,
+            ticket_export: None,
+            ticket_issue_types: Vec::new(),
+            ticket_only_new: false,
+            ticket_runbook_link: None,
+        }
+    }
+
+    #[test]
+    fn flags_mutually_exclusive_reported() {
+        let mut a = args();
+        a.watch = true;
+        a.dashboard = true;
+        assert!(validate_args(&a).iter().any(|e| e.contains("mutually exclusive")));
+    }
+
+    #[test]
+    fn read_only_assert_rejects_apply() {
+        let mut a = args();
+        a.read_only_assert = true;
+        a.apply = Some("overrides.json".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.contains("read-only-assert")));
+    }
+
+    #[test]
+    fn read_only_assert_alone_is_accepted() {
+        let mut a = args();
+        a.read_only_assert = true;
+        assert!(validate_args(&a).is_empty());
+    }
+
+    /// Table-driven: one row per independent error rule, each checked
+    /// against the one field mutation that should trip it and nothing else.
+    /// A rule silently stopping firing (e.g. after a field got renamed)
+    /// wouldn't be caught by a single happy-path test on a different rule.
+    #[test]
+    fn each_error_rule_fires_on_its_own_and_carries_its_code() {
+        struct Case {
+            name: &'static str,
+            mutate: fn(&mut Args),
+            expected_code: &'static str,
+        }
+
+        let cases = vec![
+            Case { name: "vm-count zero", mutate: |a| a.vm_count = 0, expected_code: "vm-count" },
+            Case {
+                name: "route-by-attribute without route-config",
+                mutate: |a| a.route_by_attribute = Some("Owner".to_string()),
+                expected_code: "route-config",
+            },
+            Case { name: "time-budget zero", mutate: |a| a.time_budget = Some(0), expected_code: "time-budget" },
+            Case { name: "alert-cooldown zero", mutate: |a| a.alert_cooldown = Some(0), expected_code: "alert-cooldown" },
+            Case {
+                name: "max-total-requests zero",
+                mutate: |a| a.max_total_requests = Some(0),
+                expected_code: "max-total-requests",
+            },
+            Case {
+                name: "per-vm-timeout-ms zero",
+                mutate: |a| a.per_vm_timeout_ms = Some(0),
+                expected_code: "per-vm-timeout-ms",
+            },
+            Case {
+                name: "session-count-warn zero",
+                mutate: |a| a.session_count_warn = 0,
+                expected_code: "session-count-warn",
+            },
+            Case { name: "max-file-checks zero", mutate: |a| a.max_file_checks = Some(0), expected_code: "max-file-checks" },
+            Case { name: "host-concurrency zero", mutate: |a| a.host_concurrency = Some(0), expected_code: "host-concurrency" },
+            Case {
+                name: "underuse-threshold out of range",
+                mutate: |a| a.underuse_threshold = 150.0,
+                expected_code: "underuse-threshold",
+            },
+            Case {
+                name: "output-on-change without output",
+                mutate: |a| a.output_on_change = true,
+                expected_code: "output-on-change",
+            },
+            Case {
+                name: "site-config without site",
+                mutate: |a| a.site_config = Some("sites.json".to_string()),
+                expected_code: "site-config",
+            },
+            Case {
+                name: "explain-selection without select",
+                mutate: |a| a.explain_selection = true,
+                expected_code: "explain-selection",
+            },
+            Case {
+                name: "max-suspend-hours zero",
+                mutate: |a| a.max_suspend_hours = Some(0.0),
+                expected_code: "max-suspend-hours",
+            },
+            Case {
+                name: "check-boot-storm without check-uptime",
+                mutate: |a| a.check_boot_storm = true,
+                expected_code: "check-boot-storm",
+            },
+            Case {
+                name: "check-drs-rules without drs-rules",
+                mutate: |a| a.check_drs_rules = true,
+                expected_code: "drs-rules",
+            },
+            Case {
+                name: "template without template-output",
+                mutate: |a| a.template = Some("t.json".to_string()),
+                expected_code: "template-output",
+            },
+            Case {
+                name: "ticket-only-new without ticket-export",
+                mutate: |a| a.ticket_only_new = true,
+                expected_code: "ticket-only-new",
+            },
+            Case {
+                name: "ticket-runbook-link without ticket-export",
+                mutate: |a| a.ticket_runbook_link = Some("https://runbooks.example.com".to_string()),
+                expected_code: "ticket-runbook-link",
+            },
+            Case {
+                name: "ticket-issue-types without ticket-export",
+                mutate: |a| a.ticket_issue_types = vec!["HARDWARE_VERSION_OLD".to_string()],
+                expected_code: "ticket-issue-types",
+            },
+            Case {
+                name: "unknown ticket-issue-types code",
+                mutate: |a| {
+                    a.ticket_export = Some("tickets.json".to_string());
+                    a.ticket_issue_types = vec!["NOT_A_REAL_CODE".to_string()];
+                },
+                expected_code: "ticket-issue-types",
+            },
+        ];
+
+        for case in cases {
+            let mut a = args();
+            (case.mutate)(&mut a);
+            let issues = validate_args_detailed(&a);
+            assert!(
+                issues.iter().any(|i| i.code == case.expected_code && i.severity == ValidationSeverity::Error),
+                "case '{}': expected an error with code '{}', got {:?}",
+                case.name,
+                case.expected_code,
+                issues
+            );
+        }
+    }
+
+    #[test]
+    fn disable_issues_and_issue_threshold_warnings_overlap_is_a_warning_not_an_error() {
+        let mut a = args();
+        a.disable_issues = vec!["TOOLS_NOT_RUNNING".to_string()];
+        a.issue_threshold_warnings = vec!["TOOLS_NOT_RUNNING".to_string()];
+        let issues = validate_args_detailed(&a);
+        assert!(validate_args(&a).is_empty(), "a dead override must not block --config-validate");
+        assert!(issues.iter().any(|i| {
+            i.code == "issue-threshold-warnings" && i.severity == ValidationSeverity::Warning && i.message.contains("TOOLS_NOT_RUNNING")
+        }));
+    }
+
+    #[test]
+    fn issue_threshold_warnings_without_overlapping_disable_issues_is_clean() {
+        let mut a = args();
+        a.disable_issues = vec!["TOOLS_NOT_RUNNING".to_string()];
+        a.issue_threshold_warnings = vec!["CLOCK_SKEW".to_string()];
+        assert!(validate_args_detailed(&a).is_empty());
+    }
+
+    #[test]
+    fn preview_thresholds_matching_the_current_thresholds_is_a_warning_not_an_error() {
+        let mut a = args();
+        a.preview_thresholds = vec![
+            format!("cpu={}", crate::vcenter::CPU_HIGH_THRESHOLD_PCT),
+            format!("memory={}", crate::vcenter::MEMORY_HIGH_THRESHOLD_PCT),
+        ];
+        let issues = validate_args_detailed(&a);
+        assert!(validate_args(&a).is_empty());
+        assert!(issues
+            .iter()
+            .any(|i| i.code == "preview-thresholds" && i.severity == ValidationSeverity::Warning));
+    }
+
+    #[test]
+    fn preview_thresholds_changing_a_value_has_no_warning() {
+        let mut a = args();
+        a.preview_thresholds = vec!["cpu=70".to_string(), "memory=70".to_string()];
+        assert!(validate_args_detailed(&a).is_empty());
+    }
+
+    #[test]
+    fn route_by_attribute_requires_route_config() {
+        let mut a = args();
+        a.route_by_attribute = Some("Owner".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("route-config")));
+    }
+
+    #[test]
+    fn unrecognized_topology_extension_reported() {
+        let mut a = args();
+        a.topology_output = Some("out.svg".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("topology-output")));
+    }
+
+    #[test]
+    fn unknown_disable_issues_code_reported() {
+        let mut a = args();
+        a.disable_issues = vec!["NOT_A_REAL_CODE".to_string()];
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("disable-issues")));
+    }
+
+    #[test]
+    fn unknown_issue_threshold_warnings_code_reported() {
+        let mut a = args();
+        a.issue_threshold_warnings = vec!["NOT_A_REAL_CODE".to_string()];
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("issue-threshold-warnings")));
+    }
+
+    #[test]
+    fn demo_with_notifier_config_requires_demo_allow_notify() {
+        let mut a = args();
+        a.demo = true;
+        a.notifier_config = Some("notifiers.json".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("demo-allow-notify")));
+    }
+
+    #[test]
+    fn zero_alert_cooldown_reported() {
+        let mut a = args();
+        a.alert_cooldown = Some(0);
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("alert-cooldown")));
+    }
+
+    #[test]
+    fn boot_storm_requires_uptime_check() {
+        let mut a = args();
+        a.check_boot_storm = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("check-boot-storm")));
+
+        a.check_uptime = true;
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("check-boot-storm")));
+    }
+
+    #[test]
+    fn invalid_boot_storm_threshold_reported() {
+        let mut a = args();
+        a.boot_storm_threshold = "not-a-number".to_string();
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("boot-storm-threshold")));
+    }
+
+    #[test]
+    fn check_drs_rules_requires_drs_rules_path() {
+        let mut a = args();
+        a.check_drs_rules = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("drs-rules")));
+
+        a.drs_rules = Some("rules.json".to_string());
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("drs-rules")));
+    }
+
+    #[test]
+    fn vm_list_stdin_rejected_with_watch_or_dashboard() {
+        let mut a = args();
+        a.vm_list_stdin = true;
+        a.watch = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("vm-list-stdin")));
+
+        a.watch = false;
+        a.dashboard = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("vm-list-stdin")));
+    }
+
+    #[test]
+    fn template_requires_template_output() {
+        let mut a = args();
+        a.template = Some("report.hbs".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("template-output")));
+
+        a.template_output = Some("out.txt".to_string());
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("template-output")));
+    }
+
+    #[test]
+    fn output_on_change_requires_output() {
+        let mut a = args();
+        a.output_on_change = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("output-on-change")));
+
+        a.output = Some("report.json".to_string());
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("output-on-change")));
+    }
+
+    #[test]
+    fn site_config_requires_site() {
+        let mut a = args();
+        a.site_config = Some("sites.json".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("site-config")));
+
+        a.site = Some("dc-east".to_string());
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("site-config")));
+    }
+
+    #[test]
+    fn zero_max_total_requests_reported() {
+        let mut a = args();
+        a.max_total_requests = Some(0);
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("max-total-requests")));
+    }
+
+    #[test]
+    fn zero_per_vm_timeout_ms_reported() {
+        let mut a = args();
+        a.per_vm_timeout_ms = Some(0);
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("per-vm-timeout-ms")));
+    }
+
+    #[test]
+    fn zero_session_count_warn_reported() {
+        let mut a = args();
+        a.session_count_warn = 0;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("session-count-warn")));
+    }
+
+    #[test]
+    fn zero_max_file_checks_reported() {
+        let mut a = args();
+        a.max_file_checks = Some(0);
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("max-file-checks")));
+    }
+
+    #[test]
+    fn zero_max_suspend_hours_reported() {
+        let mut a = args();
+        a.max_suspend_hours = Some(0.0);
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("max-suspend-hours")));
+    }
+
+    #[test]
+    fn clean_args_have_no_errors() {
+        assert!(validate_args(&args()).is_empty());
+    }
+
+    #[test]
+    fn sso_token_is_mutually_exclusive_with_username_and_password() {
+        let mut a = args();
+        a.sso_token = Some("saml-assertion-xyz".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("sso-token")));
+    }
+
+    #[test]
+    fn sso_token_alone_is_a_valid_credential_combination() {
+        let mut a = args();
+        a.username = None;
+        a.password = None;
+        a.sso_token = Some("saml-assertion-xyz".to_string());
+        assert!(validate_args(&a).is_empty());
+    }
+
+    #[test]
+    fn neither_sso_token_nor_username_and_password_is_reported() {
+        let mut a = args();
+        a.username = None;
+        a.password = None;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("username/password")));
+    }
+
+    #[test]
+    fn only_username_without_password_is_reported() {
+        let mut a = args();
+        a.password = None;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("username/password")));
+    }
+
+    #[test]
+    fn cloud_csp_token_alone_is_a_valid_credential_combination() {
+        let mut a = args();
+        a.username = None;
+        a.password = None;
+        a.cloud_csp_token = Some("refresh-token-xyz".to_string());
+        assert!(validate_args(&a).is_empty());
+    }
+
+    #[test]
+    fn cloud_csp_token_is_mutually_exclusive_with_username_and_password() {
+        let mut a = args();
+        a.cloud_csp_token = Some("refresh-token-xyz".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("cloud-csp-token")));
+    }
+
+    #[test]
+    fn cloud_csp_token_is_mutually_exclusive_with_sso_token() {
+        let mut a = args();
+        a.username = None;
+        a.password = None;
+        a.sso_token = Some("saml-assertion-xyz".to_string());
+        a.cloud_csp_token = Some("refresh-token-xyz".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("cloud-csp-token")));
+    }
+
+    #[test]
+    fn invalid_select_expression_reported() {
+        let mut a = args();
+        a.select = Some("region == \"us-east\"".to_string());
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("select")));
+    }
+
+    #[test]
+    fn explain_selection_requires_select() {
+        let mut a = args();
+        a.explain_selection = true;
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("explain-selection")));
+
+        a.select = Some("power == on".to_string());
+        assert!(!validate_args(&a).iter().any(|e| e.starts_with("explain-selection")));
+    }
+
+    #[test]
+    fn unsupported_preview_threshold_key_reported() {
+        let mut a = args();
+        a.preview_thresholds = vec!["disk=85".to_string()];
+        assert!(validate_args(&a).iter().any(|e| e.starts_with("preview-thresholds")));
+    }
+}
@@ -0,0 +1,2291 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use rand::Rng;
+
+use crate::api_rate_log::ApiRateLog;
+use crate::check_timing::CheckTiming;
+use crate::auth::Session;
+use crate::bootevents::{bucket_boot_history_by_vm, parse_event as parse_boot_event, BootHistory, RawBootEvent};
+use crate::suspendevents::{bucket_suspend_time_by_vm, parse_event as parse_suspend_event, RawSuspendEvent};
+use crate::datastore;
+use crate::inventory;
+use crate::metrics_provider::{MetricsFetchError, MetricsProvider, SimulatedMetricsProvider, CPU_USAGE_PCT, MEMORY_USAGE_PCT};
+use crate::migration::{bucket_migrations_by_vm, parse_event, MigrationSummary, RawMigrationEvent};
+use crate::reachability::{ReachabilityProbe, TcpProbe};
+use crate::request_budget::{DegradableCheck, RequestBudget, DEGRADE_PRIORITY};
+use crate::sessions::SessionRecord;
+use crate::vm::{DetectedIssue, HostMetrics, MetricsSourceStatus, PowerState, Severity, VMIssueType, VMResourceStatus};
+
+/// CPU/memory usage thresholds above which a VM is flagged, regardless of
+/// the opt-in `--check-*` detectors.
+pub(crate) const CPU_HIGH_THRESHOLD_PCT: f64 = 90.0;
+pub(crate) const MEMORY_HIGH_THRESHOLD_PCT: f64 = 90.0;
+
+/// `DetectionOptions::cpu_high_threshold_pct`/`memory_high_threshold_pct`'s
+/// defaults - these are the always-on thresholds (not gated behind a
+/// `check_*` flag the way the opt-in detectors are), so they live on
+/// `DetectionOptions` itself rather than needing their own bool. Overriding
+/// them via `--cpu-threshold`/`--memory-threshold` and `--replay`ing a prior
+/// `--format json` report recomputes issues, health scores and exit codes
+/// against the new threshold without a fresh vCenter query - the same
+/// "what if the threshold were 70 instead of 90" question
+/// `--preview-threshold-changes` answers in advance, just committed instead
+/// of previewed. See [`crate::replay::replay`].
+fn default_cpu_high_threshold_pct() -> f64 {
+    CPU_HIGH_THRESHOLD_PCT
+}
+fn default_memory_high_threshold_pct() -> f64 {
+    MEMORY_HIGH_THRESHOLD_PCT
+}
+
+/// How far a guest-visible memory/vCPU figure may differ from the
+/// configured size, as a percentage of the configured size, before
+/// `--check-guest-resource-mismatch` recomputes usage against it and
+/// raises [`VMIssueType::GuestResourceMismatch`].
+pub(crate) const GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT: f64 = 10.0;
+
+/// Minimum `disk_allocated_gb` before `--check-storage-waste` even looks at
+/// a VM - a barely-used small disk isn't worth a ticket the way a barely-used
+/// multi-TB one is.
+pub(crate) const STORAGE_WASTE_MIN_ALLOCATED_GB: f64 = 500.0;
+
+/// Above this percentage of `disk_allocated_gb` actually used,
+/// `--check-storage-waste` considers the disk fairly sized, not wasted.
+pub(crate) const STORAGE_WASTE_MAX_USED_PCT: f64 = 10.0;
+
+/// Source of VM health data. The production implementation will talk to vCenter's
+/// SOAP or REST APIs; [`SimulatedClient`] is the only implementation today and is
+/// also what backs the demo/test paths.
+pub trait VCenterClient {
+    fn fetch_vm_statuses(&self) -> Result<Vec<VMResourceStatus>>;
+
+    /// The session this client is authenticated as, including its detected
+    /// [`crate::auth::VCenterVersion`] - needed wherever a report or
+    /// notification payload wants to annotate which vCenter produced it.
+    fn session(&self) -> &Session;
+
+    /// Whether the SOAP `PerformanceManager` connection went down at any
+    /// point this run - surfaced in the report header, JSON metadata, and
+    /// notifications so a partial-metrics run isn't mistaken for a clean one.
+    fn metrics_degraded(&self) -> bool;
+}
+
+/// Opt-in detectors, each gated behind its own `--check-*` flag. New detectors
+/// should add a field here rather than growing [`SimulatedClient::new`]'s
+/// parameter list.
+#[derive(Debug, Clone)]
+pub struct DetectionOptions {
+    /// Always-on CPU usage threshold above which a VM is flagged with
+    /// [`VMIssueType::HighCpuUsage`], from `--cpu-threshold`. Defaults to
+    /// [`CPU_HIGH_THRESHOLD_PCT`].
+    pub cpu_high_threshold_pct: f64,
+    /// Same as `cpu_high_threshold_pct`, for memory, from `--memory-threshold`.
+    /// Defaults to [`MEMORY_HIGH_THRESHOLD_PCT`].
+    pub memory_high_threshold_pct: f64,
+    pub check_clock: bool,
+    pub clock_skew_threshold_secs: f64,
+    pub check_reachability: bool,
+    pub reachability_port: u16,
+    pub reachability_timeout_ms: u64,
+    /// Process/service names that must be running in the guest, per `--check-process`.
+    pub required_processes: Vec<String>,
+    pub check_vcpu_allocation: bool,
+    /// Max allowed ratio of VM vCPUs to host physical cores before
+    /// `--check-vcpu-allocation` flags the VM.
+    pub max_vcpu_ratio: f64,
+    pub check_migrations: bool,
+    /// Window, in hours, the migration event query covers. Only takes
+    /// effect with `--check-migrations`.
+    pub migration_window_hours: f64,
+    /// Migrations within the window above which a VM is flagged. Only
+    /// takes effect with `--check-migrations`.
+    pub max_migrations: u32,
+    pub check_uptime: bool,
+    /// Uptime below which a powered-on VM is flagged as recently rebooted.
+    /// Only takes effect with `--check-uptime`. Also the signal
+    /// `--check-boot-storm` correlates across the fleet.
+    pub short_uptime_threshold_secs: f64,
+    /// Window, in hours, the `created_recently`/`VmPoweredOnEvent` lookup
+    /// covers. Only takes effect with `--check-uptime`. See
+    /// [`crate::bootevents`].
+    pub boot_history_window_hours: f64,
+    /// Power-on events within the window above which a short-uptime VM is
+    /// reclassified as [`VMIssueType::RebootLoop`] instead of `UptimeShort`.
+    /// Only takes effect with `--check-uptime`.
+    pub reboot_loop_count: u32,
+    /// Flag VMs whose host is disconnected or in maintenance mode.
+    pub check_host_state: bool,
+    /// Flag every VM on a host whose hardware-sensor query reported yellow
+    /// or red, from `--check-host-health`. See
+    /// [`host_hardware_unhealthy_issue`].
+    pub check_host_health: bool,
+    pub check_hw_version: bool,
+    /// Minimum virtual hardware version (the numeric suffix of e.g.
+    /// `vmx-19`) before `--check-hw-version` flags a VM as outdated.
+    pub min_hw_version: u32,
+    /// Per-VM analysis budget, in milliseconds, from `--per-vm-timeout-ms`.
+    /// A VM whose guest-side checks (tools, processes, clock) would run
+    /// past this is abandoned rather than allowed to hold the run hostage,
+    /// and reported alongside `--time-budget`/`--max-total-requests`
+    /// deferrals. `None` (the default) disables the guard - same shape as
+    /// `--max-total-requests`. See [`SimulatedClient::timed_out`].
+    pub per_vm_timeout_ms: Option<u64>,
+    /// For powered-off VMs, confirms the VMX path recorded in the VM's
+    /// config is still present on its datastore via `--check-vm-files`,
+    /// raising [`VMIssueType::BackingFilesMissing`] when it isn't. See
+    /// [`crate::datastore`].
+    pub check_vm_files: bool,
+    /// Caps how many powered-off VMs `--check-vm-files` will browse a
+    /// datastore for in a single run. `None` (the default) disables the
+    /// cap - same shape as `--max-total-requests`.
+    pub max_file_checks: Option<u32>,
+    /// Raises [`VMIssueType::HotAddDisabledUnderLoad`] for a VM already
+    /// flagged `HighCpuUsage`/`HighMemoryUsage` whose matching hot-add
+    /// setting is disabled, from `--check-hotadd`. See
+    /// [`hotadd_under_load_issue`].
+    pub check_hotadd: bool,
+    /// Compares VMware Tools' guest-visible memory/vCPU count against the
+    /// configured size and, when they disagree by more than
+    /// [`GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT`], recomputes usage against
+    /// the guest-visible figure and raises [`VMIssueType::GuestResourceMismatch`],
+    /// from `--check-guest-resource-mismatch`. See [`guest_resource_mismatch_issue`].
+    pub check_guest_resource_mismatch: bool,
+    /// Flags a VM whose `disk_allocated_gb` is at least
+    /// [`STORAGE_WASTE_MIN_ALLOCATED_GB`] while `disk_used_gb` is below
+    /// [`STORAGE_WASTE_MAX_USED_PCT`] of it, raising
+    /// [`VMIssueType::StorageWaste`], from `--check-storage-waste`. See
+    /// [`storage_waste_issue`].
+    pub check_storage_waste: bool,
+    /// Hours a VM can sit suspended before [`suspended_issue`] reclassifies
+    /// it from `Suspended` to the more severe `VMIssueType::SuspendedTooLong`,
+    /// from `--max-suspend-hours`. `None` (the default) never escalates -
+    /// every suspended VM just gets `Suspended`.
+    pub max_suspend_hours: Option<f64>,
+    /// Carries every counter [`crate::metrics_provider::MetricsProvider`]
+    /// returned for a VM into `VMResourceStatus::raw_metrics`, from
+    /// `--include-raw-metrics`. Doesn't change which counters are fetched -
+    /// only whether the already-fetched map is kept around instead of
+    /// being reduced down to `cpu_usage_pct`/`memory_usage_pct`.
+    pub include_raw_metrics: bool,
+}
+
+impl Default for DetectionOptions {
+    fn default() -> Self {
+        Self {
+            cpu_high_threshold_pct: default_cpu_high_threshold_pct(),
+            memory_high_threshold_pct: default_memory_high_threshold_pct(),
+            check_clock: false,
+            clock_skew_threshold_secs: 5.0,
+            check_reachability: false,
+            reachability_port: 443,
+            reachability_timeout_ms: 1000,
+            required_processes: Vec::new(),
+            check_vcpu_allocation: false,
+            max_vcpu_ratio: 1.0,
+            check_migrations: false,
+            migration_window_hours: 24.0,
+            max_migrations: 5,
+            check_uptime: false,
+            short_uptime_threshold_secs: 900.0,
+            boot_history_window_hours: 1.0,
+            reboot_loop_count: 3,
+            check_host_state: false,
+            check_host_health: false,
+            check_hw_version: false,
+            min_hw_version: 15,
+            per_vm_timeout_ms: None,
+            check_vm_files: false,
+            max_file_checks: None,
+            check_hotadd: false,
+            check_guest_resource_mismatch: false,
+            check_storage_waste: false,
+            max_suspend_hours: None,
+            include_raw_metrics: false,
+        }
+    }
+}
+
+/// Generates a plausible-looking inventory in memory, without any network access.
+pub struct SimulatedClient {
+    pub session: Session,
+    pub vm_count: usize,
+    pub options: DetectionOptions,
+    probe: Box<dyn ReachabilityProbe>,
+    metrics_provider: Box<dyn MetricsProvider>,
+    rate_log: ApiRateLog,
+    /// Request coalescing for concurrent callers asking for the same host
+    /// has nothing to dedupe against yet: there's no `VCenterAPIClient`,
+    /// no async runtime, and [`Self::fetch_vm_statuses`] walks `vm_count`
+    /// VMs on the current thread, so [`Self::host_metrics_for`] is never
+    /// entered by two callers at once - the `Mutex` below is already the
+    /// strictest possible serialization, just without anything to await.
+    /// Once a real multi-threaded or async transport exists, this is where
+    /// a per-key in-flight slot (cached value or a shared future the other
+    /// waiters subscribe to) would replace the plain cache, same shape as
+    /// the `/api`-vs-`/rest` probe-caching gap noted on [`crate::auth::Session`].
+    host_metrics_cache: Mutex<HashMap<String, HostMetrics>>,
+    vm_metrics_cache: Mutex<HashMap<String, HashMap<String, f64>>>,
+    /// Once set, [`Self::prefetch_vm_metrics`] stops calling
+    /// `metrics_provider` entirely for the rest of the run - same
+    /// once-degraded-stays-degraded shape as [`RequestBudget`]'s
+    /// `degraded` list. See [`crate::metrics_provider::MetricsFetchError`].
+    metrics_connection_down: AtomicBool,
+    /// Names of VMs whose metrics were never collected because the
+    /// connection went down, as opposed to a VM that's simply powered off -
+    /// consulted by [`Self::vm_metrics_source_for`].
+    metrics_unavailable: Mutex<HashSet<String>>,
+    request_budget: RequestBudget,
+    timed_out: Mutex<Vec<String>>,
+    timing: CheckTiming,
+}
+
+impl SimulatedClient {
+    pub fn new(session: Session, vm_count: usize, options: DetectionOptions) -> Self {
+        Self {
+            session,
+            vm_count,
+            options,
+            probe: Box::new(TcpProbe),
+            metrics_provider: Box::new(SimulatedMetricsProvider),
+            rate_log: ApiRateLog::new(false),
+            host_metrics_cache: Mutex::new(HashMap::new()),
+            vm_metrics_cache: Mutex::new(HashMap::new()),
+            metrics_connection_down: AtomicBool::new(false),
+            metrics_unavailable: Mutex::new(HashSet::new()),
+            request_budget: RequestBudget::new(None),
+            timed_out: Mutex::new(Vec::new()),
+            timing: CheckTiming::new(false),
+        }
+    }
+
+    /// Enables `--timing`'s per-check request count/latency log. See
+    /// [`CheckTiming`]; `--api-rate-log`'s `.with_api_rate_log` is the same
+    /// idea keyed by endpoint instead of check name.
+    pub fn with_timing(mut self, enabled: bool) -> Self {
+        self.timing = CheckTiming::new(enabled);
+        self
+    }
+
+    pub fn timing(&self) -> &CheckTiming {
+        &self.timing
+    }
+
+    /// Swaps in a different `--metrics-source` implementation. See
+    /// [`crate::metrics_provider::MetricsProvider`].
+    pub fn with_metrics_provider(mut self, metrics_provider: Box<dyn MetricsProvider>) -> Self {
+        self.metrics_provider = metrics_provider;
+        self
+    }
+
+    /// VMs abandoned this run by `--per-vm-timeout-ms`, in fetch order.
+    /// Empty unless the option is set.
+    pub fn timed_out(&self) -> Vec<String> {
+        self.timed_out.lock().unwrap().clone()
+    }
+
+    /// Host `cpu.usage.average`/`mem.usage.average`, queried once per host
+    /// and cached for the rest of the run. A host whose metrics can't be
+    /// read is simply absent from the result; it never fails VM analysis.
+    fn host_metrics_for(&self, host: &str, rng: &mut impl Rng) -> HostMetrics {
+        let mut cache = self.host_metrics_cache.lock().unwrap();
+        if let Some(metrics) = cache.get(host) {
+            return metrics.clone();
+        }
+        const CORE_COUNTS: [u32; 5] = [16, 24, 32, 48, 64];
+        const SENSOR_NAMES: [&str; 4] = ["Power Supply 2", "Fan 3", "DIMM A2 ECC", "CPU 1 Temperature"];
+        let connection_state = if rng.gen_bool(0.03) {
+            crate::vm::HostConnectionState::Disconnected
+        } else {
+            crate::vm::HostConnectionState::Connected
+        };
+        let (sensor_status, failing_sensor) = if rng.gen_bool(0.03) {
+            (crate::vm::HostSensorStatus::Red, Some(SENSOR_NAMES[rng.gen_range(0..SENSOR_NAMES.len())].to_string()))
+        } else if rng.gen_bool(0.05) {
+            (crate::vm::HostSensorStatus::Yellow, Some(SENSOR_NAMES[rng.gen_range(0..SENSOR_NAMES.len())].to_string()))
+        } else {
+            (crate::vm::HostSensorStatus::Green, None)
+        };
+        let metrics = HostMetrics {
+            cpu_usage_pct: rng.gen_range(0.0..100.0),
+            memory_usage_pct: rng.gen_range(0.0..100.0),
+            physical_cores: CORE_COUNTS[rng.gen_range(0..CORE_COUNTS.len())],
+            connection_state,
+            in_maintenance_mode: connection_state == crate::vm::HostConnectionState::Connected && rng.gen_bool(0.05),
+            sensor_status,
+            failing_sensor,
+        };
+        cache.insert(host.to_string(), metrics.clone());
+        metrics
+    }
+
+    /// Snapshot of every host metric queried so far this run, for the
+    /// statistics-section host utilization table.
+    pub fn host_metrics(&self) -> HashMap<String, HostMetrics> {
+        self.host_metrics_cache.lock().unwrap().clone()
+    }
+
+    /// Populates `host_metrics_cache` for every host in `hosts` with a
+    /// single bulk `/vcenter/host` listing call, tried up to
+    /// `MAX_PREFETCH_ATTEMPTS` times in all on simulated transient failure. If the
+    /// listing never comes back, falls back to [`Self::host_metrics_for`]'s
+    /// per-host lazy lookups - no host is ever left without metrics, the
+    /// run just pays for one call per host instead of one call total. Logs
+    /// which mode ended up in effect.
+    fn prefetch_host_metrics(&self, hosts: &[String], rng: &mut impl Rng) {
+        const MAX_PREFETCH_ATTEMPTS: u32 = 3;
+        for attempt in 1..=MAX_PREFETCH_ATTEMPTS {
+            self.rate_log.record("/vcenter/host", 0.0);
+            self.request_budget.record(1);
+            if rng.gen_bool(0.97) {
+                for host in hosts {
+                    self.host_metrics_for(host, rng);
+                }
+                eprintln!(
+                    "host metrics: bulk listing succeeded on attempt {attempt}/{MAX_PREFETCH_ATTEMPTS}, {} host(s) prefetched",
+                    hosts.len()
+                );
+                return;
+            }
+            eprintln!("host metrics: bulk listing attempt {attempt}/{MAX_PREFETCH_ATTEMPTS} failed, retrying");
+        }
+        eprintln!(
+            "host metrics: bulk listing unavailable after {MAX_PREFETCH_ATTEMPTS} attempt(s); \
+             falling back to per-host lazy lookups"
+        );
+    }
+
+    /// A VM's `cpu.usage.average`/`mem.usage.average`, as populated by
+    /// [`Self::prefetch_vm_metrics`]. Absent (never fetched, or the host's
+    /// batch genuinely had nothing for it) means "use a default", not an
+    /// error - same contract as [`crate::metrics_provider::MetricsProvider`].
+    fn vm_metrics_for(&self, vm_name: &str) -> HashMap<String, f64> {
+        self.vm_metrics_cache.lock().unwrap().get(vm_name).cloned().unwrap_or_default()
+    }
+
+    /// Groups `vm_names` by host and bills one simulated
+    /// `PerformanceManager.QueryPerf` call per host instead of one per VM -
+    /// the real SOAP `QueryPerf` takes a list of `ManagedObjectReference`s
+    /// and returns samples for all of them in a single round trip, which is
+    /// exactly what a dense host benefits from. A host's batch occasionally
+    /// fails (same kind of simulated transient failure as
+    /// [`Self::prefetch_host_metrics`]); when it does, that host's VMs fall
+    /// back to one [`MetricsProvider::vm_performance_metrics`] call each
+    /// rather than losing their metrics, same as the request that wanted
+    /// this asked for.
+    ///
+    /// A [`MetricsFetchError`] from `metrics_provider` means the SOAP
+    /// `PerformanceManager` connection itself is down, not that one VM lacks
+    /// data: once that happens, every VM still pending (the rest of the
+    /// current host's batch, and every later host) is marked
+    /// [`crate::vm::MetricsSourceStatus::Unavailable`] without another call
+    /// to `metrics_provider` for the rest of the run - same
+    /// once-degraded-stays-degraded shape [`RequestBudget`] uses for
+    /// `--max-total-requests`.
+    fn prefetch_vm_metrics(&self, vm_names: &[String], vm_hosts: &[String], power_states: &[PowerState], rng: &mut impl Rng) {
+        let mut by_host: HashMap<&str, Vec<(&str, PowerState)>> = HashMap::new();
+        for ((name, host), state) in vm_names.iter().zip(vm_hosts.iter()).zip(power_states.iter()) {
+            by_host.entry(host.as_str()).or_default().push((name.as_str(), *state));
+        }
+        let mut cache = self.vm_metrics_cache.lock().unwrap();
+        for (host, vms) in by_host {
+            if self.metrics_connection_down.load(Ordering::Relaxed) {
+                self.mark_unavailable(vms.iter().map(|(name, _)| *name));
+                continue;
+            }
+            let batch_ok = rng.gen_bool(0.95);
+            if batch_ok {
+                self.rate_log.record("QueryPerf", 0.0);
+            } else {
+                eprintln!("vm metrics: batched QueryPerf for host {host} failed, falling back to per-VM queries");
+            }
+            for (name, state) in &vms {
+                if self.metrics_connection_down.load(Ordering::Relaxed) {
+                    self.mark_unavailable(std::iter::once(*name));
+                    continue;
+                }
+                if !batch_ok {
+                    self.rate_log.record("QueryPerf", 0.0);
+                }
+                match self.metrics_provider.vm_performance_metrics(name, *state) {
+                    Ok(Some(metrics)) => {
+                        cache.insert(name.to_string(), metrics);
+                    }
+                    Ok(None) => {}
+                    Err(MetricsFetchError) => self.mark_connection_down(name),
+                }
+            }
+        }
+    }
+
+    /// Latches `metrics_connection_down`, logging once the first time this
+    /// is called this run, and marks `vm_name` unavailable.
+    fn mark_connection_down(&self, vm_name: &str) {
+        if !self.metrics_connection_down.swap(true, Ordering::Relaxed) {
+            eprintln!(
+                "vm metrics: SOAP PerformanceManager connection is down, metrics unavailable for the rest of this run"
+            );
+        }
+        self.metrics_unavailable.lock().unwrap().insert(vm_name.to_string());
+    }
+
+    fn mark_unavailable<'a>(&self, vm_names: impl Iterator<Item = &'a str>) {
+        let mut unavailable = self.metrics_unavailable.lock().unwrap();
+        unavailable.extend(vm_names.map(str::to_string));
+    }
+
+    /// Whether `vm_name`'s `cpu_usage_pct`/`memory_usage_pct` were actually
+    /// collected this run, for [`VMResourceStatus::metrics_source`].
+    fn vm_metrics_source_for(&self, vm_name: &str) -> MetricsSourceStatus {
+        if self.metrics_unavailable.lock().unwrap().contains(vm_name) {
+            MetricsSourceStatus::Unavailable
+        } else {
+            MetricsSourceStatus::Available
+        }
+    }
+
+    /// Enables `--api-rate-log` instrumentation on the client's API calls.
+    pub fn with_api_rate_log(mut self, enabled: bool) -> Self {
+        self.rate_log = ApiRateLog::new(enabled);
+        self
+    }
+
+    pub fn api_rate_log(&self) -> &ApiRateLog {
+        &self.rate_log
+    }
+
+    /// Sets `--max-total-requests`'s ceiling. `None` (the default) disables
+    /// enforcement.
+    pub fn with_max_total_requests(mut self, ceiling: Option<u64>) -> Self {
+        self.request_budget = RequestBudget::new(ceiling);
+        self
+    }
+
+    pub fn request_budget(&self) -> &RequestBudget {
+        &self.request_budget
+    }
+
+    #[cfg(test)]
+    pub fn with_probe(mut self, probe: Box<dyn ReachabilityProbe>) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Queries the vCenter session list scoped to this client's own user
+    /// (`GET /api/session`, or SOAP `SessionManager.sessionList` filtered
+    /// client-side, on an older vCenter), for `--session-count-warn`/
+    /// `--reap-stale-sessions`. That call requires the `Sessions.View`
+    /// privilege; this tree has no RBAC model to simulate a real privilege
+    /// matrix against, so instead it simulates the one thing that
+    /// privilege gate actually determines - whether the call succeeds -
+    /// with a 90% success chance, the same kind of roll
+    /// [`Self::prefetch_host_metrics`] makes (at 97%) for its bulk listing. `None` means insufficient privilege (or
+    /// any other failure); callers must degrade silently to an "unknown"
+    /// session count rather than failing the run over a permissions gap.
+    pub fn own_sessions(&self) -> Option<Vec<SessionRecord>> {
+        let mut rng = rand::thread_rng();
+        self.rate_log.record("/api/session", 0.0);
+        self.request_budget.record(1);
+        if !rng.gen_bool(0.9) {
+            return None;
+        }
+        const MAX_OTHER_SESSIONS: u32 = 30;
+        let leaked = rng.gen_range(0..MAX_OTHER_SESSIONS);
+        let mut sessions: Vec<SessionRecord> = (0..leaked)
+            .map(|i| SessionRecord {
+                id: format!("sess-{i:03}"),
+                idle_minutes: rng.gen_range(0..240),
+                is_current: false,
+            })
+            .collect();
+        sessions.push(SessionRecord {
+            id: "sess-current".to_string(),
+            idle_minutes: 0,
+            is_current: true,
+        });
+        Some(sessions)
+    }
+
+    /// Terminates each of `sessions` via `SessionManager.TerminateSession`,
+    /// logging every termination. Requires the
+    /// `Sessions.TerminateSession` privilege, same caveat as
+    /// [`Self::own_sessions`]; simulated here as always succeeding once
+    /// the call is made, since there's no real transport to fail against.
+    pub fn reap_sessions(&self, sessions: &[&SessionRecord]) {
+        for session in sessions {
+            self.rate_log.record("/api/session/terminate", 0.0);
+            self.request_budget.record(1);
+            eprintln!("session reaping: terminated {} (idle {}m)", session.id, session.idle_minutes);
+        }
+    }
+}
+
+/// VMs where tools aren't running can't report skew and have `skew = None`;
+/// they're skipped, not flagged.
+fn clock_skew_issue(skew: Option<f64>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_clock {
+        return None;
+    }
+    let skew = skew?;
+    if skew.abs() <= options.clock_skew_threshold_secs {
+        return None;
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::ClockSkew,
+        skew,
+        options.clock_skew_threshold_secs,
+        format!("guest clock skew of {skew:.1}s"),
+    ))
+}
+
+/// Checks each `--check-process` name against what VMware Tools reports as
+/// running. VMs without tools running can't report processes and are
+/// skipped, not flagged, matching the clock-skew and reachability checks.
+fn missing_process_issues(
+    tools_running: bool,
+    running_processes: &[String],
+    options: &DetectionOptions,
+) -> Vec<DetectedIssue> {
+    if !tools_running {
+        return Vec::new();
+    }
+    options
+        .required_processes
+        .iter()
+        .filter(|required| !running_processes.iter().any(|p| p == *required))
+        .map(|required| {
+            DetectedIssue::new(
+                VMIssueType::ProcessNotRunning,
+                format!("required process/service '{required}' is not running"),
+            )
+        })
+        .collect()
+}
+
+fn reachability_issue(reachable: Option<bool>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_reachability || reachable != Some(false) {
+        return None;
+    }
+    Some(DetectedIssue::new(
+        VMIssueType::Unresponsive,
+        "guest did not respond to a reachability probe",
+    ))
+}
+
+/// A hot host explains, rather than excuses, a VM's own CPU/memory issue -
+/// so this only fires alongside an existing `HighCpuUsage`/`HighMemoryUsage`
+/// issue, never on its own. `host_metrics` is `None` when the host's own
+/// metrics couldn't be read; that must not block the VM's own issues.
+fn host_overcommitted_issue(vm_issues: &[DetectedIssue], host_metrics: Option<&HostMetrics>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    let has_metric_issue = vm_issues
+        .iter()
+        .any(|i| matches!(i.issue_type, VMIssueType::HighCpuUsage | VMIssueType::HighMemoryUsage));
+    if !has_metric_issue {
+        return None;
+    }
+    let metrics = host_metrics?;
+    if metrics.cpu_usage_pct > options.cpu_high_threshold_pct {
+        Some(DetectedIssue::measured(
+            VMIssueType::HostOvercommitted,
+            metrics.cpu_usage_pct,
+            options.cpu_high_threshold_pct,
+            format!("host at {:.0}% CPU", metrics.cpu_usage_pct),
+        ))
+    } else if metrics.memory_usage_pct > options.memory_high_threshold_pct {
+        Some(DetectedIssue::measured(
+            VMIssueType::HostOvercommitted,
+            metrics.memory_usage_pct,
+            options.memory_high_threshold_pct,
+            format!("host at {:.0}% memory", metrics.memory_usage_pct),
+        ))
+    } else {
+        None
+    }
+}
+
+/// `--check-hotadd`: a VM already running hot (carries `HighCpuUsage` or
+/// `HighMemoryUsage`) can't be scaled up without a reboot if the matching
+/// resource's hot-add is disabled - sharper than `HotAddDisabled`
+/// (`--require-hot-add`), which fires on the setting alone regardless of
+/// current load.
+fn hotadd_under_load_issue(vm_issues: &[DetectedIssue], cpu_hot_add_enabled: bool, memory_hot_add_enabled: bool, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_hotadd {
+        return None;
+    }
+    let cpu_high = vm_issues.iter().any(|i| i.issue_type == VMIssueType::HighCpuUsage);
+    let memory_high = vm_issues.iter().any(|i| i.issue_type == VMIssueType::HighMemoryUsage);
+    if cpu_high && !cpu_hot_add_enabled {
+        Some(DetectedIssue::new(
+            VMIssueType::HotAddDisabledUnderLoad,
+            "CPU usage is high and CPU hot-add is disabled",
+        ))
+    } else if memory_high && !memory_hot_add_enabled {
+        Some(DetectedIssue::new(
+            VMIssueType::HotAddDisabledUnderLoad,
+            "memory usage is high and memory hot-add is disabled",
+        ))
+    } else {
+        None
+    }
+}
+
+/// Outcome of [`guest_resource_mismatch`]: the (possibly recomputed)
+/// cpu/memory usage percentages, which basis they ended up computed
+/// against, and the [`VMIssueType::GuestResourceMismatch`] issue, if any.
+struct GuestResourceMismatch {
+    cpu_usage_pct: f64,
+    memory_usage_pct: f64,
+    usage_basis: crate::vm::UsageBasis,
+    issue: Option<DetectedIssue>,
+}
+
+/// How far `guest_visible` differs from `configured`, as a percentage of
+/// `configured`. `0.0` when `configured` is `0.0` - there's no ratio to
+/// take, and a VM with no configured memory/vCPUs at all isn't this
+/// check's problem.
+fn resource_mismatch_pct(configured: f64, guest_visible: f64) -> f64 {
+    if configured <= 0.0 {
+        return 0.0;
+    }
+    (guest_visible - configured).abs() / configured * 100.0
+}
+
+/// `--check-guest-resource-mismatch`: compares VMware Tools' guest-visible
+/// memory/vCPU count against the configured size vCenter reports. Beyond
+/// [`GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT`], `cpu_usage_pct`/`memory_usage_pct`
+/// (computed against the configured size by the metrics collector) are
+/// misleading - a guest that only onlined half its hot-added memory is
+/// really running at twice the usage vCenter's configured-size percentage
+/// suggests - so they're recomputed against the guest-visible figure
+/// instead, and the mismatch itself is raised as an issue. `None` for
+/// either guest-visible figure (Tools not running, or the check didn't
+/// run) leaves that figure's usage untouched.
+fn guest_resource_mismatch(
+    cpu_usage_pct: f64,
+    memory_usage_pct: f64,
+    cpu_count: u32,
+    memory_gb: f64,
+    guest_visible_cpu_count: Option<u32>,
+    guest_visible_memory_mb: Option<f64>,
+    options: &DetectionOptions,
+) -> GuestResourceMismatch {
+    let configured_memory_mb = memory_gb * 1024.0;
+    if !options.check_guest_resource_mismatch {
+        return GuestResourceMismatch {
+            cpu_usage_pct,
+            memory_usage_pct,
+            usage_basis: crate::vm::UsageBasis::Configured,
+            issue: None,
+        };
+    }
+    let memory_mismatch_pct = guest_visible_memory_mb.map_or(0.0, |mb| resource_mismatch_pct(configured_memory_mb, mb));
+    let cpu_mismatch_pct = guest_visible_cpu_count.map_or(0.0, |count| resource_mismatch_pct(cpu_count as f64, count as f64));
+    let memory_mismatched = guest_visible_memory_mb.is_some() && memory_mismatch_pct > GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT;
+    let cpu_mismatched = guest_visible_cpu_count.is_some() && cpu_mismatch_pct > GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT;
+    if !memory_mismatched && !cpu_mismatched {
+        return GuestResourceMismatch {
+            cpu_usage_pct,
+            memory_usage_pct,
+            usage_basis: crate::vm::UsageBasis::Configured,
+            issue: None,
+        };
+    }
+    let adjusted_memory_pct = if memory_mismatched {
+        let guest_visible_mb = guest_visible_memory_mb.expect("memory_mismatched implies Some");
+        memory_usage_pct / 100.0 * configured_memory_mb / guest_visible_mb * 100.0
+    } else {
+        memory_usage_pct
+    };
+    let adjusted_cpu_pct = if cpu_mismatched {
+        let guest_visible_count = guest_visible_cpu_count.expect("cpu_mismatched implies Some");
+        cpu_usage_pct / 100.0 * cpu_count as f64 / guest_visible_count as f64 * 100.0
+    } else {
+        cpu_usage_pct
+    };
+    let issue = DetectedIssue::measured(
+        VMIssueType::GuestResourceMismatch,
+        memory_mismatch_pct.max(cpu_mismatch_pct),
+        GUEST_RESOURCE_MISMATCH_THRESHOLD_PCT,
+        format!(
+            "guest-visible memory {:.0} MB vs configured {:.0} MB; guest-visible vCPUs {} vs configured {}",
+            guest_visible_memory_mb.unwrap_or(configured_memory_mb),
+            configured_memory_mb,
+            guest_visible_cpu_count.unwrap_or(cpu_count),
+            cpu_count,
+        ),
+    );
+    GuestResourceMismatch {
+        cpu_usage_pct: adjusted_cpu_pct,
+        memory_usage_pct: adjusted_memory_pct,
+        usage_basis: crate::vm::UsageBasis::GuestVisible,
+        issue: Some(issue),
+    }
+}
+
+/// `--check-storage-waste`: flags a VM provisioned with a large disk it
+/// barely uses - `disk_allocated_gb` at least [`STORAGE_WASTE_MIN_ALLOCATED_GB`]
+/// while `disk_used_gb` sits below [`STORAGE_WASTE_MAX_USED_PCT`] of it.
+/// `disk_used_gb` is `None` when Tools isn't running (nothing to read) or
+/// the check didn't run, in which case there's nothing to compare against.
+fn storage_waste_issue(disk_allocated_gb: f64, disk_used_gb: Option<f64>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_storage_waste {
+        return None;
+    }
+    if disk_allocated_gb < STORAGE_WASTE_MIN_ALLOCATED_GB {
+        return None;
+    }
+    let used_gb = disk_used_gb?;
+    let used_pct = used_gb / disk_allocated_gb * 100.0;
+    if used_pct > STORAGE_WASTE_MAX_USED_PCT {
+        return None;
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::StorageWaste,
+        used_pct,
+        STORAGE_WASTE_MAX_USED_PCT,
+        format!("{used_gb:.0} GB used of {disk_allocated_gb:.0} GB allocated ({used_pct:.1}%)"),
+    ))
+}
+
+/// Flags a VM whose host is disconnected or in maintenance mode - the VM
+/// itself may be fine, but its host condition puts it at elevated risk.
+/// `host_metrics` is `None` when the host's own metrics couldn't be read;
+/// that must not block the VM's own issues.
+fn host_degraded_issue(host_metrics: Option<&HostMetrics>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_host_state {
+        return None;
+    }
+    let metrics = host_metrics?;
+    if metrics.connection_state == crate::vm::HostConnectionState::Disconnected {
+        return Some(DetectedIssue::new(VMIssueType::HostDegraded, "host is disconnected"));
+    }
+    if metrics.in_maintenance_mode {
+        return Some(DetectedIssue::new(VMIssueType::HostDegraded, "host is in maintenance mode"));
+    }
+    None
+}
+
+/// Flags a VM whose host reported a yellow or red hardware sensor - a
+/// failing PSU, fan, or memory module endangers every VM on the host, not
+/// just the one being fetched, so this raises the same
+/// [`VMIssueType::HostHardwareUnhealthy`] issue for each of them. Same
+/// `host_metrics`-absent contract as [`host_degraded_issue`]: a host whose
+/// sensors couldn't be read must not block the VM's own issues.
+fn host_hardware_unhealthy_issue(host_metrics: Option<&HostMetrics>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_host_health {
+        return None;
+    }
+    let metrics = host_metrics?;
+    if metrics.sensor_status == crate::vm::HostSensorStatus::Green {
+        return None;
+    }
+    let sensor = metrics.failing_sensor.as_deref().unwrap_or("unidentified sensor");
+    Some(DetectedIssue::new(VMIssueType::HostHardwareUnhealthy, format!("host sensor '{sensor}' reports {:?}", metrics.sensor_status)))
+}
+
+/// Advisory only: a VM can run fine on an over-allocated host, but every
+/// vCPU beyond the host's physical cores still costs the scheduler, so this
+/// fires on vCPU count alone, independent of the VM's current usage.
+fn over_allocated_cpu_issue(vcpu_count: u32, physical_cores: u32, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_vcpu_allocation {
+        return None;
+    }
+    let max_vcpus = options.max_vcpu_ratio * physical_cores as f64;
+    if (vcpu_count as f64) <= max_vcpus {
+        return None;
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::OverAllocatedCpu,
+        vcpu_count as f64,
+        max_vcpus,
+        format!("{vcpu_count} vCPUs against {physical_cores} physical cores on the host"),
+    ))
+}
+
+/// Parses the numeric suffix out of a `vmx-N` virtual hardware version
+/// string. Anything that doesn't match the simulated/vCenter-reported shape
+/// can't be compared against `--min-hw-version`, so it's skipped rather
+/// than flagged.
+fn hardware_version_number(hardware_version: &str) -> Option<u32> {
+    hardware_version.strip_prefix("vmx-")?.parse().ok()
+}
+
+/// A VM name's stable baseline change-version: the value `change_version`
+/// lands on whenever a call's per-VM roll says "unchanged". There's no real
+/// config/power history to hash here, just the name, so two calls for the
+/// same VM agree on this baseline the same way two calls for the same VM
+/// agree on its `inventory_path` - both are recomputed every call rather
+/// than tracked across them.
+fn stable_change_version(name: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    name.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Advisory only: an old vHW version doesn't mean the VM is unhealthy, but
+/// it can't use newer host features and blocks some operations (hot-add
+/// limits, certain vMotion compatibility checks) until it's upgraded.
+fn hardware_version_old_issue(hardware_version: &str, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_hw_version {
+        return None;
+    }
+    let version = hardware_version_number(hardware_version)?;
+    if version >= options.min_hw_version {
+        return None;
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::HardwareVersionOld,
+        version as f64,
+        options.min_hw_version as f64,
+        format!("hardware version {hardware_version} is below the minimum vmx-{}", options.min_hw_version),
+    ))
+}
+
+/// Flags VMs that migrated (vMotion/DRS) more than `--max-migrations` times
+/// within `--migration-window-hours` - usually DRS misconfiguration or an
+/// affinity-rule fight, not a problem with the VM itself.
+fn excessive_migrations_issue(migration_count: u32, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_migrations || migration_count <= options.max_migrations {
+        return None;
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::ExcessiveMigrations,
+        migration_count as f64,
+        options.max_migrations as f64,
+        format!("{migration_count} migrations in the last {:.0}h", options.migration_window_hours),
+    ))
+}
+
+/// Flags a powered-on VM whose uptime is below `--short-uptime-threshold-secs`,
+/// i.e. it rebooted recently - unremarkable alone, `--check-boot-storm`
+/// watches for many of these clustering together in time (see
+/// [`crate::bootstorm`]). `created_recently`/`power_on_count` (see
+/// [`crate::bootevents`]) refine that verdict: a fresh deployment is never a
+/// crash loop, so `created_recently` wins outright and just downgrades the
+/// severity to `Informational`; otherwise, more than `--reboot-loop-count`
+/// power cycles in the window is a crash loop, not a single recent reboot
+/// (raised as [`VMIssueType::RebootLoop`] instead).
+fn uptime_issue(power_state: PowerState, uptime_secs: f64, created_recently: bool, power_on_count: u32, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if !options.check_uptime || power_state != PowerState::PoweredOn || uptime_secs >= options.short_uptime_threshold_secs {
+        return None;
+    }
+    if created_recently {
+        let mut issue = DetectedIssue::measured(
+            VMIssueType::UptimeShort,
+            uptime_secs,
+            options.short_uptime_threshold_secs,
+            format!("uptime {uptime_secs:.0}s"),
+        );
+        issue.severity = Severity::Informational;
+        return Some(issue);
+    }
+    if power_on_count > options.reboot_loop_count {
+        return Some(DetectedIssue::measured(
+            VMIssueType::RebootLoop,
+            power_on_count as f64,
+            options.reboot_loop_count as f64,
+            format!("{power_on_count} power-on events in the last {:.1}h", options.boot_history_window_hours),
+        ));
+    }
+    Some(DetectedIssue::measured(
+        VMIssueType::UptimeShort,
+        uptime_secs,
+        options.short_uptime_threshold_secs,
+        format!("uptime {uptime_secs:.0}s"),
+    ))
+}
+
+/// Always flags a suspended VM - same unconditional treatment as
+/// [`VMIssueType::PoweredOff`] for a powered-off one. `suspended_duration_secs`
+/// is `None` when the VM's `VmSuspendedEvent` fell outside the event query's
+/// lookback (or the simulated client simply has none), in which case there's
+/// nothing to compare against `--max-suspend-hours` and the VM just gets the
+/// baseline `Suspended` issue.
+fn suspended_issue(power_state: PowerState, suspended_duration_secs: Option<f64>, options: &DetectionOptions) -> Option<DetectedIssue> {
+    if power_state != PowerState::Suspended {
+        return None;
+    }
+    if let (Some(duration_secs), Some(max_hours)) = (suspended_duration_secs, options.max_suspend_hours) {
+        let max_secs = max_hours * 3600.0;
+        if duration_secs > max_secs {
+            return Some(DetectedIssue::measured(
+                VMIssueType::SuspendedTooLong,
+                duration_secs / 3600.0,
+                max_hours,
+                format!("suspended for {:.1}h", duration_secs / 3600.0),
+            ));
+        }
+    }
+    Some(DetectedIssue::new(
+        VMIssueType::Suspended,
+        match suspended_duration_secs {
+            Some(duration_secs) => format!("suspended for {:.1}h", duration_secs / 3600.0),
+            None => "VM is suspended".to_string(),
+        },
+    ))
+}
+
+/// How far apart `uptime_secs` and `last_power_on_secs_ago` can drift before
+/// [`power_on_disagreement_warning`] speaks up. Clock drift and the
+/// few-second gap between the power-on event firing and the guest OS
+/// finishing boot both cost some slack; this is generous enough to absorb
+/// that without also absorbing a genuinely stale event log.
+const POWER_ON_DISAGREEMENT_THRESHOLD_SECS: f64 = 300.0;
+
+/// Sanity-checks `uptime_secs` (measured directly by the guest) against
+/// `last_power_on_secs_ago` (inferred from the `VmPoweredOnEvent` vCenter
+/// logged, see [`crate::bootevents`]) and warns, rather than overriding
+/// either, when they disagree by more than [`POWER_ON_DISAGREEMENT_THRESHOLD_SECS`] -
+/// `uptime_secs` stays authoritative for issue detection since it's measured
+/// rather than inferred, but a wide gap usually means the event log missed a
+/// reboot (or the guest's clock is wrong), which is worth a human noticing.
+/// Returns `None` when `--check-uptime` found no power-on event to compare
+/// against, or when the two agree within tolerance.
+fn power_on_disagreement_warning(vm_name: &str, uptime_secs: f64, last_power_on_secs_ago: Option<f64>) -> Option<String> {
+    let last_power_on_secs_ago = last_power_on_secs_ago?;
+    let drift = (uptime_secs - last_power_on_secs_ago).abs();
+    if drift <= POWER_ON_DISAGREEMENT_THRESHOLD_SECS {
+        return None;
+    }
+    Some(format!(
+        "{vm_name}: uptime ({uptime_secs:.0}s) and last power-on event ({last_power_on_secs_ago:.0}s ago) disagree by {drift:.0}s, trusting uptime"
+    ))
+}
+
+/// Runs every detector against metrics that are already known, without
+/// touching the network. Shared by the live fetch path
+/// (`fetch_vm_statuses_inner`) and `--replay`, which reruns this against a
+/// saved report's stored metrics with new threshold flags instead of
+/// re-querying vCenter.
+#[allow(clippy::too_many_arguments)]
+pub fn detect_issues(
+    power_state: PowerState,
+    cpu_usage_pct: f64,
+    memory_usage_pct: f64,
+    metrics_source: MetricsSourceStatus,
+    cpu_count: u32,
+    cores_per_socket: u32,
+    hardware_version: &str,
+    tools_running: bool,
+    clock_skew_secs: Option<f64>,
+    reachable: Option<bool>,
+    running_processes: &[String],
+    host_metrics: Option<&HostMetrics>,
+    migration_count: u32,
+    uptime_secs: f64,
+    created_recently: bool,
+    power_on_count: u32,
+    cpu_hot_add_enabled: bool,
+    memory_hot_add_enabled: bool,
+    suspended_duration_secs: Option<f64>,
+    disk_allocated_gb: f64,
+    disk_used_gb: Option<f64>,
+    options: &DetectionOptions,
+) -> Vec<DetectedIssue> {
+    let mut issues = Vec::new();
+    if power_state == PowerState::PoweredOff {
+        issues.push(DetectedIssue::new(VMIssueType::PoweredOff, "VM is powered off"));
+    }
+    if power_state == PowerState::Unknown {
+        issues.push(DetectedIssue::new(VMIssueType::StateUnknown, "VM power state could not be determined"));
+    }
+    if let Some(issue) = suspended_issue(power_state, suspended_duration_secs, options) {
+        issues.push(issue);
+    }
+    // A VM whose metrics collection failed reports a false 0.0, not a
+    // genuinely idle VM - skip high-usage detection for it rather than
+    // alerting on that false zero. See `MetricsSourceStatus`. An unknown
+    // power state is the same kind of false reading one level up - the
+    // metric/tools/uptime checks below all assume a real power state to
+    // reason about, so they're skipped for it too rather than evaluated
+    // against data that doesn't mean anything.
+    if metrics_source == MetricsSourceStatus::Available && power_state != PowerState::Unknown {
+        if cpu_usage_pct > options.cpu_high_threshold_pct {
+            issues.push(DetectedIssue::measured(
+                VMIssueType::HighCpuUsage,
+                cpu_usage_pct,
+                options.cpu_high_threshold_pct,
+                format!("CPU usage at {cpu_usage_pct:.1}%"),
+            ));
+        }
+        if memory_usage_pct > options.memory_high_threshold_pct {
+            issues.push(DetectedIssue::measured(
+                VMIssueType::HighMemoryUsage,
+                memory_usage_pct,
+                options.memory_high_threshold_pct,
+                format!("Memory usage at {memory_usage_pct:.1}%"),
+            ));
+        }
+    }
+    if let Some(issue) = clock_skew_issue(clock_skew_secs, options) {
+        issues.push(issue);
+    }
+    if let Some(issue) = reachability_issue(reachable, options) {
+        issues.push(issue);
+    }
+    if power_state != PowerState::Unknown {
+        issues.extend(missing_process_issues(tools_running, running_processes, options));
+    }
+    if let Some(issue) = host_overcommitted_issue(&issues, host_metrics, options) {
+        issues.push(issue);
+    }
+    if let Some(metrics) = host_metrics {
+        if let Some(issue) = over_allocated_cpu_issue(cpu_count * cores_per_socket, metrics.physical_cores, options) {
+            issues.push(issue);
+        }
+    }
+    if let Some(issue) = excessive_migrations_issue(migration_count, options) {
+        issues.push(issue);
+    }
+    if power_state != PowerState::Unknown {
+        if let Some(issue) = uptime_issue(power_state, uptime_secs, created_recently, power_on_count, options) {
+            issues.push(issue);
+        }
+    }
+    if let Some(issue) = host_degraded_issue(host_metrics, options) {
+        issues.push(issue);
+    }
+    if let Some(issue) = host_hardware_unhealthy_issue(host_metrics, options) {
+        issues.push(issue);
+    }
+    if let Some(issue) = hardware_version_old_issue(hardware_version, options) {
+        issues.push(issue);
+    }
+    if let Some(issue) = hotadd_under_load_issue(&issues, cpu_hot_add_enabled, memory_hot_add_enabled, options) {
+        issues.push(issue);
+    }
+    if let Some(issue) = storage_waste_issue(disk_allocated_gb, disk_used_gb, options) {
+        issues.push(issue);
+    }
+    issues
+}
+
+impl SimulatedClient {
+    /// Simulates the one-event-query-for-the-whole-window call a real
+    /// client would make for `--check-migrations`, then buckets the result
+    /// by VM client-side (see [`crate::migration::bucket_migrations_by_vm`]),
+    /// rather than querying per VM.
+    fn migration_summaries(&self, vm_names: &[String], vm_hosts: &[String], rng: &mut impl Rng) -> HashMap<String, MigrationSummary> {
+        if !self.options.check_migrations {
+            return HashMap::new();
+        }
+        const OTHER_HOST_COUNT: u32 = 8;
+        // vCenter's event query returns more than just migrations; the raw
+        // rows are parsed through `parse_event` the same way a real query
+        // result would be, to filter down to `VmMigratedEvent`/`DrsVmMigratedEvent`.
+        let mut raw_events = Vec::new();
+        for (vm_name, from_host) in vm_names.iter().zip(vm_hosts) {
+            let migrations_in_window = if rng.gen_bool(0.25) { rng.gen_range(0..=8) } else { 0 };
+            for _ in 0..migrations_in_window {
+                let to_host_idx = rng.gen_range(0..OTHER_HOST_COUNT);
+                raw_events.push(RawMigrationEvent {
+                    event_type: if rng.gen_bool(0.5) { "VmMigratedEvent" } else { "DrsVmMigratedEvent" }.to_string(),
+                    vm_name: vm_name.clone(),
+                    hours_ago: rng.gen_range(0.0..72.0),
+                    from_host: from_host.clone(),
+                    to_host: format!("esxi-{:02}.{}", to_host_idx, self.session.host),
+                });
+            }
+            // An unrelated event type for the same VM, to exercise the filter.
+            if rng.gen_bool(0.3) {
+                raw_events.push(RawMigrationEvent {
+                    event_type: "VmPoweredOnEvent".to_string(),
+                    vm_name: vm_name.clone(),
+                    hours_ago: rng.gen_range(0.0..72.0),
+                    from_host: from_host.clone(),
+                    to_host: from_host.clone(),
+                });
+            }
+        }
+        let events = raw_events.iter().filter_map(parse_event).collect::<Vec<_>>();
+        bucket_migrations_by_vm(&events, self.options.migration_window_hours)
+    }
+
+    /// Simulates the `VmCreatedEvent`/`VmClonedEvent`/`VmRegisteredEvent`/
+    /// `VmPoweredOnEvent` query `--check-uptime` would make for one VM, then
+    /// buckets it through [`crate::bootevents::bucket_boot_history_by_vm`]
+    /// the same way a real query result would be. Only a short-uptime VM
+    /// gets any events at all - a VM that's been up for weeks has nothing
+    /// relevant in the window either way.
+    fn simulated_boot_history(&self, vm_name: &str, is_short_uptime: bool, rng: &mut impl Rng) -> BootHistory {
+        if !self.options.check_uptime || !is_short_uptime {
+            return BootHistory::default();
+        }
+        let window = self.options.boot_history_window_hours;
+        let raw_events = if rng.gen_bool(0.5) {
+            vec![RawBootEvent {
+                event_type: "VmCreatedEvent".to_string(),
+                vm_name: vm_name.to_string(),
+                hours_ago: rng.gen_range(0.0..window * 0.9),
+            }]
+        } else {
+            let power_on_count = rng.gen_range(1..=self.options.reboot_loop_count + 3);
+            (0..power_on_count)
+                .map(|_| RawBootEvent {
+                    event_type: "VmPoweredOnEvent".to_string(),
+                    vm_name: vm_name.to_string(),
+                    hours_ago: rng.gen_range(0.0..window * 0.9),
+                })
+                .collect()
+        };
+        let events = raw_events.iter().filter_map(parse_boot_event).collect::<Vec<_>>();
+        bucket_boot_history_by_vm(&events, window).remove(vm_name).unwrap_or_default()
+    }
+
+    /// Simulates the `VmSuspendedEvent` query [`suspended_issue`] uses to
+    /// compute how long a suspended VM has been down, then buckets it
+    /// through [`crate::suspendevents::bucket_suspend_time_by_vm`] the same
+    /// way a real query result would be. Only a suspended VM gets an event
+    /// at all; most of those get one, but an occasional one simulates a
+    /// suspend event that's aged out of vCenter's event retention.
+    fn simulated_suspend_duration(&self, vm_name: &str, power_state: PowerState, rng: &mut impl Rng) -> Option<f64> {
+        if power_state != PowerState::Suspended || rng.gen_bool(0.1) {
+            return None;
+        }
+        let raw_events = [RawSuspendEvent {
+            event_type: "VmSuspendedEvent".to_string(),
+            vm_name: vm_name.to_string(),
+            hours_ago: rng.gen_range(0.0..200.0),
+        }];
+        let events = raw_events.iter().filter_map(parse_suspend_event).collect::<Vec<_>>();
+        bucket_suspend_time_by_vm(&events).remove(vm_name)
+    }
+
+    fn fetch_vm_statuses_inner(&self) -> Result<Vec<VMResourceStatus>> {
+        let mut rng = rand::thread_rng();
+        self.request_budget.record(1); // the bulk ListVMs call itself
+        let clusters = ["cluster-a", "cluster-b", "cluster-c"];
+        let vm_names: Vec<String> = (0..self.vm_count).map(|i| format!("vm-{i:04}")).collect();
+        let vm_hosts: Vec<String> = (0..self.vm_count).map(|i| format!("esxi-{:02}.{}", i % 8, self.session.host)).collect();
+        let distinct_hosts: Vec<String> = vm_hosts.iter().cloned().collect::<std::collections::BTreeSet<_>>().into_iter().collect();
+        self.prefetch_host_metrics(&distinct_hosts, &mut rng);
+        // Power state has to be decided before metrics can be batched by
+        // host, so it's rolled here instead of inline in the per-VM loop
+        // below.
+        let power_states: Vec<PowerState> = (0..self.vm_count)
+            .map(|_| {
+                if rng.gen_bool(0.05) {
+                    PowerState::PoweredOff
+                } else if rng.gen_bool(0.02) {
+                    PowerState::Suspended
+                } else if rng.gen_bool(0.01) {
+                    // A detail call that half-failed and came back with
+                    // neither a recognized power state nor an error -
+                    // rare, but `detect_issues` needs to be exercised
+                    // against it rather than only ever seeing the three
+                    // clean states.
+                    PowerState::Unknown
+                } else {
+                    PowerState::PoweredOn
+                }
+            })
+            .collect();
+        self.prefetch_vm_metrics(&vm_names, &vm_hosts, &power_states, &mut rng);
+        // `/vcenter/datacenter` + `/vcenter/folder`, once per run; see
+        // `crate::inventory`.
+        self.request_budget.record(2);
+        const FOLDERS_PER_CLUSTER: u32 = 4;
+        let folders = inventory::build_synthetic_folders(&clusters, FOLDERS_PER_CLUSTER);
+        // Checks billed against `--max-total-requests` that this run
+        // actually has turned on; see `crate::request_budget`.
+        let active_checks: Vec<DegradableCheck> = DEGRADE_PRIORITY
+            .into_iter()
+            .filter(|check| match check {
+                DegradableCheck::Reachability => self.options.check_reachability,
+                DegradableCheck::Process => !self.options.required_processes.is_empty(),
+                DegradableCheck::ClockSkew => self.options.check_clock,
+            })
+            .collect();
+        let migration_summaries = if self.options.check_migrations {
+            self.timing.time("migrations", || self.migration_summaries(&vm_names, &vm_hosts, &mut rng))
+        } else {
+            self.migration_summaries(&vm_names, &vm_hosts, &mut rng)
+        };
+        // Long enough that `uptime_issue` never fires unless
+        // `--check-uptime` is on. A handful of VMs boot close together
+        // ("storm_time") so `--check-boot-storm` has something realistic to
+        // correlate against in the simulated fleet.
+        const LONG_UPTIME_SECS: f64 = 30.0 * 86400.0;
+        let storm_time = rng.gen_range(60.0..self.options.short_uptime_threshold_secs * 0.9);
+        let mut out = Vec::with_capacity(self.vm_count);
+        // `--check-vm-files` bookkeeping: `file_checks_performed` enforces
+        // `--max-file-checks`, `warned_datastores` dedupes the
+        // browse-forbidden warning per datastore rather than per VM.
+        let mut file_checks_performed: u32 = 0;
+        let mut file_check_cap_warned = false;
+        let mut warned_datastores: std::collections::HashSet<String> = std::collections::HashSet::new();
+        for i in 0..self.vm_count {
+            if self.request_budget.is_exhausted() {
+                self.request_budget.defer(&vm_names[i]);
+                continue;
+            }
+            if let Some(timeout_ms) = self.options.per_vm_timeout_ms {
+                // Simulates the occasional guest whose tools/process/clock
+                // queries hang - an unresponsive agent, a stuck VMware
+                // Tools upgrade. Most VMs answer in well under a second;
+                // a small fraction run long enough that any reasonable
+                // timeout would abandon them rather than stall the run.
+                let simulated_latency_ms = if rng.gen_bool(0.03) {
+                    rng.gen_range(timeout_ms + 1..timeout_ms + 30_000)
+                } else {
+                    rng.gen_range(10..500)
+                };
+                if simulated_latency_ms > timeout_ms {
+                    self.timed_out.lock().unwrap().push(vm_names[i].clone());
+                    continue;
+                }
+            }
+            // `cpu_count`/`memory_gb` are generated here, not parsed out of a
+            // `memory.size_MiB`/`count`-shaped API payload - there's no
+            // `get_vm_hardware_info` or raw vSphere response in this tree to
+            // harden against key-variant drift across API versions. That
+            // kind of defensive parsing belongs in a real `VCenterClient`
+            // implementation, which doesn't exist here yet; see
+            // [`crate::auth::Session`] for the same gap on the
+            // authentication side.
+            const VCPU_COUNTS: [u32; 4] = [1, 2, 4, 8];
+            const CORES_PER_SOCKET_OPTIONS: [u32; 2] = [1, 2];
+            const HW_VERSIONS: [u32; 6] = [10, 11, 13, 14, 17, 19];
+            const MEMORY_SIZES_GB: [f64; 6] = [2.0, 4.0, 8.0, 16.0, 32.0, 64.0];
+            let cpu_count = VCPU_COUNTS[rng.gen_range(0..VCPU_COUNTS.len())];
+            let cores_per_socket = CORES_PER_SOCKET_OPTIONS[rng.gen_range(0..CORES_PER_SOCKET_OPTIONS.len())];
+            let memory_gb = MEMORY_SIZES_GB[rng.gen_range(0..MEMORY_SIZES_GB.len())];
+            let hardware_version = format!("vmx-{}", HW_VERSIONS[rng.gen_range(0..HW_VERSIONS.len())]);
+            // Old templates more often than not predate hot-add, which is
+            // exactly the mismatch `--require-hot-add` exists to catch.
+            let cpu_hot_add_enabled = rng.gen_bool(0.85);
+            let memory_hot_add_enabled = rng.gen_bool(0.85);
+            let power_state = power_states[i];
+            let metrics = self.vm_metrics_for(&vm_names[i]);
+            let cpu = metrics.get(CPU_USAGE_PCT).copied().unwrap_or(0.0);
+            let mem = metrics.get(MEMORY_USAGE_PCT).copied().unwrap_or(0.0);
+            let metrics_source = self.vm_metrics_source_for(&vm_names[i]);
+            let tools_running = power_state == PowerState::PoweredOn && rng.gen_bool(0.9);
+            // A minority of guests don't online hot-added memory/vCPUs,
+            // leaving the guest-visible figure below what vCenter has
+            // configured - the scenario `--check-guest-resource-mismatch`
+            // exists to catch. `None` when Tools isn't running: there's no
+            // guest-visible figure to read.
+            let guest_visible_memory_mb = tools_running.then(|| {
+                if rng.gen_bool(0.15) {
+                    memory_gb * 1024.0 * rng.gen_range(0.4..0.9)
+                } else {
+                    memory_gb * 1024.0
+                }
+            });
+            let guest_visible_cpu_count = tools_running.then(|| {
+                if cpu_count > 1 && rng.gen_bool(0.1) {
+                    cpu_count - 1
+                } else {
+                    cpu_count
+                }
+            });
+            // A minority of VMs were provisioned generously and never grew
+            // into the space - the scenario `--check-storage-waste` exists
+            // to catch. `disk_used_gb` is `None` when Tools isn't running:
+            // there's no guest-reported usage figure to compare against.
+            const DISK_SIZES_GB: [f64; 6] = [50.0, 100.0, 250.0, 500.0, 1000.0, 2000.0];
+            let disk_allocated_gb = DISK_SIZES_GB[rng.gen_range(0..DISK_SIZES_GB.len())];
+            let disk_used_gb = tools_running.then(|| {
+                if disk_allocated_gb >= STORAGE_WASTE_MIN_ALLOCATED_GB && rng.gen_bool(0.2) {
+                    disk_allocated_gb * rng.gen_range(0.01..0.08)
+                } else {
+                    disk_allocated_gb * rng.gen_range(0.2..0.8)
+                }
+            });
+            let is_short_uptime = self.options.check_uptime && power_state == PowerState::PoweredOn && rng.gen_bool(0.15);
+            let uptime_secs = if is_short_uptime {
+                storm_time + rng.gen_range(-30.0..30.0)
+            } else {
+                LONG_UPTIME_SECS
+            };
+            let boot_history = if self.options.check_uptime {
+                self.timing.time("uptime", || self.simulated_boot_history(&vm_names[i], is_short_uptime, &mut rng))
+            } else {
+                self.simulated_boot_history(&vm_names[i], is_short_uptime, &mut rng)
+            };
+            let host = vm_hosts[i].clone();
+            let migration = migration_summaries.get(&vm_names[i]).cloned().unwrap_or_default();
+
+            // Guest tools must be running to read the guest/host time delta;
+            // VMs without them can't report skew and are skipped, not flagged.
+            let clock_skew_secs: Option<f64> = if tools_running {
+                Some(if rng.gen_bool(0.05) {
+                    rng.gen_range(-60.0..60.0)
+                } else {
+                    rng.gen_range(-1.0..1.0)
+                })
+            } else {
+                None
+            };
+
+            // Bill this VM's active, not-yet-degraded checks against the
+            // ceiling, then degrade the next one in priority order once
+            // past 80%; see `crate::request_budget`.
+            let billed = active_checks.iter().filter(|check| !self.request_budget.is_degraded(**check)).count() as u64;
+            self.request_budget.record(1 + billed);
+            self.request_budget.maybe_degrade(&active_checks);
+
+            // A degraded check behaves as if it were never enabled for
+            // every VM from here on, same detectors as everywhere else.
+            let mut effective_options = self.options.clone();
+            if self.request_budget.is_degraded(DegradableCheck::Reachability) {
+                effective_options.check_reachability = false;
+            }
+            if self.request_budget.is_degraded(DegradableCheck::Process) {
+                effective_options.required_processes = Vec::new();
+            }
+            if self.request_budget.is_degraded(DegradableCheck::ClockSkew) {
+                effective_options.check_clock = false;
+            }
+
+            let guest_ip = tools_running.then(|| format!("10.{}.{}.{}", i % 256, (i / 256) % 256, 1 + i % 254));
+            let reachable = if effective_options.check_reachability {
+                guest_ip.as_deref().map(|ip| {
+                    self.probe
+                        .is_reachable(ip, effective_options.reachability_port, Duration::from_millis(effective_options.reachability_timeout_ms))
+                })
+            } else {
+                None
+            };
+
+            let all_processes = ["sshd", "nginx", "postgres", "cron", "dockerd"];
+            let running_processes: Vec<String> = if tools_running {
+                all_processes
+                    .iter()
+                    .filter(|_| rng.gen_bool(0.8))
+                    .map(|p| p.to_string())
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            let host_metrics = self.host_metrics_for(&host, &mut rng);
+            let suspended_duration_secs = self.simulated_suspend_duration(&vm_names[i], power_state, &mut rng);
+            let mismatch = if effective_options.check_guest_resource_mismatch {
+                self.timing.time("guest_resource_mismatch", || {
+                    guest_resource_mismatch(cpu, mem, cpu_count, memory_gb, guest_visible_cpu_count, guest_visible_memory_mb, &effective_options)
+                })
+            } else {
+                guest_resource_mismatch(cpu, mem, cpu_count, memory_gb, guest_visible_cpu_count, guest_visible_memory_mb, &effective_options)
+            };
+            let mut issues = self.timing.time("issue_detection", || {
+                detect_issues(
+                    power_state,
+                    mismatch.cpu_usage_pct,
+                    mismatch.memory_usage_pct,
+                    metrics_source,
+                    cpu_count,
+                    cores_per_socket,
+                    &hardware_version,
+                    tools_running,
+                    clock_skew_secs,
+                    reachable,
+                    &running_processes,
+                    Some(&host_metrics),
+                    migration.count,
+                    uptime_secs,
+                    boot_history.created_recently,
+                    boot_history.power_on_count,
+                    cpu_hot_add_enabled,
+                    memory_hot_add_enabled,
+                    suspended_duration_secs,
+                    disk_allocated_gb,
+                    disk_used_gb,
+                    &effective_options,
+                )
+            });
+            if let Some(issue) = mismatch.issue {
+                issues.push(issue);
+            }
+
+            if self.options.check_vm_files && (power_state == PowerState::PoweredOff || power_state == PowerState::Suspended) {
+                let within_cap = self.options.max_file_checks.is_none_or(|cap| file_checks_performed < cap);
+                if within_cap {
+                    file_checks_performed += 1;
+                    let (datastore, path, missing_issue, missing_kind) = if power_state == PowerState::Suspended {
+                        let (datastore, vmss_path) = datastore::synthetic_vmss_path(&vm_names[i], i);
+                        (datastore, vmss_path, VMIssueType::SuspendStateMissing, "suspend file")
+                    } else {
+                        let (datastore, vmx_path) = datastore::synthetic_vmx_path(&vm_names[i], i);
+                        (datastore, vmx_path, VMIssueType::BackingFilesMissing, "VMX")
+                    };
+                    let raw = datastore::RawFileSearchResult {
+                        datastore,
+                        path,
+                        browsable: !rng.gen_bool(0.03),
+                        found: rng.gen_bool(0.95),
+                    };
+                    match datastore::parse_file_search_result(&raw) {
+                        datastore::FileSearchOutcome::Missing => {
+                            issues.push(DetectedIssue::new(missing_issue, format!("expected {missing_kind} at {}", raw.path)));
+                        }
+                        datastore::FileSearchOutcome::BrowseForbidden => {
+                            if warned_datastores.insert(raw.datastore.clone()) {
+                                eprintln!(
+                                    "check-vm-files: datastore {} refused browse, skipping file checks against it this run",
+                                    raw.datastore
+                                );
+                            }
+                        }
+                        datastore::FileSearchOutcome::Found => {}
+                    }
+                } else if !file_check_cap_warned {
+                    file_check_cap_warned = true;
+                    eprintln!("check-vm-files: max-file-checks cap reached, remaining powered-off VMs left unchecked this run");
+                }
+            }
+
+            let owners = ["alice", "bob", "carol"];
+            let mut attributes = std::collections::HashMap::new();
+            if rng.gen_bool(0.9) {
+                attributes.insert("Owner".to_string(), owners[i % owners.len()].to_string());
+            }
+            if rng.gen_bool(0.5) {
+                attributes.insert("CostCenter".to_string(), format!("CC-{:03}", i % 20));
+            }
+            let notes = rng
+                .gen_bool(0.3)
+                .then(|| format!("managed by team-{}", i % 4));
+
+            if let Some(warning) = power_on_disagreement_warning(&vm_names[i], uptime_secs, boot_history.last_power_on_secs_ago) {
+                eprintln!("{warning}");
+            }
+
+            let cluster = clusters[i % clusters.len()];
+            let folder_id = inventory::synthetic_folder_id(cluster, i, FOLDERS_PER_CLUSTER);
+            let inventory_path = inventory::vm_path(&folders, &folder_id, &vm_names[i]);
+            // Most VMs' config/power didn't change since the last call, so
+            // most of the time this lands back on the name's stable base
+            // version; `--since-last-run` relies on that to tell a VM apart
+            // that's genuinely changed from one that just got re-listed.
+            let change_version = if rng.gen_bool(0.1) {
+                stable_change_version(&vm_names[i]).wrapping_add(rng.gen_range(1..1_000_000))
+            } else {
+                stable_change_version(&vm_names[i])
+            };
+
+            out.push(VMResourceStatus {
+                name: vm_names[i].clone(),
+                host,
+                cluster: cluster.to_string(),
+                inventory_path,
+                power_state,
+                cpu_usage_pct: mismatch.cpu_usage_pct,
+                memory_usage_pct: mismatch.memory_usage_pct,
+                raw_metrics: if self.options.include_raw_metrics { metrics.clone() } else { HashMap::new() },
+                metrics_source,
+                cpu_count,
+                cores_per_socket,
+                memory_gb,
+                hardware_version,
+                cpu_hot_add_enabled,
+                memory_hot_add_enabled,
+                guest_visible_memory_mb,
+                guest_visible_cpu_count,
+                disk_allocated_gb,
+                disk_used_gb,
+                usage_basis: mismatch.usage_basis,
+                tools_running,
+                clock_skew_secs,
+                guest_ip,
+                reachable,
+                running_processes,
+                attributes,
+                notes,
+                migration_count_24h: migration.count,
+                last_migration: migration.last,
+                uptime_secs,
+                created_recently: boot_history.created_recently,
+                power_on_count: boot_history.power_on_count,
+                last_power_on_secs_ago: boot_history.last_power_on_secs_ago,
+                suspended_duration_secs,
+                health_score: 100.0,
+                change_version,
+                issues,
+            });
+        }
+        Ok(out)
+    }
+}
+
+impl VCenterClient for SimulatedClient {
+    fn fetch_vm_statuses(&self) -> Result<Vec<VMResourceStatus>> {
+        let started = Instant::now();
+        let result = self.fetch_vm_statuses_inner();
+        self.rate_log.record("ListVMs", started.elapsed().as_secs_f64() * 1000.0);
+        result
+    }
+
+    fn session(&self) -> &Session {
+        &self.session
+    }
+
+    fn metrics_degraded(&self) -> bool {
+        self.metrics_connection_down.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_skew_requires_opt_in_and_tools_running() {
+        let options = DetectionOptions {
+            check_clock: true,
+            clock_skew_threshold_secs: 5.0,
+            ..Default::default()
+        };
+        assert!(clock_skew_issue(None, &options).is_none(), "no tools, no verdict");
+        assert!(clock_skew_issue(Some(2.0), &options).is_none(), "within threshold");
+        assert!(clock_skew_issue(Some(10.0), &options).is_some());
+        assert!(clock_skew_issue(Some(-10.0), &options).is_some());
+
+        let disabled = DetectionOptions {
+            check_clock: false,
+            ..options
+        };
+        assert!(clock_skew_issue(Some(10.0), &disabled).is_none());
+    }
+
+    #[test]
+    fn reachability_only_flags_confirmed_unreachable() {
+        let options = DetectionOptions {
+            check_reachability: true,
+            ..Default::default()
+        };
+        assert!(reachability_issue(None, &options).is_none(), "no ip, no verdict");
+        assert!(reachability_issue(Some(true), &options).is_none());
+        assert!(reachability_issue(Some(false), &options).is_some());
+
+        let disabled = DetectionOptions {
+            check_reachability: false,
+            ..options
+        };
+        assert!(reachability_issue(Some(false), &disabled).is_none());
+    }
+
+    #[test]
+    fn created_recently_downgrades_uptime_short_to_informational() {
+        let options = DetectionOptions {
+            check_uptime: true,
+            short_uptime_threshold_secs: 900.0,
+            ..Default::default()
+        };
+        let fresh = uptime_issue(PowerState::PoweredOn, 60.0, true, 0, &options).unwrap();
+        assert_eq!(fresh.issue_type, VMIssueType::UptimeShort);
+        assert_eq!(fresh.severity, Severity::Informational);
+
+        let rebooted = uptime_issue(PowerState::PoweredOn, 60.0, false, 0, &options).unwrap();
+        assert_eq!(rebooted.issue_type, VMIssueType::UptimeShort);
+        assert_eq!(rebooted.severity, VMIssueType::UptimeShort.severity());
+    }
+
+    #[test]
+    fn suspended_vm_is_always_flagged_regardless_of_max_suspend_hours() {
+        assert!(suspended_issue(PowerState::PoweredOn, None, &DetectionOptions::default()).is_none());
+
+        let issue = suspended_issue(PowerState::Suspended, None, &DetectionOptions::default()).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::Suspended);
+        assert_eq!(issue.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn suspended_too_long_escalates_only_past_max_suspend_hours() {
+        let options = DetectionOptions { max_suspend_hours: Some(8.0), ..Default::default() };
+
+        let within_window = suspended_issue(PowerState::Suspended, Some(4.0 * 3600.0), &options).unwrap();
+        assert_eq!(within_window.issue_type, VMIssueType::Suspended);
+
+        let too_long = suspended_issue(PowerState::Suspended, Some(10.0 * 3600.0), &options).unwrap();
+        assert_eq!(too_long.issue_type, VMIssueType::SuspendedTooLong);
+        assert_eq!(too_long.severity, Severity::Critical);
+        assert_eq!(too_long.measured_value, Some(10.0));
+        assert_eq!(too_long.threshold, Some(8.0));
+
+        // No event to compare against `--max-suspend-hours`: falls back to the baseline issue.
+        let undated = suspended_issue(PowerState::Suspended, None, &options).unwrap();
+        assert_eq!(undated.issue_type, VMIssueType::Suspended);
+    }
+
+    #[test]
+    fn power_on_disagreement_warns_only_beyond_tolerance() {
+        assert!(
+            power_on_disagreement_warning("vm-0001", 60.0, None).is_none(),
+            "no event to compare against"
+        );
+        assert!(
+            power_on_disagreement_warning("vm-0001", 600.0, Some(610.0)).is_none(),
+            "within tolerance"
+        );
+
+        let warning = power_on_disagreement_warning("vm-0001", 600.0, Some(3600.0)).unwrap();
+        assert!(warning.contains("vm-0001"));
+        assert!(warning.contains("600"));
+        assert!(warning.contains("3600"));
+    }
+
+    #[test]
+    fn excessive_power_ons_escalate_to_reboot_loop() {
+        let options = DetectionOptions {
+            check_uptime: true,
+            short_uptime_threshold_secs: 900.0,
+            reboot_loop_count: 3,
+            ..Default::default()
+        };
+        let looping = uptime_issue(PowerState::PoweredOn, 60.0, false, 4, &options).unwrap();
+        assert_eq!(looping.issue_type, VMIssueType::RebootLoop);
+        assert_eq!(looping.severity, Severity::Critical);
+
+        let not_yet = uptime_issue(PowerState::PoweredOn, 60.0, false, 3, &options).unwrap();
+        assert_eq!(not_yet.issue_type, VMIssueType::UptimeShort);
+
+        // A VM within the creation window is a fresh deployment even if it
+        // also racked up power-on events, not a crash loop.
+        let fresh = uptime_issue(PowerState::PoweredOn, 60.0, true, 4, &options).unwrap();
+        assert_eq!(fresh.issue_type, VMIssueType::UptimeShort);
+        assert_eq!(fresh.severity, Severity::Informational);
+    }
+
+    #[test]
+    fn unknown_power_state_raises_state_unknown_and_skips_metric_tools_uptime_checks() {
+        let options = DetectionOptions {
+            cpu_high_threshold_pct: 50.0,
+            memory_high_threshold_pct: 50.0,
+            check_uptime: true,
+            short_uptime_threshold_secs: 900.0,
+            required_processes: vec!["nginx".to_string()],
+            ..Default::default()
+        };
+        let issues = detect_issues(
+            PowerState::Unknown,
+            90.0,
+            90.0,
+            MetricsSourceStatus::Available,
+            2,
+            1,
+            "vmx-19",
+            true,
+            None,
+            None,
+            &[],
+            None,
+            0,
+            60.0,
+            false,
+            0,
+            false,
+            false,
+            None,
+            0.0,
+            None,
+            &options,
+        );
+        assert!(issues.iter().any(|i| i.issue_type == VMIssueType::StateUnknown));
+        assert!(!issues.iter().any(|i| matches!(
+            i.issue_type,
+            VMIssueType::HighCpuUsage | VMIssueType::HighMemoryUsage | VMIssueType::ProcessNotRunning | VMIssueType::UptimeShort | VMIssueType::RebootLoop
+        )));
+    }
+
+    #[test]
+    fn each_real_power_state_still_runs_its_own_metric_tools_uptime_checks() {
+        let options = DetectionOptions {
+            cpu_high_threshold_pct: 50.0,
+            memory_high_threshold_pct: 50.0,
+            check_uptime: true,
+            short_uptime_threshold_secs: 900.0,
+            required_processes: vec!["nginx".to_string()],
+            ..Default::default()
+        };
+        for power_state in [PowerState::PoweredOn, PowerState::PoweredOff, PowerState::Suspended] {
+            let issues = detect_issues(
+                power_state,
+                90.0,
+                90.0,
+                MetricsSourceStatus::Available,
+                2,
+                1,
+                "vmx-19",
+                true,
+                None,
+                None,
+                &[],
+                None,
+                0,
+                60.0,
+                false,
+                0,
+                false,
+                false,
+                None,
+                0.0,
+                None,
+                &options,
+            );
+            assert!(!issues.iter().any(|i| i.issue_type == VMIssueType::StateUnknown));
+            assert!(issues.iter().any(|i| i.issue_type == VMIssueType::HighCpuUsage), "{power_state:?} should still get metric checks");
+            assert!(issues.iter().any(|i| i.issue_type == VMIssueType::HighMemoryUsage), "{power_state:?} should still get metric checks");
+            assert!(issues.iter().any(|i| i.issue_type == VMIssueType::ProcessNotRunning), "{power_state:?} should still get tools checks");
+        }
+    }
+
+    #[test]
+    fn missing_process_is_flagged_only_with_tools_running() {
+        let options = DetectionOptions {
+            required_processes: vec!["nginx".to_string(), "cron".to_string()],
+            ..Default::default()
+        };
+        let running = vec!["nginx".to_string()];
+        let issues = missing_process_issues(true, &running, &options);
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].detail.as_deref().unwrap().contains("cron"));
+
+        assert!(missing_process_issues(false, &running, &options).is_empty());
+    }
+
+    #[test]
+    fn host_overcommitted_only_fires_alongside_a_metric_issue() {
+        let hot = HostMetrics {
+            cpu_usage_pct: 95.0,
+            memory_usage_pct: 50.0,
+            physical_cores: 32,
+            connection_state: crate::vm::HostConnectionState::Connected,
+            in_maintenance_mode: false,
+            sensor_status: crate::vm::HostSensorStatus::Green,
+            failing_sensor: None,
+        };
+        let options = DetectionOptions::default();
+        assert!(host_overcommitted_issue(&[], Some(&hot), &options).is_none(), "no VM-level issue to explain");
+
+        let cpu_issue = vec![DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x")];
+        assert!(host_overcommitted_issue(&cpu_issue, None, &options).is_none(), "host metrics unavailable");
+
+        let calm = HostMetrics {
+            cpu_usage_pct: 50.0,
+            memory_usage_pct: 50.0,
+            physical_cores: 32,
+            connection_state: crate::vm::HostConnectionState::Connected,
+            in_maintenance_mode: false,
+            sensor_status: crate::vm::HostSensorStatus::Green,
+            failing_sensor: None,
+        };
+        assert!(host_overcommitted_issue(&cpu_issue, Some(&calm), &options).is_none());
+
+        let issue = host_overcommitted_issue(&cpu_issue, Some(&hot), &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::HostOvercommitted);
+        assert_eq!(issue.severity, crate::vm::Severity::Informational);
+    }
+
+    #[test]
+    fn hotadd_under_load_requires_opt_in_and_a_coinciding_metric_issue() {
+        let disabled = DetectionOptions::default();
+        let cpu_issue = vec![DetectedIssue::measured(VMIssueType::HighCpuUsage, 95.0, 90.0, "x")];
+        assert!(hotadd_under_load_issue(&cpu_issue, false, true, &disabled).is_none(), "opt-in required");
+
+        let options = DetectionOptions { check_hotadd: true, ..Default::default() };
+        assert!(hotadd_under_load_issue(&[], false, false, &options).is_none(), "no metric issue to pair with");
+        assert!(hotadd_under_load_issue(&cpu_issue, true, true, &options).is_none(), "hot-add is enabled on the hot resource");
+
+        let issue = hotadd_under_load_issue(&cpu_issue, false, true, &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::HotAddDisabledUnderLoad);
+
+        let memory_issue = vec![DetectedIssue::measured(VMIssueType::HighMemoryUsage, 95.0, 90.0, "x")];
+        let issue = hotadd_under_load_issue(&memory_issue, true, false, &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::HotAddDisabledUnderLoad);
+    }
+
+    #[test]
+    fn guest_resource_mismatch_requires_opt_in() {
+        let disabled = DetectionOptions::default();
+        let result = guest_resource_mismatch(10.0, 10.0, 4, 16.0, Some(2), Some(8192.0), &disabled);
+        assert!(result.issue.is_none(), "opt-in required");
+        assert_eq!(result.cpu_usage_pct, 10.0);
+        assert_eq!(result.memory_usage_pct, 10.0);
+        assert_eq!(result.usage_basis, crate::vm::UsageBasis::Configured);
+    }
+
+    #[test]
+    fn guest_resource_mismatch_flags_a_mismatch_and_recomputes_usage_against_the_guest_visible_size() {
+        let options = DetectionOptions { check_guest_resource_mismatch: true, ..Default::default() };
+        // Configured for 16 GB / 4 vCPUs, but the guest only onlined half the
+        // memory and one fewer vCPU - both well past the 10% threshold.
+        let result = guest_resource_mismatch(40.0, 40.0, 4, 16.0, Some(3), Some(8192.0), &options);
+        let issue = result.issue.unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::GuestResourceMismatch);
+        assert_eq!(result.usage_basis, crate::vm::UsageBasis::GuestVisible);
+        assert_eq!(result.memory_usage_pct, 80.0, "usage doubles when only half the memory is actually visible");
+        assert!((result.cpu_usage_pct - 53.333333333333336).abs() < 1e-9);
+    }
+
+    #[test]
+    fn guest_resource_mismatch_within_threshold_leaves_usage_untouched() {
+        let options = DetectionOptions { check_guest_resource_mismatch: true, ..Default::default() };
+        let result = guest_resource_mismatch(40.0, 40.0, 4, 16.0, Some(4), Some(15000.0), &options);
+        assert!(result.issue.is_none(), "within the 10% threshold");
+        assert_eq!(result.usage_basis, crate::vm::UsageBasis::Configured);
+        assert_eq!(result.memory_usage_pct, 40.0);
+        assert_eq!(result.cpu_usage_pct, 40.0);
+    }
+
+    #[test]
+    fn guest_resource_mismatch_is_none_when_tools_is_not_running() {
+        let options = DetectionOptions { check_guest_resource_mismatch: true, ..Default::default() };
+        let result = guest_resource_mismatch(40.0, 40.0, 4, 16.0, None, None, &options);
+        assert!(result.issue.is_none(), "nothing guest-visible to compare against");
+        assert_eq!(result.usage_basis, crate::vm::UsageBasis::Configured);
+        assert_eq!(result.memory_usage_pct, 40.0);
+        assert_eq!(result.cpu_usage_pct, 40.0);
+    }
+
+    #[test]
+    fn storage_waste_requires_opt_in() {
+        let disabled = DetectionOptions::default();
+        assert!(storage_waste_issue(1000.0, Some(10.0), &disabled).is_none());
+    }
+
+    #[test]
+    fn storage_waste_flags_a_large_mostly_empty_disk() {
+        let options = DetectionOptions { check_storage_waste: true, ..Default::default() };
+        let issue = storage_waste_issue(1000.0, Some(50.0), &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::StorageWaste);
+        assert_eq!(issue.measured_value, Some(5.0));
+        assert_eq!(issue.threshold, Some(STORAGE_WASTE_MAX_USED_PCT));
+    }
+
+    #[test]
+    fn storage_waste_is_none_below_the_allocation_floor_or_above_the_used_threshold() {
+        let options = DetectionOptions { check_storage_waste: true, ..Default::default() };
+        assert!(storage_waste_issue(100.0, Some(1.0), &options).is_none(), "too small to bother flagging");
+        assert!(storage_waste_issue(1000.0, Some(200.0), &options).is_none(), "20% used is not wasted");
+    }
+
+    #[test]
+    fn storage_waste_is_none_when_tools_is_not_running() {
+        let options = DetectionOptions { check_storage_waste: true, ..Default::default() };
+        assert!(storage_waste_issue(1000.0, None, &options).is_none(), "nothing to compare against");
+    }
+
+    #[test]
+    fn host_degraded_requires_opt_in_and_flags_disconnected_or_maintenance() {
+        let healthy = HostMetrics {
+            cpu_usage_pct: 50.0,
+            memory_usage_pct: 50.0,
+            physical_cores: 32,
+            connection_state: crate::vm::HostConnectionState::Connected,
+            in_maintenance_mode: false,
+            sensor_status: crate::vm::HostSensorStatus::Green,
+            failing_sensor: None,
+        };
+        let options = DetectionOptions {
+            check_host_state: true,
+            ..Default::default()
+        };
+        assert!(host_degraded_issue(Some(&healthy), &options).is_none());
+        assert!(host_degraded_issue(None, &options).is_none(), "host metrics unavailable");
+
+        let disconnected = HostMetrics {
+            connection_state: crate::vm::HostConnectionState::Disconnected,
+            ..healthy.clone()
+        };
+        assert_eq!(
+            host_degraded_issue(Some(&disconnected), &options).unwrap().issue_type,
+            VMIssueType::HostDegraded
+        );
+
+        let in_maintenance = HostMetrics {
+            in_maintenance_mode: true,
+            ..healthy
+        };
+        assert_eq!(
+            host_degraded_issue(Some(&in_maintenance), &options).unwrap().issue_type,
+            VMIssueType::HostDegraded
+        );
+
+        let disabled = DetectionOptions::default();
+        assert!(host_degraded_issue(Some(&disconnected), &disabled).is_none(), "opt-in required");
+    }
+
+    #[test]
+    fn host_hardware_unhealthy_requires_opt_in_and_flags_yellow_or_red_sensors() {
+        let healthy = HostMetrics {
+            cpu_usage_pct: 50.0,
+            memory_usage_pct: 50.0,
+            physical_cores: 32,
+            connection_state: crate::vm::HostConnectionState::Connected,
+            in_maintenance_mode: false,
+            sensor_status: crate::vm::HostSensorStatus::Green,
+            failing_sensor: None,
+        };
+        let options = DetectionOptions {
+            check_host_health: true,
+            ..Default::default()
+        };
+        assert!(host_hardware_unhealthy_issue(Some(&healthy), &options).is_none());
+        assert!(host_hardware_unhealthy_issue(None, &options).is_none(), "host metrics unavailable");
+
+        let yellow = HostMetrics {
+            sensor_status: crate::vm::HostSensorStatus::Yellow,
+            failing_sensor: Some("Fan 3".to_string()),
+            ..healthy.clone()
+        };
+        let issue = host_hardware_unhealthy_issue(Some(&yellow), &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::HostHardwareUnhealthy);
+        assert!(issue.detail.as_deref().unwrap().contains("Fan 3"));
+
+        let red = HostMetrics {
+            sensor_status: crate::vm::HostSensorStatus::Red,
+            failing_sensor: Some("Power Supply 2".to_string()),
+            ..healthy
+        };
+        assert_eq!(host_hardware_unhealthy_issue(Some(&red), &options).unwrap().issue_type, VMIssueType::HostHardwareUnhealthy);
+
+        let disabled = DetectionOptions::default();
+        assert!(host_hardware_unhealthy_issue(Some(&red), &disabled).is_none(), "opt-in required");
+    }
+
+    #[test]
+    fn over_allocated_cpu_respects_opt_in_and_ratio() {
+        let disabled = DetectionOptions::default();
+        assert!(over_allocated_cpu_issue(64, 32, &disabled).is_none(), "opt-in required");
+
+        let options = DetectionOptions {
+            check_vcpu_allocation: true,
+            max_vcpu_ratio: 1.0,
+            ..Default::default()
+        };
+        assert!(over_allocated_cpu_issue(32, 32, &options).is_none(), "at the ratio, not over it");
+
+        let issue = over_allocated_cpu_issue(64, 32, &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::OverAllocatedCpu);
+        assert_eq!(issue.measured_value, Some(64.0));
+        assert_eq!(issue.threshold, Some(32.0));
+    }
+
+    #[test]
+    fn hardware_version_old_respects_opt_in_and_threshold() {
+        let disabled = DetectionOptions::default();
+        assert!(hardware_version_old_issue("vmx-10", &disabled).is_none(), "opt-in required");
+
+        let options = DetectionOptions {
+            check_hw_version: true,
+            min_hw_version: 15,
+            ..Default::default()
+        };
+        assert!(hardware_version_old_issue("vmx-15", &options).is_none(), "at the minimum, not below it");
+        assert!(
+            hardware_version_old_issue("not-a-version", &options).is_none(),
+            "unparseable version can't be compared"
+        );
+
+        let issue = hardware_version_old_issue("vmx-10", &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::HardwareVersionOld);
+        assert_eq!(issue.measured_value, Some(10.0));
+        assert_eq!(issue.threshold, Some(15.0));
+    }
+
+    #[test]
+    fn excessive_migrations_respects_opt_in_and_threshold() {
+        let disabled = DetectionOptions::default();
+        assert!(excessive_migrations_issue(10, &disabled).is_none(), "opt-in required");
+
+        let options = DetectionOptions {
+            check_migrations: true,
+            max_migrations: 5,
+            ..Default::default()
+        };
+        assert!(excessive_migrations_issue(5, &options).is_none(), "at the threshold, not over it");
+
+        let issue = excessive_migrations_issue(6, &options).unwrap();
+        assert_eq!(issue.issue_type, VMIssueType::ExcessiveMigrations);
+        assert_eq!(issue.measured_value, Some(6.0));
+        assert_eq!(issue.threshold, Some(5.0));
+    }
+
+    struct AlwaysUnreachable;
+    impl ReachabilityProbe for AlwaysUnreachable {
+        fn is_reachable(&self, _host: &str, _port: u16, _timeout: Duration) -> bool {
+            false
+        }
+    }
+
+    #[test]
+    fn simulated_client_flags_unreachable_vms_when_opted_in() {
+        let session = Session {
+            host: "vcenter.example.com".to_string(),
+            username: "tester".to_string(),
+            token: "t".to_string(),
+            version: crate::auth::VCenterVersion {
+                product: "VMware vCenter Server".to_string(),
+                version: "8.0.2".to_string(),
+                build: "22617221".to_string(),
+            },
+        };
+        let options = DetectionOptions {
+            check_reachability: true,
+            ..Default::default()
+        };
+        let client = SimulatedClient::new(session, 20, options).with_probe(Box::new(AlwaysUnreachable));
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses
+            .iter()
+            .filter(|v| v.tools_running)
+            .all(|v| v.issues.iter().any(|i| i.issue_type == VMIssueType::Unresponsive)));
+    }
+
+    fn test_session() -> Session {
+        Session {
+            host: "vcenter.example.com".to_string(),
+            username: "tester".to_string(),
+            token: "t".to_string(),
+            version: crate::auth::VCenterVersion {
+                product: "VMware vCenter Server".to_string(),
+                version: "8.0.2".to_string(),
+                build: "22617221".to_string(),
+            },
+        }
+    }
+
+    #[test]
+    fn max_total_requests_is_never_exceeded() {
+        let options = DetectionOptions {
+            check_reachability: true,
+            check_clock: true,
+            required_processes: vec!["nginx".to_string()],
+            ..Default::default()
+        };
+        let client = SimulatedClient::new(test_session(), 200, options)
+            .with_probe(Box::new(AlwaysUnreachable))
+            .with_max_total_requests(Some(50));
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses.len() <= 200);
+        assert!(client.request_budget().consumed() <= 50, "ceiling must never be exceeded");
+        assert!(!client.request_budget().deferred().is_empty(), "a 50-request ceiling over 200 VMs must defer some");
+    }
+
+    #[test]
+    fn max_total_requests_degrades_in_priority_order() {
+        let options = DetectionOptions {
+            check_reachability: true,
+            check_clock: true,
+            required_processes: vec!["nginx".to_string()],
+            ..Default::default()
+        };
+        let client = SimulatedClient::new(test_session(), 200, options)
+            .with_probe(Box::new(AlwaysUnreachable))
+            .with_max_total_requests(Some(50));
+        client.fetch_vm_statuses().unwrap();
+
+        let budget = client.request_budget();
+        assert!(budget.is_degraded(DegradableCheck::Reachability), "reachability degrades first");
+        if budget.is_degraded(DegradableCheck::ClockSkew) {
+            assert!(budget.is_degraded(DegradableCheck::Process), "process degrades before clock skew");
+        }
+    }
+
+    #[test]
+    fn per_vm_timeout_is_a_no_op_when_unset() {
+        let client = SimulatedClient::new(test_session(), 200, DetectionOptions::default());
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert_eq!(statuses.len(), 200);
+        assert!(client.timed_out().is_empty());
+    }
+
+    #[test]
+    fn per_vm_timeout_abandons_slow_vms_when_set() {
+        let options = DetectionOptions { per_vm_timeout_ms: Some(1), ..Default::default() };
+        let client = SimulatedClient::new(test_session(), 200, options);
+        let statuses = client.fetch_vm_statuses().unwrap();
+        let timed_out = client.timed_out();
+        assert!(!timed_out.is_empty(), "a 1ms budget over 200 VMs must abandon some");
+        assert_eq!(statuses.len() + timed_out.len(), 200, "every VM is either reported or timed out, never both or neither");
+    }
+
+    #[test]
+    fn check_vm_files_is_a_no_op_when_unset() {
+        let client = SimulatedClient::new(test_session(), 200, DetectionOptions::default());
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(!statuses.iter().any(|v| v.issues.iter().any(|i| i.issue_type == VMIssueType::BackingFilesMissing)));
+    }
+
+    #[test]
+    fn check_vm_files_never_flags_a_powered_on_vm() {
+        let options = DetectionOptions { check_vm_files: true, ..Default::default() };
+        let client = SimulatedClient::new(test_session(), 200, options);
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses
+            .iter()
+            .filter(|v| v.power_state == PowerState::PoweredOn)
+            .all(|v| !v.issues.iter().any(|i| i.issue_type == VMIssueType::BackingFilesMissing)));
+    }
+
+    #[test]
+    fn max_file_checks_caps_how_many_powered_off_vms_are_checked() {
+        let options = DetectionOptions {
+            check_vm_files: true,
+            max_file_checks: Some(0),
+            ..Default::default()
+        };
+        let client = SimulatedClient::new(test_session(), 200, options);
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(!statuses.iter().any(|v| v.issues.iter().any(|i| i.issue_type == VMIssueType::BackingFilesMissing)));
+    }
+
+    #[test]
+    fn check_vm_files_never_raises_backing_files_missing_for_a_suspended_vm() {
+        let options = DetectionOptions { check_vm_files: true, ..Default::default() };
+        let client = SimulatedClient::new(test_session(), 200, options);
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses
+            .iter()
+            .filter(|v| v.power_state == PowerState::Suspended)
+            .all(|v| !v.issues.iter().any(|i| i.issue_type == VMIssueType::BackingFilesMissing)),
+            "a suspended VM's datastore check is against its .vmss file, not its VMX");
+        assert!(statuses
+            .iter()
+            .filter(|v| v.power_state == PowerState::PoweredOff)
+            .all(|v| !v.issues.iter().any(|i| i.issue_type == VMIssueType::SuspendStateMissing)));
+    }
+
+    #[test]
+    fn suspended_vms_always_carry_a_suspended_issue() {
+        let client = SimulatedClient::new(test_session(), 200, DetectionOptions::default());
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses
+            .iter()
+            .filter(|v| v.power_state == PowerState::Suspended)
+            .all(|v| v.issues.iter().any(|i| matches!(i.issue_type, VMIssueType::Suspended | VMIssueType::SuspendedTooLong))));
+    }
+
+    #[test]
+    fn prefetch_vm_metrics_batches_one_query_per_host_on_success() {
+        use rand::SeedableRng;
+        let client = SimulatedClient::new(test_session(), 0, DetectionOptions::default()).with_api_rate_log(true);
+        let vm_names: Vec<String> = (0..9).map(|i| format!("vm-{i:04}")).collect();
+        let vm_hosts: Vec<String> = (0..9).map(|i| format!("esxi-{:02}.example.com", i % 3)).collect();
+        let power_states = vec![PowerState::PoweredOn; 9];
+        // Seed 0's first three `gen_bool(0.95)` draws (one per distinct host
+        // below) all come back `true`, so this run never falls back.
+        let mut rng = rand::rngs::StdRng::seed_from_u64(0);
+        client.prefetch_vm_metrics(&vm_names, &vm_hosts, &power_states, &mut rng);
+
+        let query_perf_calls = client.api_rate_log().summaries().into_iter().find(|s| s.endpoint == "QueryPerf").unwrap();
+        assert_eq!(query_perf_calls.count, 3, "3 hosts batched into 3 calls, not 9 per-VM calls");
+        for name in &vm_names {
+            assert!(!client.vm_metrics_for(name).is_empty(), "{name} should have metrics from its host's batch");
+        }
+    }
+
+    #[test]
+    fn prefetch_vm_metrics_falls_back_to_per_vm_queries_when_a_hosts_batch_fails() {
+        use rand::SeedableRng;
+        let client = SimulatedClient::new(test_session(), 0, DetectionOptions::default()).with_api_rate_log(true);
+        let vm_names: Vec<String> = (0..4).map(|i| format!("vm-{i:04}")).collect();
+        let vm_hosts = vec!["esxi-00.example.com".to_string(); 4];
+        let power_states = vec![PowerState::PoweredOn; 4];
+        // Seed 1's first `gen_bool(0.95)` draw (this test's one host) comes
+        // back `false`, forcing the fallback path.
+        let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+        client.prefetch_vm_metrics(&vm_names, &vm_hosts, &power_states, &mut rng);
+
+        let query_perf_calls = client.api_rate_log().summaries().into_iter().find(|s| s.endpoint == "QueryPerf").unwrap();
+        assert_eq!(query_perf_calls.count, 4, "a failed batch bills one call per VM on that host instead");
+        for name in &vm_names {
+            assert!(!client.vm_metrics_for(name).is_empty(), "{name} should still get metrics through the fallback");
+        }
+    }
+
+    #[test]
+    fn timing_records_one_sample_per_vm_for_each_enabled_per_vm_check() {
+        let options = DetectionOptions {
+            check_migrations: true,
+            check_uptime: true,
+            check_guest_resource_mismatch: true,
+            ..Default::default()
+        };
+        let client = SimulatedClient::new(test_session(), 5, options).with_timing(true);
+        client.fetch_vm_statuses().unwrap();
+
+        let summaries = client.timing().summaries();
+        let count_of = |check: &str| summaries.iter().find(|s| s.check == check).map(|s| s.requests).unwrap_or(0);
+        assert_eq!(count_of("uptime"), 5, "one sample per VM");
+        assert_eq!(count_of("guest_resource_mismatch"), 5, "one sample per VM");
+        assert_eq!(count_of("issue_detection"), 5, "always-on detection runs once per VM");
+        assert_eq!(count_of("migrations"), 1, "migration_summaries is one batched call for the whole fetch");
+    }
+
+    #[test]
+    fn raw_metrics_is_empty_unless_opted_in() {
+        let client = SimulatedClient::new(test_session(), 3, DetectionOptions::default());
+        let statuses = client.fetch_vm_statuses().unwrap();
+        assert!(statuses.iter().all(|v| v.raw_metrics.is_empty()), "--include-raw-metrics required");
+    }
+
+    #[test]
+    fn raw_metrics_carries_every_counter_the_provider_returned_when_opted_in() {
+        let options = DetectionOptions { include_raw_metrics: true, ..Default::default() };
+        let client = SimulatedClient::new(test_session(), 3, options);
+        let statuses = client.fetch_vm_statuses().unwrap();
+        for vm in statuses.iter().filter(|v| v.power_state == PowerState::PoweredOn) {
+            assert!(vm.raw_metrics.contains_key(crate::metrics_provider::CPU_USAGE_PCT));
+            assert!(vm.raw_metrics.contains_key(crate::metrics_provider::MEMORY_USAGE_PCT));
+        }
+    }
+
+    /// Always reports the collector connection as down, from the first call.
+    struct AlwaysDown;
+    impl MetricsProvider for AlwaysDown {
+        fn vm_performance_metrics(&self, _vm_name: &str, _power_state: PowerState) -> Result<Option<HashMap<String, f64>>, MetricsFetchError> {
+            Err(MetricsFetchError)
+        }
+    }
+
+    /// Reports the connection down only once the given number of VMs have
+    /// already been served successfully, simulating a collector that goes
+    /// down mid-run rather than at startup.
+    struct DownAfter {
+        remaining: std::sync::atomic::AtomicUsize,
+    }
+    impl MetricsProvider for DownAfter {
+        fn vm_performance_metrics(&self, vm_name: &str, power_state: PowerState) -> Result<Option<HashMap<String, f64>>, MetricsFetchError> {
+            if self.remaining.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1)).is_ok() {
+                SimulatedMetricsProvider.vm_performance_metrics(vm_name, power_state)
+            } else {
+                Err(MetricsFetchError)
+            }
+        }
+    }
+
+    #[test]
+    fn metrics_down_at_start_marks_every_vm_unavailable_and_degrades_the_run() {
+        let client = SimulatedClient::new(test_session(), 20, DetectionOptions::default()).with_metrics_provider(Box::new(AlwaysDown));
+        let statuses = client.fetch_vm_statuses().unwrap();
+
+        assert!(client.metrics_degraded());
+        assert!(statuses
+            .iter()
+            .filter(|v| v.power_state == PowerState::PoweredOn)
+            .all(|v| v.metrics_source == MetricsSourceStatus::Unavailable));
+        assert!(!statuses
+            .iter()
+            .any(|v| v.issues.iter().any(|i| matches!(i.issue_type, VMIssueType::HighCpuUsage | VMIssueType::HighMemoryUsage))),
+            "a VM with no genuine reading must never be flagged for high usage off a false 0.0");
+    }
+
+    #[test]
+    fn metrics_down_mid_run_leaves_earlier_vms_available_and_later_ones_unavailable() {
+        let client = SimulatedClient::new(test_session(), 20, DetectionOptions::default())
+            .with_metrics_provider(Box::new(DownAfter { remaining: std::sync::atomic::AtomicUsize::new(5) }));
+        let statuses = client.fetch_vm_statuses().unwrap();
+
+        assert!(client.metrics_degraded(), "the connection going down partway through must still latch as degraded");
+        let powered_on: Vec<_> = statuses.iter().filter(|v| v.power_state == PowerState::PoweredOn).collect();
+        assert!(powered_on.iter().any(|v| v.metrics_source == MetricsSourceStatus::Available), "VMs served before the outage keep their readings");
+        assert!(powered_on.iter().any(|v| v.metrics_source == MetricsSourceStatus::Unavailable), "VMs served after the outage are marked unavailable");
+    }
+}
@@ -0,0 +1,903 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Power state of a VM as reported by vCenter. `Unknown` is the fallback
+/// vCenter itself returns when a detail call comes back without one of the
+/// three real states (e.g. it half-failed) - not a state a VM is ever
+/// actually in, but something [`crate::vcenter::detect_issues`] has to
+/// handle rather than silently treat as `PoweredOn` with zeroed metrics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PowerState {
+    PoweredOn,
+    PoweredOff,
+    Suspended,
+    Unknown,
+}
+
+impl fmt::Display for PowerState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            PowerState::PoweredOn => "poweredOn",
+            PowerState::PoweredOff => "poweredOff",
+            PowerState::Suspended => "suspended",
+            PowerState::Unknown => "unknown",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Classes of problems we can detect for a single VM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VMIssueType {
+    HighCpuUsage,
+    HighMemoryUsage,
+    ToolsNotRunning,
+    PoweredOff,
+    Unresponsive,
+    ClockSkew,
+    ProcessNotRunning,
+    /// The VM's own metric issue coincides with its host running hot. Kept
+    /// separate from `HighCpuUsage`/`HighMemoryUsage` since the remediation
+    /// differs (rebalance the host, not the VM) and it's informational: the
+    /// VM-level issue is what actually needs acting on.
+    HostOvercommitted,
+    /// VM's vCPU count exceeds `--max-vcpu-ratio` times its host's physical
+    /// core count. A right-sizing advisory, not a sign of current trouble -
+    /// over-provisioned vCPUs hurt the scheduler even when usage looks idle.
+    OverAllocatedCpu,
+    /// VM migrated (vMotion/DRS) more than `--max-migrations` times within
+    /// `--migration-window-hours`. Usually DRS misconfiguration or an
+    /// affinity-rule fight rather than a problem with the VM itself.
+    ExcessiveMigrations,
+    /// VM's uptime is below `--short-uptime-threshold-secs`, i.e. it rebooted
+    /// recently. Unremarkable alone; `--check-boot-storm` watches for many of
+    /// these clustering together in time. See [`crate::bootstorm`].
+    UptimeShort,
+    /// Fleet-wide correlated-reboot finding from `--check-boot-storm`: more
+    /// than `--boot-storm-threshold` VMs have [`VMIssueType::UptimeShort`]
+    /// within a tight time window. Raised on a synthetic pseudo-VM, not a
+    /// real one, so it can reuse the per-VM notifier pipeline for a single
+    /// consolidated alert. See [`crate::bootstorm::detect_boot_storm`].
+    BootStorm,
+    /// VM's host is disconnected or in maintenance mode, from
+    /// `--check-host-state`. The VM itself may be healthy, but its host
+    /// condition puts it at elevated risk.
+    HostDegraded,
+    /// VM's current placement breaks a `Mandatory` DRS affinity,
+    /// anti-affinity, or VM-host group rule from `--check-drs-rules`. A
+    /// `Preferential` rule being broken isn't an issue - DRS is allowed to
+    /// do that rather than leave a VM powered off. See [`crate::drs`].
+    DrsRuleViolation,
+    /// VM's virtual hardware version is below `--min-hw-version`, from
+    /// `--check-hw-version`. A lifecycle/inventory advisory - an old vHW
+    /// version doesn't mean the VM is unhealthy, but it can't use newer
+    /// host features and blocks some operations (e.g. hot-add limits)
+    /// until it's upgraded.
+    HardwareVersionOld,
+    /// A powered-on VM in `--hot-add-scope` has CPU or memory hot-add
+    /// disabled, from `--require-hot-add`. Usually a VM built from a
+    /// template that predates the setting; automation that assumes
+    /// hot-add is available fleet-wide fails against it at scale-up time.
+    /// See [`crate::hotadd`].
+    HotAddDisabled,
+    /// More than `--reboot-loop-count` power-on events within
+    /// `--boot-history-window-hours`, from `--check-uptime`. Raised instead
+    /// of `UptimeShort` - a VM cycling power repeatedly needs a different
+    /// response than one that simply rebooted once. See
+    /// [`crate::bootevents`].
+    RebootLoop,
+    /// A powered-off VM's VMX file is no longer present on its datastore,
+    /// from `--check-vm-files` - the inventory object survived a storage
+    /// cleanup that deleted its backing files, so it looks like an
+    /// ordinary `PoweredOff` VM until someone tries to start it. See
+    /// [`crate::datastore`].
+    BackingFilesMissing,
+    /// A powered-on VM already running hot (has `HighCpuUsage` or
+    /// `HighMemoryUsage`) also has the matching resource's hot-add
+    /// disabled, from `--check-hotadd`. Unlike `HotAddDisabled`, this only
+    /// fires when the VM is actually under load and can't be scaled up
+    /// without a reboot - a sharper signal than "hot-add happens to be off"
+    /// for capacity planning. See `crate::vcenter::hotadd_under_load_issue`.
+    HotAddDisabledUnderLoad,
+    /// VM is suspended. Always raised for a suspended VM, the same way
+    /// `PoweredOff` always is for a powered-off one. Reclassified as
+    /// [`VMIssueType::SuspendedTooLong`] once `--max-suspend-hours` is
+    /// exceeded. See [`crate::vcenter::suspended_issue`].
+    Suspended,
+    /// VM has been suspended longer than `--max-suspend-hours`. Raised
+    /// instead of `Suspended` - a VM suspended for a fleet-routine window
+    /// needs a different response than one that's been sitting suspended
+    /// for weeks, quietly holding its host's memory pages and RDM locks.
+    /// See [`crate::vcenter::suspended_issue`].
+    SuspendedTooLong,
+    /// A suspended VM's `.vmss` suspend-state file is no longer present on
+    /// its datastore, from `--check-vm-files` - resuming it will fail. Same
+    /// shape as `BackingFilesMissing`, just for the suspend memory file
+    /// instead of the VMX. See [`crate::datastore`].
+    SuspendStateMissing,
+    /// VMware Tools' guest-visible memory or vCPU count differs from the
+    /// configured size by more than 10%, from
+    /// `--check-guest-resource-mismatch` - usually a guest that didn't
+    /// online hot-added memory. `cpu_usage_pct`/`memory_usage_pct` are
+    /// recomputed against the guest-visible figure when this fires, since
+    /// that's what the guest is actually contending for. See
+    /// [`crate::vcenter::guest_resource_mismatch`].
+    GuestResourceMismatch,
+    /// `disk_allocated_gb` is at least `crate::vcenter::STORAGE_WASTE_MIN_ALLOCATED_GB`
+    /// while `disk_used_gb` is at most `crate::vcenter::STORAGE_WASTE_MAX_USED_PCT` of it,
+    /// from `--check-storage-waste` - a large disk that was provisioned and
+    /// then barely touched. A rightsizing advisory, not a sign of trouble,
+    /// same framing as `OverAllocatedCpu`. Not raised when Tools isn't running
+    /// (nothing to compare against) or the check didn't run. See
+    /// [`crate::vcenter::storage_waste_issue`].
+    StorageWaste,
+    /// A host-sensor (PSU/fan/memory/etc) reported yellow or red, from
+    /// `--check-host-health`'s numeric-sensor query. Raised against every
+    /// VM on the affected host - a failing PSU or fan endangers the whole
+    /// host, not just one VM on it. See
+    /// [`crate::vcenter::host_hardware_unhealthy_issue`].
+    HostHardwareUnhealthy,
+    /// VM's power state came back as [`PowerState::Unknown`] - vCenter
+    /// didn't report one of the three real states, typically a detail call
+    /// that half-failed. Raised instead of silently treating the VM as
+    /// `PoweredOn` with zeroed metrics, and all the metric/tools/uptime
+    /// checks that assume a real power state are skipped for that VM. See
+    /// [`crate::vcenter::detect_issues`].
+    StateUnknown,
+}
+
+impl fmt::Display for VMIssueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            VMIssueType::HighCpuUsage => "HIGH_CPU_USAGE",
+            VMIssueType::HighMemoryUsage => "HIGH_MEMORY_USAGE",
+            VMIssueType::ToolsNotRunning => "TOOLS_NOT_RUNNING",
+            VMIssueType::PoweredOff => "POWERED_OFF",
+            VMIssueType::Unresponsive => "UNRESPONSIVE",
+            VMIssueType::ClockSkew => "CLOCK_SKEW",
+            VMIssueType::ProcessNotRunning => "PROCESS_NOT_RUNNING",
+            VMIssueType::HostOvercommitted => "HOST_OVERCOMMITTED",
+            VMIssueType::OverAllocatedCpu => "OVER_ALLOCATED_CPU",
+            VMIssueType::ExcessiveMigrations => "EXCESSIVE_MIGRATIONS",
+            VMIssueType::UptimeShort => "UPTIME_SHORT",
+            VMIssueType::BootStorm => "BOOT_STORM",
+            VMIssueType::HostDegraded => "HOST_DEGRADED",
+            VMIssueType::DrsRuleViolation => "DRS_RULE_VIOLATION",
+            VMIssueType::HardwareVersionOld => "HARDWARE_VERSION_OLD",
+            VMIssueType::HotAddDisabled => "HOT_ADD_DISABLED",
+            VMIssueType::RebootLoop => "REBOOT_LOOP",
+            VMIssueType::BackingFilesMissing => "BACKING_FILES_MISSING",
+            VMIssueType::HotAddDisabledUnderLoad => "HOT_ADD_DISABLED_UNDER_LOAD",
+            VMIssueType::Suspended => "SUSPENDED",
+            VMIssueType::SuspendedTooLong => "SUSPENDED_TOO_LONG",
+            VMIssueType::SuspendStateMissing => "SUSPEND_STATE_MISSING",
+            VMIssueType::GuestResourceMismatch => "GUEST_RESOURCE_MISMATCH",
+            VMIssueType::StorageWaste => "STORAGE_WASTE",
+            VMIssueType::HostHardwareUnhealthy => "HOST_HARDWARE_UNHEALTHY",
+            VMIssueType::StateUnknown => "STATE_UNKNOWN",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Parses a `VMIssueType` from its `Display` code (e.g. `"POWERED_OFF"`),
+/// for `--disable-issues`, which takes the same codes a user would see in a
+/// report.
+impl std::str::FromStr for VMIssueType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "HIGH_CPU_USAGE" => Ok(VMIssueType::HighCpuUsage),
+            "HIGH_MEMORY_USAGE" => Ok(VMIssueType::HighMemoryUsage),
+            "TOOLS_NOT_RUNNING" => Ok(VMIssueType::ToolsNotRunning),
+            "POWERED_OFF" => Ok(VMIssueType::PoweredOff),
+            "UNRESPONSIVE" => Ok(VMIssueType::Unresponsive),
+            "CLOCK_SKEW" => Ok(VMIssueType::ClockSkew),
+            "PROCESS_NOT_RUNNING" => Ok(VMIssueType::ProcessNotRunning),
+            "HOST_OVERCOMMITTED" => Ok(VMIssueType::HostOvercommitted),
+            "OVER_ALLOCATED_CPU" => Ok(VMIssueType::OverAllocatedCpu),
+            "EXCESSIVE_MIGRATIONS" => Ok(VMIssueType::ExcessiveMigrations),
+            "UPTIME_SHORT" => Ok(VMIssueType::UptimeShort),
+            "BOOT_STORM" => Ok(VMIssueType::BootStorm),
+            "HOST_DEGRADED" => Ok(VMIssueType::HostDegraded),
+            "DRS_RULE_VIOLATION" => Ok(VMIssueType::DrsRuleViolation),
+            "HARDWARE_VERSION_OLD" => Ok(VMIssueType::HardwareVersionOld),
+            "HOT_ADD_DISABLED" => Ok(VMIssueType::HotAddDisabled),
+            "REBOOT_LOOP" => Ok(VMIssueType::RebootLoop),
+            "BACKING_FILES_MISSING" => Ok(VMIssueType::BackingFilesMissing),
+            "HOT_ADD_DISABLED_UNDER_LOAD" => Ok(VMIssueType::HotAddDisabledUnderLoad),
+            "SUSPENDED" => Ok(VMIssueType::Suspended),
+            "SUSPENDED_TOO_LONG" => Ok(VMIssueType::SuspendedTooLong),
+            "SUSPEND_STATE_MISSING" => Ok(VMIssueType::SuspendStateMissing),
+            "GUEST_RESOURCE_MISMATCH" => Ok(VMIssueType::GuestResourceMismatch),
+            "STORAGE_WASTE" => Ok(VMIssueType::StorageWaste),
+            "HOST_HARDWARE_UNHEALTHY" => Ok(VMIssueType::HostHardwareUnhealthy),
+            "STATE_UNKNOWN" => Ok(VMIssueType::StateUnknown),
+            other => Err(format!("unknown issue type '{other}'")),
+        }
+    }
+}
+
+/// Coarse severity bucket used to rank and colorize issues, e.g. in the
+/// topology graph and terminal report. Ordered least to most severe so
+/// `worst_severity` can take a plain `max()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Severity {
+    Informational,
+    Warning,
+    Critical,
+}
+
+impl VMIssueType {
+    pub fn severity(&self) -> Severity {
+        match self {
+            VMIssueType::ToolsNotRunning | VMIssueType::Unresponsive | VMIssueType::HostHardwareUnhealthy => Severity::Critical,
+            VMIssueType::HighCpuUsage
+            | VMIssueType::HighMemoryUsage
+            | VMIssueType::PoweredOff
+            | VMIssueType::ClockSkew
+            | VMIssueType::ProcessNotRunning
+            | VMIssueType::OverAllocatedCpu
+            | VMIssueType::ExcessiveMigrations
+            | VMIssueType::UptimeShort
+            | VMIssueType::HostDegraded
+            | VMIssueType::HardwareVersionOld
+            | VMIssueType::HotAddDisabled
+            | VMIssueType::HotAddDisabledUnderLoad
+            | VMIssueType::GuestResourceMismatch
+            | VMIssueType::Suspended
+            | VMIssueType::StateUnknown => Severity::Warning,
+            VMIssueType::HostOvercommitted | VMIssueType::StorageWaste => Severity::Informational,
+            VMIssueType::BootStorm
+            | VMIssueType::DrsRuleViolation
+            | VMIssueType::RebootLoop
+            | VMIssueType::BackingFilesMissing
+            | VMIssueType::SuspendedTooLong
+            | VMIssueType::SuspendStateMissing => Severity::Critical,
+        }
+    }
+
+    /// Default `--fail-on-issues` exit-code tier, independent of
+    /// [`VMIssueType::severity`] - severity drives report colour/ranking,
+    /// this drives whether the *run* fails CI. `Error` is reserved for
+    /// issue types that mean something is actually down or broken;
+    /// everything else, including some `Severity::Warning` types like
+    /// `HighCpuUsage`, is advisory and defaults to `Warning` here so a busy
+    /// fleet doesn't fail a pipeline just for running hot.
+    /// `--issue-threshold-warnings` downgrades specific types from this
+    /// default; there's no equivalent upgrade flag yet.
+    pub fn default_exit_severity(&self) -> ExitSeverity {
+        match self {
+            VMIssueType::ToolsNotRunning
+            | VMIssueType::Unresponsive
+            | VMIssueType::PoweredOff
+            | VMIssueType::HostDegraded
+            | VMIssueType::DrsRuleViolation
+            | VMIssueType::BootStorm
+            | VMIssueType::RebootLoop
+            | VMIssueType::BackingFilesMissing
+            | VMIssueType::SuspendedTooLong
+            | VMIssueType::SuspendStateMissing
+            | VMIssueType::HostHardwareUnhealthy => ExitSeverity::Error,
+            VMIssueType::HighCpuUsage
+            | VMIssueType::HighMemoryUsage
+            | VMIssueType::ClockSkew
+            | VMIssueType::ProcessNotRunning
+            | VMIssueType::HostOvercommitted
+            | VMIssueType::OverAllocatedCpu
+            | VMIssueType::ExcessiveMigrations
+            | VMIssueType::UptimeShort
+            | VMIssueType::HardwareVersionOld
+            | VMIssueType::HotAddDisabled
+            | VMIssueType::HotAddDisabledUnderLoad
+            | VMIssueType::GuestResourceMismatch
+            | VMIssueType::StorageWaste
+            | VMIssueType::Suspended
+            | VMIssueType::StateUnknown => ExitSeverity::Warning,
+        }
+    }
+}
+
+/// Exit code for a run that `--fail-on-issues` failed because of an
+/// error-tier issue. Distinct from the generic `anyhow` failure exit code
+/// (1) so CI can tell "a VM is actually broken" apart from "the tool itself
+/// hit a config/IO error".
+pub const ISSUE_ERROR_EXIT_CODE: i32 = 2;
+
+/// `--fail-on-issues` exit-code tier for a detected issue, resolved from
+/// [`VMIssueType::default_exit_severity`] and any `--issue-threshold-warnings`
+/// override. Logged, not colour-coded like [`Severity`] - this only decides
+/// whether the run's exit code reflects the issue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitSeverity {
+    Warning,
+    Error,
+}
+
+/// Whether any VM in `statuses` carries an issue that resolves to
+/// [`ExitSeverity::Error`], after downgrading the types named in
+/// `warning_overrides` (from `--issue-threshold-warnings`) to `Warning`.
+/// Drives `--fail-on-issues`; a no-op read, so it's safe to call purely to
+/// decide what to log before deciding whether to fail.
+pub fn has_error_tier_issue(statuses: &[VMResourceStatus], warning_overrides: &std::collections::HashSet<VMIssueType>) -> bool {
+    statuses.iter().flat_map(|vm| &vm.issues).any(|issue| {
+        !warning_overrides.contains(&issue.issue_type) && issue.issue_type.default_exit_severity() == ExitSeverity::Error
+    })
+}
+
+/// A single detected problem, with the measurement that triggered it where
+/// one exists. `measured_value`/`threshold` are populated at detection time
+/// (not re-derived later) so a report always shows the reading that was
+/// actually over threshold, even once multi-sample confirmation lands and
+/// the live usage fields may have moved on.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedIssue {
+    pub issue_type: VMIssueType,
+    pub severity: Severity,
+    /// `severity` before `crate::maintenance::annotate_maintenance_downgrades`
+    /// downgraded it for a host in maintenance mode. `None` when it wasn't
+    /// touched, so a report/audit can always tell an untouched issue from
+    /// one that's only `Informational` because its host is draining.
+    #[serde(default)]
+    pub original_severity: Option<Severity>,
+    pub measured_value: Option<f64>,
+    pub threshold: Option<f64>,
+    pub detail: Option<String>,
+    /// `--no-recommendations`-gated suggestions from `crate::recommend`,
+    /// filled in after detection by `recommend::annotate_recommendations`.
+    /// Empty until then, or always when the flag disables the rules.
+    #[serde(default)]
+    pub recommendations: Vec<crate::recommend::Recommendation>,
+    /// Stable per-(vCenter host, VM, issue type) ticketing key, filled in
+    /// after detection by `crate::fingerprint::annotate`. Empty until then.
+    /// See [`crate::fingerprint::compute`] for its compatibility promise.
+    #[serde(default)]
+    pub fingerprint: String,
+    /// The run this fingerprint was first seen in, carried forward through
+    /// `--state-file` by `crate::fingerprint::annotate`, so a report can
+    /// show how long a recurring problem has been open. `None` until
+    /// annotated.
+    #[serde(default)]
+    pub first_seen: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DetectedIssue {
+    /// For issues with no natural numeric measurement (power state, guest
+    /// reachability, a missing process name).
+    pub fn new(issue_type: VMIssueType, detail: impl Into<String>) -> Self {
+        Self {
+            issue_type,
+            severity: issue_type.severity(),
+            original_severity: None,
+            measured_value: None,
+            threshold: None,
+            detail: Some(detail.into()),
+            recommendations: Vec::new(),
+            fingerprint: String::new(),
+            first_seen: None,
+        }
+    }
+
+    /// For issues detected by crossing a numeric threshold (CPU/memory/clock
+    /// skew), so the report and notifications can show the actual reading.
+    pub fn measured(issue_type: VMIssueType, measured_value: f64, threshold: f64, detail: impl Into<String>) -> Self {
+        Self {
+            issue_type,
+            severity: issue_type.severity(),
+            original_severity: None,
+            measured_value: Some(measured_value),
+            threshold: Some(threshold),
+            detail: Some(detail.into()),
+            recommendations: Vec::new(),
+            fingerprint: String::new(),
+            first_seen: None,
+        }
+    }
+}
+
+/// Source/destination host of a single vMotion/DRS migration, for the most
+/// recent migration within `--migration-window-hours`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastMigration {
+    pub from_host: String,
+    pub to_host: String,
+}
+
+/// Point-in-time health snapshot of a single VM, as produced by a
+/// [`crate::vcenter::VCenterClient`] and consumed by the report generators.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VMResourceStatus {
+    pub name: String,
+    pub host: String,
+    pub cluster: String,
+    /// Full vCenter inventory path, e.g. `/DC1/vm/cluster-a/team-2/vm-0001`.
+    /// Disambiguates VMs that share a name across different folders.
+    /// Defaults to `/unknown` when absent from older serialized reports,
+    /// same as `hardware_version`. See [`crate::inventory`].
+    #[serde(default = "default_inventory_path")]
+    pub inventory_path: String,
+    pub power_state: PowerState,
+    pub cpu_usage_pct: f64,
+    pub memory_usage_pct: f64,
+    /// Every counter [`crate::metrics_provider::MetricsProvider`] returned
+    /// for this VM this run (today just `cpu_usage_pct`/`memory_usage_pct`
+    /// under those same keys), carried alongside the derived fields above so
+    /// downstream analytics can compute their own derivatives without
+    /// re-querying vCenter. Empty unless `--include-raw-metrics` is set, to
+    /// keep default payloads lean. Defaults to empty when absent from older
+    /// serialized reports, same as `health_score`.
+    #[serde(default)]
+    pub raw_metrics: HashMap<String, f64>,
+    /// Whether `cpu_usage_pct`/`memory_usage_pct` came back from the
+    /// metrics collector this run, or are a false `0.0` left behind by a
+    /// mid-run collector outage. Defaults to `Available` when absent from
+    /// older serialized reports, same as `health_score` - they predate
+    /// this field and their metrics were genuinely collected. See
+    /// [`MetricsSourceStatus`].
+    #[serde(default = "default_metrics_source")]
+    pub metrics_source: MetricsSourceStatus,
+    /// Virtual sockets times cores-per-socket; compared against the host's
+    /// physical core count by `--check-vcpu-allocation`.
+    pub cpu_count: u32,
+    pub cores_per_socket: u32,
+    /// Provisioned memory, in GB, independent of `memory_usage_pct` (which
+    /// is relative to this). `--rightsizing-report` halves this (with a
+    /// floor) to size down VMs whose peak usage stayed low. Defaults to
+    /// `16.0` when absent from older serialized reports, same as
+    /// `hardware_version`.
+    #[serde(default = "default_memory_gb")]
+    pub memory_gb: f64,
+    /// Virtual hardware version, e.g. `"vmx-19"`, compared against
+    /// `--min-hw-version` by `--check-hw-version`. Defaults to `"vmx-13"`
+    /// when absent from older serialized reports, same as `health_score`.
+    #[serde(default = "default_hardware_version")]
+    pub hardware_version: String,
+    /// Whether the VM's hardware reports CPU hot-add enabled, compared
+    /// against `--require-hot-add`. Defaults to `true` when absent from
+    /// older serialized reports, matching the fleet-wide assumption
+    /// `--require-hot-add` exists to check.
+    #[serde(default = "default_hot_add_enabled")]
+    pub cpu_hot_add_enabled: bool,
+    /// Same as `cpu_hot_add_enabled`, for memory.
+    #[serde(default = "default_hot_add_enabled")]
+    pub memory_hot_add_enabled: bool,
+    /// Guest-visible memory, in MB, as read from VMware Tools by
+    /// `--check-guest-resource-mismatch`. Can read lower than `memory_gb`'s
+    /// configured size when the guest didn't online hot-added memory.
+    /// `None` when Tools isn't running (nothing to read) or the check
+    /// didn't run.
+    #[serde(default)]
+    pub guest_visible_memory_mb: Option<f64>,
+    /// Same as `guest_visible_memory_mb`, for vCPU count against `cpu_count`.
+    #[serde(default)]
+    pub guest_visible_cpu_count: Option<u32>,
+    /// Provisioned datastore space, in GB, independent of `disk_used_gb`.
+    /// Compared against it by `--check-storage-waste` to flag large disks
+    /// sitting mostly empty. Defaults to `0.0` when absent from older
+    /// serialized reports, same as `memory_gb` predating a field.
+    #[serde(default)]
+    pub disk_allocated_gb: f64,
+    /// Actual space used inside the disk, in GB, as read from VMware Tools.
+    /// `None` when Tools isn't running (nothing to read) or the check
+    /// didn't run, same as `guest_visible_memory_mb`.
+    #[serde(default)]
+    pub disk_used_gb: Option<f64>,
+    /// See [`UsageBasis`]. Defaults to `Configured` when absent from older
+    /// serialized reports, same as `health_score` - they predate this
+    /// field and were always computed against the configured size.
+    #[serde(default = "default_usage_basis")]
+    pub usage_basis: UsageBasis,
+    /// Whether VMware Tools is running in the guest. Several checks (clock
+    /// skew, process/service checks) depend on tools being up and are
+    /// skipped, not flagged, when this is `false`.
+    pub tools_running: bool,
+    /// Guest clock minus host clock, in seconds, when tools could report it.
+    pub clock_skew_secs: Option<f64>,
+    /// Guest IP/hostname as reported by VMware Tools, if any.
+    pub guest_ip: Option<String>,
+    /// Result of a TCP reachability probe against `guest_ip`, when `--check-reachability` ran.
+    pub reachable: Option<bool>,
+    /// Process/service names VMware Tools reports as running in the guest.
+    /// Only populated when `tools_running` is true.
+    pub running_processes: Vec<String>,
+    /// Custom attributes (vCenter custom fields / tags), e.g. "Owner", "CostCenter".
+    pub attributes: HashMap<String, String>,
+    /// The VM's vCenter annotation/notes field.
+    pub notes: Option<String>,
+    /// Migrations (vMotion/DRS) in the last `--migration-window-hours`. `0`
+    /// when `--check-migrations` didn't run.
+    pub migration_count_24h: u32,
+    /// The most recent migration within the window, if any.
+    pub last_migration: Option<LastMigration>,
+    /// Seconds since the VM's last power-on. Only meaningful while
+    /// `PoweredOn`; a long constant otherwise so it never trips
+    /// `--check-uptime`.
+    pub uptime_secs: f64,
+    /// Whether a `VmCreatedEvent`/`VmClonedEvent`/`VmRegisteredEvent` fell
+    /// within `--boot-history-window-hours`. Downgrades `UptimeShort` to
+    /// `Informational` severity - a freshly deployed VM's first boot isn't
+    /// a reboot. `false` when `--check-uptime` didn't run.
+    pub created_recently: bool,
+    /// `VmPoweredOnEvent` count within `--boot-history-window-hours`. More
+    /// than `--reboot-loop-count` of these reclassifies the VM's issue as
+    /// [`VMIssueType::RebootLoop`] instead of `UptimeShort`. `0` when
+    /// `--check-uptime` didn't run. See [`crate::bootevents`].
+    pub power_on_count: u32,
+    /// Seconds since the most recent `VmPoweredOnEvent` within
+    /// `--boot-history-window-hours`, when `--check-uptime` found one - a
+    /// second, event-derived corroboration for `uptime_secs`. `None` when
+    /// `--check-uptime` didn't run or no such event fell in the window.
+    /// [`crate::vcenter::power_on_disagreement_warning`] compares the two
+    /// and prefers `uptime_secs` when they disagree significantly, since
+    /// that's measured directly rather than inferred from an event log.
+    #[serde(default)]
+    pub last_power_on_secs_ago: Option<f64>,
+    /// Seconds since a suspended VM's `VmSuspendedEvent`, from
+    /// [`crate::suspendevents`], compared against `--max-suspend-hours` to
+    /// decide between `Suspended` and `SuspendedTooLong`. `None` for a VM
+    /// that isn't suspended, or a suspended one whose suspend event fell
+    /// outside the event query's lookback and so couldn't be dated.
+    #[serde(default)]
+    pub suspended_duration_secs: Option<f64>,
+    pub issues: Vec<DetectedIssue>,
+    /// 100 minus the sum of per-issue-type weights in `issues`, floored at 0.
+    /// Computed by [`crate::scoring::annotate_health_scores`] after every
+    /// issue-mutating step (disabled-issue stripping, DRS/boot-storm
+    /// flagging) has run; `100.0` before that, same as an issue-free VM.
+    /// Defaults to `100.0` when absent from older serialized reports
+    /// (`--replay` input, the bundled demo fixture) so they still parse.
+    #[serde(default = "default_health_score")]
+    pub health_score: f64,
+    /// vCenter's per-VM config/power change-version marker, compared against
+    /// `--state-file`'s last-seen value by `--since-last-run` to decide
+    /// whether this VM needs analyzing this run. Defaults to `0` when absent
+    /// from older serialized reports, which `--since-last-run` treats the
+    /// same as a VM seen for the first time.
+    #[serde(default = "default_change_version")]
+    pub change_version: u64,
+}
+
+fn default_health_score() -> f64 {
+    100.0
+}
+
+pub(crate) fn default_metrics_source() -> MetricsSourceStatus {
+    MetricsSourceStatus::Available
+}
+
+pub(crate) fn default_hardware_version() -> String {
+    "vmx-13".to_string()
+}
+
+pub(crate) fn default_hot_add_enabled() -> bool {
+    true
+}
+
+pub(crate) fn default_usage_basis() -> UsageBasis {
+    UsageBasis::Configured
+}
+
+pub(crate) fn default_inventory_path() -> String {
+    "/unknown".to_string()
+}
+
+pub(crate) fn default_memory_gb() -> f64 {
+    16.0
+}
+
+pub(crate) fn default_change_version() -> u64 {
+    0
+}
+
+/// A host's connection state, from `/vcenter/host`. VMs on a `Disconnected`
+/// host are as much at risk as ones on a host in maintenance mode, so
+/// `--check-host-state` treats both the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HostConnectionState {
+    Connected,
+    Disconnected,
+}
+
+/// Whether this VM's CPU/memory usage came back from the run's
+/// `MetricsProvider`. `Unavailable` means the collector's connection itself
+/// was down for this VM - e.g. the SOAP `PerformanceManager` endpoint
+/// (`/sdk`) unreachable while the REST API stays healthy - not that the VM
+/// genuinely has nothing to report (a powered-off VM is still `Available`,
+/// just with `0.0` usage). `cpu_usage_pct`/`memory_usage_pct` are `0.0` for
+/// an `Unavailable` VM, and high-usage detection is skipped for it rather
+/// than alerting on that false zero. See
+/// [`crate::metrics_provider::MetricsFetchError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetricsSourceStatus {
+    Available,
+    Unavailable,
+}
+
+/// Which size `cpu_usage_pct`/`memory_usage_pct` were computed against.
+/// `Configured` (the vCenter-configured `cpu_count`/`memory_gb`) unless
+/// `--check-guest-resource-mismatch` found a guest-visible figure that
+/// differs from it by enough to matter, in which case usage is recomputed
+/// against `guest_visible_cpu_count`/`guest_visible_memory_mb` instead -
+/// that's what the guest is actually contending for. See
+/// [`VMIssueType::GuestResourceMismatch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UsageBasis {
+    Configured,
+    GuestVisible,
+}
+
+/// A host's aggregate hardware-sensor health, from the host health/numeric-
+/// sensor overview (`/api/appliance/.../hardware/health` analog for an
+/// ESXi host - PSU, fan, memory, etc). `Yellow`/`Red` mirror vSphere's own
+/// sensor levels; `--check-host-health` treats both as unhealthy - a fan
+/// already in alarm state is exactly where a full host outage starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HostSensorStatus {
+    Green,
+    Yellow,
+    Red,
+}
+
+/// Host-level `cpu.usage.average`/`mem.usage.average` perf counters, queried
+/// once per host (not per VM) to confirm whether a cluster of flagged VMs is
+/// explained by the host itself being overcommitted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostMetrics {
+    pub cpu_usage_pct: f64,
+    pub memory_usage_pct: f64,
+    /// Physical core count, from host hardware info, used by
+    /// `--check-vcpu-allocation` to catch vCPU over-allocation.
+    pub physical_cores: u32,
+    pub connection_state: HostConnectionState,
+    /// Whether the host has been placed into maintenance mode, e.g. ahead of
+    /// patching. Used by `--check-host-state`.
+    pub in_maintenance_mode: bool,
+    /// Aggregate hardware-sensor health, from `--check-host-health`'s
+    /// numeric-sensor query. Used by `crate::vcenter::host_hardware_unhealthy_issue`.
+    /// Defaults to `Green` when absent from older serialized host metrics,
+    /// same as `MetricsSourceStatus` defaulting to `Available`.
+    #[serde(default = "default_sensor_status")]
+    pub sensor_status: HostSensorStatus,
+    /// Which sensor is driving `sensor_status` when it isn't `Green` (e.g.
+    /// `"Power Supply 2"`, `"Fan 3"`, `"DIMM A2 ECC"`). `None` when healthy.
+    #[serde(default)]
+    pub failing_sensor: Option<String>,
+}
+
+pub(crate) fn default_sensor_status() -> HostSensorStatus {
+    HostSensorStatus::Green
+}
+
+impl VMResourceStatus {
+    pub fn has_issues(&self) -> bool {
+        !self.issues.is_empty()
+    }
+
+    /// The most severe issue currently affecting this VM, if any.
+    pub fn worst_severity(&self) -> Option<Severity> {
+        self.issues.iter().map(|i| i.severity).max()
+    }
+}
+
+/// Strips issue types in `disabled` from every VM, fleet-wide, so they never
+/// reach reports, statistics, or notifications. Run this once, right after
+/// detection, before anything downstream reads `issues`. A no-op when
+/// `disabled` is empty (`--disable-issues` wasn't set).
+pub fn strip_disabled_issues(statuses: &mut [VMResourceStatus], disabled: &std::collections::HashSet<VMIssueType>) {
+    if disabled.is_empty() {
+        return;
+    }
+    for vm in statuses {
+        vm.issues.retain(|issue| !disabled.contains(&issue.issue_type));
+    }
+}
+
+/// Resolves `--vm-list-stdin`'s names against the fleet already fetched
+/// this run, in one pass over `statuses` rather than one lookup per name -
+/// `SimulatedClient` (and vCenter's own `PropertyCollector`-backed client
+/// behind it) already returns the whole fleet in a single batched fetch,
+/// so there's no per-name query left to replace; the win here is turning
+/// what used to be a silent drop of unmatched names into a reported list.
+/// Names not present among `statuses` are returned sorted, so
+/// `--vm-list-stdin` typos and decommissioned VMs show up in the report
+/// instead of just vanishing. An empty `names` is a no-op - nothing to
+/// filter down to - so callers don't need to special-case "the flag
+/// wasn't used".
+pub fn resolve_name_list(statuses: Vec<VMResourceStatus>, names: &std::collections::HashSet<String>) -> (Vec<VMResourceStatus>, Vec<String>) {
+    if names.is_empty() {
+        return (statuses, Vec::new());
+    }
+    let found_names: std::collections::HashSet<&str> = statuses.iter().map(|vm| vm.name.as_str()).collect();
+    let mut not_found: Vec<String> = names.iter().filter(|name| !found_names.contains(name.as_str())).cloned().collect();
+    not_found.sort();
+    let matched = statuses.into_iter().filter(|vm| names.contains(&vm.name)).collect();
+    (matched, not_found)
+}
+
+/// `--uptime-format`: how `format_uptime` renders a VM's uptime in the text
+/// report. `Seconds`/`Iso8601` exist for downstream parsers that can't be
+/// bothered with (or burned by) `Human`'s free-text units.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UptimeFormat {
+    /// `1d 2h 3m`, rounded down to the minute.
+    Human,
+    /// The raw seconds, same as the JSON/CSV reports already carry.
+    Seconds,
+    /// ISO 8601 duration, e.g. `P1DT2H3M`.
+    Iso8601,
+}
+
+/// Renders `uptime_secs` per `--uptime-format`, for the text report only -
+/// the JSON/CSV reports already carry the raw `uptime_secs` field and don't
+/// need a second, lossier representation. Pure and deterministic.
+pub fn format_uptime(uptime_secs: f64, format: UptimeFormat) -> String {
+    let total_mins = (uptime_secs.max(0.0) / 60.0).floor() as u64;
+    let days = total_mins / (24 * 60);
+    let hours = (total_mins / 60) % 24;
+    let mins = total_mins % 60;
+    match format {
+        UptimeFormat::Human => format!("{days}d {hours}h {mins}m"),
+        UptimeFormat::Seconds => format!("{:.0}", uptime_secs.max(0.0)),
+        UptimeFormat::Iso8601 => format!("P{days}DT{hours}H{mins}M"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vm(issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: "vm-0001".to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn issue_type_round_trips_through_display_and_from_str() {
+        for code in [
+            "HIGH_CPU_USAGE",
+            "HIGH_MEMORY_USAGE",
+            "TOOLS_NOT_RUNNING",
+            "POWERED_OFF",
+            "UNRESPONSIVE",
+            "CLOCK_SKEW",
+            "PROCESS_NOT_RUNNING",
+            "HOST_OVERCOMMITTED",
+            "OVER_ALLOCATED_CPU",
+            "EXCESSIVE_MIGRATIONS",
+        ] {
+            let parsed: VMIssueType = code.parse().unwrap();
+            assert_eq!(parsed.to_string(), code);
+        }
+        assert!("NOT_A_REAL_CODE".parse::<VMIssueType>().is_err());
+    }
+
+    #[test]
+    fn strip_disabled_issues_removes_only_the_named_types() {
+        let mut statuses = vec![vm(vec![
+            DetectedIssue::new(VMIssueType::PoweredOff, "x"),
+            DetectedIssue::new(VMIssueType::Unresponsive, "y"),
+        ])];
+        let disabled = std::collections::HashSet::from([VMIssueType::PoweredOff]);
+
+        strip_disabled_issues(&mut statuses, &disabled);
+
+        assert_eq!(statuses[0].issues.len(), 1);
+        assert_eq!(statuses[0].issues[0].issue_type, VMIssueType::Unresponsive);
+    }
+
+    #[test]
+    fn resolve_name_list_keeps_only_named_vms() {
+        let mut kept = vm(Vec::new());
+        kept.name = "vm-kept".to_string();
+        let mut dropped = vm(Vec::new());
+        dropped.name = "vm-dropped".to_string();
+        let names = std::collections::HashSet::from(["vm-kept".to_string()]);
+
+        let (matched, not_found) = resolve_name_list(vec![kept, dropped], &names);
+
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "vm-kept");
+        assert!(not_found.is_empty());
+    }
+
+    #[test]
+    fn resolve_name_list_reports_names_missing_from_the_fetched_fleet() {
+        let mut present = vm(Vec::new());
+        present.name = "vm-present".to_string();
+        let names = std::collections::HashSet::from(["vm-present".to_string(), "vm-decommissioned".to_string(), "vm-typo".to_string()]);
+
+        let (matched, not_found) = resolve_name_list(vec![present], &names);
+
+        assert_eq!(matched.len(), 1);
+        assert_eq!(matched[0].name, "vm-present");
+        assert_eq!(not_found, vec!["vm-decommissioned".to_string(), "vm-typo".to_string()]);
+    }
+
+    #[test]
+    fn resolve_name_list_is_a_no_op_when_empty() {
+        let statuses = vec![vm(Vec::new())];
+        let (matched, not_found) = resolve_name_list(statuses.clone(), &std::collections::HashSet::new());
+        assert_eq!(matched.len(), statuses.len());
+        assert!(not_found.is_empty());
+    }
+
+    #[test]
+    fn error_tier_issue_fails_but_warning_tier_does_not() {
+        let warning_only = vec![vm(vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")])];
+        assert!(!has_error_tier_issue(&warning_only, &std::collections::HashSet::new()));
+
+        let error_tier = vec![vm(vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        assert!(has_error_tier_issue(&error_tier, &std::collections::HashSet::new()));
+    }
+
+    #[test]
+    fn issue_threshold_warnings_downgrades_an_error_tier_type() {
+        let statuses = vec![vm(vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let overrides = std::collections::HashSet::from([VMIssueType::PoweredOff]);
+        assert!(!has_error_tier_issue(&statuses, &overrides));
+    }
+
+    #[test]
+    fn strip_disabled_issues_is_a_no_op_when_nothing_is_disabled() {
+        let mut statuses = vec![vm(vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        strip_disabled_issues(&mut statuses, &std::collections::HashSet::new());
+        assert_eq!(statuses[0].issues.len(), 1);
+    }
+
+    #[test]
+    fn format_uptime_human_renders_days_hours_minutes() {
+        let secs = 1.0 * 86400.0 + 2.0 * 3600.0 + 3.0 * 60.0;
+        assert_eq!(format_uptime(secs, UptimeFormat::Human), "1d 2h 3m");
+    }
+
+    #[test]
+    fn format_uptime_seconds_renders_the_raw_value() {
+        assert_eq!(format_uptime(125.0, UptimeFormat::Seconds), "125");
+    }
+
+    #[test]
+    fn format_uptime_iso8601_matches_the_human_breakdown() {
+        let secs = 3.0 * 86400.0 + 4.0 * 3600.0;
+        assert_eq!(format_uptime(secs, UptimeFormat::Iso8601), "P3DT4H0M");
+    }
+}
@@ -0,0 +1,62 @@
+//! `--vmc-profile`: VMware Cloud on AWS SDDCs expose the same vCenter REST
+//! API as an on-prem deployment, but customers don't get the underlying
+//! ESXi hosts - the host-management endpoints `--check-host-state` and
+//! `--check-host-health` would otherwise query are forbidden on an SDDC, so
+//! both checks are disabled under this profile instead of failing every VM
+//! whose host they can't reach. See [`crate::cli::Args::vmc_profile`].
+//!
+//! VMC also restricts vCenter-appliance-health queries, but that has no
+//! dedicated check in this tree to disable - there's nothing to gate here
+//! until one exists.
+
+/// One line per host-management check (`--check-host-state`,
+/// `--check-host-health`) disabled under `--vmc-profile`, or none for a
+/// check that's either off or wasn't requested in the first place.
+pub fn disabled_check_notes(vmc_profile: bool, check_host_state_requested: bool, check_host_health_requested: bool) -> Vec<String> {
+    if !vmc_profile {
+        return Vec::new();
+    }
+    let mut notes = Vec::new();
+    if check_host_state_requested {
+        notes.push("--check-host-state needs host-management access VMware Cloud on AWS doesn't grant customers over their SDDC's hosts; disabled under --vmc-profile".to_string());
+    }
+    if check_host_health_requested {
+        notes.push("--check-host-health needs host-management access VMware Cloud on AWS doesn't grant customers over their SDDC's hosts; disabled under --vmc-profile".to_string());
+    }
+    notes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_notes_when_profile_is_off() {
+        assert!(disabled_check_notes(false, true, true).is_empty());
+    }
+
+    #[test]
+    fn no_notes_when_neither_check_was_requested() {
+        assert!(disabled_check_notes(true, false, false).is_empty());
+    }
+
+    #[test]
+    fn notes_check_host_state_when_requested() {
+        let notes = disabled_check_notes(true, true, false);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("--check-host-state"));
+    }
+
+    #[test]
+    fn notes_check_host_health_when_requested() {
+        let notes = disabled_check_notes(true, false, true);
+        assert_eq!(notes.len(), 1);
+        assert!(notes[0].contains("--check-host-health"));
+    }
+
+    #[test]
+    fn notes_both_checks_when_both_are_requested() {
+        let notes = disabled_check_notes(true, true, true);
+        assert_eq!(notes.len(), 2);
+    }
+}
@@ -0,0 +1,383 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+
+use crate::alerting::CooldownTracker;
+use crate::auth::VCenterVersion;
+use crate::bootstorm;
+use crate::cli::{Args, OutputFormat};
+use crate::drs::{ComplianceReport, DrsRule};
+use crate::notifier::{NotifierRegistry, NotifierRegistryConfig, NotifyRunResult, RunSummary};
+use crate::report::{compute_statistics, export_csv_report, export_json_report, generate_report, JsonSchemaVersion};
+use crate::service::notify::Notifier;
+use crate::vm::{UptimeFormat, VMIssueType, VMResourceStatus};
+use crate::vcenter::VCenterClient;
+
+fn issue_set(vm: &VMResourceStatus) -> HashSet<VMIssueType> {
+    vm.issues.iter().map(|issue| issue.issue_type).collect()
+}
+
+/// Picks what `--delta-only` renders this cycle (0-indexed): the full fleet
+/// when it's off, the first cycle, or `--full-every` falls due, otherwise
+/// only VMs whose issue set differs from `previous`'s (new or cleared
+/// issues). Pure and independent of the clock/network so it can be unit
+/// tested on its own.
+fn select_render_scope(
+    statuses: &[VMResourceStatus],
+    previous: &HashMap<String, HashSet<VMIssueType>>,
+    cycle: u32,
+    delta_only: bool,
+    full_every: u32,
+) -> Vec<VMResourceStatus> {
+    let force_full = !delta_only || cycle == 0 || (full_every > 0 && cycle.is_multiple_of(full_every));
+    if force_full {
+        statuses.to_vec()
+    } else {
+        statuses
+            .iter()
+            .filter(|vm| previous.get(&vm.name) != Some(&issue_set(vm)))
+            .cloned()
+            .collect()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn render(
+    statuses: &[VMResourceStatus],
+    format: OutputFormat,
+    include_stats: bool,
+    schema_version: JsonSchemaVersion,
+    notify_result: Option<&NotifyRunResult>,
+    compliance: Option<&ComplianceReport>,
+    vcenter_version: Option<&VCenterVersion>,
+    exclude_powered_off_from_stats: bool,
+    uptime_format: UptimeFormat,
+    run_id: &str,
+    group_by: Option<crate::report::GroupBy>,
+    site: Option<&str>,
+    acknowledgements: &crate::acknowledge::AcknowledgementReport,
+    compact: bool,
+    preview: Option<&crate::preview::PreviewReport>,
+    metrics_degraded: bool,
+) -> Result<String> {
+    let host_metrics = std::collections::BTreeMap::new();
+    Ok(match format {
+        OutputFormat::Text => generate_report(
+            statuses,
+            include_stats,
+            &[],
+            &[],
+            &host_metrics,
+            notify_result,
+            compliance,
+            vcenter_version,
+            exclude_powered_off_from_stats,
+            uptime_format,
+            run_id,
+            None,
+            None,
+            None,
+            group_by,
+            site,
+            acknowledgements,
+            None,
+            preview,
+            metrics_degraded,
+        ),
+        OutputFormat::Json => export_json_report(
+            statuses,
+            include_stats,
+            &[],
+            &[],
+            schema_version,
+            &[],
+            &host_metrics,
+            notify_result,
+            compliance,
+            vcenter_version,
+            exclude_powered_off_from_stats,
+            run_id,
+            None,
+            None,
+            None,
+            site,
+            acknowledgements,
+            compact,
+            preview,
+            metrics_degraded,
+            &[],
+        )?,
+        OutputFormat::Csv => export_csv_report(statuses, run_id, site),
+    })
+}
+
+fn summarize(statuses: &[VMResourceStatus]) -> String {
+    let with_issues = statuses.iter().filter(|v| v.has_issues()).count();
+    let now = chrono::Local::now().format("%H:%M");
+    format!("{} VMs, {} issues, last poll {}", statuses.len(), with_issues, now)
+}
+
+/// Polls `client` on `args.interval_secs`, rendering and writing a report after
+/// every successful poll. `systemd` service signaling is driven from here
+/// (Windows SCM status is set by [`crate::service::windows`], which doesn't
+/// run this loop): `notifier.ready()` fires once, after authentication has already
+/// succeeded by the time this is called, and `notifier.watchdog()` fires after
+/// each completed poll.
+///
+/// When `--notifier-config` is set, every cycle also notifies through each
+/// configured backend. `--alert-cooldown` suppresses re-alerting on the same
+/// VM+issue within its window via [`CooldownTracker`] - alert history is
+/// in-memory for this process only, so a restart starts with a clean slate.
+pub fn run_watch_mode(args: &Args, client: &dyn VCenterClient, notifier: &Notifier, run_id: &str) -> Result<()> {
+    let disabled_issues = args
+        .disabled_issue_types()
+        .map_err(|err| anyhow::anyhow!("disable-issues: {err}"))?;
+    let registry = args
+        .notifier_config
+        .as_ref()
+        .map(|path| -> Result<NotifierRegistry> {
+            Ok(NotifierRegistry::from_config(NotifierRegistryConfig::load(path, args.strict_json)?))
+        })
+        .transpose()?;
+    let mut cooldown = CooldownTracker::new();
+    let mut previously_had_issues: BTreeSet<String> = BTreeSet::new();
+    let mut previous_issue_sets: HashMap<String, HashSet<VMIssueType>> = HashMap::new();
+    let mut cycle: u32 = 0;
+    let boot_storm_threshold = args
+        .check_boot_storm
+        .then(|| args.boot_storm_threshold())
+        .transpose()
+        .map_err(|err| anyhow::anyhow!("boot-storm-threshold: {err}"))?;
+    let drs_rules: Option<Vec<DrsRule>> = if args.check_drs_rules {
+        let path = args
+            .drs_rules
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("--check-drs-rules requires --drs-rules"))?;
+        Some(crate::drs::DrsRuleConfig::load(path, args.strict_json)?.rules)
+    } else {
+        None
+    };
+    let score_weights = match &args.score_weights {
+        Some(path) => crate::scoring::load_weight_overrides(path, args.strict_json)?,
+        None => HashMap::new(),
+    };
+
+    let vcenter_version = client.session().version.clone();
+    let proposed_thresholds = args.preview_thresholds().map_err(|err| anyhow::anyhow!("preview-thresholds: {err}"))?;
+
+    notifier.ready()?;
+    loop {
+        let mut statuses = client.fetch_vm_statuses()?;
+        crate::vm::strip_disabled_issues(&mut statuses, &disabled_issues);
+        let acknowledgements = crate::acknowledge::apply_acknowledgements(&mut statuses, chrono::Local::now().date_naive());
+
+        let boot_storm_finding = boot_storm_threshold.and_then(|threshold| bootstorm::detect_from_statuses(&statuses, threshold));
+        if let Some(finding) = &boot_storm_finding {
+            eprintln!(
+                "[{run_id}] boot-storm: {} VM(s) across {} host(s) rebooted within a {:.0}s window: {}",
+                finding.vm_names.len(),
+                finding.hosts.len(),
+                finding.window_end_secs_ago - finding.window_start_secs_ago,
+                finding.vm_names.join(", ")
+            );
+            if args.suppress_individual_boot_storm_alerts {
+                bootstorm::suppress_clustered_alerts(&mut statuses, finding);
+            }
+        }
+
+        let compliance = drs_rules.as_ref().map(|rules| {
+            let placements: HashMap<String, String> = statuses.iter().map(|vm| (vm.name.clone(), vm.host.clone())).collect();
+            let report = crate::drs::evaluate(rules, &placements);
+            crate::drs::flag_violations(&mut statuses, &report);
+            report
+        });
+
+        crate::scoring::annotate_health_scores(&mut statuses, &score_weights);
+
+        let notify_result = registry.as_ref().map(|registry| {
+            let mut to_notify = match args.alert_cooldown {
+                Some(mins) => cooldown.filter(&statuses, Duration::from_secs(mins * 60), Instant::now()),
+                None => statuses.clone(),
+            };
+            if let Some(finding) = &boot_storm_finding {
+                to_notify.push(bootstorm::synthetic_boot_storm_vm(finding));
+            }
+            let summary = RunSummary::from(&compute_statistics(&statuses, false))
+                .with_version(Some(vcenter_version.clone()))
+                .with_run_id(Some(run_id.to_string()))
+                .with_metrics_degraded(client.metrics_degraded());
+            registry.notify_all(&summary, &to_notify, &previously_had_issues)
+        });
+        previously_had_issues = statuses.iter().filter(|v| v.has_issues()).map(|v| v.name.clone()).collect();
+
+        let render_statuses = select_render_scope(&statuses, &previous_issue_sets, cycle, args.delta_only, args.full_every);
+        previous_issue_sets = statuses.iter().map(|vm| (vm.name.clone(), issue_set(vm))).collect();
+        for note in crate::vmc::disabled_check_notes(args.vmc_profile, args.check_host_state, args.check_host_health) {
+            eprintln!("[{run_id}] vmc-profile: {note}");
+        }
+        if args.sanity_check_thresholds {
+            for warning in crate::sanitycheck::unapproached_thresholds(&statuses, &args.detection_options()) {
+                eprintln!("[{run_id}] sanity-check-thresholds: {warning}");
+            }
+        }
+        let preview = proposed_thresholds.as_ref().map(|proposed| crate::preview::preview_threshold_changes(&statuses, proposed));
+
+        let rendered = render(
+            &render_statuses,
+            args.format,
+            !args.no_stats,
+            args.json_schema_version.into(),
+            notify_result.as_ref(),
+            compliance.as_ref(),
+            Some(&vcenter_version),
+            args.exclude_powered_off_from_stats,
+            args.uptime_format.into(),
+            run_id,
+            args.group_by.map(Into::into),
+            args.site.as_deref(),
+            &acknowledgements,
+            args.compact_json,
+            preview.as_ref(),
+            client.metrics_degraded(),
+        )?;
+        crate::sink::sink_for(args).write(&rendered)?;
+        if let Some(path) = &args.topology_output {
+            crate::topology::write_topology_output(path, &statuses, args.topology_context, args.topology_max_nodes)?;
+        }
+
+        crate::route_and_print(args, &statuses, run_id)?;
+
+        notifier.watchdog()?;
+        notifier.status(&summarize(&statuses))?;
+
+        cycle += 1;
+        thread::sleep(Duration::from_secs(args.interval_secs));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notifier::NotifyOutcome;
+    use crate::vm::{DetectedIssue, PowerState};
+
+    fn vm(name: &str, issues: Vec<DetectedIssue>) -> VMResourceStatus {
+        VMResourceStatus {
+            name: name.to_string(),
+            host: "esxi-01".to_string(),
+            cluster: "cluster-a".to_string(),
+            inventory_path: "/unknown".to_string(),
+            power_state: PowerState::PoweredOn,
+            cpu_usage_pct: 10.0,
+            memory_usage_pct: 10.0,
+            raw_metrics: std::collections::HashMap::new(),
+            metrics_source: crate::vm::MetricsSourceStatus::Available,
+            cpu_count: 2,
+            cores_per_socket: 1,
+            memory_gb: 16.0,
+            hardware_version: "vmx-19".to_string(),
+            cpu_hot_add_enabled: true,
+            memory_hot_add_enabled: true,
+            guest_visible_memory_mb: None,
+            guest_visible_cpu_count: None,
+            disk_allocated_gb: 100.0,
+            disk_used_gb: Some(50.0),
+            usage_basis: crate::vm::UsageBasis::Configured,
+            tools_running: true,
+            clock_skew_secs: None,
+            guest_ip: None,
+            reachable: None,
+            running_processes: Vec::new(),
+            attributes: HashMap::new(),
+            notes: None,
+            migration_count_24h: 0,
+            last_migration: None,
+            uptime_secs: 30.0 * 86400.0,
+            created_recently: false,
+            power_on_count: 0,
+            last_power_on_secs_ago: None,
+            suspended_duration_secs: None,
+            health_score: 100.0,
+            change_version: 0,
+            issues,
+        }
+    }
+
+    #[test]
+    fn render_includes_the_notifications_section_when_a_result_is_passed() {
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let notify_result = NotifyRunResult {
+            outcomes: vec![NotifyOutcome {
+                notifier: "slack-oncall".to_string(),
+                vms_notified: 1,
+            }],
+            failures: Vec::new(),
+        };
+        let rendered = render(
+            &statuses,
+            OutputFormat::Text,
+            true,
+            JsonSchemaVersion::V2,
+            Some(&notify_result),
+            None,
+            None,
+            false,
+            UptimeFormat::Human,
+            "test-run-id",
+            None,
+            None,
+            &Default::default(),
+            false,
+            None,
+            false,
+        )
+        .unwrap();
+        assert!(rendered.contains("NOTIFICATIONS"));
+        assert!(rendered.contains("slack-oncall"));
+    }
+
+    #[test]
+    fn without_delta_only_every_cycle_is_full() {
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let previous: HashMap<String, HashSet<VMIssueType>> = HashMap::new();
+        let scope = select_render_scope(&statuses, &previous, 5, false, 0);
+        assert_eq!(scope.len(), 1);
+    }
+
+    #[test]
+    fn first_cycle_is_always_full_even_with_delta_only() {
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let previous: HashMap<String, HashSet<VMIssueType>> = HashMap::new();
+        let scope = select_render_scope(&statuses, &previous, 0, true, 0);
+        assert_eq!(scope.len(), 1);
+    }
+
+    #[test]
+    fn delta_only_keeps_only_vms_whose_issue_set_changed() {
+        let statuses = vec![
+            vm("vm-unchanged", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")]),
+            vm("vm-new-issue", vec![DetectedIssue::new(VMIssueType::HighCpuUsage, "x")]),
+            vm("vm-cleared", vec![]),
+        ];
+        let mut previous: HashMap<String, HashSet<VMIssueType>> = HashMap::new();
+        previous.insert("vm-unchanged".to_string(), issue_set(&statuses[0]));
+        previous.insert("vm-new-issue".to_string(), HashSet::new());
+        previous.insert("vm-cleared".to_string(), [VMIssueType::HighMemoryUsage].into_iter().collect());
+
+        let scope = select_render_scope(&statuses, &previous, 1, true, 0);
+        let names: HashSet<&str> = scope.iter().map(|vm| vm.name.as_str()).collect();
+        assert_eq!(names, HashSet::from(["vm-new-issue", "vm-cleared"]));
+    }
+
+    #[test]
+    fn full_every_forces_a_full_cycle_even_with_nothing_changed() {
+        let statuses = vec![vm("vm-1", vec![DetectedIssue::new(VMIssueType::PoweredOff, "x")])];
+        let mut previous: HashMap<String, HashSet<VMIssueType>> = HashMap::new();
+        previous.insert("vm-1".to_string(), issue_set(&statuses[0]));
+
+        assert!(select_render_scope(&statuses, &previous, 1, true, 3).is_empty());
+        assert_eq!(select_render_scope(&statuses, &previous, 3, true, 3).len(), 1);
+    }
+}
@@ -0,0 +1,75 @@
+//! Integration coverage for `--demo`, against the same bundled fixture the
+//! binary embeds at `fixtures/demo_inventory.json` - so the fixture can't
+//! drift out of sync with what a real `--demo` run ships without failing a
+//! test. Runs the compiled binary directly since this crate has no lib target.
+
+use std::process::Command;
+
+fn demo_inventory_json() -> serde_json::Value {
+    let raw = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/demo_inventory.json"))
+        .expect("bundled demo fixture should be readable");
+    serde_json::from_str(&raw).expect("bundled demo fixture should be valid JSON")
+}
+
+fn network_monitor() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_network-monitor"))
+}
+
+#[test]
+fn fixture_has_a_realistic_fleet_size_across_multiple_hosts() {
+    let inventory = demo_inventory_json();
+    let vms = inventory["vms"].as_array().expect("vms array");
+    assert!(vms.len() >= 30 && vms.len() <= 50, "expected 30-50 VMs, got {}", vms.len());
+
+    let hosts: std::collections::HashSet<_> = vms.iter().map(|v| v["host"].as_str().unwrap()).collect();
+    assert!(hosts.len() >= 2, "expected at least two hosts in the demo fleet");
+
+    let host_metrics = inventory["host_metrics"].as_object().expect("host_metrics object");
+    assert_eq!(host_metrics.len(), hosts.len());
+}
+
+#[test]
+fn demo_run_watermarks_text_output_and_exits_cleanly() {
+    let output = network_monitor()
+        .args(["--demo", "--host", "unused", "--username", "unused", "--password", "unused"])
+        .output()
+        .expect("failed to run network-monitor");
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("DEMO DATA"));
+}
+
+#[test]
+fn demo_run_with_json_format_stays_valid_json_and_is_watermarked() {
+    let output = network_monitor()
+        .args(["--demo", "--format", "json", "--host", "unused", "--username", "unused", "--password", "unused"])
+        .output()
+        .expect("failed to run network-monitor");
+
+    assert!(output.status.success(), "stderr: {}", String::from_utf8_lossy(&output.stderr));
+    let value: serde_json::Value = serde_json::from_str(&String::from_utf8_lossy(&output.stdout))
+        .expect("demo JSON output should parse");
+    assert_eq!(value["demo_data_watermark"], "DEMO DATA - synthetic fixture inventory, not a real vCenter");
+}
+
+#[test]
+fn demo_refuses_to_combine_with_notifier_config_without_the_allow_flag() {
+    let output = network_monitor()
+        .args([
+            "--demo",
+            "--notifier-config",
+            "does-not-exist.json",
+            "--host",
+            "unused",
+            "--username",
+            "unused",
+            "--password",
+            "unused",
+        ])
+        .output()
+        .expect("failed to run network-monitor");
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("demo-allow-notify"));
+}